@@ -0,0 +1,18 @@
+use connected_papers::schema::schemas;
+use std::path::PathBuf;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "schema".to_owned()),
+    );
+    std::fs::create_dir_all(&dir)?;
+
+    for (name, schema) in schemas() {
+        let path = dir.join(format!("{name}.schema.json"));
+        std::fs::write(&path, serde_json::to_string_pretty(&schema)?)?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
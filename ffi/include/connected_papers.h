@@ -0,0 +1,72 @@
+#ifndef CONNECTED_PAPERS_H
+#define CONNECTED_PAPERS_H
+
+/* Generated with cbindgen from ffi/src/lib.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque client handle
+typedef struct CpClient CpClient;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the last error on the calling thread, or `NULL` if no call failed yet
+//
+// The message is owned by the library and stays valid until the next failing call on the
+// same thread.
+const char *cp_last_error(void);
+
+// Create a client
+//
+// `api_key` is the Connected Papers API key. When `NULL`, it is read from the
+// `CONNECTED_PAPERS_API_KEY` environment variable, and if that is unset only the Semantic
+// Scholar functions are available. Release the client with [`cp_client_free`].
+//
+// # Safety
+//
+// `api_key` must be `NULL` or a NUL-terminated string.
+CpClient *cp_client_new(const char *api_key);
+
+// Release a client created with [`cp_client_new`]; `NULL` is ignored
+//
+// # Safety
+//
+// `client` must be `NULL` or a pointer returned by [`cp_client_new`] not freed yet.
+void cp_client_free(CpClient *client);
+
+// Release a string returned by the library; `NULL` is ignored
+//
+// # Safety
+//
+// `string` must be `NULL` or a string returned by this library not freed yet.
+void cp_string_free(char *string);
+
+// Graph response of a paper as JSON
+//
+// Blocks until the Connected Papers API answers. Returns `NULL` on failure.
+//
+// # Safety
+//
+// `client` must be a live client and `paper_id` a NUL-terminated string.
+char *cp_get_graph_json(const CpClient *client, const char *paper_id, bool fresh_only);
+
+// Semantic Scholar relevance search as JSON
+//
+// `limit` is the maximum number of papers, at most 100, or 0 for the API default. Blocks
+// until the Semantic Scholar API answers. Returns `NULL` on failure.
+//
+// # Safety
+//
+// `client` must be a live client and `query` a NUL-terminated string.
+char *ss_search_json(const CpClient *client, const char *query, uint8_t limit);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CONNECTED_PAPERS_H */
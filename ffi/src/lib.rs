@@ -0,0 +1,219 @@
+//! C bindings
+//!
+//! A minimal C ABI over an opaque client handle, so that plugins written in C or C++ can
+//! fetch Connected Papers graphs and search Semantic Scholar. Results are returned as JSON
+//! strings following the serialized layout of the crate.
+//!
+//! Every string returned by the library must be released with [`cp_string_free`]. Functions
+//! returning a pointer return `NULL` on failure, with the reason available from
+//! [`cp_last_error`]. The header in `include/connected_papers.h` is generated with cbindgen:
+//!
+//! ```sh
+//! cbindgen --config ffi/cbindgen.toml --crate connected-papers-ffi --output ffi/include/connected_papers.h
+//! ```
+
+use connected_papers::{ConnectedPapers, PaperField, PaperSearchParamBuilder, SemanticScholar};
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString, c_char},
+    ptr,
+};
+use tokio::runtime::Runtime;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Opaque client handle
+pub struct CpClient {
+    connected_papers: Option<ConnectedPapers>,
+    semantic_scholar: SemanticScholar,
+    runtime: Runtime,
+}
+
+/// Record the error of the current call and return `NULL`
+fn fail<T>(message: impl ToString) -> *mut T {
+    let message = CString::new(message.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+    ptr::null_mut()
+}
+
+/// Hand a string over to the caller
+fn into_c_string(string: String) -> *mut c_char {
+    match CString::new(string) {
+        Ok(string) => string.into_raw(),
+        Err(e) => fail(e),
+    }
+}
+
+/// Borrow a string from the caller, `None` if it is `NULL` or not UTF-8
+///
+/// # Safety
+///
+/// `string` must be `NULL` or point to a NUL-terminated string valid for `'a`.
+unsafe fn from_c_str<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(string) }.to_str().ok()
+}
+
+/// Message of the last error on the calling thread, or `NULL` if no call failed yet
+///
+/// The message is owned by the library and stays valid until the next failing call on the
+/// same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn cp_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Create a client
+///
+/// `api_key` is the Connected Papers API key. When `NULL`, it is read from the
+/// `CONNECTED_PAPERS_API_KEY` environment variable, and if that is unset only the Semantic
+/// Scholar functions are available. Release the client with [`cp_client_free`].
+///
+/// # Safety
+///
+/// `api_key` must be `NULL` or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cp_client_new(api_key: *const c_char) -> *mut CpClient {
+    let connected_papers = if api_key.is_null() {
+        ConnectedPapers::from_env().ok()
+    } else {
+        match unsafe { from_c_str(api_key) } {
+            Some(api_key) => Some(ConnectedPapers::with_api_key(api_key)),
+            None => return fail("api_key is not valid UTF-8"),
+        }
+    };
+    let runtime = match Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => return fail(e),
+    };
+    Box::into_raw(Box::new(CpClient {
+        connected_papers,
+        semantic_scholar: SemanticScholar::default(),
+        runtime,
+    }))
+}
+
+/// Release a client created with [`cp_client_new`]; `NULL` is ignored
+///
+/// # Safety
+///
+/// `client` must be `NULL` or a pointer returned by [`cp_client_new`] not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cp_client_free(client: *mut CpClient) {
+    if !client.is_null() {
+        drop(unsafe { Box::from_raw(client) });
+    }
+}
+
+/// Release a string returned by the library; `NULL` is ignored
+///
+/// # Safety
+///
+/// `string` must be `NULL` or a string returned by this library not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cp_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+/// Graph response of a paper as JSON
+///
+/// Blocks until the Connected Papers API answers. Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `client` must be a live client and `paper_id` a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cp_get_graph_json(
+    client: *const CpClient,
+    paper_id: *const c_char,
+    fresh_only: bool,
+) -> *mut c_char {
+    let Some(client) = (unsafe { client.as_ref() }) else {
+        return fail("client is NULL");
+    };
+    let Some(paper_id) = (unsafe { from_c_str(paper_id) }) else {
+        return fail("paper_id is NULL or not valid UTF-8");
+    };
+    let Some(ref connected_papers) = client.connected_papers else {
+        return fail("no Connected Papers API key");
+    };
+
+    match client
+        .runtime
+        .block_on(connected_papers.get_graph(paper_id, fresh_only))
+        .and_then(|response| Ok(serde_json::to_string(&response)?))
+    {
+        Ok(json) => into_c_string(json),
+        Err(e) => fail(e),
+    }
+}
+
+/// Semantic Scholar relevance search as JSON
+///
+/// `limit` is the maximum number of papers, at most 100, or 0 for the API default. Blocks
+/// until the Semantic Scholar API answers. Returns `NULL` on failure.
+///
+/// # Safety
+///
+/// `client` must be a live client and `query` a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ss_search_json(
+    client: *const CpClient,
+    query: *const c_char,
+    limit: u8,
+) -> *mut c_char {
+    let Some(client) = (unsafe { client.as_ref() }) else {
+        return fail("client is NULL");
+    };
+    let Some(query) = (unsafe { from_c_str(query) }) else {
+        return fail("query is NULL or not valid UTF-8");
+    };
+
+    let mut builder = PaperSearchParamBuilder::new(query);
+    for field in PaperField::SUMMARY {
+        builder.field(field);
+    }
+    if limit > 0 {
+        builder.limit(limit);
+    }
+    let result = builder.build().and_then(|param| {
+        let response = client
+            .runtime
+            .block_on(client.semantic_scholar.query(&param))?;
+        Ok(serde_json::to_string(&response)?)
+    });
+    match result {
+        Ok(json) => into_c_string(json),
+        Err(e) => fail(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_null_arguments() {
+        unsafe {
+            let client = cp_client_new(c"TEST_TOKEN".as_ptr());
+            assert!(!client.is_null());
+
+            assert!(ss_search_json(client, ptr::null(), 0).is_null());
+            let error = CStr::from_ptr(cp_last_error()).to_str().unwrap();
+            assert_eq!(error, "query is NULL or not valid UTF-8");
+
+            assert!(cp_get_graph_json(ptr::null(), c"id".as_ptr(), false).is_null());
+            let error = CStr::from_ptr(cp_last_error()).to_str().unwrap();
+            assert_eq!(error, "client is NULL");
+
+            cp_client_free(client);
+            cp_string_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Graph payloads of any shape must deserialize or fail, never panic
+
+#![no_main]
+
+use connected_papers::{Graph, GraphResponse};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<GraphResponse>(data);
+    if let Ok(value) = serde_json::from_slice(data) {
+        let _ = Graph::from_json(value);
+    }
+});
@@ -0,0 +1,16 @@
+//! Parsed queries print back to a query parsing to the same expression
+
+#![no_main]
+
+use connected_papers::QueryExpr;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|query: &str| {
+    if let Ok(expr) = query.parse::<QueryExpr>() {
+        let printed = expr.to_string();
+        match printed.parse::<QueryExpr>() {
+            Ok(reparsed) => assert_eq!(reparsed, expr, "{:?} printed as {:?}", query, printed),
+            Err(e) => panic!("{:?} printed as {:?}, which fails to parse: {}", query, printed, e),
+        }
+    }
+});
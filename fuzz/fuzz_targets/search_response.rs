@@ -0,0 +1,15 @@
+//! Search pages and batch responses of any shape must deserialize or fail, never panic
+
+#![no_main]
+
+use connected_papers::{
+    PaperBulkSearchResponse, PaperSearchResponse, ResolvedId, S2NestedPaper,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<PaperSearchResponse>(data);
+    let _ = serde_json::from_slice::<PaperBulkSearchResponse>(data);
+    let _ = serde_json::from_slice::<Vec<Option<S2NestedPaper>>>(data);
+    let _ = serde_json::from_slice::<Vec<Option<ResolvedId>>>(data);
+});
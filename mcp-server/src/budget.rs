@@ -0,0 +1,117 @@
+//! Session quota budget
+//!
+//! Agents can burn through a monthly Connected Papers quota in a single session. A [`Budget`]
+//! counts the graph fetches of the session, remembers the `remaining_requests` Connected
+//! Papers last reported, and refuses further fetches once the session budget or the quota is
+//! spent.
+
+use crate::error::ToolError;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Environment variable holding the number of expensive calls allowed per session
+pub const BUDGET_ENV: &str = "CONNECTED_PAPERS_SESSION_BUDGET";
+
+/// Calls of the session and what is left
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    /// Expensive Connected Papers calls made
+    pub connected_papers_calls: u32,
+    /// Expensive calls allowed in the session, unlimited if `None`
+    pub session_budget: Option<u32>,
+    /// Requests left on the API key, as last reported by Connected Papers
+    pub remaining_requests: Option<u64>,
+}
+
+/// Per-session budget of expensive tool calls
+#[derive(Debug, Default)]
+pub struct Budget {
+    usage: Mutex<Usage>,
+}
+
+impl Budget {
+    /// Create a budget allowing `max_calls` expensive calls, unlimited if `None`
+    pub fn new(max_calls: Option<u32>) -> Self {
+        Self {
+            usage: Mutex::new(Usage {
+                session_budget: max_calls,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Create a budget from [`BUDGET_ENV`], unlimited if unset
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var(BUDGET_ENV) {
+            Ok(value) => {
+                let max_calls = value
+                    .trim()
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("invalid {} {:?}: {}", BUDGET_ENV, value, e))?;
+                Ok(Self::new(Some(max_calls)))
+            }
+            Err(_) => Ok(Self::new(None)),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Usage> {
+        self.usage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reserve an expensive call, failing with `quota_exceeded` once the budget is spent
+    pub fn spend(&self) -> Result<(), ToolError> {
+        let mut usage = self.lock();
+        if let Some(budget) = usage
+            .session_budget
+            .filter(|budget| usage.connected_papers_calls >= *budget)
+        {
+            return Err(ToolError::new(
+                "quota_exceeded",
+                format!(
+                    "Session budget of {} expensive calls spent; see get_usage",
+                    budget
+                ),
+            ));
+        }
+        if usage.remaining_requests == Some(0) {
+            return Err(ToolError::new(
+                "quota_exceeded",
+                "No Connected Papers requests left on the API key; see get_usage",
+            ));
+        }
+        usage.connected_papers_calls += 1;
+        Ok(())
+    }
+
+    /// Record the requests left on the API key
+    pub fn set_remaining(&self, remaining: Option<u64>) {
+        if remaining.is_some() {
+            self.lock().remaining_requests = remaining;
+        }
+    }
+
+    /// Calls of the session so far
+    pub fn usage(&self) -> Usage {
+        self.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let budget = Budget::new(Some(2));
+        assert!(budget.spend().is_ok());
+        assert!(budget.spend().is_ok());
+        assert_eq!(budget.spend().unwrap_err().code, "quota_exceeded");
+        assert_eq!(budget.usage().connected_papers_calls, 2);
+
+        let budget = Budget::new(None);
+        budget.set_remaining(Some(0));
+        budget.set_remaining(None);
+        assert!(budget.spend().is_err());
+        assert_eq!(budget.usage().remaining_requests, Some(0));
+    }
+}
@@ -0,0 +1,192 @@
+//! Command-line options
+//!
+//! MCP hosts talk to the server over stdin and stdout, and some of them treat anything on
+//! stderr as protocol noise. Logs can go to a rotated file instead, and `--quiet` silences
+//! stderr entirely.
+
+use anyhow::{Context, anyhow, bail};
+use std::path::{Path, PathBuf};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+/// Usage printed by `--help`
+pub const USAGE: &str = "\
+Usage: connected-papers-mcp [OPTIONS]
+
+Options:
+  -q, --quiet               Write no logs to stderr
+      --log-file <PATH>     Also write logs to PATH
+      --log-rotation <WHEN> Start a new log file: never (default), minutely, hourly or daily
+      --max-log-files <N>   Keep at most N rotated log files
+  -h, --help                Print this help
+  -V, --version             Print the version
+
+Environment:
+  CONNECTED_PAPERS_API_KEY         API key of Connected Papers
+  CONNECTED_PAPERS_SESSION_BUDGET  Graph fetches allowed per session
+  RUST_LOG                         Log filter, e.g. connected_papers=debug";
+
+/// When the log file is rotated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogRotation {
+    /// Always write to the same file
+    #[default]
+    Never,
+    /// Start a new file every minute
+    Minutely,
+    /// Start a new file every hour
+    Hourly,
+    /// Start a new file every day
+    Daily,
+}
+
+impl std::str::FromStr for LogRotation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "never" => Ok(LogRotation::Never),
+            "minutely" => Ok(LogRotation::Minutely),
+            "hourly" => Ok(LogRotation::Hourly),
+            "daily" => Ok(LogRotation::Daily),
+            _ => bail!(
+                "invalid log rotation {:?}: never, minutely, hourly or daily",
+                s
+            ),
+        }
+    }
+}
+
+impl From<LogRotation> for Rotation {
+    fn from(rotation: LogRotation) -> Self {
+        match rotation {
+            LogRotation::Never => Rotation::NEVER,
+            LogRotation::Minutely => Rotation::MINUTELY,
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+        }
+    }
+}
+
+/// What the command line asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Run the server
+    Serve(Options),
+    /// Print the usage
+    Help,
+    /// Print the version
+    Version,
+}
+
+/// Logging options of the server
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Write no logs to stderr
+    pub quiet: bool,
+    /// File logs are written to, with the rotation date appended when rotated
+    pub log_file: Option<PathBuf>,
+    /// When the log file is rotated
+    pub log_rotation: LogRotation,
+    /// Rotated log files kept, all of them if `None`
+    pub max_log_files: Option<usize>,
+}
+
+impl Options {
+    /// Appender writing to the log file, if any
+    pub fn log_appender(&self) -> anyhow::Result<Option<RollingFileAppender>> {
+        let Some(path) = &self.log_file else {
+            return Ok(None);
+        };
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("invalid log file {}", path.display()))?;
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let mut builder = RollingFileAppender::builder()
+            .rotation(self.log_rotation.into())
+            .filename_prefix(file_name.to_string_lossy());
+        if let Some(max_log_files) = self.max_log_files {
+            builder = builder.max_log_files(max_log_files);
+        }
+        builder
+            .build(directory)
+            .with_context(|| format!("cannot open log file {}", path.display()))
+            .map(Some)
+    }
+}
+
+/// Parse the arguments following the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Command> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_owned(), Some(value.to_owned()))
+            }
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| anyhow!("{} needs a value", flag))
+        };
+        match flag.as_str() {
+            "-q" | "--quiet" => options.quiet = true,
+            "--log-file" => options.log_file = Some(PathBuf::from(value()?)),
+            "--log-rotation" => options.log_rotation = value()?.parse()?,
+            "--max-log-files" => {
+                let value = value()?;
+                let max_log_files = value
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| anyhow!("invalid --max-log-files {:?}", value))?;
+                options.max_log_files = Some(max_log_files);
+            }
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            _ => bail!("unknown argument {:?}\n\n{}", flag, USAGE),
+        }
+    }
+    Ok(Command::Serve(options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> anyhow::Result<Command> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(args(&[]).unwrap(), Command::Serve(Options::default()));
+        assert_eq!(
+            args(&[
+                "-q",
+                "--log-file",
+                "logs/mcp.log",
+                "--log-rotation=daily",
+                "--max-log-files",
+                "7"
+            ])
+            .unwrap(),
+            Command::Serve(Options {
+                quiet: true,
+                log_file: Some(PathBuf::from("logs/mcp.log")),
+                log_rotation: LogRotation::Daily,
+                max_log_files: Some(7),
+            })
+        );
+        assert_eq!(args(&["--quiet", "--help"]).unwrap(), Command::Help);
+        assert!(args(&["--log-file"]).is_err());
+        assert!(args(&["--log-rotation", "weekly"]).is_err());
+        assert!(args(&["--max-log-files", "0"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+    }
+}
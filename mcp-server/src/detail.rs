@@ -0,0 +1,141 @@
+//! Size of graph responses
+//!
+//! Whole graphs easily exceed the context window of a model. Graph tools take a [`Detail`]
+//! level and a cap on the nodes listed, keeping the nodes most similar to the seed, so that
+//! agents ask for just what they need.
+
+use connected_papers::{Graph, Paper};
+use rmcp::schemars;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+/// Nodes listed by default at the `nodes` and `full` levels
+pub const DEFAULT_MAX_NODES: usize = 20;
+
+/// How much of a graph a tool returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Detail {
+    /// Counts, parameters and the seed paper
+    #[default]
+    Summary,
+    /// The summary and the most similar nodes with their title, year and similarity
+    Nodes,
+    /// The summary, the most similar nodes with their metadata and the edges between them
+    Full,
+}
+
+/// The seed and the nodes most similar to it, at most `max_nodes` in all
+///
+/// The similarity of a node is the weight of its edge to the seed. Nodes are sorted by
+/// decreasing similarity, then by id, so the selection is deterministic.
+pub fn top_nodes(graph: &Graph, max_nodes: usize) -> Vec<(&Paper, Option<f64>)> {
+    let similarity = |id: &str| {
+        graph
+            .edges
+            .iter()
+            .find(|edge| {
+                (edge.0 == graph.start_id && edge.1 == id)
+                    || (edge.1 == graph.start_id && edge.0 == id)
+            })
+            .map(|edge| edge.2)
+    };
+    let mut nodes = graph
+        .nodes
+        .iter()
+        .filter(|(id, _)| **id != graph.start_id)
+        .map(|(id, paper)| (paper, similarity(id)))
+        .collect::<Vec<_>>();
+    nodes.sort_by(|a, b| {
+        let weight = |similarity: Option<f64>| similarity.unwrap_or(f64::MIN);
+        weight(b.1)
+            .total_cmp(&weight(a.1))
+            .then_with(|| a.0.id.cmp(&b.0.id))
+    });
+    graph
+        .nodes
+        .get(&graph.start_id)
+        .map(|seed| (seed, None))
+        .into_iter()
+        .chain(nodes)
+        .take(max_nodes)
+        .collect()
+}
+
+/// The nodes, and at the `full` level the edges between them, of a graph response
+pub fn graph_nodes(graph: &Graph, detail: Detail, max_nodes: usize) -> Value {
+    let nodes = top_nodes(graph, max_nodes);
+    let listed = nodes
+        .iter()
+        .map(|(paper, _)| paper.id.as_str())
+        .collect::<HashSet<_>>();
+    let mut result = json!({
+        "nodes": nodes
+            .iter()
+            .map(|(paper, similarity)| node(paper, *similarity, detail))
+            .collect::<Vec<_>>(),
+        "nodes_truncated": graph.nodes.len() > nodes.len(),
+    });
+    if detail == Detail::Full {
+        result["edges"] = graph
+            .edges
+            .iter()
+            .filter(|edge| listed.contains(edge.0.as_str()) && listed.contains(edge.1.as_str()))
+            .map(|edge| json!([edge.0, edge.1, edge.2]))
+            .collect();
+    }
+    result
+}
+
+fn node(paper: &Paper, similarity: Option<f64>, detail: Detail) -> Value {
+    let mut node = json!({
+        "id": paper.id,
+        "title": paper.title,
+        "year": paper.year,
+        "citations_length": paper.citations_length,
+        "similarity": similarity,
+    });
+    if detail == Detail::Full {
+        node["authors"] = json!(
+            paper
+                .authors()
+                .iter()
+                .map(|author| author.name.as_deref().unwrap_or("Unknown"))
+                .collect::<Vec<_>>()
+        );
+        node["venue"] = json!(paper.venue);
+        node["doi"] = json!(paper.doi);
+        node["arxiv_id"] = json!(paper.arxiv_id);
+        node["abstract"] = json!(paper.abstract_);
+        node["url"] = json!(paper.url);
+        node["is_open_access"] = json!(paper.is_open_access);
+        node["references_length"] = json!(paper.references_length);
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_nodes() {
+        let graph = Graph::example();
+        let ids = |max_nodes| {
+            top_nodes(&graph, max_nodes)
+                .into_iter()
+                .map(|(paper, _)| paper.id.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(10), vec!["seed", "newer", "older"]);
+        assert_eq!(ids(2), vec!["seed", "newer"]);
+
+        let nodes = graph_nodes(&graph, Detail::Nodes, 2);
+        assert_eq!(nodes["nodes"][1]["similarity"], 0.6);
+        assert_eq!(nodes["nodes_truncated"], true);
+        assert!(nodes.get("edges").is_none());
+        let full = graph_nodes(&graph, Detail::Full, 2);
+        assert_eq!(full["edges"], json!([["seed", "newer", 0.6]]));
+        assert_eq!(full["nodes"][0]["authors"], json!([]));
+    }
+}
@@ -0,0 +1,152 @@
+//! Tool errors
+//!
+//! Failed tool calls return an MCP error result whose text is a JSON object with a stable
+//! `code`, e.g. `rate_limited` or `invalid_input`, and a retry hint, so that agents can tell
+//! a call worth repeating later from one that needs different arguments.
+
+use connected_papers::{Error, GraphResponseType, is_retryable};
+use serde::Serialize;
+
+/// Result of a tool; errors are the JSON of a [`ToolError`] and become MCP error results
+pub type ToolResult = Result<String, String>;
+
+/// Machine-readable error of a tool call
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolError {
+    /// Stable error code
+    pub code: &'static str,
+    /// Human-readable description
+    pub message: String,
+    /// Whether the same call may succeed later
+    pub retryable: bool,
+    /// Suggested delay before retrying, in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+impl ToolError {
+    /// Error that retrying does not fix
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: false,
+            retry_after_secs: None,
+        }
+    }
+
+    /// Mark the error as retryable after the given delay
+    pub fn retry_after(mut self, secs: u64) -> Self {
+        self.retryable = true;
+        self.retry_after_secs = Some(secs);
+        self
+    }
+
+    /// Error of a failed client call, the message starting with what the tool was doing
+    pub fn from_error(action: &str, error: &Error) -> Self {
+        let code = match error.inner() {
+            Error::RequestFailed(failure) => match failure.status {
+                429 => "rate_limited",
+                401 | 403 => "unauthorized",
+                404 => "not_found",
+                400..=499 => "invalid_input",
+                _ => "upstream_error",
+            },
+            Error::APIKeyNotFound(_) => "unauthorized",
+            Error::ReqwestError(_) => "network_error",
+            Error::InvalidParameter(_)
+            | Error::UnsupportedFields(_)
+            | Error::UnsupportedUrlHost(_) => "invalid_input",
+            Error::GraphUnavailable(status) => graph_status_code(*status),
+            Error::UnsupportedGraphVersion(_) => "unsupported_version",
+            Error::ResponseTooLarge { .. } => "response_too_large",
+            _ => "internal_error",
+        };
+        let tool_error = Self::new(code, format!("{}: {}", action, error));
+        if !is_retryable(error) {
+            tool_error
+        } else if code == "rate_limited" {
+            tool_error.retry_after(60)
+        } else {
+            tool_error.retry_after(5)
+        }
+    }
+
+    /// Error of a graph response without a graph
+    pub fn graph_unavailable(id: &str, status: GraphResponseType) -> Self {
+        let code = graph_status_code(status);
+        let tool_error = Self::new(
+            code,
+            format!("Graph of paper {} not available. Status: {:?}", id, status),
+        );
+        match status {
+            GraphResponseType::Overloaded => tool_error.retry_after(40),
+            GraphResponseType::InProgress | GraphResponseType::Queued => tool_error.retry_after(5),
+            GraphResponseType::Error => tool_error.retry_after(5),
+            _ => tool_error,
+        }
+    }
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let payload = serde_json::json!({ "error": self });
+        match serde_json::to_string_pretty(&payload) {
+            Ok(json) => f.write_str(&json),
+            Err(_) => write!(f, "Error ({}): {}", self.code, self.message),
+        }
+    }
+}
+
+impl From<ToolError> for String {
+    fn from(error: ToolError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Error code of a graph status that carries no graph
+fn graph_status_code(status: GraphResponseType) -> &'static str {
+    match status {
+        GraphResponseType::BadId | GraphResponseType::NotInDb => "not_found",
+        GraphResponseType::BadToken => "unauthorized",
+        GraphResponseType::BadRequest => "invalid_input",
+        GraphResponseType::OutOfRequests => "quota_exceeded",
+        GraphResponseType::Overloaded => "rate_limited",
+        GraphResponseType::Error => "upstream_error",
+        GraphResponseType::InProgress
+        | GraphResponseType::Queued
+        | GraphResponseType::OldGraph
+        | GraphResponseType::FreshGraph => "graph_building",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use connected_papers::ApiError;
+
+    #[test]
+    fn test_tool_error() {
+        let failure = |status| {
+            Error::RequestFailed(ApiError {
+                status,
+                message: None,
+                body: String::new(),
+                request_id: None,
+            })
+        };
+
+        let error = ToolError::from_error("Failed to get graph", &failure(429));
+        assert_eq!(error.code, "rate_limited");
+        assert_eq!(error.retry_after_secs, Some(60));
+        let error = ToolError::from_error("Failed to get graph", &failure(400));
+        assert_eq!(error.code, "invalid_input");
+        assert!(!error.retryable);
+
+        let error = ToolError::graph_unavailable("abc", GraphResponseType::Queued);
+        assert_eq!(error.code, "graph_building");
+        assert!(error.retryable);
+        let json: serde_json::Value = serde_json::from_str(&error.to_string()).unwrap();
+        assert_eq!(json["error"]["code"], "graph_building");
+    }
+}
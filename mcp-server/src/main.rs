@@ -1,4 +1,6 @@
-use connected_papers::{ConnectedPapers, GraphResponse, GraphResponseType};
+use connected_papers::{
+    CitationStyle, ConnectedPapers, GraphResponse, GraphResponseType, PaperLike,
+};
 use rmcp::{
     ServerHandler, ServiceExt,
     handler::server::{tool::ToolRouter, wrapper::Parameters},
@@ -7,13 +9,25 @@ use rmcp::{
     transport::stdio,
 };
 use serde_json::json;
-use tracing_subscriber::EnvFilter;
+use std::sync::Arc;
+use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+mod budget;
+mod cli;
+mod detail;
+mod error;
+
+use budget::Budget;
+use cli::Command;
+use detail::{DEFAULT_MAX_NODES, Detail, graph_nodes};
+use error::{ToolError, ToolResult};
 
 #[derive(Debug, Clone)]
 pub struct ConnectedPapersMCP {
     #[allow(dead_code)]
     tool_router: ToolRouter<Self>,
-    api_key: String,
+    client: ConnectedPapers,
+    budget: Arc<Budget>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -23,6 +37,15 @@ pub struct GetGraphRequest {
     #[schemars(description = "If true, force a fresh graph rebuild (ignore cached graphs)")]
     #[serde(default)]
     pub fresh_only: bool,
+    #[schemars(
+        description = "How much of the graph to return: summary (counts and seed paper, the default), nodes (also the most similar nodes with title, year and similarity) or full (also node metadata and the edges between listed nodes)"
+    )]
+    #[serde(default)]
+    pub detail: Detail,
+    #[schemars(
+        description = "Maximum number of nodes listed at the nodes and full levels, the seed and the nodes most similar to it, 20 by default"
+    )]
+    pub max_nodes: Option<usize>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -34,16 +57,49 @@ pub struct GetPaperInfoRequest {
     pub fresh_only: bool,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct FormatCitationRequest {
+    #[schemars(description = "The (Semantic Scholar primary) ID of the paper")]
+    pub id: String,
+    #[schemars(description = "The citation style: apa, mla, chicago or ieee")]
+    pub style: String,
+    #[schemars(description = "If true, force a fresh graph rebuild (ignore cached graphs)")]
+    #[serde(default)]
+    pub fresh_only: bool,
+}
+
 #[tool_router]
 impl ConnectedPapersMCP {
-    pub fn new(api_key: &str) -> Self {
-        Self {
-            api_key: api_key.to_owned(),
+    pub fn new(api_key: &str, budget: Budget) -> connected_papers::Result<Self> {
+        let client = ConnectedPapers::builder()
+            .api_key(api_key)
+            .coalesce_requests()
+            .build()?;
+        Ok(Self {
+            client,
+            budget: Arc::new(budget),
             tool_router: Self::tool_router(),
-        }
+        })
+    }
+
+    /// Fetch a graph, counted against the session budget
+    async fn fetch_graph(
+        &self,
+        action: &str,
+        id: &str,
+        fresh_only: bool,
+    ) -> Result<GraphResponse, ToolError> {
+        self.budget.spend()?;
+        let response = self
+            .client
+            .get_graph(id, fresh_only)
+            .await
+            .map_err(|e| ToolError::from_error(action, &e))?;
+        self.budget.set_remaining(response.remaining_requests);
+        Ok(response)
     }
 
-    fn format_graph_response(response: GraphResponse) -> String {
+    fn format_graph_response(response: GraphResponse, detail: Detail, max_nodes: usize) -> String {
         let status_str = match response.status {
             GraphResponseType::BadId => "BAD_ID",
             GraphResponseType::Error => "ERROR",
@@ -89,6 +145,14 @@ impl ConnectedPapersMCP {
                 "current_corpus_date": graph.current_corpus_date.to_string(),
                 "creation_time": graph.creation_time.to_string(),
             });
+            if detail != Detail::Summary {
+                let nodes = graph_nodes(&graph, detail, max_nodes);
+                result["graph"]["nodes"] = nodes["nodes"].clone();
+                result["graph"]["nodes_truncated"] = nodes["nodes_truncated"].clone();
+                if let Some(edges) = nodes.get("edges") {
+                    result["graph"]["edges"] = edges.clone();
+                }
+            }
 
             // Include the start paper (main paper) details
             if let Some(start_paper) = graph.nodes.get(&graph.start_id) {
@@ -116,20 +180,27 @@ impl ConnectedPapersMCP {
     }
 
     #[tool(
-        description = "Get the graph of a paper by its Semantic Scholar ID. Returns graph structure, status, and metadata."
+        description = "Get the graph of a paper by its Semantic Scholar ID. Returns graph structure, status, and metadata; the detail and max_nodes parameters control how many nodes are listed."
     )]
     pub async fn get_graph(
         &self,
-        Parameters(GetGraphRequest { id, fresh_only }): Parameters<GetGraphRequest>,
-    ) -> String {
-        let client = ConnectedPapers::with_api_key(&self.api_key);
-
-        match client.get_graph(&id, fresh_only).await {
-            Ok(response) => Self::format_graph_response(response),
-            Err(e) => serde_json::to_string_pretty(&json!({
-                "error": format!("Failed to get graph: {}", e),
-            }))
-            .unwrap_or_else(|_| format!("Error: Failed to get graph: {}", e)),
+        Parameters(GetGraphRequest {
+            id,
+            fresh_only,
+            detail,
+            max_nodes,
+        }): Parameters<GetGraphRequest>,
+    ) -> ToolResult {
+        match self
+            .fetch_graph("Failed to get graph", &id, fresh_only)
+            .await
+        {
+            Ok(response) => Ok(Self::format_graph_response(
+                response,
+                detail,
+                max_nodes.unwrap_or(DEFAULT_MAX_NODES),
+            )),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -139,10 +210,11 @@ impl ConnectedPapersMCP {
     pub async fn get_paper_info(
         &self,
         Parameters(GetPaperInfoRequest { id, fresh_only }): Parameters<GetPaperInfoRequest>,
-    ) -> String {
-        let client = ConnectedPapers::with_api_key(&self.api_key);
-
-        match client.get_graph(&id, fresh_only).await {
+    ) -> ToolResult {
+        match self
+            .fetch_graph("Failed to get paper info", &id, fresh_only)
+            .await
+        {
             Ok(response) => {
                 if let Some(graph) = response.graph_json {
                     if let Some(paper) = graph.nodes.get(&graph.start_id) {
@@ -178,62 +250,92 @@ impl ConnectedPapersMCP {
                             "number_of_authors": paper.number_of_authors,
                             "corpus_id": paper.corpus_id,
                         });
-                        serde_json::to_string_pretty(&result)
-                            .unwrap_or_else(|_| format!("{:?}", result))
+                        Ok(serde_json::to_string_pretty(&result)
+                            .unwrap_or_else(|_| format!("{:?}", result)))
                     } else {
-                        serde_json::to_string_pretty(&json!({
-                            "error": format!("Paper {} not found in graph", id),
-                        }))
-                        .unwrap_or_else(|_| format!("Error: Paper {} not found in graph", id))
+                        Err(
+                            ToolError::new("not_found", format!("Paper {} not found in graph", id))
+                                .into(),
+                        )
                     }
                 } else {
-                    serde_json::to_string_pretty(&json!({
-                        "error": format!("Graph not available. Status: {:?}", response.status),
-                        "status": format!("{:?}", response.status),
-                        "progress": response.progress,
-                    }))
-                    .unwrap_or_else(|_| {
-                        format!("Error: Graph not available. Status: {:?}", response.status)
-                    })
+                    Err(ToolError::graph_unavailable(&id, response.status).into())
                 }
             }
-            Err(e) => serde_json::to_string_pretty(&json!({
-                "error": format!("Failed to get paper info: {}", e),
-            }))
-            .unwrap_or_else(|_| format!("Error: Failed to get paper info: {}", e)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    #[tool(
+        description = "Format a paper as a reference in the APA, MLA, Chicago or IEEE citation style."
+    )]
+    pub async fn format_citation(
+        &self,
+        Parameters(FormatCitationRequest {
+            id,
+            style,
+            fresh_only,
+        }): Parameters<FormatCitationRequest>,
+    ) -> ToolResult {
+        let style = match style.parse::<CitationStyle>() {
+            Ok(style) => style,
+            Err(e) => return Err(ToolError::new("invalid_input", e.to_string()).into()),
+        };
+        match self
+            .fetch_graph("Failed to format citation", &id, fresh_only)
+            .await
+        {
+            Ok(response) => match response
+                .graph_json
+                .as_ref()
+                .and_then(|graph| graph.nodes.get(&graph.start_id))
+            {
+                Some(paper) => Ok(serde_json::to_string_pretty(&json!({
+                    "style": style.to_string(),
+                    "citation": paper.cite(style),
+                }))
+                .unwrap_or_else(|_| paper.cite(style))),
+                None => Err(ToolError::graph_unavailable(&id, response.status).into()),
+            },
+            Err(e) => Err(e.into()),
         }
     }
 
     #[tool(description = "Get the remaining number of API requests available for your API key.")]
-    pub async fn get_remaining_usages(&self) -> String {
-        let client = ConnectedPapers::with_api_key(&self.api_key);
+    pub async fn get_remaining_usages(&self) -> ToolResult {
+        let client = &self.client;
 
         match client.get_remaining_usages().await {
-            Ok(remaining) => serde_json::to_string_pretty(&json!({
-                "remaining_usages": remaining,
-            }))
-            .unwrap_or_else(|_| format!("Remaining usages: {}", remaining)),
-            Err(e) => serde_json::to_string_pretty(&json!({
-                "error": format!("Failed to get remaining usages: {}", e),
-            }))
-            .unwrap_or_else(|_| format!("Error: Failed to get remaining usages: {}", e)),
+            Ok(remaining) => {
+                self.budget.set_remaining(Some(remaining));
+                Ok(serde_json::to_string_pretty(&json!({
+                    "remaining_usages": remaining,
+                }))
+                .unwrap_or_else(|_| format!("Remaining usages: {}", remaining)))
+            }
+            Err(e) => Err(ToolError::from_error("Failed to get remaining usages", &e).into()),
         }
     }
 
+    #[tool(
+        description = "Get the graph fetches made in this session, the session budget and the remaining requests last reported by Connected Papers. Makes no request."
+    )]
+    pub async fn get_usage(&self) -> ToolResult {
+        let usage = self.budget.usage();
+        Ok(serde_json::to_string_pretty(&usage).unwrap_or_else(|_| format!("{:?}", usage)))
+    }
+
     #[tool(description = "Get a list of paper IDs that have free access (no API key required).")]
-    pub async fn get_free_access_papers(&self) -> String {
-        let client = ConnectedPapers::with_api_key(&self.api_key);
+    pub async fn get_free_access_papers(&self) -> ToolResult {
+        let client = &self.client;
 
         match client.get_free_access_papers().await {
-            Ok(papers) => serde_json::to_string_pretty(&json!({
+            Ok(papers) => Ok(serde_json::to_string_pretty(&json!({
                 "free_access_papers": papers,
                 "count": papers.len(),
             }))
-            .unwrap_or_else(|_| format!("Free access papers count: {}", papers.len())),
-            Err(e) => serde_json::to_string_pretty(&json!({
-                "error": format!("Failed to get free access papers: {}", e),
-            }))
-            .unwrap_or_else(|_| format!("Error: Failed to get free access papers: {}", e)),
+            .unwrap_or_else(|_| format!("Free access papers count: {}", papers.len()))),
+            Err(e) => Err(ToolError::from_error("Failed to get free access papers", &e).into()),
         }
     }
 }
@@ -252,30 +354,55 @@ impl ServerHandler for ConnectedPapersMCP {
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
                 .build(),
-            instructions: Some("MCP Server for Connected Papers. Provides tools to query paper graphs, get paper information, check API usage, and access free papers.".to_owned()),
+            instructions: Some("MCP Server for Connected Papers. Provides tools to query paper graphs, get paper information, format citations, check API and session usage, and access free papers.".to_owned()),
         }
     }
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
+    let options = match cli::parse(std::env::args().skip(1))? {
+        Command::Serve(options) => options,
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+        Command::Version => {
+            println!("connected-papers-mcp {}", env!("CARGO_PKG_VERSION"));
+            return Ok(());
+        }
+    };
+
+    let stderr_layer =
+        (!options.quiet).then(|| fmt::layer().with_writer(std::io::stderr).with_ansi(false));
+    let file_layer = options
+        .log_appender()?
+        .map(|appender| fmt::layer().with_writer(appender).with_ansi(false));
+    tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
+        .with(stderr_layer)
+        .with(file_layer)
         .init();
 
     tracing::info!("Starting Connected Papers MCP server");
 
+    match serve().await {
+        // Returning the error would print it to stderr
+        Err(e) if options.quiet => {
+            tracing::error!("Error: {:?}", e);
+            std::process::exit(1);
+        }
+        result => result.inspect_err(|e| tracing::error!("Error: {:?}", e)),
+    }
+}
+
+async fn serve() -> anyhow::Result<()> {
     let api_key =
         std::env::var("CONNECTED_PAPERS_API_KEY").unwrap_or_else(|_| "TEST_TOKEN".to_string());
 
-    let service = ConnectedPapersMCP::new(&api_key)
+    let service = ConnectedPapersMCP::new(&api_key, Budget::from_env()?)?
         .serve(stdio())
-        .await
-        .inspect_err(|e| {
-            tracing::error!("Error: {:?}", e);
-        })?;
+        .await?;
 
     service.waiting().await?;
 
@@ -0,0 +1,126 @@
+//! Node.js bindings
+//!
+//! Exposes [`ConnectedPapers`](connected_papers::ConnectedPapers) and
+//! [`SemanticScholar`](connected_papers::SemanticScholar) to JavaScript with promise-based
+//! methods, together with the exporters. Models cross the boundary as JSON values and reach
+//! JavaScript as plain objects and arrays, following the serialized layout of the crate.
+//!
+//! Build with the [napi-rs](https://napi.rs) CLI, which enables the `napi` feature:
+//!
+//! ```sh
+//! cd node && npm install && npm run build
+//! ```
+#![cfg(feature = "napi")]
+
+use connected_papers::{
+    Graph, PaperField, PaperSearchParamBuilder, S2NestedPaper, to_bibtex, to_markdown,
+};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use serde_json::Value;
+
+/// Convert an error of the crate into a JavaScript error
+fn to_napi(error: impl ToString) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+/// Convert a serializable value into a JSON value
+fn to_value<T: serde::Serialize>(value: &T) -> Result<Value> {
+    serde_json::to_value(value).map_err(to_napi)
+}
+
+/// Convert a JSON value into a deserializable value
+fn from_value<T: serde::de::DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))
+}
+
+/// Connected Papers client
+#[napi(js_name = "ConnectedPapers")]
+pub struct JsConnectedPapers {
+    inner: connected_papers::ConnectedPapers,
+}
+
+#[napi]
+impl JsConnectedPapers {
+    /// Create a client, reading the API key from `CONNECTED_PAPERS_API_KEY` when not given
+    #[napi(constructor)]
+    pub fn new(api_key: Option<String>) -> Result<Self> {
+        let inner = match api_key {
+            Some(api_key) => connected_papers::ConnectedPapers::with_api_key(&api_key),
+            None => connected_papers::ConnectedPapers::from_env().map_err(to_napi)?,
+        };
+        Ok(Self { inner })
+    }
+
+    /// Graph response of a paper
+    #[napi]
+    pub async fn get_graph(&self, paper_id: String, fresh_only: Option<bool>) -> Result<Value> {
+        let response = self
+            .inner
+            .get_graph(&paper_id, fresh_only.unwrap_or_default())
+            .await
+            .map_err(to_napi)?;
+        to_value(&response)
+    }
+
+    /// Number of remaining API calls
+    #[napi]
+    pub async fn get_remaining_usages(&self) -> Result<i64> {
+        let usages = self.inner.get_remaining_usages().await.map_err(to_napi)?;
+        Ok(usages as i64)
+    }
+}
+
+/// Semantic Scholar client
+#[napi(js_name = "SemanticScholar")]
+pub struct JsSemanticScholar {
+    inner: connected_papers::SemanticScholar,
+}
+
+#[napi]
+impl JsSemanticScholar {
+    /// Create a client, anonymous unless an API key is given
+    #[napi(constructor)]
+    pub fn new(api_key: Option<String>) -> Self {
+        let inner = match api_key {
+            Some(api_key) => connected_papers::SemanticScholar::with_api_key(&api_key),
+            None => connected_papers::SemanticScholar::default(),
+        };
+        Self { inner }
+    }
+
+    /// Relevance search, resolving to an array of papers
+    #[napi]
+    pub async fn search_papers(&self, query: String, limit: Option<u32>) -> Result<Value> {
+        let mut builder = PaperSearchParamBuilder::new(&query);
+        for field in PaperField::SUMMARY {
+            builder.field(field);
+        }
+        if let Some(limit) = limit {
+            let limit = u8::try_from(limit)
+                .map_err(|_| Error::new(Status::InvalidArg, "limit must be at most 100"))?;
+            builder.limit(limit);
+        }
+        let param = builder.build().map_err(to_napi)?;
+        let response = self.inner.query(&param).await.map_err(to_napi)?;
+        to_value(&response.data())
+    }
+}
+
+/// Write a graph as a GraphML document
+#[napi]
+pub fn graph_to_graphml(graph: Value) -> Result<String> {
+    Ok(from_value::<Graph>(graph)?.to_graphml())
+}
+
+/// Write Semantic Scholar papers as a Markdown reading list
+#[napi]
+pub fn papers_to_markdown(papers: Value) -> Result<String> {
+    Ok(to_markdown(&from_value::<Vec<S2NestedPaper>>(papers)?))
+}
+
+/// Write Semantic Scholar papers as a BibTeX bibliography
+#[napi]
+pub fn papers_to_bibtex(papers: Value) -> Result<String> {
+    Ok(to_bibtex(&from_value::<Vec<S2NestedPaper>>(papers)?))
+}
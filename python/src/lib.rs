@@ -0,0 +1,247 @@
+//! Python bindings
+//!
+//! Exposes blocking facades of [`ConnectedPapers`] and [`SemanticScholar`] together with the
+//! exporters to Python. Models cross the boundary as JSON and reach Python as plain `dict`
+//! and `list` objects, following the serialized layout of the crate.
+//!
+//! Build with [maturin](https://www.maturin.rs), which enables the `python` feature:
+//!
+//! ```sh
+//! cd python && maturin develop --release
+//! ```
+#![cfg(feature = "python")]
+
+use connected_papers::{
+    ConnectedPapers, Graph, PaperBatchParamBuilder, PaperField, PaperId, PaperSearchParamBuilder,
+    PaperTitleSearchParamBuilder, S2NestedPaper, SemanticScholar, ss::paper_id::PaperIdSearchParam,
+    to_bibtex, to_markdown,
+};
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{future::Future, sync::OnceLock};
+use tokio::runtime::Runtime;
+
+create_exception!(connected_papers, ConnectedPapersError, PyException);
+
+/// Runtime shared by every blocking call
+fn runtime() -> PyResult<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = Runtime::new().map_err(|e| ConnectedPapersError::new_err(e.to_string()))?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Run a future to completion with the GIL released
+fn block_on<F, T>(py: Python<'_>, future: F) -> PyResult<T>
+where
+    F: Future<Output = connected_papers::Result<T>> + Send,
+    T: Send,
+{
+    let runtime = runtime()?;
+    py.allow_threads(|| runtime.block_on(future))
+        .map_err(|e| ConnectedPapersError::new_err(e.to_string()))
+}
+
+/// Convert a serializable value into Python objects through JSON
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json =
+        serde_json::to_string(value).map_err(|e| ConnectedPapersError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// Convert Python objects into a deserializable value through JSON
+fn from_python<T: DeserializeOwned>(value: &Bound<'_, PyAny>) -> PyResult<T> {
+    let json: String = value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract()?;
+    serde_json::from_str(&json).map_err(|e| ConnectedPapersError::new_err(e.to_string()))
+}
+
+/// Blocking Connected Papers client
+#[pyclass(name = "ConnectedPapers", module = "connected_papers", frozen)]
+struct PyConnectedPapers {
+    inner: ConnectedPapers,
+}
+
+#[pymethods]
+impl PyConnectedPapers {
+    /// Create a client, reading the API key from `CONNECTED_PAPERS_API_KEY` when not given
+    #[new]
+    #[pyo3(signature = (api_key=None))]
+    fn new(api_key: Option<&str>) -> PyResult<Self> {
+        let inner = match api_key {
+            Some(api_key) => ConnectedPapers::with_api_key(api_key),
+            None => ConnectedPapers::from_env()
+                .map_err(|e| ConnectedPapersError::new_err(e.to_string()))?,
+        };
+        Ok(Self { inner })
+    }
+
+    /// Graph response of a paper, as a dict
+    #[pyo3(signature = (paper_id, fresh_only=false))]
+    fn get_graph(&self, py: Python<'_>, paper_id: &str, fresh_only: bool) -> PyResult<PyObject> {
+        let response = block_on(py, self.inner.get_graph(paper_id, fresh_only))?;
+        to_python(py, &response)
+    }
+
+    /// Graph of the paper best matching a title, or `None` when no paper matches
+    ///
+    /// The title is resolved with `semantic_scholar`, or an unauthenticated client when not
+    /// given. The result is a dict with the match `score`, the matched `paper` and the graph
+    /// `response`.
+    #[pyo3(signature = (title, fresh_only=false, semantic_scholar=None))]
+    fn get_graph_by_title(
+        &self,
+        py: Python<'_>,
+        title: &str,
+        fresh_only: bool,
+        semantic_scholar: Option<PyRef<'_, PySemanticScholar>>,
+    ) -> PyResult<Option<PyObject>> {
+        let semantic_scholar = match semantic_scholar {
+            Some(client) => client.inner.clone(),
+            None => self.inner.semantic_scholar(),
+        };
+        let found = block_on(
+            py,
+            self.inner
+                .get_graph_by_title(&semantic_scholar, title, fresh_only),
+        )?;
+        found
+            .map(|found| {
+                to_python(
+                    py,
+                    &serde_json::json!({
+                        "score": found.score,
+                        "paper": found.paper,
+                        "response": found.response,
+                    }),
+                )
+            })
+            .transpose()
+    }
+
+    /// Number of remaining API calls
+    fn get_remaining_usages(&self, py: Python<'_>) -> PyResult<u64> {
+        block_on(py, self.inner.get_remaining_usages())
+    }
+
+    /// IDs of the free access papers
+    fn get_free_access_papers(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        block_on(py, self.inner.get_free_access_papers())
+    }
+}
+
+/// Blocking Semantic Scholar client
+#[pyclass(name = "SemanticScholar", module = "connected_papers", frozen)]
+struct PySemanticScholar {
+    inner: SemanticScholar,
+}
+
+#[pymethods]
+impl PySemanticScholar {
+    /// Create a client, anonymous unless an API key is given
+    #[new]
+    #[pyo3(signature = (api_key=None))]
+    fn new(api_key: Option<&str>) -> Self {
+        let inner = match api_key {
+            Some(api_key) => SemanticScholar::with_api_key(api_key),
+            None => SemanticScholar::default(),
+        };
+        Self { inner }
+    }
+
+    /// Relevance search, returning a list of paper dicts
+    #[pyo3(signature = (query, limit=None))]
+    fn search(&self, py: Python<'_>, query: &str, limit: Option<u8>) -> PyResult<PyObject> {
+        let mut builder = PaperSearchParamBuilder::new(query);
+        for field in PaperField::SUMMARY {
+            builder.field(field);
+        }
+        if let Some(limit) = limit {
+            builder.limit(limit);
+        }
+        let param = builder
+            .build()
+            .map_err(|e| ConnectedPapersError::new_err(e.to_string()))?;
+        let response = block_on(py, self.inner.query(&param))?;
+        to_python(py, &response.data())
+    }
+
+    /// Paper best matching a title, or `None`
+    fn match_title(&self, py: Python<'_>, title: &str) -> PyResult<Option<PyObject>> {
+        let mut builder = PaperTitleSearchParamBuilder::new(title);
+        for field in PaperField::SUMMARY {
+            builder.field(field);
+        }
+        let param = builder
+            .build()
+            .map_err(|e| ConnectedPapersError::new_err(e.to_string()))?;
+        let matched = block_on(py, self.inner.query(&param))?;
+        matched
+            .map(|matched| to_python(py, &matched.paper))
+            .transpose()
+    }
+
+    /// Details of a paper by ID, e.g. an S2 ID or `DOI:10.18653/v1/N18-3011`, or `None`
+    fn paper(&self, py: Python<'_>, paper_id: &str) -> PyResult<Option<PyObject>> {
+        let mut param = PaperIdSearchParam::new(&PaperId::id(paper_id));
+        for field in PaperField::SUMMARY {
+            param.add_field(field);
+        }
+        let paper = block_on(py, self.inner.query(&param))?;
+        paper.map(|paper| to_python(py, &paper)).transpose()
+    }
+
+    /// Details of up to 500 papers by ID, `None` for the IDs Semantic Scholar does not know
+    fn papers(&self, py: Python<'_>, paper_ids: Vec<String>) -> PyResult<PyObject> {
+        let mut builder = PaperBatchParamBuilder::default();
+        for paper_id in paper_ids {
+            builder.id(PaperId::id(paper_id));
+        }
+        for field in PaperField::SUMMARY {
+            builder.field(field);
+        }
+        let param = builder
+            .build()
+            .map_err(|e| ConnectedPapersError::new_err(e.to_string()))?;
+        let papers = block_on(py, self.inner.query(&param))?;
+        to_python(py, &papers)
+    }
+}
+
+/// Write a graph dict as a GraphML document
+#[pyfunction]
+fn graph_to_graphml(graph: &Bound<'_, PyAny>) -> PyResult<String> {
+    Ok(from_python::<Graph>(graph)?.to_graphml())
+}
+
+/// Write Semantic Scholar paper dicts as a Markdown reading list
+#[pyfunction]
+fn papers_to_markdown(papers: &Bound<'_, PyAny>) -> PyResult<String> {
+    Ok(to_markdown(&from_python::<Vec<S2NestedPaper>>(papers)?))
+}
+
+/// Write Semantic Scholar paper dicts as a BibTeX bibliography
+#[pyfunction]
+fn papers_to_bibtex(papers: &Bound<'_, PyAny>) -> PyResult<String> {
+    Ok(to_bibtex(&from_python::<Vec<S2NestedPaper>>(papers)?))
+}
+
+#[pymodule]
+#[pyo3(name = "connected_papers")]
+fn connected_papers_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add(
+        "ConnectedPapersError",
+        m.py().get_type::<ConnectedPapersError>(),
+    )?;
+    m.add_class::<PyConnectedPapers>()?;
+    m.add_class::<PySemanticScholar>()?;
+    m.add_function(wrap_pyfunction!(graph_to_graphml, m)?)?;
+    m.add_function(wrap_pyfunction!(papers_to_markdown, m)?)?;
+    m.add_function(wrap_pyfunction!(papers_to_bibtex, m)?)?;
+    Ok(())
+}
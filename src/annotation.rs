@@ -0,0 +1,313 @@
+//! Graph annotations
+//!
+//! Tags, notes and a read flag per node, kept in a JSON file together with the graph they
+//! annotate. Refreshing the graph with a new fetch keeps the annotations of the papers it
+//! still contains, matched by paper id, which makes an annotated graph a reading list that
+//! follows the graph as it changes.
+
+use crate::{Graph, error::Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// Annotation of a single node
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeAnnotation {
+    /// Free-form tags, e.g. `to-cite` or `methods`
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    /// Free-form note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Whether the paper was read
+    #[serde(default)]
+    pub read: bool,
+}
+
+impl NodeAnnotation {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.note.is_none() && !self.read
+    }
+}
+
+/// Annotations of the nodes of a graph, keyed by paper id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Annotations {
+    nodes: BTreeMap<String, NodeAnnotation>,
+}
+
+impl Annotations {
+    /// Create empty annotations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The annotation of a paper, if any
+    pub fn get(&self, paper_id: &str) -> Option<&NodeAnnotation> {
+        self.nodes.get(paper_id)
+    }
+
+    /// Iterate over the annotated papers and their annotations
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NodeAnnotation)> {
+        self.nodes
+            .iter()
+            .map(|(paper_id, annotation)| (paper_id.as_str(), annotation))
+    }
+
+    /// Add a tag to a paper
+    pub fn tag(&mut self, paper_id: &str, tag: &str) {
+        self.entry(paper_id).tags.insert(tag.to_owned());
+    }
+
+    /// Remove a tag from a paper
+    pub fn untag(&mut self, paper_id: &str, tag: &str) {
+        self.update(paper_id, |annotation| {
+            annotation.tags.remove(tag);
+        });
+    }
+
+    /// Set the note of a paper, removing it with `None`
+    pub fn set_note(&mut self, paper_id: &str, note: Option<&str>) {
+        match note {
+            Some(note) => self.entry(paper_id).note = Some(note.to_owned()),
+            None => self.update(paper_id, |annotation| annotation.note = None),
+        }
+    }
+
+    /// Mark a paper as read or unread
+    pub fn set_read(&mut self, paper_id: &str, read: bool) {
+        if read {
+            self.entry(paper_id).read = true;
+        } else {
+            self.update(paper_id, |annotation| annotation.read = false);
+        }
+    }
+
+    /// Remove the annotation of a paper, returning it
+    pub fn remove(&mut self, paper_id: &str) -> Option<NodeAnnotation> {
+        self.nodes.remove(paper_id)
+    }
+
+    /// Ids of the papers with a tag
+    pub fn tagged(&self, tag: &str) -> Vec<&str> {
+        self.iter()
+            .filter(|(_, annotation)| annotation.tags.contains(tag))
+            .map(|(paper_id, _)| paper_id)
+            .collect()
+    }
+
+    /// Ids of the nodes of a graph not marked as read, e.g. the rest of a reading list
+    pub fn unread<'a>(&self, graph: &'a Graph) -> Vec<&'a str> {
+        let mut unread = graph
+            .nodes
+            .keys()
+            .filter(|id| !self.get(id).is_some_and(|annotation| annotation.read))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        unread.sort_unstable();
+        unread
+    }
+
+    /// Suggest up to `limit` tags for a paper from the [keyphrases](crate::keywords) of its
+    /// title and abstract
+    ///
+    /// Tags already used on other papers come first, so that suggestions converge on the
+    /// existing vocabulary; tags the paper already has are left out.
+    #[cfg(feature = "analysis")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+    pub fn suggest_tags<P: crate::PaperLike>(&self, paper: &P, limit: usize) -> Vec<String> {
+        let used = self
+            .nodes
+            .values()
+            .flat_map(|annotation| &annotation.tags)
+            .collect::<BTreeSet<_>>();
+        let own = self.get(paper.id()).map(|annotation| &annotation.tags);
+        let (mut known, new): (Vec<_>, Vec<_>) =
+            crate::keywords(std::slice::from_ref(paper), usize::MAX)
+                .iter()
+                .map(crate::Keyword::tag)
+                .filter(|tag| !own.is_some_and(|own| own.contains(tag)))
+                .partition(|tag| used.contains(tag));
+        known.extend(new);
+        known.truncate(limit);
+        known
+    }
+
+    fn entry(&mut self, paper_id: &str) -> &mut NodeAnnotation {
+        self.nodes.entry(paper_id.to_owned()).or_default()
+    }
+
+    /// Change an existing annotation, dropping it once empty
+    fn update(&mut self, paper_id: &str, change: impl FnOnce(&mut NodeAnnotation)) {
+        if let Some(annotation) = self.nodes.get_mut(paper_id) {
+            change(annotation);
+            if annotation.is_empty() {
+                self.nodes.remove(paper_id);
+            }
+        }
+    }
+
+    /// Move the annotations of papers whose node id changed to their new id
+    ///
+    /// Returns the ids of the annotated papers the graph does not contain; their annotations
+    /// are kept in case the papers come back in a later fetch.
+    fn rekey(&mut self, graph: &Graph) -> Vec<String> {
+        let mut missing = Vec::new();
+        let ids = self.nodes.keys().cloned().collect::<Vec<_>>();
+        for id in ids {
+            if graph.nodes.contains_key(&id) {
+                continue;
+            }
+            let moved = graph
+                .nodes
+                .iter()
+                .find(|(key, paper)| paper.paper_id == id && !self.nodes.contains_key(*key))
+                .map(|(key, _)| key.clone());
+            match moved {
+                Some(key) => {
+                    let annotation = self.nodes.remove(&id).expect("id is annotated");
+                    self.nodes.insert(key, annotation);
+                }
+                None => missing.push(id),
+            }
+        }
+        missing
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stored {
+    graph: Graph,
+    #[serde(default)]
+    annotations: Annotations,
+}
+
+/// A graph with its annotations, persisted together as a JSON file
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedGraph {
+    path: Option<PathBuf>,
+    pub graph: Graph,
+    pub annotations: Annotations,
+}
+
+impl AnnotatedGraph {
+    /// Create an in-memory annotated graph without annotations
+    pub fn new(graph: Graph) -> Self {
+        Self {
+            path: None,
+            graph,
+            annotations: Annotations::new(),
+        }
+    }
+
+    /// Open the annotated graph stored at `path`
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let stored: Stored = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        Ok(Self {
+            path: Some(path),
+            graph: stored.graph,
+            annotations: stored.annotations,
+        })
+    }
+
+    /// Store the annotated graph at `path` from now on
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// The file backing this graph, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Write the graph and its annotations to their file; in-memory graphs are left untouched
+    pub fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.path {
+            let stored = Stored {
+                graph: self.graph.clone(),
+                annotations: self.annotations.clone(),
+            };
+            std::fs::write(path, serde_json::to_string_pretty(&stored)?)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the graph with a newer fetch, keeping the annotations of its papers
+    ///
+    /// Annotations follow their paper by id. Returns the ids of the annotated papers missing
+    /// from the new graph, whose annotations are kept.
+    pub fn refresh(&mut self, graph: Graph) -> Vec<String> {
+        self.graph = graph;
+        self.annotations.rekey(&self.graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Paper;
+
+    #[test]
+    fn test_annotations() {
+        let mut annotations = Annotations::new();
+        annotations.tag("seed", "to-cite");
+        annotations.tag("older", "to-cite");
+        annotations.set_note("older", Some("Introduces the method"));
+        annotations.set_read("seed", true);
+        assert_eq!(annotations.tagged("to-cite"), vec!["older", "seed"]);
+        assert_eq!(
+            annotations.unread(&Graph::example()),
+            vec!["newer", "older"]
+        );
+
+        annotations.untag("seed", "to-cite");
+        annotations.set_read("seed", false);
+        assert!(annotations.get("seed").is_none());
+        assert_eq!(
+            serde_json::to_value(&annotations).unwrap(),
+            serde_json::json!({ "older": {
+                "tags": ["to-cite"],
+                "note": "Introduces the method",
+                "read": false
+            } })
+        );
+    }
+
+    #[cfg(feature = "analysis")]
+    #[test]
+    fn test_suggest_tags() {
+        let mut paper = Paper::new("a");
+        paper.title = Some("Citation graphs".to_owned());
+        paper.abstract_ = Some("Literature maps from co-citation analysis.".to_owned());
+        let mut annotations = Annotations::new();
+        annotations.tag("other", "co-citation-analysis");
+        annotations.tag("a", "literature-maps");
+        assert_eq!(
+            annotations.suggest_tags(&paper, 2),
+            vec!["co-citation-analysis", "citation-graphs"]
+        );
+    }
+
+    #[test]
+    fn test_refresh() {
+        let mut annotated = AnnotatedGraph::new(Graph::example());
+        annotated.annotations.tag("newer", "methods");
+        annotated.annotations.tag("older", "survey");
+
+        let mut graph = Graph::example();
+        graph.nodes.remove("older");
+        let mut renamed = graph.nodes.remove("newer").unwrap();
+        renamed.id = "newer-v2".to_owned();
+        graph.nodes.insert("newer-v2".to_owned(), renamed);
+        graph.nodes.insert("added".to_owned(), Paper::new("added"));
+
+        assert_eq!(annotated.refresh(graph), vec!["older".to_owned()]);
+        assert_eq!(annotated.annotations.tagged("methods"), vec!["newer-v2"]);
+        assert_eq!(annotated.annotations.tagged("survey"), vec!["older"]);
+    }
+}
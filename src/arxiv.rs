@@ -0,0 +1,199 @@
+//! arXiv identifiers
+//!
+//! Semantic Scholar treats `ARXIV:2106.15928v1` and `ARXIV:2106.15928` differently, while
+//! both name the same work. [`ArxivId`] parses the identifiers found in the wild, prefixed,
+//! versioned or as links, into the bare identifier and its version, to normalize them one
+//! way or the other and to compare works across versions.
+
+use crate::{
+    error::{Error, Result},
+    ss::PaperId,
+};
+use std::str::FromStr;
+
+/// Whether normalized arXiv identifiers keep their version
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArxivVersion {
+    /// Drop the version, referring to the latest one
+    #[default]
+    Strip,
+    /// Keep the version if any
+    Preserve,
+}
+
+/// Parsed arXiv identifier
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArxivId {
+    /// Identifier without its version, e.g. `2106.15928` or `hep-th/9901001`
+    pub id: String,
+    /// Version, e.g. `1` for `2106.15928v1`
+    pub version: Option<u32>,
+}
+
+impl ArxivId {
+    /// Parse an identifier such as `2106.15928v1`, `arXiv:2106.15928`, `hep-th/9901001` or
+    /// `https://arxiv.org/abs/2106.15928v2`
+    pub fn parse(id: &str) -> Result<Self> {
+        let invalid = || Error::InvalidParameter(format!("invalid arXiv identifier: {}", id));
+        let mut rest = id.trim();
+        for prefix in ["https://", "http://", "www.", "export.", "arxiv.org/"] {
+            rest = strip_prefix_ignore_case(rest, prefix).unwrap_or(rest);
+        }
+        for prefix in ["abs/", "pdf/", "html/"] {
+            rest = strip_prefix_ignore_case(rest, prefix).unwrap_or(rest);
+        }
+        rest = strip_prefix_ignore_case(rest, "arxiv:").unwrap_or(rest);
+        let rest = rest.trim_end_matches('/').trim_end_matches(".pdf");
+
+        let (base, version) = match rest.rsplit_once(['v', 'V']) {
+            Some((base, version))
+                if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                (base, Some(version.parse().map_err(|_| invalid())?))
+            }
+            _ => (rest, None),
+        };
+        if !is_new_style(base) && !is_old_style(base) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            id: base.to_owned(),
+            version,
+        })
+    }
+
+    /// The identifier with or without its version
+    pub fn normalized(&self, version: ArxivVersion) -> String {
+        match (version, self.version) {
+            (ArxivVersion::Preserve, Some(v)) => format!("{}v{}", self.id, v),
+            _ => self.id.clone(),
+        }
+    }
+
+    /// Whether both identifiers refer to the same work, whatever their versions
+    pub fn same_work(&self, other: &ArxivId) -> bool {
+        self.id == other.id
+    }
+}
+
+impl FromStr for ArxivId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for ArxivId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.normalized(ArxivVersion::Preserve))
+    }
+}
+
+impl PaperId {
+    /// Create an arXiv ID for a given version of a work, e.g. `ARXIV:2106.15928v2`
+    ///
+    /// Any version already in `id` is replaced.
+    pub fn arxiv_versioned(id: &str, version: u32) -> Result<Self> {
+        let id = ArxivId::parse(id)?;
+        Ok(PaperId::ArXiv(format!("{}v{}", id.id, version)))
+    }
+
+    /// Create an arXiv ID normalized with or without its version
+    pub fn arxiv_normalized(id: &str, version: ArxivVersion) -> Result<Self> {
+        Ok(PaperId::ArXiv(ArxivId::parse(id)?.normalized(version)))
+    }
+
+    /// Whether both IDs refer to the same work
+    ///
+    /// arXiv IDs and arXiv URLs are compared ignoring their versions; other IDs must be equal.
+    pub fn same_work(&self, other: &PaperId) -> bool {
+        match (self.arxiv_id(), other.arxiv_id()) {
+            (Some(a), Some(b)) => a.same_work(&b),
+            _ => self == other,
+        }
+    }
+
+    /// The arXiv identifier of an arXiv ID or arXiv URL
+    fn arxiv_id(&self) -> Option<ArxivId> {
+        match self {
+            PaperId::ArXiv(id) => ArxivId::parse(id).ok(),
+            PaperId::URL(url) if url.to_lowercase().contains("arxiv.org/") => {
+                ArxivId::parse(url).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    text.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &text[prefix.len()..])
+}
+
+/// `YYMM.NNNN` or `YYMM.NNNNN`, used since April 2007
+fn is_new_style(id: &str) -> bool {
+    id.split_once('.').is_some_and(|(month, number)| {
+        month.len() == 4
+            && matches!(number.len(), 4 | 5)
+            && month
+                .chars()
+                .chain(number.chars())
+                .all(|c| c.is_ascii_digit())
+    })
+}
+
+/// `archive(.subject)/YYMMNNN`, e.g. `hep-th/9901001` or `math.GT/0309136`
+fn is_old_style(id: &str) -> bool {
+    id.split_once('/').is_some_and(|(archive, number)| {
+        !archive.is_empty()
+            && archive
+                .chars()
+                .all(|c| c.is_ascii_alphabetic() || matches!(c, '-' | '.'))
+            && number.len() == 7
+            && number.chars().all(|c| c.is_ascii_digit())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arxiv_id() {
+        let id = ArxivId::parse("arXiv:2106.15928v2").unwrap();
+        assert_eq!(id.id, "2106.15928");
+        assert_eq!(id.version, Some(2));
+        assert_eq!(id.normalized(ArxivVersion::Strip), "2106.15928");
+        assert_eq!(id.to_string(), "2106.15928v2");
+        assert_eq!(
+            "https://arxiv.org/pdf/hep-th/9901001v1.pdf"
+                .parse::<ArxivId>()
+                .unwrap(),
+            ArxivId {
+                id: "hep-th/9901001".to_owned(),
+                version: Some(1),
+            }
+        );
+        assert!(ArxivId::parse("math.GT/0309136").is_ok());
+        assert!(ArxivId::parse("2106.159").is_err());
+        assert!(ArxivId::parse("10.1000/xyz").is_err());
+
+        assert_eq!(
+            PaperId::arxiv_versioned("2106.15928v1", 3).unwrap(),
+            PaperId::arxiv("2106.15928v3")
+        );
+        assert_eq!(
+            PaperId::arxiv_normalized("ARXIV:2106.15928v1", ArxivVersion::Strip).unwrap(),
+            PaperId::arxiv("2106.15928")
+        );
+        assert!(PaperId::arxiv("2106.15928v1").same_work(&PaperId::arxiv("2106.15928")));
+        assert!(
+            PaperId::url("https://arxiv.org/abs/2106.15928v2")
+                .same_work(&PaperId::arxiv("2106.15928"))
+        );
+        assert!(!PaperId::arxiv("2106.15928").same_work(&PaperId::arxiv("2106.15929")));
+        assert!(!PaperId::doi("10.1000/a").same_work(&PaperId::doi("10.1000/b")));
+    }
+}
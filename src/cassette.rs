@@ -0,0 +1,156 @@
+//! Record and replay of API responses
+//!
+//! A [`Cassette`] stores every response in a directory, one JSON file per request keyed by a
+//! hash of its method, URL and body. In replay mode the recorded responses are served without
+//! touching the network, which keeps integration tests and demos deterministic and free of
+//! quota usage.
+
+use crate::{
+    error::{Error, Result},
+    utils::fnv1a,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// How a [`Cassette`] treats requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests to the API and write every response to the cassette
+    Record,
+    /// Serve responses from the cassette, failing requests that were never recorded
+    Replay,
+}
+
+/// Directory of recorded API responses
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    dir: PathBuf,
+    mode: CassetteMode,
+}
+
+/// A recorded response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Recording {
+    pub(crate) method: String,
+    pub(crate) url: String,
+    pub(crate) status: u16,
+    pub(crate) body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) request_id: Option<String>,
+}
+
+impl Cassette {
+    /// Record responses into the given directory
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Record,
+        }
+    }
+
+    /// Replay responses from the given directory
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Replay,
+        }
+    }
+
+    /// The directory holding the recorded responses
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether the cassette records or replays
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Write the response to a request by hand, e.g. a captured payload served in tests
+    ///
+    /// `method` is upper case, `url` the full request URL with its query string and `body` the
+    /// request body, empty for `GET`.
+    pub fn insert(
+        &self,
+        method: &str,
+        url: &str,
+        body: &[u8],
+        status: u16,
+        response: &str,
+    ) -> Result<()> {
+        self.save(
+            &Self::key(method, url, body),
+            &Recording {
+                method: method.to_owned(),
+                url: url.to_owned(),
+                status,
+                body: response.to_owned(),
+                request_id: None,
+            },
+        )
+    }
+
+    /// Stable key of a request, the 64-bit FNV-1a hash of its method, URL and body
+    pub(crate) fn key(method: &str, url: &str, body: &[u8]) -> String {
+        fnv1a(&[method.as_bytes(), b" ", url.as_bytes(), b"\n", body])
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Load the recording for the given key, if any
+    #[cfg(any(
+        feature = "connected-papers",
+        feature = "semantic-scholar",
+        feature = "crossref"
+    ))]
+    pub(crate) fn load(&self, key: &str) -> Result<Option<Recording>> {
+        match std::fs::read_to_string(self.path(key)) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the recording for the given key
+    pub(crate) fn save(&self, key: &str, recording: &Recording) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string_pretty(recording)?;
+        std::fs::write(self.path(key), content).map_err(Error::from)
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "connected-papers",
+        feature = "semantic-scholar",
+        feature = "crossref"
+    )
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cassette_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cassette-{}", std::process::id()));
+        let cassette = Cassette::record(&dir);
+        let key = Cassette::key("GET", "https://example.org/paper", b"");
+        assert_eq!(key, Cassette::key("GET", "https://example.org/paper", b""));
+        assert_ne!(key, Cassette::key("POST", "https://example.org/paper", b""));
+
+        let recording = Recording {
+            method: "GET".to_owned(),
+            url: "https://example.org/paper".to_owned(),
+            status: 200,
+            body: "{}".to_owned(),
+            request_id: None,
+        };
+        cassette.save(&key, &recording).unwrap();
+        let replay = Cassette::replay(&dir);
+        assert_eq!(replay.load(&key).unwrap(), Some(recording));
+        assert_eq!(replay.load("missing").unwrap(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
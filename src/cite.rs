@@ -0,0 +1,445 @@
+//! Citation styles
+//!
+//! Formats a paper as a reference in one of the common citation styles from the metadata
+//! exposed by [`PaperLike`]: authors, year, title, venue, volume, pages and DOI. Citations
+//! are plain text, without italics, so that they can be pasted anywhere.
+//!
+//! For exact journal styles, [`to_csl_json`] converts a paper to the CSL-JSON input of
+//! Citation Style Language processors.
+
+use crate::{
+    error::{Error, Result},
+    paper::PaperLike,
+};
+use serde_json::{Map, Value, json};
+use std::str::FromStr;
+
+/// Citation style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CitationStyle {
+    /// American Psychological Association, 7th edition
+    Apa,
+    /// Modern Language Association, 9th edition
+    Mla,
+    /// Chicago Manual of Style, notes and bibliography
+    Chicago,
+    /// Institute of Electrical and Electronics Engineers
+    Ieee,
+}
+
+impl std::fmt::Display for CitationStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CitationStyle::Apa => write!(f, "apa"),
+            CitationStyle::Mla => write!(f, "mla"),
+            CitationStyle::Chicago => write!(f, "chicago"),
+            CitationStyle::Ieee => write!(f, "ieee"),
+        }
+    }
+}
+
+impl FromStr for CitationStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "apa" => Ok(CitationStyle::Apa),
+            "mla" => Ok(CitationStyle::Mla),
+            "chicago" => Ok(CitationStyle::Chicago),
+            "ieee" => Ok(CitationStyle::Ieee),
+            _ => Err(Error::InvalidParameter(format!(
+                "unknown citation style {}, expected apa, mla, chicago or ieee",
+                s
+            ))),
+        }
+    }
+}
+
+/// Format a paper as a reference in the given style
+///
+/// Missing metadata is left out, and the paper id stands in for a missing title.
+pub fn format_citation<P: PaperLike + ?Sized>(paper: &P, style: CitationStyle) -> String {
+    let names = paper
+        .author_names()
+        .into_iter()
+        .filter_map(Name::parse)
+        .collect::<Vec<_>>();
+    let title = paper.title().unwrap_or(paper.id());
+    match style {
+        CitationStyle::Apa => apa(paper, &names, title),
+        CitationStyle::Mla => mla(paper, &names, title),
+        CitationStyle::Chicago => chicago(paper, &names, title),
+        CitationStyle::Ieee => ieee(paper, &names, title),
+    }
+}
+
+/// Convert a paper to a CSL-JSON item, the input of Citation Style Language processors
+///
+/// The item is typed as a journal article since the paper models do not tell journals and
+/// proceedings apart; missing metadata is left out.
+pub fn to_csl_json<P: PaperLike + ?Sized>(paper: &P) -> Value {
+    let mut item = Map::new();
+    item.insert("id".to_owned(), paper.id().into());
+    item.insert("type".to_owned(), "article-journal".into());
+    if let Some(title) = paper.title() {
+        item.insert("title".to_owned(), title.into());
+    }
+    let authors = paper
+        .author_names()
+        .into_iter()
+        .filter_map(Name::parse)
+        .map(|name| {
+            if name.given.is_empty() {
+                json!({ "family": name.family })
+            } else {
+                json!({ "family": name.family, "given": name.given.join(" ") })
+            }
+        })
+        .collect::<Vec<_>>();
+    if !authors.is_empty() {
+        item.insert("author".to_owned(), authors.into());
+    }
+    if let Some(year) = paper.year() {
+        item.insert("issued".to_owned(), json!({ "date-parts": [[year]] }));
+    }
+    let abstract_ = paper.abstract_clean();
+    let fields = [
+        ("container-title", paper.venue()),
+        ("volume", paper.volume()),
+        ("page", paper.pages()),
+        ("DOI", paper.doi()),
+        ("abstract", abstract_.as_deref()),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            item.insert(key.to_owned(), value.into());
+        }
+    }
+    if let Some(link) = paper.primary_link() {
+        item.insert("URL".to_owned(), link.into());
+    }
+    Value::Object(item)
+}
+
+/// An author name split into given names and family name
+///
+/// The last word is taken as the family name, as Semantic Scholar gives names in
+/// western order.
+struct Name<'a> {
+    given: Vec<&'a str>,
+    family: &'a str,
+}
+
+impl<'a> Name<'a> {
+    fn parse(name: &'a str) -> Option<Self> {
+        let mut words = name.split_whitespace().collect::<Vec<_>>();
+        let family = words.pop()?;
+        Some(Self {
+            given: words,
+            family,
+        })
+    }
+
+    /// Initials of the given names, e.g. `W. J.`
+    fn initials(&self) -> String {
+        self.given
+            .iter()
+            .filter_map(|given| given.chars().next())
+            .map(|initial| format!("{}.", initial))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// `Family, G.`
+    fn family_initials(&self) -> String {
+        if self.given.is_empty() {
+            self.family.to_owned()
+        } else {
+            format!("{}, {}", self.family, self.initials())
+        }
+    }
+
+    /// `G. Family`
+    fn initials_family(&self) -> String {
+        if self.given.is_empty() {
+            self.family.to_owned()
+        } else {
+            format!("{} {}", self.initials(), self.family)
+        }
+    }
+
+    /// `Family, Given`
+    fn family_given(&self) -> String {
+        if self.given.is_empty() {
+            self.family.to_owned()
+        } else {
+            format!("{}, {}", self.family, self.given.join(" "))
+        }
+    }
+
+    /// `Given Family`
+    fn given_family(&self) -> String {
+        if self.given.is_empty() {
+            self.family.to_owned()
+        } else {
+            format!("{} {}", self.given.join(" "), self.family)
+        }
+    }
+}
+
+/// End a sentence with a period unless it already ends with a punctuation mark
+fn sentence(text: &str) -> String {
+    if text.ends_with(['.', '?', '!']) {
+        text.to_owned()
+    } else {
+        format!("{}.", text)
+    }
+}
+
+/// `Ammar, W., Groeneveld, D., & Bhagavatula, C. (2018). Title. Venue, 1, 84-91. https://doi.org/...`
+fn apa<P: PaperLike + ?Sized>(paper: &P, names: &[Name], title: &str) -> String {
+    let authors = names.iter().map(Name::family_initials).collect::<Vec<_>>();
+    let authors = match authors.as_slice() {
+        [] => None,
+        [author] => Some(author.clone()),
+        [rest @ .., last] if authors.len() <= 20 => {
+            Some(format!("{}, & {}", rest.join(", "), last))
+        }
+        [.., last] => Some(format!("{}, . . . {}", authors[..19].join(", "), last)),
+    };
+    let year = paper
+        .year()
+        .map_or_else(|| "(n.d.).".to_owned(), |year| format!("({}).", year));
+
+    let mut parts = Vec::new();
+    match authors {
+        Some(authors) => {
+            parts.push(authors);
+            parts.push(year);
+            parts.push(sentence(title));
+        }
+        None => {
+            parts.push(sentence(title));
+            parts.push(year);
+        }
+    }
+    if let Some(venue) = paper.venue() {
+        let mut source = vec![venue.to_owned()];
+        source.extend(paper.volume().map(ToOwned::to_owned));
+        source.extend(paper.pages().map(ToOwned::to_owned));
+        parts.push(sentence(&source.join(", ")));
+    }
+    parts.extend(paper.primary_link());
+    parts.join(" ")
+}
+
+/// `Ammar, Waleed, et al. "Title." Venue, vol. 1, 2018, pp. 84-91. https://doi.org/....`
+fn mla<P: PaperLike + ?Sized>(paper: &P, names: &[Name], title: &str) -> String {
+    let authors = match names {
+        [] => None,
+        [author] => Some(author.family_given()),
+        [first, second] => Some(format!(
+            "{}, and {}",
+            first.family_given(),
+            second.given_family()
+        )),
+        [first, ..] => Some(format!("{}, et al", first.family_given())),
+    };
+
+    let mut parts = Vec::new();
+    parts.extend(authors.as_deref().map(sentence));
+    parts.push(format!("\"{}\"", sentence(title)));
+    let mut source = Vec::new();
+    source.extend(paper.venue().map(ToOwned::to_owned));
+    source.extend(paper.volume().map(|volume| format!("vol. {}", volume)));
+    source.extend(paper.year().map(|year| year.to_string()));
+    source.extend(paper.pages().map(|pages| format!("pp. {}", pages)));
+    if !source.is_empty() {
+        parts.push(sentence(&source.join(", ")));
+    }
+    parts.extend(paper.primary_link().as_deref().map(sentence));
+    parts.join(" ")
+}
+
+/// `Ammar, Waleed, Dirk Groeneveld, and Chandra Bhagavatula. "Title." Venue 1 (2018): 84-91. https://doi.org/....`
+fn chicago<P: PaperLike + ?Sized>(paper: &P, names: &[Name], title: &str) -> String {
+    let authors = match names {
+        [] => None,
+        [author] => Some(author.family_given()),
+        [first, second] => Some(format!(
+            "{}, and {}",
+            first.family_given(),
+            second.given_family()
+        )),
+        [first, rest @ .., last] if names.len() <= 10 => {
+            let mut authors = vec![first.family_given()];
+            authors.extend(rest.iter().map(Name::given_family));
+            Some(format!(
+                "{}, and {}",
+                authors.join(", "),
+                last.given_family()
+            ))
+        }
+        [first, rest @ ..] => {
+            let mut authors = vec![first.family_given()];
+            authors.extend(rest[..6].iter().map(Name::given_family));
+            Some(format!("{}, et al", authors.join(", ")))
+        }
+    };
+
+    let mut parts = Vec::new();
+    parts.extend(authors.as_deref().map(sentence));
+    parts.push(format!("\"{}\"", sentence(title)));
+    let mut source = String::new();
+    if let Some(venue) = paper.venue() {
+        source.push_str(venue);
+        if let Some(volume) = paper.volume() {
+            source.push(' ');
+            source.push_str(volume);
+        }
+    }
+    if let Some(year) = paper.year() {
+        if source.is_empty() {
+            source = year.to_string();
+        } else {
+            source = format!("{} ({})", source, year);
+        }
+    }
+    match paper.pages() {
+        Some(pages) if !source.is_empty() => source = format!("{}: {}", source, pages),
+        _ => (),
+    }
+    if !source.is_empty() {
+        parts.push(sentence(&source));
+    }
+    parts.extend(paper.primary_link().as_deref().map(sentence));
+    parts.join(" ")
+}
+
+/// `W. Ammar, D. Groeneveld, and C. Bhagavatula, "Title," Venue, vol. 1, pp. 84-91, 2018, doi: ....`
+fn ieee<P: PaperLike + ?Sized>(paper: &P, names: &[Name], title: &str) -> String {
+    let authors = names.iter().map(Name::initials_family).collect::<Vec<_>>();
+    let authors = match authors.as_slice() {
+        [] => None,
+        [author] => Some(author.clone()),
+        [first, second] => Some(format!("{} and {}", first, second)),
+        [rest @ .., last] if authors.len() <= 6 => {
+            Some(format!("{}, and {}", rest.join(", "), last))
+        }
+        [first, ..] => Some(format!("{} et al.", first)),
+    };
+
+    let mut details = Vec::new();
+    details.extend(paper.venue().map(ToOwned::to_owned));
+    details.extend(paper.volume().map(|volume| format!("vol. {}", volume)));
+    details.extend(paper.pages().map(|pages| format!("pp. {}", pages)));
+    details.extend(paper.year().map(|year| year.to_string()));
+    details.extend(paper.doi().map(|doi| format!("doi: {}", doi)));
+
+    let mut citation = String::new();
+    if let Some(authors) = authors {
+        citation.push_str(&authors);
+        citation.push_str(", ");
+    }
+    if details.is_empty() {
+        citation.push_str(&format!("\"{}\"", sentence(title)));
+    } else {
+        citation.push_str(&format!("\"{},\" {}", title, sentence(&details.join(", "))));
+    }
+    if let (None, Some(link)) = (paper.doi(), paper.primary_link()) {
+        citation.push_str(&format!(" [Online]. Available: {}", link));
+    }
+    citation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2Paper;
+
+    fn paper(authors: &[&str]) -> S2Paper {
+        serde_json::from_value(serde_json::json!({
+            "paperId": "649def34f8be52c8b66281af98ae884c09aef38b",
+            "title": "Construction of the Literature Graph in Semantic Scholar",
+            "year": 2018,
+            "venue": "North American Chapter of the Association for Computational Linguistics",
+            "journal": { "name": "NAACL", "pages": "84-91" },
+            "externalIds": { "DOI": "10.18653/v1/N18-3011" },
+            "authors": authors.iter().map(|name| serde_json::json!({ "name": name })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_citation_styles() {
+        let paper = paper(&["Waleed Ammar", "Dirk Groeneveld"]);
+        assert_eq!(
+            paper.cite(CitationStyle::Apa),
+            "Ammar, W., & Groeneveld, D. (2018). Construction of the Literature Graph in Semantic Scholar. NAACL, 84-91. https://doi.org/10.18653/v1/N18-3011"
+        );
+        assert_eq!(
+            paper.cite(CitationStyle::Mla),
+            "Ammar, Waleed, and Dirk Groeneveld. \"Construction of the Literature Graph in Semantic Scholar.\" NAACL, 2018, pp. 84-91. https://doi.org/10.18653/v1/N18-3011."
+        );
+        assert_eq!(
+            paper.cite(CitationStyle::Chicago),
+            "Ammar, Waleed, and Dirk Groeneveld. \"Construction of the Literature Graph in Semantic Scholar.\" NAACL (2018): 84-91. https://doi.org/10.18653/v1/N18-3011."
+        );
+        assert_eq!(
+            paper.cite(CitationStyle::Ieee),
+            "W. Ammar and D. Groeneveld, \"Construction of the Literature Graph in Semantic Scholar,\" NAACL, pp. 84-91, 2018, doi: 10.18653/v1/N18-3011."
+        );
+    }
+
+    #[test]
+    fn test_csl_json() {
+        let item = to_csl_json(&paper(&["Waleed Ammar", "Dirk Groeneveld"]));
+        assert_eq!(
+            item,
+            serde_json::json!({
+                "id": "649def34f8be52c8b66281af98ae884c09aef38b",
+                "type": "article-journal",
+                "title": "Construction of the Literature Graph in Semantic Scholar",
+                "author": [
+                    { "family": "Ammar", "given": "Waleed" },
+                    { "family": "Groeneveld", "given": "Dirk" }
+                ],
+                "issued": { "date-parts": [[2018]] },
+                "container-title": "NAACL",
+                "page": "84-91",
+                "DOI": "10.18653/v1/N18-3011",
+                "URL": "https://doi.org/10.18653/v1/N18-3011"
+            })
+        );
+    }
+
+    #[test]
+    fn test_many_authors() {
+        let paper = paper(&["Waleed Ammar", "Dirk Groeneveld", "Chandra Bhagavatula"]);
+        assert!(
+            paper
+                .cite(CitationStyle::Apa)
+                .starts_with("Ammar, W., Groeneveld, D., & Bhagavatula, C. (2018).")
+        );
+        assert!(
+            paper
+                .cite(CitationStyle::Mla)
+                .starts_with("Ammar, Waleed, et al. \"")
+        );
+        assert!(
+            paper
+                .cite(CitationStyle::Chicago)
+                .starts_with("Ammar, Waleed, Dirk Groeneveld, and Chandra Bhagavatula. \"")
+        );
+        assert!(
+            paper
+                .cite(CitationStyle::Ieee)
+                .starts_with("W. Ammar, D. Groeneveld, and C. Bhagavatula, \"")
+        );
+        assert_eq!(
+            "IEEE".parse::<CitationStyle>().unwrap(),
+            CitationStyle::Ieee
+        );
+        assert!("harvard".parse::<CitationStyle>().is_err());
+    }
+}
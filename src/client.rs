@@ -1,27 +1,40 @@
 //! Connected Papers Client
 
 use crate::{
-    Error, ExternalIds, FieldOfStudy, PublicationType,
-    error::Result,
-    utils::{APIKey, Method, build_request},
+    Cassette, ExternalIds, FieldOfStudy, Journal, OpenAccessPdf, PaperId, PublicationType,
+    S2Author, S2NestedPaper, Tldr,
+    arxiv::ArxivId,
+    doi::normalize_doi,
+    error::{Error, Result},
+    graph_version::{migrate, supported_version},
+    progress::ProgressEvent,
+    utils::{
+        APIKey, Coalescer, HttpConfig, Method, Transport, build_request, list_accessors,
+        null_as_default, parse_value, send,
+    },
 };
+#[cfg(feature = "semantic-scholar")]
+use crate::{MatchedPaper, PaperTitleSearchParamBuilder, SemanticScholar};
 #[cfg(feature = "stream")]
 use async_stream::stream;
 use chrono::{NaiveDate, NaiveDateTime};
 #[cfg(feature = "stream")]
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use reqwest::{Client, StatusCode};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 #[cfg(feature = "stream")]
 use std::pin::Pin;
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 static APP_USER_AGENT: &str =
     concat!("RS", env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
 const BASE_URL: &str = "https://rest.prod.connectedpapers.com/papers-api";
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(90);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum GraphResponseType {
     BadId,
@@ -37,7 +50,26 @@ pub enum GraphResponseType {
     Overloaded,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl GraphResponseType {
+    /// Whether the status ends a graph request without a graph, e.g. `BAD_TOKEN` or
+    /// `OUT_OF_REQUESTS`
+    ///
+    /// `OVERLOADED` is transient and retried, so it is not a failure.
+    pub fn is_failure(self) -> bool {
+        matches!(
+            self,
+            GraphResponseType::BadId
+                | GraphResponseType::Error
+                | GraphResponseType::NotInDb
+                | GraphResponseType::BadToken
+                | GraphResponseType::BadRequest
+                | GraphResponseType::OutOfRequests
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct GraphResponse {
     pub status: GraphResponseType,
@@ -49,16 +81,34 @@ pub struct GraphResponse {
     pub remaining_requests: Option<u64>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Graph of the paper best matching a title
+#[cfg(feature = "semantic-scholar")]
+#[cfg_attr(docsrs, doc(cfg(feature = "semantic-scholar")))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleGraph {
+    /// Match score of the title search
+    pub score: f64,
+    /// Matched paper
+    pub paper: S2NestedPaper,
+    /// Graph response for the matched paper
+    pub response: GraphResponse,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct Graph {
     pub nodes: HashMap<String, Paper>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub edges: Vec<Edge>,
     #[serde(rename = "common_citations")]
+    #[serde(default, deserialize_with = "null_as_default")]
     pub citations: Vec<Citation>,
     #[serde(rename = "common_references")]
+    #[serde(default, deserialize_with = "null_as_default")]
     pub references: Vec<Reference>,
     #[serde(rename = "common_authors")]
+    #[serde(default, deserialize_with = "null_as_default")]
     pub authors: Vec<AuthorDetail>,
     pub parameters: Parameter,
     pub path_lengths: HashMap<String, f64>,
@@ -67,7 +117,8 @@ pub struct Graph {
     pub creation_time: NaiveDateTime,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct Parameter {
     pub paper_id: String,
@@ -76,13 +127,17 @@ pub struct Parameter {
     pub max_load: u32,
     pub num_neighbors: u32,
     pub spring_iterations: u32,
+    /// Schema version of the graph, see [`GRAPH_VERSION`](crate::GRAPH_VERSION)
+    #[serde(deserialize_with = "supported_version")]
     pub params_version: u32,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Edge(pub String, pub String, pub f64);
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Author {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -91,7 +146,8 @@ pub struct Author {
     pub name: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct AuthorDetail {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -106,248 +162,367 @@ pub struct AuthorDetail {
     pub url: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Paper {
-    pub id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "corpusid")]
-    pub corpus_id: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub authors: Option<Vec<Author>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub title: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub year: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fields_of_study: Option<Vec<FieldOfStudy>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pdf_urls: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub venue: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_volume: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_pages: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub doi: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pmid: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub mag_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub arxiv_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub external_ids: Option<ExternalIds>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub is_open_access: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tldr: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub publication_types: Option<Vec<PublicationType>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub publication_date: Option<NaiveDate>,
-    #[serde(rename = "paperId")]
-    pub paper_id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "citations_length")]
-    pub citations_length: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "references_length")]
-    pub references_length: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "abstract")]
-    pub abstract_: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "ref_with_start")]
-    pub ref_with_start: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "cit_with_start")]
-    pub cit_with_start: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub path: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "path_length")]
-    pub path_length: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pos: Option<[f64; 2]>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "number_of_authors")]
-    pub number_of_authors: Option<u8>,
+/// Define a Connected Papers paper model with the fields shared by [`Paper`], [`Citation`]
+/// and [`Reference`] followed by its own fields
+macro_rules! paper_model {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $($(#[$field_meta:meta])* pub $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+        #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+        #[serde(rename_all = "camelCase")]
+        pub struct $name {
+            pub id: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[serde(rename = "corpusid")]
+            pub corpus_id: Option<u64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub authors: Option<Vec<Author>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub title: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub year: Option<u32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub fields_of_study: Option<Vec<FieldOfStudy>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub pdf_urls: Option<Vec<String>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub venue: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub journal_name: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub journal_volume: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub journal_pages: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub doi: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub pmid: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub mag_id: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub arxiv_id: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub external_ids: Option<ExternalIds>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub is_open_access: Option<bool>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub tldr: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub publication_types: Option<Vec<PublicationType>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub publication_date: Option<NaiveDate>,
+            #[serde(rename = "paperId")]
+            pub paper_id: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[serde(rename = "citations_length")]
+            pub citations_length: Option<u32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[serde(rename = "references_length")]
+            pub references_length: Option<u32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[serde(rename = "abstract")]
+            pub abstract_: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub url: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            #[serde(rename = "number_of_authors")]
+            pub number_of_authors: Option<u8>,
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        list_accessors!($name {
+            authors: Author,
+            fields_of_study: FieldOfStudy,
+            pdf_urls: String,
+            publication_types: PublicationType,
+        });
+    };
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Citation {
-    pub id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "corpusid")]
-    pub corpus_id: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub authors: Option<Vec<Author>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub title: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub year: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fields_of_study: Option<Vec<FieldOfStudy>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pdf_urls: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub venue: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_volume: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_pages: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub doi: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pmid: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub mag_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub arxiv_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub external_ids: Option<ExternalIds>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub is_open_access: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tldr: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub publication_types: Option<Vec<PublicationType>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub publication_date: Option<NaiveDate>,
-    #[serde(rename = "paperId")]
-    pub paper_id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "edges_count")]
-    pub edges_count: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "total_citations")]
-    pub total_citations: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "citations_length")]
-    pub citations_length: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "references_length")]
-    pub references_length: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "pi_name")]
-    pub pi_name: Option<String>,
-    #[serde(rename = "abstract")]
-    pub abstract_: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "local_references")]
-    pub local_references: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "number_of_authors")]
-    pub number_of_authors: Option<u8>,
+paper_model! {
+    pub struct Paper {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "ref_with_start")]
+        pub ref_with_start: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "cit_with_start")]
+        pub cit_with_start: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub path: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "path_length")]
+        pub path_length: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub pos: Option<[f64; 2]>,
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Reference {
-    pub id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "corpusid")]
-    pub corpus_id: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub authors: Option<Vec<Author>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub title: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub year: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fields_of_study: Option<Vec<FieldOfStudy>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pdf_urls: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub venue: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_volume: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub journal_pages: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub doi: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pmid: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub mag_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub arxiv_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub external_ids: Option<ExternalIds>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub is_open_access: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tldr: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub publication_types: Option<Vec<PublicationType>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub publication_date: Option<NaiveDate>,
-    #[serde(rename = "paperId")]
-    pub paper_id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "edges_count")]
-    pub edges_count: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "total_citations")]
-    pub total_citations: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "citations_length")]
-    pub citations_length: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "references_length")]
-    pub references_length: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "pi_name")]
-    pub pi_name: Option<String>,
-    #[serde(rename = "abstract")]
-    pub abstract_: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "local_citations")]
-    pub local_citations: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    #[serde(rename = "number_of_authors")]
-    pub number_of_authors: Option<u8>,
+paper_model! {
+    pub struct Citation {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "edges_count")]
+        pub edges_count: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "total_citations")]
+        pub total_citations: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "pi_name")]
+        pub pi_name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "local_references")]
+        pub local_references: Option<Vec<String>>,
+    }
+}
+
+paper_model! {
+    pub struct Reference {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "edges_count")]
+        pub edges_count: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "total_citations")]
+        pub total_citations: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "pi_name")]
+        pub pi_name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "local_citations")]
+        pub local_citations: Option<Vec<String>>,
+    }
+}
+
+impl Paper {
+    /// Create a paper with the given id and no other field set
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_owned(),
+            paper_id: id.to_owned(),
+            ..Default::default()
+        }
+    }
 }
 
+impl Graph {
+    /// Small graph of a seed paper and two neighbors, e.g. for tests and examples
+    pub fn example() -> Self {
+        let paper = |id: &str, title: &str, year: u32, citations: u32, pos: [f64; 2]| {
+            let mut paper = Paper::new(id);
+            paper.title = Some(title.to_owned());
+            paper.year = Some(year);
+            paper.citations_length = Some(citations);
+            paper.pos = Some(pos);
+            (id.to_owned(), paper)
+        };
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        Self {
+            nodes: HashMap::from([
+                paper("seed", "Seed paper", 2018, 120, [0.0, 0.0]),
+                paper("newer", "Newer paper", 2022, 15, [1.0, 0.5]),
+                paper("older", "Older paper", 2012, 300, [-1.0, -0.5]),
+            ]),
+            edges: vec![
+                Edge("seed".to_owned(), "newer".to_owned(), 0.6),
+                Edge("seed".to_owned(), "older".to_owned(), 0.4),
+            ],
+            parameters: Parameter {
+                paper_id: "seed".to_owned(),
+                total_nodes: 3,
+                ..Default::default()
+            },
+            start_id: "seed".to_owned(),
+            current_corpus_date: date,
+            creation_time: date.and_hms_opt(0, 0, 0).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    /// Find the node a paper ID of any kind refers to
+    ///
+    /// Nodes are matched by their Semantic Scholar ID, corpus ID, DOI, arXiv ID, PubMed,
+    /// PubMed Central, MAG or ACL ID, using their external ids too, or by their URL. DOIs are
+    /// compared normalized and arXiv IDs whatever their versions.
+    pub fn find_node(&self, id: &PaperId) -> Option<&Paper> {
+        if let PaperId::S2Id(id) = id {
+            if let Some(node) = self.nodes.get(id) {
+                return Some(node);
+            }
+        }
+        let mut nodes = self.nodes.values().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        nodes.into_iter().find(|node| node.matches(id))
+    }
+}
+
+impl Paper {
+    /// Whether a paper ID refers to this paper
+    fn matches(&self, id: &PaperId) -> bool {
+        let ids = self.external_ids.as_ref();
+        let number = |value: Option<&str>, id: u64| {
+            value.is_some_and(|value| {
+                value.trim_start_matches("PMC").parse::<u64>().ok() == Some(id)
+            })
+        };
+        match id {
+            PaperId::S2Id(id) => self.id == *id || self.paper_id == *id,
+            PaperId::CorpusId(id) => {
+                self.corpus_id.or(ids.and_then(|ids| ids.corpus_id)) == Some(*id)
+            }
+            PaperId::DOI(doi) => {
+                let doi = normalize_doi(doi);
+                self.doi
+                    .iter()
+                    .chain(ids.and_then(|ids| ids.doi.as_ref()))
+                    .any(|own| normalize_doi(own) == doi)
+            }
+            PaperId::ArXiv(arxiv) => ArxivId::parse(arxiv).is_ok_and(|arxiv| {
+                self.arxiv_id
+                    .iter()
+                    .chain(ids.and_then(|ids| ids.arxiv.as_ref()))
+                    .any(|own| ArxivId::parse(own).is_ok_and(|own| own.same_work(&arxiv)))
+            }),
+            PaperId::MAG(id) => {
+                number(self.mag_id.as_deref(), *id)
+                    || number(ids.and_then(|ids| ids.mag.as_deref()), *id)
+            }
+            PaperId::ACL(acl) => ids
+                .and_then(|ids| ids.acl.as_deref())
+                .is_some_and(|own| own.eq_ignore_ascii_case(acl)),
+            PaperId::PubMed(id) => {
+                number(self.pmid.as_deref(), *id)
+                    || number(ids.and_then(|ids| ids.pubmed.as_deref()), *id)
+            }
+            PaperId::PubMedCentral(id) => {
+                number(ids.and_then(|ids| ids.pubmed_central.as_deref()), *id)
+            }
+            PaperId::URL(url) => {
+                self.url.as_deref() == Some(url.as_str())
+                    || ArxivId::parse(url)
+                        .is_ok_and(|arxiv| self.matches(&PaperId::ArXiv(arxiv.to_string())))
+            }
+        }
+    }
+}
+
+/// Connected Papers client
+///
+/// Cloning is cheap: clones share the connection pool, the settings and the requests in
+/// flight, so spawned tasks can each take a clone instead of sharing an `Arc`.
 #[derive(Debug, Clone)]
 pub struct ConnectedPapers {
-    api_key: Option<String>,
+    api_key: Option<Arc<str>>,
     client: Client,
+    transport: Transport,
+    overload_retry: Option<OverloadRetry>,
 }
 
 impl Default for ConnectedPapers {
     fn default() -> Self {
         Self {
             api_key: None,
-            client: Client::builder()
-                .timeout(Duration::from_secs(90))
-                .user_agent(APP_USER_AGENT)
-                .build()
+            client: HttpConfig::default()
+                .build(APP_USER_AGENT, DEFAULT_TIMEOUT)
                 .unwrap(),
+            transport: Transport::default(),
+            overload_retry: None,
         }
     }
 }
 
+/// Builder for [`ConnectedPapers`]
+#[derive(Debug, Clone, Default)]
+pub struct ConnectedPapersBuilder {
+    api_key: Option<String>,
+    http: HttpConfig,
+    transport: Transport,
+    overload_retry: Option<OverloadRetry>,
+}
+
+impl ConnectedPapersBuilder {
+    /// Set the API key
+    pub fn api_key(&mut self, api_key: &str) -> &mut Self {
+        self.api_key = Some(api_key.to_owned());
+        self
+    }
+
+    /// Set the request timeout, 90 seconds by default
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    /// Route all requests through the given HTTP(S) proxy, e.g. `http://proxy.example.org:8080`
+    pub fn proxy(&mut self, url: &str) -> &mut Self {
+        self.http.proxy = Some(url.to_owned());
+        self
+    }
+
+    /// Trust an additional PEM-encoded root certificate
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "rustls-tls", feature = "native-tls"))))]
+    pub fn root_certificate(&mut self, pem: &[u8]) -> &mut Self {
+        self.http.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Record responses to, or replay them from, the given cassette
+    pub fn cassette(&mut self, cassette: Cassette) -> &mut Self {
+        self.transport.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Reject responses larger than the given number of bytes
+    ///
+    /// Unlimited by default. Requests selecting large fields, such as embeddings together
+    /// with citations, can otherwise exhaust memory when many run concurrently.
+    pub fn max_response_size(&mut self, bytes: u64) -> &mut Self {
+        self.transport.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Send identical requests issued concurrently only once, sharing the response
+    ///
+    /// Concurrent requests for the same graph through clones of the built client, or through
+    /// its Semantic Scholar client, then cost a single request.
+    pub fn coalesce_requests(&mut self) -> &mut Self {
+        self.transport.coalescer = Some(Coalescer::default());
+        self
+    }
+
+    /// Retry graph requests answered with `OVERLOADED` in [`ConnectedPapers::get_graph`]
+    ///
+    /// Plain graph requests are not retried by default. Pollers and streams always retry,
+    /// with this policy if set and the default one otherwise.
+    pub fn retry_overloaded(&mut self, retry: OverloadRetry) -> &mut Self {
+        self.overload_retry = Some(retry);
+        self
+    }
+
+    /// Build the client
+    pub fn build(&self) -> Result<ConnectedPapers> {
+        Ok(ConnectedPapers {
+            api_key: self.api_key.as_deref().map(Arc::from),
+            client: self.http.build(APP_USER_AGENT, DEFAULT_TIMEOUT)?,
+            transport: self.transport.clone(),
+            overload_retry: self.overload_retry,
+        })
+    }
+}
+
 impl ConnectedPapers {
+    /// Create a builder to configure the client
+    pub fn builder() -> ConnectedPapersBuilder {
+        ConnectedPapersBuilder::default()
+    }
+
     /// Create a new client with the given API key
     pub fn with_api_key(api_key: &str) -> Self {
         Self {
-            api_key: Some(api_key.to_owned()),
+            api_key: Some(Arc::from(api_key)),
             ..Self::default()
         }
     }
@@ -358,26 +533,123 @@ impl ConnectedPapers {
         Ok(Self::with_api_key(&api_key))
     }
 
+    #[cfg(feature = "semantic-scholar")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "semantic-scholar")))]
+    /// Semantic Scholar client without API key sharing the connection pool and transport
+    ///
+    /// Its requests go through the same cassette and coalesce with those of this client.
+    pub fn semantic_scholar(&self) -> SemanticScholar {
+        SemanticScholar::with_transport(self.client.clone(), self.transport.clone())
+    }
+
     /// Get the graph for a given paper ID
     ///
     /// # Arguments
     ///
     /// * `id` - The ID of the paper to get the graph for
     /// * `fresh_only` - If `true`, force a fresh graph rebuild (ignore cached graphs)
+    ///
+    /// An `OVERLOADED` response is returned as is unless the client was built with
+    /// [`ConnectedPapersBuilder::retry_overloaded`].
     pub async fn get_graph(&self, id: &str, fresh_only: bool) -> Result<GraphResponse> {
+        match self.overload_retry {
+            Some(retry) => self.get_graph_with_retry(id, fresh_only, retry).await,
+            None => self.fetch_graph(id, fresh_only).await,
+        }
+    }
+
+    /// Get the graph for a given paper ID, retrying while Connected Papers is overloaded
+    ///
+    /// Returns the last response, still `OVERLOADED`, once the retries are exhausted.
+    pub async fn get_graph_with_retry(
+        &self,
+        id: &str,
+        fresh_only: bool,
+        retry: OverloadRetry,
+    ) -> Result<GraphResponse> {
+        let mut response = self.fetch_graph(id, fresh_only).await?;
+        for (attempt, delay) in (2..).zip(retry.delays()) {
+            if response.status != GraphResponseType::Overloaded {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+            response = self
+                .fetch_graph(id, fresh_only)
+                .await
+                .map_err(|e| e.with_attempt(attempt))?;
+        }
+        Ok(response)
+    }
+
+    async fn fetch_graph(&self, id: &str, fresh_only: bool) -> Result<GraphResponse> {
         let url = if fresh_only {
             format!("{}/graph/1/{}", BASE_URL, id)
         } else {
             format!("{}/graph/0/{}", BASE_URL, id)
         };
         let req_builder = build_request(&self.client, Method::Get, &url, self.api_key());
-        let resp = req_builder.send().await?;
-        match resp.status() {
+        let resp = send(req_builder, &self.transport).await?;
+        match resp.status {
             StatusCode::OK => {
-                let body = resp.json::<GraphResponse>().await?;
-                Ok(body)
+                let mut body = resp.json::<serde_json::Value>()?;
+                if let Some(graph) = body.get_mut("graph_json").filter(|graph| !graph.is_null()) {
+                    migrate(graph).map_err(|e| e.with_context(resp.context.clone()))?;
+                }
+                parse_value(body).map_err(|e| e.with_context(resp.context))
             }
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+            _ => Err(resp.into_error()),
+        }
+    }
+
+    #[cfg(feature = "semantic-scholar")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "semantic-scholar")))]
+    /// Get the graph for the paper best matching a title
+    ///
+    /// The title, e.g. taken from a plain citation string, is resolved to a paper ID with the
+    /// Semantic Scholar title match endpoint. Returns `None` if no paper matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `semantic_scholar` - The client resolving the title, with its API key and base URLs
+    /// * `title` - The title of the paper to get the graph for
+    /// * `fresh_only` - If `true`, force a fresh graph rebuild (ignore cached graphs)
+    pub async fn get_graph_by_title(
+        &self,
+        semantic_scholar: &SemanticScholar,
+        title: &str,
+        fresh_only: bool,
+    ) -> Result<Option<TitleGraph>> {
+        let param = PaperTitleSearchParamBuilder::new(title).build()?;
+        let Some(MatchedPaper { score, paper }) = semantic_scholar.query(&param).await? else {
+            return Ok(None);
+        };
+        let response = self.get_graph(&paper.paper_id, fresh_only).await?;
+        Ok(Some(TitleGraph {
+            score,
+            paper,
+            response,
+        }))
+    }
+
+    /// Poll the graph of a paper, returning each status update until completion
+    ///
+    /// The poller behaves like [`ConnectedPapers::get_graph_stream`] without requiring the
+    /// `stream` feature; see there for the arguments.
+    pub fn graph_poller(
+        &self,
+        id: &str,
+        fresh_only: bool,
+        wait_until_complete: bool,
+    ) -> GraphPoller<'_> {
+        GraphPoller {
+            client: self,
+            id: id.to_owned(),
+            fresh_only,
+            current_fresh_only: fresh_only,
+            wait_until_complete,
+            newest_graph: None,
+            polled: false,
+            done: false,
         }
     }
 
@@ -400,72 +672,12 @@ impl ConnectedPapers {
         fresh_only: bool,
         wait_until_complete: bool,
     ) -> Pin<Box<dyn Stream<Item = Result<GraphResponse>> + Send + '_>> {
-        let id = id.to_owned();
+        let mut poller = self.graph_poller(id, fresh_only, wait_until_complete);
         Box::pin(stream! {
-            let mut current_fresh_only = fresh_only;
-            let mut newest_graph: Option<Graph> = None;
-
             loop {
-                match self.get_graph(&id, current_fresh_only).await {
-                    Ok(mut response) => {
-                        if let Some(ref graph) = response.graph_json {
-                            newest_graph = Some(graph.clone());
-                        }
-
-                        if response.status == GraphResponseType::OldGraph {
-                            if wait_until_complete && !fresh_only {
-                                current_fresh_only = true;
-                                response.graph_json = newest_graph.clone();
-                                yield Ok(response);
-                                tokio::time::sleep(Duration::from_secs(1)).await;
-                                continue;
-                            } else if !fresh_only {
-                                response.graph_json = newest_graph.clone();
-                                yield Ok(response);
-                                return;
-                            }
-                        }
-
-                        if response.status == GraphResponseType::Overloaded {
-                            for &delay in &[Duration::from_secs(5), Duration::from_secs(10), Duration::from_secs(20), Duration::from_secs(40)] {
-                                tokio::time::sleep(delay).await;
-                                match self.get_graph(&id, current_fresh_only).await {
-                                    Ok(new_response) if new_response.status != GraphResponseType::Overloaded => {
-                                        response = new_response;
-                                        break;
-                                    }
-                                    Ok(new_response) => {
-                                        response = new_response;
-                                    }
-                                    Err(e) => {
-                                        yield Err(e);
-                                        return;
-                                    }
-                                }
-                            }
-                        }
-
-                        let status = response.status;
-                        response.graph_json = newest_graph.clone();
-                        yield Ok(response);
-
-                        let is_terminal = matches!(
-                            status,
-                            GraphResponseType::BadId
-                                | GraphResponseType::Error
-                                | GraphResponseType::NotInDb
-                                | GraphResponseType::FreshGraph
-                                | GraphResponseType::BadToken
-                                | GraphResponseType::BadRequest
-                                | GraphResponseType::OutOfRequests
-                        );
-
-                        if !wait_until_complete || is_terminal {
-                            return;
-                        }
-
-                        tokio::time::sleep(Duration::from_secs(1)).await;
-                    }
+                match poller.next_update().await {
+                    Ok(Some(response)) => yield Ok(response),
+                    Ok(None) => return,
                     Err(e) => {
                         yield Err(e);
                         return;
@@ -478,31 +690,31 @@ impl ConnectedPapers {
     pub(crate) fn api_key(&self) -> Option<APIKey> {
         self.api_key.as_ref().map(|key| APIKey {
             header: "X-Api-Key".to_owned(),
-            value: key.to_owned(),
+            value: key.to_string(),
         })
     }
 
     pub async fn get_remaining_usages(&self) -> Result<u64> {
         let url = format!("{}/remaining-usages", BASE_URL);
         let req_builder = build_request(&self.client, Method::Get, &url, self.api_key());
-        let resp = req_builder.send().await?;
-        match resp.status() {
+        let resp = send(req_builder, &self.transport).await?;
+        match resp.status {
             StatusCode::OK => {
-                let body = resp.json::<serde_json::Value>().await?;
+                let body = resp.json::<serde_json::Value>()?;
                 let remaining_usages = body["remaining"].as_u64().unwrap_or(0);
                 Ok(remaining_usages)
             }
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+            _ => Err(resp.into_error()),
         }
     }
 
     pub async fn get_free_access_papers(&self) -> Result<Vec<String>> {
         let url = format!("{}/free-access-papers", BASE_URL);
         let req_builder = build_request(&self.client, Method::Get, &url, self.api_key());
-        let resp = req_builder.send().await?;
-        match resp.status() {
+        let resp = send(req_builder, &self.transport).await?;
+        match resp.status {
             StatusCode::OK => {
-                let body = resp.json::<serde_json::Value>().await?;
+                let body = resp.json::<serde_json::Value>()?;
                 let free_access_papers = body["papers"]
                     .as_array()
                     .map(|values| {
@@ -514,7 +726,302 @@ impl ConnectedPapers {
                     .unwrap_or_default();
                 Ok(free_access_papers)
             }
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+            _ => Err(resp.into_error()),
+        }
+    }
+}
+
+/// Exponential backoff of graph requests answered with `OVERLOADED`
+///
+/// By default, waits 5, 10, 20 and 40 seconds before the successive retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverloadRetry {
+    /// Delay before the first retry, doubled before each following one
+    pub initial_delay: Duration,
+    /// Number of retries before giving up
+    pub max_retries: u32,
+}
+
+impl Default for OverloadRetry {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(5),
+            max_retries: 4,
+        }
+    }
+}
+
+impl OverloadRetry {
+    /// Delays before the successive retries
+    pub fn delays(&self) -> impl Iterator<Item = Duration> {
+        let initial_delay = self.initial_delay;
+        (0..self.max_retries).map(move |i| initial_delay.saturating_mul(2u32.saturating_pow(i)))
+    }
+}
+
+/// Polls the graph of a paper until it is built, see [`ConnectedPapers::graph_poller`]
+#[derive(Debug, Clone)]
+pub struct GraphPoller<'a> {
+    client: &'a ConnectedPapers,
+    id: String,
+    fresh_only: bool,
+    current_fresh_only: bool,
+    wait_until_complete: bool,
+    newest_graph: Option<Graph>,
+    polled: bool,
+    done: bool,
+}
+
+impl GraphPoller<'_> {
+    /// Fetch the next status update, carrying the newest graph seen so far
+    ///
+    /// Updates are at least a second apart. Returns `None` once a terminal status was
+    /// returned, and ends the polling after an error.
+    pub async fn next_update(&mut self) -> Result<Option<GraphResponse>> {
+        if self.done {
+            return Ok(None);
+        }
+        if self.polled {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        self.polled = true;
+        self.done = true;
+
+        let retry = self.client.overload_retry.unwrap_or_default();
+        let mut response = self
+            .client
+            .get_graph_with_retry(&self.id, self.current_fresh_only, retry)
+            .await?;
+        if let Some(ref graph) = response.graph_json {
+            self.newest_graph = Some(graph.clone());
+        }
+
+        if response.status == GraphResponseType::OldGraph && !self.fresh_only {
+            if self.wait_until_complete {
+                self.current_fresh_only = true;
+                self.done = false;
+            }
+            response.graph_json = self.newest_graph.clone();
+            return Ok(Some(response));
+        }
+
+        let is_terminal =
+            response.status == GraphResponseType::FreshGraph || response.status.is_failure();
+        self.done = !self.wait_until_complete || is_terminal;
+        response.graph_json = self.newest_graph.clone();
+        Ok(Some(response))
+    }
+
+    /// Whether the polling has ended
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// Progress or outcome of a graph build, see [`GraphStreamExt::into_results`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphUpdate {
+    /// The graph is queued or being built
+    Progress(ProgressEvent),
+    /// A built graph, `fresh` unless served from the cache
+    ///
+    /// When waiting for a fresh graph, a cached graph is followed by the rebuilt one.
+    Graph { graph: Box<Graph>, fresh: bool },
+}
+
+impl GraphUpdate {
+    /// Update of a graph response, `None` while Connected Papers is overloaded
+    ///
+    /// Failure statuses, and graph statuses without a graph, become an
+    /// [`Error::GraphUnavailable`] carrying the status.
+    pub fn from_response(response: GraphResponse) -> Result<Option<Self>> {
+        match response.status {
+            GraphResponseType::Overloaded => Ok(None),
+            status @ (GraphResponseType::FreshGraph | GraphResponseType::OldGraph) => {
+                let graph = response.graph_json.ok_or(Error::GraphUnavailable(status))?;
+                Ok(Some(GraphUpdate::Graph {
+                    graph: Box::new(graph),
+                    fresh: status == GraphResponseType::FreshGraph,
+                }))
+            }
+            status if status.is_failure() => Err(Error::GraphUnavailable(status)),
+            _ => Ok(ProgressEvent::from_graph_response(&response).map(GraphUpdate::Progress)),
+        }
+    }
+}
+
+/// Adapters of the stream returned by [`ConnectedPapers::get_graph_stream`]
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub trait GraphStreamExt<'a>: Stream<Item = Result<GraphResponse>> + Send + Sized + 'a {
+    /// Yield only the progress and the graphs, ending with an error on a failure status
+    ///
+    /// `BAD_TOKEN`, `OUT_OF_REQUESTS` and the other failure statuses, as well as a stream
+    /// ending while Connected Papers is still overloaded, yield an
+    /// [`Error::GraphUnavailable`] carrying the status.
+    fn into_results(self) -> Pin<Box<dyn Stream<Item = Result<GraphUpdate>> + Send + 'a>> {
+        Box::pin(stream! {
+            let mut responses = Box::pin(self);
+            let mut overloaded = false;
+            while let Some(response) = responses.next().await {
+                let update = response.and_then(|response| {
+                    overloaded = response.status == GraphResponseType::Overloaded;
+                    GraphUpdate::from_response(response)
+                });
+                match update {
+                    Ok(Some(update)) => yield Ok(update),
+                    Ok(None) => (),
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                }
+            }
+            if overloaded {
+                yield Err(Error::GraphUnavailable(GraphResponseType::Overloaded));
+            }
+        })
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<'a, S> GraphStreamExt<'a> for S where S: Stream<Item = Result<GraphResponse>> + Send + 'a {}
+
+impl From<Paper> for S2NestedPaper {
+    fn from(paper: Paper) -> Self {
+        let mut external_ids = paper.external_ids.unwrap_or_default();
+        external_ids.corpus_id = external_ids.corpus_id.or(paper.corpus_id);
+        external_ids.doi = external_ids.doi.or(paper.doi);
+        external_ids.arxiv = external_ids.arxiv.or(paper.arxiv_id);
+        external_ids.mag = external_ids.mag.or(paper.mag_id);
+        external_ids.pubmed = external_ids.pubmed.or(paper.pmid);
+
+        let journal = if paper.journal_name.is_some()
+            || paper.journal_volume.is_some()
+            || paper.journal_pages.is_some()
+        {
+            Some(Journal {
+                name: paper.journal_name,
+                volume: paper.journal_volume,
+                pages: paper.journal_pages,
+            })
+        } else {
+            None
+        };
+
+        S2NestedPaper {
+            paper_id: paper.paper_id,
+            corpus_id: external_ids.corpus_id,
+            external_ids: Some(external_ids),
+            url: paper.url,
+            title: paper.title,
+            abstract_: paper.abstract_,
+            venue: paper.venue,
+            publication_venue: None,
+            year: paper.year,
+            reference_count: paper.references_length,
+            citation_count: paper.citations_length,
+            influential_citation_count: None,
+            is_open_access: paper.is_open_access,
+            open_access_pdf: paper
+                .pdf_urls
+                .and_then(|urls| urls.into_iter().next())
+                .map(|url| OpenAccessPdf {
+                    url: Some(url),
+                    status: None,
+                    license: None,
+                    legal_disclaimer: None,
+                }),
+            fields_of_study: paper.fields_of_study,
+            s2_fields_of_study: None,
+            publication_types: paper.publication_types,
+            publication_date: paper.publication_date,
+            journal,
+            citation_styles: None,
+            tldr: paper.tldr.map(|text| Tldr {
+                model: None,
+                text: Some(text),
+            }),
+            authors: paper.authors.map(|authors| {
+                authors
+                    .into_iter()
+                    .map(|author| S2Author {
+                        author_id: author.ids.and_then(|ids| ids.into_iter().flatten().next()),
+                        external_ids: None,
+                        url: None,
+                        name: author.name,
+                        affiliations: None,
+                        homepage: None,
+                        paper_count: None,
+                        citation_count: None,
+                        h_index: None,
+                        papers: None,
+                    })
+                    .collect()
+            }),
+            citations: None,
+            references: None,
+            embedding: None,
+            text_availability: None,
+        }
+    }
+}
+
+impl From<S2NestedPaper> for Paper {
+    fn from(paper: S2NestedPaper) -> Self {
+        let external_ids = paper.external_ids.unwrap_or_default();
+        let (journal_name, journal_volume, journal_pages) = paper
+            .journal
+            .map(|journal| (journal.name, journal.volume, journal.pages))
+            .unwrap_or_default();
+        let number_of_authors = paper
+            .authors
+            .as_ref()
+            .map(|authors| authors.len().min(u8::MAX as usize) as u8);
+
+        Paper {
+            id: paper.paper_id.clone(),
+            corpus_id: paper.corpus_id.or(external_ids.corpus_id),
+            authors: paper.authors.map(|authors| {
+                authors
+                    .into_iter()
+                    .map(|author| Author {
+                        ids: author.author_id.map(|id| vec![Some(id)]),
+                        name: author.name,
+                    })
+                    .collect()
+            }),
+            title: paper.title,
+            year: paper.year,
+            fields_of_study: paper.fields_of_study,
+            pdf_urls: paper
+                .open_access_pdf
+                .and_then(|pdf| pdf.url)
+                .map(|url| vec![url]),
+            venue: paper.venue,
+            journal_name,
+            journal_volume,
+            journal_pages,
+            doi: external_ids.doi.clone(),
+            pmid: external_ids.pubmed.clone(),
+            mag_id: external_ids.mag.clone(),
+            arxiv_id: external_ids.arxiv.clone(),
+            external_ids: Some(external_ids),
+            is_open_access: paper.is_open_access,
+            tldr: None,
+            publication_types: paper.publication_types,
+            publication_date: paper.publication_date,
+            paper_id: paper.paper_id,
+            citations_length: paper.citation_count,
+            references_length: paper.reference_count,
+            abstract_: paper.abstract_,
+            url: paper.url,
+            number_of_authors,
+            ref_with_start: None,
+            cit_with_start: None,
+            path: None,
+            path_length: None,
+            pos: None,
         }
     }
 }
@@ -523,6 +1030,19 @@ impl ConnectedPapers {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_graph_example() {
+        let graph = Graph::example();
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(
+            graph.nodes[&graph.start_id].title.as_deref(),
+            Some("Seed paper")
+        );
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(serde_json::from_str::<Graph>(&json).unwrap(), graph);
+        assert_eq!(Paper::new("a").paper_id, "a");
+    }
+
     #[tokio::test]
     async fn test_get_remaining_usages() {
         let client = ConnectedPapers::with_api_key("TEST_TOKEN");
@@ -546,4 +1066,331 @@ mod tests {
             .unwrap();
         println!("Graph: {:?}", graph);
     }
+
+    #[test]
+    fn test_find_node() {
+        let mut graph = Graph::example();
+        let newer = graph.nodes.get_mut("newer").unwrap();
+        newer.doi = Some("10.1000/XYZ".to_owned());
+        newer.external_ids = Some(ExternalIds {
+            arxiv: Some("2106.15928".to_owned()),
+            pubmed_central: Some("PMC2323736".to_owned()),
+            ..Default::default()
+        });
+        graph.nodes.get_mut("older").unwrap().pmid = Some("19872477".to_owned());
+
+        let found = |id: PaperId| graph.find_node(&id).map(|node| node.id.as_str());
+        assert_eq!(found(PaperId::id("seed")), Some("seed"));
+        assert_eq!(
+            found(PaperId::doi("https://doi.org/10.1000/xyz")),
+            Some("newer")
+        );
+        assert_eq!(found(PaperId::arxiv("2106.15928v2")), Some("newer"));
+        assert_eq!(
+            found(PaperId::url("https://arxiv.org/abs/2106.15928")),
+            Some("newer")
+        );
+        assert_eq!(found(PaperId::pubmed_central(2323736)), Some("newer"));
+        assert_eq!(found(PaperId::pubmed(19872477)), Some("older"));
+        assert_eq!(found(PaperId::doi("10.1000/abc")), None);
+    }
+
+    #[test]
+    fn test_paper_conversion() {
+        let paper: Paper = serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "paperId": "abc",
+            "corpusid": 42,
+            "title": "A paper",
+            "year": 2020,
+            "doi": "10.1000/xyz",
+            "arxivId": "2001.00001",
+            "journalName": "Journal",
+            "pdfUrls": ["https://example.org/a.pdf"],
+            "authors": [{ "ids": [null, "7"], "name": "Ada Lovelace" }],
+            "citations_length": 10,
+            "references_length": 3
+        }))
+        .unwrap();
+
+        let nested = S2NestedPaper::from(paper);
+        let external_ids = nested.external_ids.clone().unwrap();
+        assert_eq!(nested.paper_id, "abc");
+        assert_eq!(nested.corpus_id, Some(42));
+        assert_eq!(external_ids.doi.as_deref(), Some("10.1000/xyz"));
+        assert_eq!(external_ids.arxiv.as_deref(), Some("2001.00001"));
+        assert_eq!(nested.citation_count, Some(10));
+        assert_eq!(
+            nested.journal.as_ref().unwrap().name.as_deref(),
+            Some("Journal")
+        );
+        assert_eq!(
+            nested.authors.as_ref().unwrap()[0].author_id.as_deref(),
+            Some("7")
+        );
+
+        let paper = Paper::from(nested);
+        assert_eq!(paper.id, "abc");
+        assert_eq!(paper.doi.as_deref(), Some("10.1000/xyz"));
+        assert_eq!(paper.references_length, Some(3));
+        assert_eq!(
+            paper.pdf_urls,
+            Some(vec!["https://example.org/a.pdf".to_owned()])
+        );
+        assert_eq!(paper.number_of_authors, Some(1));
+    }
+
+    #[test]
+    fn test_common_paper_fields() {
+        let value = serde_json::json!({
+            "id": "abc",
+            "paperId": "abc",
+            "title": "A paper",
+            "edges_count": 2,
+            "local_references": ["def"]
+        });
+        let citation: Citation = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(citation.title.as_deref(), Some("A paper"));
+        assert_eq!(citation.local_references, Some(vec!["def".to_owned()]));
+        assert_eq!(serde_json::to_value(&citation).unwrap(), value);
+    }
+
+    #[test]
+    fn test_builder() {
+        let client = ConnectedPapers::builder()
+            .api_key("TEST_TOKEN")
+            .proxy("http://127.0.0.1:8080")
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap();
+        assert_eq!(client.api_key.as_deref(), Some("TEST_TOKEN"));
+        assert!(
+            ConnectedPapers::builder()
+                .proxy("http://[::1")
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cheap_clone() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<ConnectedPapers>();
+
+        let client = ConnectedPapers::builder()
+            .api_key("TEST_TOKEN")
+            .cassette(Cassette::replay("cassettes"))
+            .build()
+            .unwrap();
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(
+            client.api_key.as_ref().unwrap(),
+            clone.api_key.as_ref().unwrap()
+        ));
+        assert!(Arc::ptr_eq(
+            client.transport.cassette.as_ref().unwrap(),
+            clone.transport.cassette.as_ref().unwrap()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_graph_poller() {
+        use crate::cassette::Recording;
+
+        let dir = std::env::temp_dir().join(format!("graph-poller-{}", std::process::id()));
+        for (fresh, status) in [(0, "OLD_GRAPH"), (1, "FRESH_GRAPH")] {
+            let url = format!("{}/graph/{}/abc", BASE_URL, fresh);
+            Cassette::record(&dir)
+                .save(
+                    &Cassette::key("GET", &url, b""),
+                    &Recording {
+                        method: "GET".to_owned(),
+                        status: 200,
+                        body: serde_json::json!({ "status": status }).to_string(),
+                        url,
+                        request_id: None,
+                    },
+                )
+                .unwrap();
+        }
+        let client = ConnectedPapers::builder()
+            .cassette(Cassette::replay(&dir))
+            .build()
+            .unwrap();
+
+        let mut poller = client.graph_poller("abc", false, false);
+        let response = poller.next_update().await.unwrap().unwrap();
+        assert_eq!(response.status, GraphResponseType::OldGraph);
+        assert!(poller.is_done());
+        assert!(poller.next_update().await.unwrap().is_none());
+
+        let mut poller = client.graph_poller("abc", false, true);
+        let mut statuses = Vec::new();
+        while let Some(response) = poller.next_update().await.unwrap() {
+            statuses.push(response.status);
+        }
+        assert_eq!(
+            statuses,
+            vec![GraphResponseType::OldGraph, GraphResponseType::FreshGraph]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "semantic-scholar")]
+    async fn test_graph_by_title() {
+        let dir = std::env::temp_dir().join(format!("graph-by-title-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        cassette
+            .insert(
+                "GET",
+                "https://s2.example.org/graph/v1/paper/search/match?query=Graph",
+                b"",
+                200,
+                r#"{"data": [{"paperId": "abc", "title": "Graph", "matchScore": 42.5}]}"#,
+            )
+            .unwrap();
+        let url = format!("{}/graph/0/abc", BASE_URL);
+        let body = serde_json::json!({ "status": "NOT_IN_DB" }).to_string();
+        cassette.insert("GET", &url, b"", 200, &body).unwrap();
+        let semantic_scholar = SemanticScholar::builder()
+            .base_url(crate::Product::Graph, "https://s2.example.org/graph/v1")
+            .cassette(Cassette::replay(&dir))
+            .build()
+            .unwrap();
+        let client = ConnectedPapers::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+
+        let found = client
+            .get_graph_by_title(&semantic_scholar, "Graph", false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((found.score, found.paper.paper_id.as_str()), (42.5, "abc"));
+        assert_eq!(found.response.status, GraphResponseType::NotInDb);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_overload_retry() {
+        assert_eq!(
+            OverloadRetry::default().delays().collect::<Vec<_>>(),
+            [5, 10, 20, 40].map(Duration::from_secs)
+        );
+
+        let dir = std::env::temp_dir().join(format!("overload-retry-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        let url = format!("{}/graph/0/abc", BASE_URL);
+        let body = serde_json::json!({ "status": "OVERLOADED" }).to_string();
+        cassette.insert("GET", &url, b"", 200, &body).unwrap();
+        let retry = OverloadRetry {
+            initial_delay: Duration::from_millis(1),
+            max_retries: 2,
+        };
+        let client = ConnectedPapers::builder()
+            .cassette(cassette)
+            .retry_overloaded(retry)
+            .build()
+            .unwrap();
+        assert_eq!(client.overload_retry, Some(retry));
+        let response = client.get_graph("abc", false).await.unwrap();
+        assert_eq!(response.status, GraphResponseType::Overloaded);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_graph_response() {
+        let dir = std::env::temp_dir().join(format!("invalid-graph-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        let url = format!("{}/graph/0/abc", BASE_URL);
+        let body = serde_json::json!({ "status": "FRESH_GRAPH", "progress": "half" }).to_string();
+        cassette.insert("GET", &url, b"", 200, &body).unwrap();
+        let client = ConnectedPapers::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+        let error = client.get_graph("abc", false).await.unwrap_err();
+        assert!(matches!(error.inner(), Error::JsonError(_)));
+        assert_eq!(
+            error.context().unwrap().endpoint,
+            "GET /papers-api/graph/0/abc"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_graph_updates() {
+        let response = |status, graph: Option<Graph>| GraphResponse {
+            status,
+            graph_json: graph,
+            progress: Some(50.0),
+            remaining_requests: None,
+        };
+        assert_eq!(
+            GraphUpdate::from_response(response(GraphResponseType::InProgress, None)),
+            Ok(Some(GraphUpdate::Progress(ProgressEvent::GraphBuilding(
+                50.0
+            ))))
+        );
+        assert_eq!(
+            GraphUpdate::from_response(response(GraphResponseType::Overloaded, None)),
+            Ok(None)
+        );
+        assert_eq!(
+            GraphUpdate::from_response(response(GraphResponseType::FreshGraph, None)),
+            Err(Error::GraphUnavailable(GraphResponseType::FreshGraph))
+        );
+        assert!(!GraphResponseType::Overloaded.is_failure());
+
+        #[cfg(feature = "stream")]
+        {
+            let updates = |responses: Vec<GraphResponse>| {
+                futures::stream::iter(responses.into_iter().map(Ok))
+                    .into_results()
+                    .collect::<Vec<_>>()
+            };
+            let graph = Graph::example();
+            let results = updates(vec![
+                response(GraphResponseType::Queued, None),
+                response(GraphResponseType::OldGraph, Some(graph.clone())),
+                response(GraphResponseType::Overloaded, None),
+                response(GraphResponseType::FreshGraph, Some(graph.clone())),
+            ])
+            .await;
+            assert_eq!(
+                results,
+                vec![
+                    Ok(GraphUpdate::Progress(ProgressEvent::GraphQueued)),
+                    Ok(GraphUpdate::Graph {
+                        graph: Box::new(graph.clone()),
+                        fresh: false
+                    }),
+                    Ok(GraphUpdate::Graph {
+                        graph: Box::new(graph),
+                        fresh: true
+                    }),
+                ]
+            );
+
+            let results = updates(vec![
+                response(GraphResponseType::Queued, None),
+                response(GraphResponseType::OutOfRequests, None),
+                response(GraphResponseType::Queued, None),
+            ])
+            .await;
+            assert_eq!(
+                results[1],
+                Err(Error::GraphUnavailable(GraphResponseType::OutOfRequests))
+            );
+            assert_eq!(results.len(), 2);
+
+            let results = updates(vec![response(GraphResponseType::Overloaded, None)]).await;
+            assert_eq!(
+                results,
+                vec![Err(Error::GraphUnavailable(GraphResponseType::Overloaded))]
+            );
+        }
+    }
 }
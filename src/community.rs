@@ -0,0 +1,212 @@
+//! Communities of a graph
+//!
+//! Groups the nodes of a graph into communities by weighted label propagation over its
+//! similarity edges, and names each community after the keyphrases that set its papers apart
+//! from the rest of the graph, the way topic maps label their regions.
+
+use crate::{
+    client::Graph,
+    keywords::{Keyword, keywords},
+    paper::PaperLike,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Rounds of label propagation before giving up on convergence
+const MAX_ROUNDS: usize = 100;
+
+/// Keyphrases joined into a community label
+const LABEL_PHRASES: usize = 2;
+
+/// Community of a graph named after its keyphrases
+#[derive(Debug, Clone, PartialEq)]
+pub struct Community {
+    /// The most distinctive keyphrases joined with commas, e.g. `graph neural networks,
+    /// citation recommendation`, or `Community 1` when the papers have no keyphrase
+    pub label: String,
+    /// Keyphrases of the community, most distinctive first
+    pub keywords: Vec<Keyword>,
+    /// Ids of the nodes in the community, sorted
+    pub nodes: Vec<String>,
+}
+
+impl Graph {
+    /// Group the nodes into communities by label propagation over the weighted edges
+    ///
+    /// Every node starts in its own community and repeatedly joins the community its edges
+    /// weigh the most towards, visiting nodes by id and breaking ties towards the smallest
+    /// id, so the result is deterministic. Communities are sorted by decreasing size; nodes
+    /// without edges form their own.
+    pub fn communities(&self) -> Vec<Vec<String>> {
+        let mut ids = self.nodes.keys().map(String::as_str).collect::<Vec<_>>();
+        ids.sort_unstable();
+        let mut neighbors = HashMap::<&str, Vec<(&str, f64)>>::new();
+        for edge in &self.edges {
+            if edge.0 != edge.1
+                && self.nodes.contains_key(&edge.0)
+                && self.nodes.contains_key(&edge.1)
+            {
+                neighbors
+                    .entry(&edge.0)
+                    .or_default()
+                    .push((&edge.1, edge.2));
+                neighbors
+                    .entry(&edge.1)
+                    .or_default()
+                    .push((&edge.0, edge.2));
+            }
+        }
+
+        let mut labels = ids
+            .iter()
+            .map(|&id| (id, id))
+            .collect::<HashMap<&str, &str>>();
+        for _ in 0..MAX_ROUNDS {
+            let mut changed = false;
+            for &id in &ids {
+                let mut weights = BTreeMap::<&str, f64>::new();
+                for &(neighbor, weight) in neighbors.get(id).into_iter().flatten() {
+                    *weights.entry(labels[neighbor]).or_default() += weight;
+                }
+                let best = weights
+                    .into_iter()
+                    .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(a.0)));
+                if let Some((label, _)) = best.filter(|(label, _)| labels[id] != *label) {
+                    labels.insert(id, label);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let mut communities = BTreeMap::<&str, Vec<String>>::new();
+        for &id in &ids {
+            communities
+                .entry(labels[id])
+                .or_default()
+                .push(id.to_owned());
+        }
+        let mut communities = communities.into_values().collect::<Vec<_>>();
+        communities.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        communities
+    }
+
+    /// [Communities](Graph::communities) named after their distinctive keyphrases
+    ///
+    /// Keyphrases are extracted from the titles and abstracts of each community and ranked
+    /// by their score times the share of the graph papers containing them that fall in the
+    /// community, so that phrases common to the whole graph do not name every community.
+    pub fn labeled_communities(&self) -> Vec<Community> {
+        let all = self.nodes.values().cloned().collect::<Vec<_>>();
+        let graph_counts = keyword_counts(&all);
+
+        self.communities()
+            .into_iter()
+            .enumerate()
+            .map(|(i, nodes)| {
+                let papers = nodes
+                    .iter()
+                    .map(|id| self.nodes[id].clone())
+                    .collect::<Vec<_>>();
+                let keywords = distinctive_keywords(&papers, &graph_counts);
+                Community {
+                    label: label(&keywords).unwrap_or_else(|| format!("Community {}", i + 1)),
+                    keywords,
+                    nodes,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Number of papers containing each keyphrase of a paper set
+pub(crate) fn keyword_counts<P: PaperLike>(papers: &[P]) -> HashMap<String, usize> {
+    keywords(papers, usize::MAX)
+        .into_iter()
+        .map(|keyword| (keyword.phrase, keyword.papers))
+        .collect()
+}
+
+/// Keyphrases of papers weighted by the share of their occurrences in the wider set
+pub(crate) fn distinctive_keywords<P: PaperLike>(
+    papers: &[P],
+    counts: &HashMap<String, usize>,
+) -> Vec<Keyword> {
+    let mut keywords = keywords(papers, usize::MAX)
+        .into_iter()
+        .map(|mut keyword| {
+            let total = counts
+                .get(&keyword.phrase)
+                .copied()
+                .unwrap_or(keyword.papers);
+            keyword.score *= keyword.papers as f64 / total.max(1) as f64;
+            keyword
+        })
+        .collect::<Vec<_>>();
+    keywords.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.phrase.cmp(&b.phrase))
+    });
+    keywords
+}
+
+/// Label made of the best keyphrases, skipping phrases overlapping one already taken
+pub(crate) fn label(keywords: &[Keyword]) -> Option<String> {
+    let mut phrases = Vec::<&str>::new();
+    for keyword in keywords {
+        if phrases.len() == LABEL_PHRASES {
+            break;
+        }
+        let overlaps = phrases
+            .iter()
+            .any(|phrase| phrase.contains(&keyword.phrase) || keyword.phrase.contains(phrase));
+        if !overlaps {
+            phrases.push(&keyword.phrase);
+        }
+    }
+    (!phrases.is_empty()).then(|| phrases.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Edge, Paper};
+
+    #[test]
+    fn test_labeled_communities() {
+        let mut graph = Graph::example();
+        graph.nodes.clear();
+        graph.edges.clear();
+        let topics = [
+            ("a", "Graph neural networks for citation recommendation"),
+            ("b", "Graph neural networks for link prediction"),
+            ("c", "Protein folding with deep learning"),
+            ("d", "Protein folding in simulations"),
+            ("e", "Untitled"),
+        ];
+        for (id, title) in topics {
+            let mut paper = Paper::new(id);
+            paper.title = Some(title.to_owned());
+            graph.nodes.insert(id.to_owned(), paper);
+        }
+        for (source, target, weight) in [("a", "b", 0.9), ("c", "d", 0.8), ("b", "c", 0.1)] {
+            graph
+                .edges
+                .push(Edge(source.to_owned(), target.to_owned(), weight));
+        }
+
+        assert_eq!(
+            graph.communities(),
+            vec![vec!["a", "b"], vec!["c", "d"], vec!["e"]]
+        );
+        let communities = graph.labeled_communities();
+        assert_eq!(
+            communities[0].label,
+            "graph neural networks, citation recommendation"
+        );
+        assert_eq!(communities[1].label, "protein folding, deep learning");
+        assert_eq!(communities[2].label, "untitled");
+    }
+}
@@ -0,0 +1,90 @@
+//! DOI cleanup and validation
+//!
+//! DOIs are case-insensitive and often copied as links or with a `doi:` prefix, while
+//! Semantic Scholar silently returns nothing for a malformed one. [`normalize_doi`] brings
+//! DOIs to a single form for matching and [`parse_doi`] also checks their syntax, so a typo
+//! fails loudly before reaching the API.
+
+use crate::{
+    error::{Error, Result},
+    ss::PaperId,
+};
+
+/// Prefixes stripped from DOIs, compared ignoring case
+const DOI_PREFIXES: &[&str] = &[
+    "https://doi.org/",
+    "http://doi.org/",
+    "https://dx.doi.org/",
+    "http://dx.doi.org/",
+    "doi.org/",
+    "dx.doi.org/",
+    "doi:",
+];
+
+/// Lowercase DOI without its resolver or `doi:` prefix, e.g. `10.18653/v1/n18-3011` for
+/// `https://doi.org/10.18653/v1/N18-3011`
+pub fn normalize_doi(doi: &str) -> String {
+    let doi = doi.trim().to_lowercase();
+    DOI_PREFIXES
+        .iter()
+        .find_map(|prefix| doi.strip_prefix(prefix))
+        .map_or_else(|| doi.clone(), |doi| doi.trim_start().to_owned())
+}
+
+/// Normalize a DOI and check its syntax
+///
+/// A DOI is `10.` followed by the digits and dots of the registrant code, a slash and a
+/// non-empty suffix without whitespace or control characters.
+pub fn parse_doi(doi: &str) -> Result<String> {
+    let normalized = normalize_doi(doi);
+    let valid = normalized
+        .strip_prefix("10.")
+        .and_then(|rest| rest.split_once('/'))
+        .is_some_and(|(registrant, suffix)| {
+            !registrant.is_empty()
+                && registrant
+                    .split('.')
+                    .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+                && !suffix.is_empty()
+                && !suffix.chars().any(|c| c.is_whitespace() || c.is_control())
+        });
+    if valid {
+        Ok(normalized)
+    } else {
+        Err(Error::InvalidParameter(format!("invalid DOI: {}", doi)))
+    }
+}
+
+impl PaperId {
+    /// Create a DOI after normalizing it and checking its syntax, see [`parse_doi`]
+    pub fn checked_doi(doi: &str) -> Result<Self> {
+        parse_doi(doi).map(PaperId::DOI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_doi() {
+        assert_eq!(
+            parse_doi(" https://doi.org/10.18653/v1/N18-3011").unwrap(),
+            "10.18653/v1/n18-3011"
+        );
+        assert_eq!(
+            parse_doi("DOI: 10.1002/(SICI)1097-4571(199806)49:8<693::AID-ASI4>3.0.CO;2-0").unwrap(),
+            "10.1002/(sici)1097-4571(199806)49:8<693::aid-asi4>3.0.co;2-0"
+        );
+        assert!(parse_doi("10.1000.10/abc").is_ok());
+        assert!(parse_doi("10.18653").is_err());
+        assert!(parse_doi("11.1000/abc").is_err());
+        assert!(parse_doi("10.abc/def").is_err());
+        assert!(parse_doi("10.1000/a b").is_err());
+        assert!(parse_doi("10.1000/").is_err());
+        assert_eq!(
+            PaperId::checked_doi("http://dx.doi.org/10.1000/XYZ").unwrap(),
+            PaperId::doi("10.1000/xyz")
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Enrichment of papers from other sources
+//!
+//! An [`Enricher`] looks up extra information about a paper, typically by DOI, in a source
+//! neither API covers. [`enrich_papers`] runs one over a set of papers and keys what it found
+//! by paper id. With the `crossref` feature, [`CrossrefFunders`] attaches the funders and
+//! grant numbers Crossref records for a DOI, to trace funded outputs inside a graph.
+
+#[cfg(feature = "connected-papers")]
+use crate::client::Graph;
+use crate::{error::Result, paper::PaperLike};
+use futures::{StreamExt, stream};
+use std::{collections::BTreeMap, future::Future};
+
+/// Source of extra information about papers
+pub trait Enricher {
+    type Output;
+
+    /// Look up a paper, returning `None` when the source knows nothing about it
+    fn enrich<P: PaperLike + Sync + ?Sized>(
+        &self,
+        paper: &P,
+    ) -> impl Future<Output = Result<Option<Self::Output>>> + Send;
+}
+
+/// Run an enricher over papers, `concurrency` lookups at a time
+///
+/// Returns what was found keyed by paper id; the first failed lookup fails the whole run.
+pub async fn enrich_papers<P, E>(
+    enricher: &E,
+    papers: &[P],
+    concurrency: usize,
+) -> Result<BTreeMap<String, E::Output>>
+where
+    P: PaperLike + Sync,
+    E: Enricher,
+{
+    enrich_all(enricher, papers, concurrency).await
+}
+
+async fn enrich_all<'a, P, E>(
+    enricher: &E,
+    papers: impl IntoIterator<Item = &'a P>,
+    concurrency: usize,
+) -> Result<BTreeMap<String, E::Output>>
+where
+    P: PaperLike + Sync + 'a,
+    E: Enricher,
+{
+    let mut lookups = stream::iter(papers)
+        .map(|paper| async move {
+            let output = enricher.enrich(paper).await?;
+            Ok::<_, crate::Error>(output.map(|output| (paper.id().to_owned(), output)))
+        })
+        .buffer_unordered(concurrency.max(1));
+    let mut enriched = BTreeMap::new();
+    while let Some(found) = lookups.next().await {
+        enriched.extend(found?);
+    }
+    Ok(enriched)
+}
+
+#[cfg(feature = "connected-papers")]
+impl Graph {
+    /// Run an enricher over the nodes of the graph, see [`enrich_papers`]
+    pub async fn enrich_with<E: Enricher>(
+        &self,
+        enricher: &E,
+        concurrency: usize,
+    ) -> Result<BTreeMap<String, E::Output>> {
+        enrich_all(enricher, self.nodes.values(), concurrency).await
+    }
+}
+
+#[cfg(feature = "crossref")]
+pub use crossref::*;
+
+#[cfg(feature = "crossref")]
+mod crossref {
+    use super::Enricher;
+    use crate::{
+        error::{Error, Result},
+        paper::PaperLike,
+        polite::Politeness,
+        utils::{HttpConfig, Method, Transport, build_request, send},
+    };
+    use reqwest::{Client, StatusCode, Url};
+    use serde::Deserialize;
+    use std::time::Duration;
+
+    const BASE_URL: &str = "https://api.crossref.org";
+
+    const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Funder of a paper as recorded by Crossref
+    #[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+    pub struct Funder {
+        /// Funder name, e.g. `National Science Foundation`
+        #[serde(default)]
+        pub name: String,
+        /// DOI of the funder in the Open Funder Registry, e.g. `10.13039/100000001`
+        #[serde(rename = "DOI")]
+        pub doi: Option<String>,
+        /// Grant numbers
+        #[serde(default, rename = "award")]
+        pub awards: Vec<String>,
+    }
+
+    /// Enricher looking up the funders of papers in Crossref by DOI
+    ///
+    /// Papers without a DOI, unknown to Crossref or without funding metadata are skipped.
+    /// Requests go through a [`Politeness`] layer.
+    #[derive(Debug, Clone)]
+    pub struct CrossrefFunders {
+        client: Client,
+        transport: Transport,
+        politeness: Politeness,
+        base_url: String,
+    }
+
+    impl Default for CrossrefFunders {
+        fn default() -> Self {
+            Self::with_politeness(Politeness::default())
+        }
+    }
+
+    impl CrossrefFunders {
+        /// Create an enricher identifying itself with a contact email
+        ///
+        /// Crossref routes requests carrying an email to its faster "polite" pool.
+        pub fn with_mailto(email: &str) -> Self {
+            Self::with_politeness(
+                Politeness::builder()
+                    .contact(email)
+                    .build()
+                    .expect("default limits are valid"),
+            )
+        }
+
+        /// Create an enricher sharing a politeness layer, sending its contact email if any
+        pub fn with_politeness(politeness: Politeness) -> Self {
+            Self {
+                client: HttpConfig::default()
+                    .build(&politeness.user_agent(), DEFAULT_TIMEOUT)
+                    .unwrap(),
+                transport: Transport::default(),
+                politeness,
+                base_url: BASE_URL.to_owned(),
+            }
+        }
+
+        fn work_url(&self, doi: &str) -> Result<Url> {
+            let mut url = Url::parse(&self.base_url)
+                .map_err(|e| Error::InvalidParameter(format!("invalid base URL: {}", e)))?;
+            url.path_segments_mut()
+                .map_err(|_| Error::InvalidParameter("invalid base URL".to_owned()))?
+                .push("works")
+                .push(doi);
+            if let Some(mailto) = self.politeness.contact() {
+                url.query_pairs_mut().append_pair("mailto", mailto);
+            }
+            Ok(url)
+        }
+    }
+
+    impl Enricher for CrossrefFunders {
+        type Output = Vec<Funder>;
+
+        async fn enrich<P: PaperLike + Sync + ?Sized>(
+            &self,
+            paper: &P,
+        ) -> Result<Option<Vec<Funder>>> {
+            let Some(doi) = paper.doi() else {
+                return Ok(None);
+            };
+            let url = self.work_url(doi)?;
+            let req_builder = build_request(&self.client, Method::Get, url.as_str(), None);
+            let _permit = self.politeness.acquire(url.as_str()).await?;
+            let resp = send(req_builder, &self.transport).await?;
+            match resp.status {
+                StatusCode::OK => Ok(funders(resp.json()?)),
+                StatusCode::NOT_FOUND => Ok(None),
+                _ => Err(resp.into_error()),
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct WorkResponse {
+        message: Work,
+    }
+
+    #[derive(Deserialize)]
+    struct Work {
+        #[serde(default)]
+        funder: Vec<Funder>,
+    }
+
+    fn funders(response: WorkResponse) -> Option<Vec<Funder>> {
+        let funders = response.message.funder;
+        (!funders.is_empty()).then_some(funders)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_crossref_funders() {
+            let response = serde_json::from_value(serde_json::json!({
+                "status": "ok",
+                "message": {
+                    "DOI": "10.1000/xyz",
+                    "funder": [{
+                        "DOI": "10.13039/100000001",
+                        "name": "National Science Foundation",
+                        "doi-asserted-by": "publisher",
+                        "award": ["IIS-1234567"]
+                    }]
+                }
+            }))
+            .unwrap();
+            assert_eq!(
+                funders(response),
+                Some(vec![Funder {
+                    name: "National Science Foundation".to_owned(),
+                    doi: Some("10.13039/100000001".to_owned()),
+                    awards: vec!["IIS-1234567".to_owned()],
+                }])
+            );
+
+            let enricher = CrossrefFunders::with_mailto("me@example.org");
+            assert_eq!(
+                enricher.work_url("10.1000/a b").unwrap().as_str(),
+                "https://api.crossref.org/works/10.1000%2Fa%20b?mailto=me%40example.org"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2NestedPaper;
+
+    /// Enricher answering the length of the title
+    struct TitleLength;
+
+    impl Enricher for TitleLength {
+        type Output = usize;
+
+        async fn enrich<P: PaperLike + Sync + ?Sized>(&self, paper: &P) -> Result<Option<usize>> {
+            Ok(paper.title().map(str::len))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrich_papers() {
+        let mut titled = S2NestedPaper::new("a");
+        titled.title = Some("Title".to_owned());
+        let papers = [titled, S2NestedPaper::new("b")];
+        let enriched = enrich_papers(&TitleLength, &papers, 2).await.unwrap();
+        assert_eq!(enriched, BTreeMap::from([("a".to_owned(), 5)]));
+    }
+}
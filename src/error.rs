@@ -1,16 +1,153 @@
 //! Error
 
+#[cfg(feature = "connected-papers")]
+use crate::client::GraphResponseType;
+
 /// Custom `Error`
+///
+/// Some variants only exist with the feature of the API they belong to, so new variants may
+/// appear as features are enabled.
 #[derive(Debug, thiserror::Error, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
     #[error(transparent)]
     APIKeyNotFound(#[from] std::env::VarError),
     #[error("{0}")]
-    RequestFailed(String),
+    RequestFailed(ApiError),
     #[error("{0}")]
     ReqwestError(String),
+    #[error("{0}")]
+    JsonError(String),
+    #[error("{0}")]
+    IoError(String),
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
+    #[error(
+        "response of at least {size} bytes exceeds the limit of {limit} bytes; request fewer fields, e.g. without embedding, citations or references, or fewer results per page"
+    )]
+    ResponseTooLarge { size: u64, limit: u64 },
+    #[error("no recorded response for {0}")]
+    NotRecorded(String),
+    #[error("unsupported fields for this endpoint: {}", .0.join(", "))]
+    UnsupportedFields(Vec<String>),
+    #[error(
+        "unsupported paper URL host {0}, Semantic Scholar only resolves URLs from {supported}",
+        supported = crate::SUPPORTED_URL_HOSTS.join(", ")
+    )]
+    UnsupportedUrlHost(String),
+    #[cfg(feature = "connected-papers")]
+    #[error("graph unavailable: {0:?}")]
+    GraphUnavailable(GraphResponseType),
+    #[cfg(feature = "connected-papers")]
+    #[error(
+        "unsupported graph schema version {0}, the newest supported is {newest}",
+        newest = crate::GRAPH_VERSION
+    )]
+    UnsupportedGraphVersion(u32),
+    #[error("CSL error: {0}")]
+    CslError(String),
+    #[error("{source} (in {context})")]
+    WithContext {
+        source: Box<Error>,
+        context: ErrorContext,
+    },
+}
+
+impl Error {
+    /// The error without its request context
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::WithContext { source, .. } => source.inner(),
+            error => error,
+        }
+    }
+
+    /// The request the error occurred in, if known
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::WithContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Attach the request the error occurred in, keeping any context already attached
+    #[cfg(any(
+        feature = "connected-papers",
+        feature = "semantic-scholar",
+        feature = "crossref"
+    ))]
+    pub(crate) fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            Error::WithContext { .. } => self,
+            error => Error::WithContext {
+                source: Box::new(error),
+                context,
+            },
+        }
+    }
+
+    /// Record the attempt of a retried request in its context, if one is attached
+    pub(crate) fn with_attempt(mut self, attempt: u32) -> Self {
+        if let Error::WithContext {
+            ref mut context, ..
+        } = self
+        {
+            context.attempt = attempt;
+        }
+        self
+    }
+}
+
+/// Request an error occurred in
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// HTTP method and path of the endpoint, e.g. `GET /graph/v1/paper/search`
+    pub endpoint: String,
+    /// Query parameters, with long values truncated
+    pub params: Vec<(String, String)>,
+    /// Attempt number of the request, starting at 1
+    pub attempt: u32,
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.endpoint)?;
+        for (i, (key, value)) in self.params.iter().enumerate() {
+            write!(f, "{}{}={}", if i == 0 { '?' } else { '&' }, key, value)?;
+        }
+        if self.attempt > 1 {
+            write!(f, ", attempt {}", self.attempt)?;
+        }
+        Ok(())
+    }
+}
+
+/// Unsuccessful response returned by an API
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    /// HTTP status code
+    pub status: u16,
+    /// Error message of a JSON body such as `{"error": "..."}` or `{"message": "..."}`
+    pub message: Option<String>,
+    /// Response body
+    pub body: String,
+    /// Request identifier assigned by the server, to quote when contacting support
+    pub request_id: Option<String>,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HTTP {}: {}",
+            self.status,
+            self.message.as_ref().unwrap_or(&self.body)
+        )?;
+        if let Some(ref request_id) = self.request_id {
+            write!(f, " (request id: {})", request_id)?;
+        }
+        Ok(())
+    }
 }
 
 impl From<reqwest::Error> for Error {
@@ -19,5 +156,50 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::JsonError(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::IoError(error.to_string())
+    }
+}
+
 /// Custom `Result`
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(all(
+    test,
+    any(
+        feature = "connected-papers",
+        feature = "semantic-scholar",
+        feature = "crossref"
+    )
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_context() {
+        let error = Error::RequestFailed(ApiError {
+            status: 429,
+            message: None,
+            body: "Too Many Requests".to_owned(),
+            request_id: Some("abc".to_owned()),
+        })
+        .with_context(ErrorContext {
+            endpoint: "GET /graph/v1/paper/search".to_owned(),
+            params: vec![("query".to_owned(), "graph".to_owned())],
+            attempt: 2,
+        });
+        assert_eq!(
+            error.to_string(),
+            "HTTP 429: Too Many Requests (request id: abc) (in GET /graph/v1/paper/search?query=graph, attempt 2)"
+        );
+        assert!(matches!(error.inner(), Error::RequestFailed(_)));
+        assert_eq!(error.context().unwrap().attempt, 2);
+    }
+}
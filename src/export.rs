@@ -0,0 +1,805 @@
+//! Export of papers and graphs
+//!
+//! Writes papers as a Markdown reading list, a Pandoc Markdown bibliography, a BibTeX
+//! bibliography, possibly merged into an existing `.bib` file, CSV for reference managers or
+//! Dublin Core XML for institutional repositories, and Connected Papers graphs as GraphML for
+//! graph tools such as Gephi or Cytoscape.
+
+use crate::{
+    cite::to_csl_json,
+    client::Graph,
+    doi::normalize_doi,
+    paper::PaperLike,
+    utils::{escape, normalize_title},
+};
+use std::{collections::HashSet, fmt::Write};
+
+/// Export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Markdown reading list
+    Markdown,
+    /// GraphML graph
+    GraphMl,
+    /// BibTeX bibliography
+    Bibtex,
+    /// Pandoc Markdown with the references in its YAML metadata block
+    Pandoc,
+    /// CSV for the Paperpile and ReadCube importers
+    Csv,
+    /// Dublin Core XML following the OpenAIRE guidelines
+    DublinCore,
+}
+
+/// Write papers as a Markdown list, one item with authors, year, title and link per paper
+///
+/// Each item quotes the [summary](PaperLike::summary) of its paper below, marking summaries
+/// extracted from the abstract rather than taken from the TL;DR.
+pub fn to_markdown<P: PaperLike>(papers: &[P]) -> String {
+    let mut markdown = String::new();
+    for paper in papers {
+        markdown.push_str("- ");
+        match paper.author_names().as_slice() {
+            [] => (),
+            [author] => {
+                let _ = write!(markdown, "{} ", escape_markdown(author));
+            }
+            [first, second] => {
+                let _ = write!(
+                    markdown,
+                    "{} & {} ",
+                    escape_markdown(first),
+                    escape_markdown(second)
+                );
+            }
+            [first, ..] => {
+                let _ = write!(markdown, "{} et al. ", escape_markdown(first));
+            }
+        }
+        if let Some(year) = paper.year() {
+            let _ = write!(markdown, "({}). ", year);
+        }
+        let title = paper.title().unwrap_or(paper.id());
+        let _ = write!(markdown, "*{}*", escape_markdown(title));
+        match (paper.doi(), paper.primary_link()) {
+            (Some(doi), Some(link)) => {
+                let _ = write!(markdown, " [doi:{}]({})", doi, link);
+            }
+            (None, Some(link)) => {
+                let _ = write!(markdown, " <{}>", link);
+            }
+            _ => (),
+        }
+        markdown.push('\n');
+        if let Some(summary) = paper.summary() {
+            let _ = writeln!(
+                markdown,
+                "  > {}{}",
+                escape_markdown(&summary.text),
+                if summary.generated {
+                    " *(extracted from the abstract)*"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+    markdown
+}
+
+/// Write papers as BibTeX entries
+///
+/// Citation keys are made by [`bibtex_key`], e.g. `ammar2018construction`. Repeated keys get
+/// a numeric suffix.
+pub fn to_bibtex<P: PaperLike>(papers: &[P]) -> String {
+    let mut keys = HashSet::new();
+    let mut bibtex = String::new();
+    for paper in papers {
+        let key = unique_key(&mut keys, bibtex_key(paper));
+        write_bibtex_entry(&mut bibtex, &key, paper);
+    }
+    bibtex
+}
+
+/// Write papers as CSV, with the columns of the Paperpile and ReadCube importers
+///
+/// The columns are `Title`, `Authors`, `Year`, `DOI`, `URL` and `Abstract`, with the authors
+/// separated by semicolons and the URL given by [`PaperLike::primary_link`]. Fields are
+/// quoted as in RFC 4180 and rows end with CRLF.
+pub fn to_csv<P: PaperLike>(papers: &[P]) -> String {
+    let mut csv = String::from("Title,Authors,Year,DOI,URL,Abstract\r\n");
+    for paper in papers {
+        let row = [
+            paper.title().unwrap_or_default().to_owned(),
+            paper.author_names().join("; "),
+            paper
+                .year()
+                .map(|year| year.to_string())
+                .unwrap_or_default(),
+            paper.doi().unwrap_or_default().to_owned(),
+            paper.primary_link().unwrap_or_default(),
+            paper.abstract_clean().unwrap_or_default(),
+        ];
+        let row = row
+            .iter()
+            .map(|field| escape_csv(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&row);
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Write a paper as an `oai_dc` Dublin Core record, following the OpenAIRE guidelines
+///
+/// Creators are written as `Family, Given`, the DOI both as a resolvable identifier and as an
+/// `info:eu-repo` alternative identifier, and the venue, volume and pages as the source. The
+/// access rights are unknown to the APIs and left for the repository to add.
+pub fn to_dublin_core<P: PaperLike + ?Sized>(paper: &P) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    write_dublin_core_record(&mut xml, "", DUBLIN_CORE_NAMESPACES, paper);
+    xml
+}
+
+/// Write papers as Dublin Core records, see [`to_dublin_core`]
+///
+/// The `oai_dc:dc` records are wrapped in a `records` element, one per paper.
+pub fn to_dublin_core_records<P: PaperLike>(papers: &[P]) -> String {
+    dublin_core_records(papers)
+}
+
+fn dublin_core_records<'a, P: PaperLike + ?Sized + 'a>(
+    papers: impl IntoIterator<Item = &'a P>,
+) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records{}>\n",
+        DUBLIN_CORE_NAMESPACES
+    );
+    for paper in papers {
+        write_dublin_core_record(&mut xml, "  ", "", paper);
+    }
+    xml.push_str("</records>\n");
+    xml
+}
+
+const DUBLIN_CORE_NAMESPACES: &str = concat!(
+    " xmlns:oai_dc=\"http://www.openarchives.org/OAI/2.0/oai_dc/\"",
+    " xmlns:dc=\"http://purl.org/dc/elements/1.1/\"",
+);
+
+fn write_dublin_core_record<P: PaperLike + ?Sized>(
+    xml: &mut String,
+    indent: &str,
+    namespaces: &str,
+    paper: &P,
+) {
+    let _ = writeln!(xml, "{}<oai_dc:dc{}>", indent, namespaces);
+    let mut element = |name: &str, value: &str| {
+        let _ = writeln!(
+            xml,
+            "{}  <dc:{}>{}</dc:{}>",
+            indent,
+            name,
+            escape(value),
+            name
+        );
+    };
+    element("title", paper.title().unwrap_or(paper.id()));
+    for author in paper.author_names() {
+        let creator = match author.rsplit_once(' ') {
+            Some((given, family)) => format!("{}, {}", family, given),
+            None => author.to_owned(),
+        };
+        element("creator", &creator);
+    }
+    if let Some(year) = paper.year() {
+        element("date", &year.to_string());
+    }
+    element("type", "info:eu-repo/semantics/article");
+    if let Some(doi) = paper.doi() {
+        element("identifier", &format!("https://doi.org/{}", doi));
+        element(
+            "relation",
+            &format!("info:eu-repo/semantics/altIdentifier/doi/{}", doi),
+        );
+    } else if let Some(link) = paper.primary_link() {
+        element("identifier", &link);
+    }
+    if let Some(abstract_) = paper.abstract_clean() {
+        element("description", &abstract_);
+    }
+    if let Some(venue) = paper.venue() {
+        let mut source = venue.to_owned();
+        if let Some(volume) = paper.volume() {
+            let _ = write!(source, ", {}", volume);
+        }
+        if let Some(pages) = paper.pages() {
+            let _ = write!(source, ", {}", pages);
+        }
+        element("source", &source);
+    }
+    let _ = writeln!(xml, "{}</oai_dc:dc>", indent);
+}
+
+/// Write papers as a Pandoc Markdown document listing them as references
+///
+/// The YAML metadata block holds the papers as CSL references keyed by [`bibtex_key`], so
+/// that the document can cite them as `[@ammar2018construction]` and `pandoc --citeproc`
+/// renders them, and lists them all under `nocite` so that uncited papers are rendered too.
+/// The bibliography is placed in the `# References` section.
+pub fn to_pandoc<P: PaperLike>(papers: &[P]) -> String {
+    let mut keys = HashSet::new();
+    let references = papers
+        .iter()
+        .map(|paper| {
+            let key = unique_key(&mut keys, bibtex_key(paper));
+            let mut item = to_csl_json(paper);
+            item["id"] = key.clone().into();
+            (key, item)
+        })
+        .collect::<Vec<_>>();
+
+    let mut pandoc = String::from("---\n");
+    if !references.is_empty() {
+        let citations = references
+            .iter()
+            .map(|(key, _)| format!("@{}", key))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(pandoc, "nocite: |\n  {}", citations);
+        pandoc.push_str("references:\n");
+        for (_, item) in &references {
+            // JSON is valid YAML flow style
+            let _ = writeln!(pandoc, "- {}", item);
+        }
+    }
+    pandoc.push_str("---\n\n# References\n\n::: {#refs}\n:::\n");
+    pandoc
+}
+
+/// Append to a BibTeX document the entries of the papers it does not contain yet
+///
+/// See [`Bibliography`] for how papers are matched and keys kept unique.
+pub fn merge_bibtex<P: PaperLike>(existing: &str, papers: &[P]) -> String {
+    let entries = Bibliography::parse(existing).merge(papers);
+    if entries.is_empty() {
+        return existing.to_owned();
+    }
+    let mut merged = existing.trim_end().to_owned();
+    if !merged.is_empty() {
+        merged.push_str("\n\n");
+    }
+    merged.push_str(&entries);
+    merged
+}
+
+/// Entries of an existing BibTeX bibliography, e.g. a `.bib` file
+///
+/// Papers already in the bibliography are matched by DOI, or by title ignoring case and
+/// punctuation, so that merging a graph or search result does not duplicate references.
+/// Keys of new entries get a numeric suffix when they collide with a key already taken,
+/// compared case-insensitively as BibTeX does.
+#[derive(Debug, Clone, Default)]
+pub struct Bibliography {
+    keys: HashSet<String>,
+    dois: HashSet<String>,
+    titles: HashSet<String>,
+}
+
+impl Bibliography {
+    /// Read the keys, DOIs and titles of the entries of a BibTeX document
+    ///
+    /// `@comment`, `@string` and `@preamble` blocks are skipped.
+    pub fn parse(bibtex: &str) -> Self {
+        let mut bibliography = Self::default();
+        let mut rest = bibtex;
+        while let Some(at) = rest.find('@') {
+            rest = &rest[at + 1..];
+            let Some(open) = rest.find(['{', '(']) else {
+                break;
+            };
+            let kind = rest[..open].trim().to_ascii_lowercase();
+            let close = if rest[open..].starts_with('{') {
+                '}'
+            } else {
+                ')'
+            };
+            let body = &rest[open + 1..];
+            let len = entry_len(body, close);
+            rest = &body[len..];
+            if matches!(kind.as_str(), "comment" | "string" | "preamble") {
+                continue;
+            }
+            let Some((key, fields)) = body[..len].split_once(',') else {
+                continue;
+            };
+            bibliography.keys.insert(key.trim().to_lowercase());
+            for (name, value) in bibtex_fields(fields) {
+                match name.as_str() {
+                    "doi" => {
+                        bibliography.dois.insert(normalize_doi(value));
+                    }
+                    "title" => {
+                        bibliography.titles.insert(normalize_title(value));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        bibliography
+    }
+
+    /// Whether a key is taken
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.keys.contains(&key.to_lowercase())
+    }
+
+    /// Whether the paper is already in the bibliography
+    pub fn contains<P: PaperLike + ?Sized>(&self, paper: &P) -> bool {
+        if paper
+            .doi()
+            .is_some_and(|doi| self.dois.contains(&normalize_doi(doi)))
+        {
+            return true;
+        }
+        paper
+            .title()
+            .map(normalize_title)
+            .is_some_and(|title| !title.is_empty() && self.titles.contains(&title))
+    }
+
+    /// Key of a paper, with a numeric suffix if it collides with a key already taken
+    pub fn key_for<P: PaperLike + ?Sized>(&self, paper: &P) -> String {
+        let base = bibtex_key(paper);
+        if !self.contains_key(&base) {
+            return base;
+        }
+        (2..)
+            .map(|n| format!("{}{}", base, n))
+            .find(|key| !self.contains_key(key))
+            .expect("some suffix is free")
+    }
+
+    /// Add the papers not in the bibliography yet, returning their BibTeX entries
+    pub fn merge<P: PaperLike>(&mut self, papers: &[P]) -> String {
+        let mut bibtex = String::new();
+        for paper in papers {
+            if self.contains(paper) {
+                continue;
+            }
+            let key = unique_key(&mut self.keys, bibtex_key(paper));
+            if let Some(doi) = paper.doi() {
+                self.dois.insert(normalize_doi(doi));
+            }
+            if let Some(title) = paper.title() {
+                self.titles.insert(normalize_title(title));
+            }
+            write_bibtex_entry(&mut bibtex, &key, paper);
+        }
+        bibtex
+    }
+}
+
+fn write_bibtex_entry<P: PaperLike + ?Sized>(bibtex: &mut String, key: &str, paper: &P) {
+    let _ = writeln!(bibtex, "@article{{{},", key);
+    if let Some(title) = paper.title() {
+        let _ = writeln!(bibtex, "  title = {{{{{}}}}},", escape_bibtex(title));
+    }
+    let authors = paper.author_names();
+    if !authors.is_empty() {
+        let authors = authors
+            .iter()
+            .map(|author| escape_bibtex(author))
+            .collect::<Vec<_>>()
+            .join(" and ");
+        let _ = writeln!(bibtex, "  author = {{{}}},", authors);
+    }
+    if let Some(year) = paper.year() {
+        let _ = writeln!(bibtex, "  year = {{{}}},", year);
+    }
+    if let Some(doi) = paper.doi() {
+        let _ = writeln!(bibtex, "  doi = {{{}}},", escape_bibtex(doi));
+    } else if let Some(link) = paper.primary_link() {
+        let _ = writeln!(bibtex, "  url = {{{}}},", link);
+    }
+    bibtex.push_str("}\n\n");
+}
+
+/// Take `base` as a key, or the first free one with a numeric suffix
+fn unique_key(keys: &mut HashSet<String>, base: String) -> String {
+    if keys.insert(base.to_lowercase()) {
+        return base;
+    }
+    let key = (2..)
+        .map(|n| format!("{}{}", base, n))
+        .find(|key| !keys.contains(&key.to_lowercase()))
+        .expect("some suffix is free");
+    keys.insert(key.to_lowercase());
+    key
+}
+
+/// Length of the body of an entry, up to its closing delimiter
+fn entry_len(body: &str, close: char) -> usize {
+    let mut depth = 0usize;
+    for (i, c) in body.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 && close == '}' => return i,
+            '}' => depth = depth.saturating_sub(1),
+            c if c == close && depth == 0 => return i,
+            _ => (),
+        }
+    }
+    body.len()
+}
+
+/// Fields of an entry as lowercase names and values without their delimiters
+fn bibtex_fields<'a>(fields: &'a str) -> Vec<(String, &'a str)> {
+    let mut parsed = Vec::new();
+    let mut push = |field: &'a str| {
+        if let Some((name, value)) = field.split_once('=') {
+            let value = value.trim().trim_matches(['{', '}', '"']).trim();
+            parsed.push((name.trim().to_ascii_lowercase(), value));
+        }
+    };
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in fields.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '"' if depth == 0 => quoted = !quoted,
+            ',' if depth == 0 && !quoted => {
+                push(&fields[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    push(&fields[start..]);
+    parsed
+}
+
+impl Graph {
+    /// Write the graph as a GraphML document
+    ///
+    /// Nodes carry their title, year, citation count and whether they are the seed paper;
+    /// edges carry their similarity weight.
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+            "  <key id=\"year\" for=\"node\" attr.name=\"year\" attr.type=\"int\"/>\n",
+            "  <key id=\"citations\" for=\"node\" attr.name=\"citations\" attr.type=\"int\"/>\n",
+            "  <key id=\"seed\" for=\"node\" attr.name=\"seed\" attr.type=\"boolean\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+            "  <graph id=\"G\" edgedefault=\"undirected\">\n",
+        ));
+
+        let mut ids = self.nodes.keys().collect::<Vec<_>>();
+        ids.sort();
+        for id in ids {
+            let paper = &self.nodes[id];
+            let _ = writeln!(graphml, "    <node id=\"{}\">", escape(id));
+            if let Some(ref title) = paper.title {
+                let _ = writeln!(
+                    graphml,
+                    "      <data key=\"title\">{}</data>",
+                    escape(title)
+                );
+            }
+            if let Some(year) = paper.year {
+                let _ = writeln!(graphml, "      <data key=\"year\">{}</data>", year);
+            }
+            if let Some(citations) = paper.citations_length {
+                let _ = writeln!(
+                    graphml,
+                    "      <data key=\"citations\">{}</data>",
+                    citations
+                );
+            }
+            let _ = writeln!(
+                graphml,
+                "      <data key=\"seed\">{}</data>",
+                *id == self.start_id
+            );
+            graphml.push_str("    </node>\n");
+        }
+
+        for edge in &self.edges {
+            if !self.nodes.contains_key(&edge.0) || !self.nodes.contains_key(&edge.1) {
+                continue;
+            }
+            let _ = writeln!(
+                graphml,
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"weight\">{}</data></edge>",
+                escape(&edge.0),
+                escape(&edge.1),
+                edge.2
+            );
+        }
+
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+
+    /// Write the papers of the graph as Dublin Core records, ordered by id
+    ///
+    /// See [`to_dublin_core_records`].
+    pub fn to_dublin_core(&self) -> String {
+        let mut ids = self.nodes.keys().collect::<Vec<_>>();
+        ids.sort();
+        dublin_core_records(ids.into_iter().map(|id| &self.nodes[id]))
+    }
+}
+
+/// Stable BibTeX key of a paper
+///
+/// Made of the last name of the first author, the year and the first word of the title in
+/// lowercase ASCII, e.g. `ammar2018construction`, falling back to the paper id when it has
+/// no author, year or title.
+pub fn bibtex_key<P: PaperLike + ?Sized>(paper: &P) -> String {
+    let ascii_lowercase = |word: &str| {
+        word.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect::<String>()
+    };
+    let author = paper
+        .author_names()
+        .first()
+        .and_then(|name| name.split_whitespace().last())
+        .map(ascii_lowercase)
+        .unwrap_or_default();
+    let year = paper
+        .year()
+        .map(|year| year.to_string())
+        .unwrap_or_default();
+    let word = paper
+        .title()
+        .and_then(|title| {
+            title
+                .split_whitespace()
+                .map(ascii_lowercase)
+                .find(|word| !word.is_empty())
+        })
+        .unwrap_or_default();
+
+    let key = format!("{}{}{}", author, year, word);
+    if key.is_empty() {
+        ascii_lowercase(paper.id())
+    } else {
+        key
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '[' | ']' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub(crate) fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn escape_bibtex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '&' | '%' | '$' | '#' | '_' | '{' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2NestedPaper;
+
+    fn paper(id: &str, title: &str, authors: &[&str]) -> S2NestedPaper {
+        serde_json::from_value(serde_json::json!({
+            "paperId": id,
+            "title": title,
+            "year": 2018,
+            "externalIds": { "DOI": "10.18653/v1/N18-3011" },
+            "authors": authors.iter().map(|name| serde_json::json!({ "name": name })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_markdown() {
+        let papers = vec![
+            paper(
+                "a",
+                "Construction of the *Literature* Graph",
+                &["Waleed Ammar"],
+            ),
+            paper("b", "Other", &["A", "B", "C"]),
+        ];
+        let markdown = to_markdown(&papers);
+        assert!(markdown.starts_with(
+            "- Waleed Ammar (2018). *Construction of the \\*Literature\\* Graph* [doi:10.18653/v1/N18-3011](https://doi.org/10.18653/v1/N18-3011)\n"
+        ));
+        assert!(markdown.contains("- A et al. (2018). *Other*"));
+        assert!(!markdown.contains("  > "));
+
+        let mut summarized = paper("c", "Summarized", &["A"]);
+        summarized.tldr = Some(crate::Tldr {
+            model: None,
+            text: Some("A short summary.".to_owned()),
+        });
+        let mut extracted = paper("d", "Extracted", &["A"]);
+        extracted.abstract_ = Some("<p>Only one sentence.</p>".to_owned());
+        let markdown = to_markdown(&[summarized, extracted]);
+        assert!(markdown.contains("*Summarized* [doi:10.18653/v1/N18-3011](https://doi.org/10.18653/v1/N18-3011)\n  > A short summary.\n"));
+        assert!(markdown.ends_with("  > Only one sentence. *(extracted from the abstract)*\n"));
+    }
+
+    #[test]
+    fn test_bibtex() {
+        let papers = vec![
+            paper(
+                "a",
+                "Construction of the Literature Graph",
+                &["Waleed Ammar"],
+            ),
+            paper(
+                "b",
+                "Construction & more",
+                &["Waleed Ammar", "Dirk Groeneveld"],
+            ),
+        ];
+        let bibtex = to_bibtex(&papers);
+        assert!(bibtex.contains("@article{ammar2018construction,"));
+        assert!(bibtex.contains("@article{ammar2018construction2,"));
+        assert!(bibtex.contains("title = {{Construction \\& more}},"));
+        assert!(bibtex.contains("author = {Waleed Ammar and Dirk Groeneveld},"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut other = paper("b", "Other, \"quoted\"", &["A", "B"]);
+        other.abstract_ = Some("<jats:p>Line\nbreak, &amp; more</jats:p>".to_owned());
+        let papers = vec![
+            paper(
+                "a",
+                "Construction of the Literature Graph",
+                &["Waleed Ammar"],
+            ),
+            other,
+        ];
+        assert_eq!(
+            to_csv(&papers),
+            concat!(
+                "Title,Authors,Year,DOI,URL,Abstract\r\n",
+                "Construction of the Literature Graph,Waleed Ammar,2018,10.18653/v1/N18-3011,https://doi.org/10.18653/v1/N18-3011,\r\n",
+                "\"Other, \"\"quoted\"\"\",A; B,2018,10.18653/v1/N18-3011,https://doi.org/10.18653/v1/N18-3011,\"Line break, & more\"\r\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_pandoc() {
+        let papers = vec![
+            paper(
+                "a",
+                "Construction of the Literature Graph",
+                &["Waleed Ammar"],
+            ),
+            paper("b", "Construction: more", &["Waleed Ammar"]),
+        ];
+        let pandoc = to_pandoc(&papers);
+        assert!(pandoc.starts_with(
+            "---\nnocite: |\n  @ammar2018construction, @ammar2018construction2\nreferences:\n- {"
+        ));
+        assert!(pandoc.contains("\"id\":\"ammar2018construction2\""));
+        assert!(pandoc.contains("\"title\":\"Construction: more\""));
+        assert!(pandoc.ends_with("---\n\n# References\n\n::: {#refs}\n:::\n"));
+    }
+
+    #[test]
+    fn test_merge_bibtex() {
+        let existing = concat!(
+            "@comment{generated by hand}\n",
+            "@Article{Ammar2018Construction,\n",
+            "  title = {{Construction of the Literature Graph}},\n",
+            "  doi = {10.18653/V1/N18-3011},\n",
+            "}\n",
+        );
+        let bibliography = Bibliography::parse(existing);
+        assert!(bibliography.contains_key("ammar2018construction"));
+        assert!(!bibliography.contains_key("generated"));
+
+        let known = paper("a", "Another title", &["Waleed Ammar"]);
+        let mut by_title = paper("b", "Construction of the literature graph!", &["W. Ammar"]);
+        by_title.external_ids = None;
+        let mut new = paper("c", "Construction revisited", &["Waleed Ammar"]);
+        new.external_ids = Some(crate::ExternalIds {
+            doi: Some("10.1000/new".to_owned()),
+            ..Default::default()
+        });
+        assert!(bibliography.contains(&known));
+        assert!(bibliography.contains(&by_title));
+        assert!(!bibliography.contains(&new));
+        assert_eq!(bibliography.key_for(&new), "ammar2018construction2");
+
+        let merged = merge_bibtex(existing, &[known, by_title, new.clone(), new]);
+        assert!(merged.starts_with(existing));
+        assert_eq!(merged.matches("@article{").count(), 1);
+        assert!(merged.contains("@article{ammar2018construction2,"));
+        assert!(merged.contains("doi = {10.1000/new},"));
+    }
+
+    #[test]
+    fn test_graphml() {
+        let graph: Graph = serde_json::from_value(serde_json::json!({
+            "nodes": {
+                "a": { "id": "a", "paperId": "a", "title": "Seed <paper>", "year": 2010 },
+                "b": { "id": "b", "paperId": "b", "title": "Other", "citations_length": 4 }
+            },
+            "edges": [["a", "b", 0.5], ["a", "missing", 0.2]],
+            "common_citations": [],
+            "common_references": [],
+            "common_authors": [],
+            "parameters": {
+                "paper_id": "a", "total_nodes": 2, "num_commons": 0, "max_load": 0,
+                "num_neighbors": 0, "spring_iterations": 0, "params_version": 1
+            },
+            "path_lengths": {},
+            "start_id": "a",
+            "current_corpus_date": "2024-01-01",
+            "creation_time": "2024-01-01T00:00:00"
+        }))
+        .unwrap();
+        let graphml = graph.to_graphml();
+        assert_eq!(graphml.matches("<node ").count(), 2);
+        assert_eq!(graphml.matches("<edge ").count(), 1);
+        assert!(graphml.contains("<data key=\"title\">Seed &lt;paper&gt;</data>"));
+        assert!(graphml.contains("<data key=\"seed\">true</data>"));
+        let dublin_core = graph.to_dublin_core();
+        assert_eq!(dublin_core.matches("<oai_dc:dc>").count(), 2);
+        assert!(dublin_core.contains("    <dc:title>Seed &lt;paper&gt;</dc:title>\n"));
+    }
+
+    #[test]
+    fn test_dublin_core() {
+        let mut paper = paper("a", "Construction & more", &["Waleed Ammar", "Plato"]);
+        paper.venue = Some("NAACL".to_owned());
+        let xml = to_dublin_core(&paper);
+        assert!(xml.starts_with(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<oai_dc:dc xmlns:oai_dc=\"http://www.openarchives.org/OAI/2.0/oai_dc/\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n",
+            "  <dc:title>Construction &amp; more</dc:title>\n",
+            "  <dc:creator>Ammar, Waleed</dc:creator>\n",
+            "  <dc:creator>Plato</dc:creator>\n",
+            "  <dc:date>2018</dc:date>\n",
+            "  <dc:type>info:eu-repo/semantics/article</dc:type>\n",
+            "  <dc:identifier>https://doi.org/10.18653/v1/N18-3011</dc:identifier>\n",
+            "  <dc:relation>info:eu-repo/semantics/altIdentifier/doi/10.18653/v1/N18-3011</dc:relation>\n",
+        )));
+        assert!(xml.ends_with("  <dc:source>NAACL</dc:source>\n</oai_dc:dc>\n"));
+        let records = to_dublin_core_records(&[paper.clone(), paper]);
+        assert!(
+            records
+                .starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records xmlns:oai_dc=")
+        );
+        assert_eq!(records.matches("  <oai_dc:dc>\n").count(), 2);
+        assert!(records.ends_with("  </oai_dc:dc>\n</records>\n"));
+    }
+}
@@ -0,0 +1,318 @@
+//! Graphs from arbitrary paper sets
+//!
+//! Connected Papers only maps the neighborhood of a paper it covers. [`GraphBuilder`] derives
+//! a graph of the same shape from any set of Semantic Scholar papers, e.g. the output of a
+//! bulk search: it fetches their citations and references, links the papers sharing the most
+//! of them, as co-citation and bibliographic coupling do, and lays the graph out with the
+//! [force-directed layout](crate::layout).
+
+use crate::{
+    client::{Edge, Graph, Paper, Parameter},
+    error::{Error, Result},
+    graph_version::GRAPH_VERSION,
+    layout::{LayoutOptions, force_layout},
+    paper::PaperLike,
+    pipeline::MAX_BATCH_SIZE,
+    ss::{PaperBatchParam, PaperField, PaperId, S2NestedPaper, SemanticScholar},
+};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Fields requested for the papers of a derived graph
+const BUILD_FIELDS: [PaperField; 9] = [
+    PaperField::Title,
+    PaperField::Year,
+    PaperField::Authors,
+    PaperField::ExternalIds,
+    PaperField::Venue,
+    PaperField::Abstract,
+    PaperField::CitationCount,
+    PaperField::Citations,
+    PaperField::References,
+];
+
+/// Builder of Connected-Papers-like graphs from sets of papers
+#[derive(Debug, Clone)]
+pub struct GraphBuilder {
+    neighbors: usize,
+    layout: LayoutOptions,
+    seed: Option<String>,
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self {
+            neighbors: 8,
+            layout: LayoutOptions::default(),
+            seed: None,
+        }
+    }
+}
+
+impl GraphBuilder {
+    /// Create a builder with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of most similar papers each paper is linked to, 8 by default
+    pub fn neighbors(&mut self, neighbors: usize) -> &mut Self {
+        self.neighbors = neighbors;
+        self
+    }
+
+    /// Set the options of the force-directed layout
+    pub fn layout(&mut self, layout: LayoutOptions) -> &mut Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Set the seed paper of the graph, the most connected paper by default
+    pub fn seed(&mut self, paper_id: &str) -> &mut Self {
+        self.seed = Some(paper_id.to_owned());
+        self
+    }
+
+    /// Fetch the citations and references of papers and build their graph
+    ///
+    /// Papers are fetched by batches of 500 with the batch endpoint, which returns at most
+    /// 9999 citations and references per paper. Papers Semantic Scholar does not know are left
+    /// out of the graph.
+    pub async fn fetch<P: PaperLike>(
+        &self,
+        client: &SemanticScholar,
+        papers: &[P],
+    ) -> Result<Graph> {
+        self.check()?;
+        let mut fetched = Vec::with_capacity(papers.len());
+        for chunk in papers.chunks(MAX_BATCH_SIZE) {
+            let param = PaperBatchParam {
+                ids: chunk.iter().map(|paper| PaperId::id(paper.id())).collect(),
+                fields: Some(BUILD_FIELDS.to_vec()),
+                nested: Vec::new(),
+            };
+            fetched.extend(client.query(&param).await?.into_iter().flatten());
+        }
+        self.build(&fetched)
+    }
+
+    /// Build the graph of papers already fetched with their citations and references
+    ///
+    /// The similarity of two papers is the number of citing and referenced papers they
+    /// share, divided by the geometric mean of their numbers of citations and references.
+    /// Each paper is linked to its most similar papers. The common citations, references and
+    /// authors of the graph are left empty.
+    pub fn build(&self, papers: &[S2NestedPaper]) -> Result<Graph> {
+        self.check()?;
+        let mut seen = HashSet::new();
+        let papers = papers
+            .iter()
+            .filter(|paper| seen.insert(paper.paper_id.as_str()))
+            .collect::<Vec<_>>();
+        if papers.is_empty() {
+            return Err(Error::InvalidParameter("papers is empty".to_owned()));
+        }
+        if let Some(seed) = self
+            .seed
+            .as_ref()
+            .filter(|seed| !papers.iter().any(|paper| paper.paper_id == **seed))
+        {
+            return Err(Error::InvalidParameter(format!(
+                "seed {} is not in the papers",
+                seed
+            )));
+        }
+
+        let edges = self.edges(&papers);
+        let mut degrees = HashMap::<&str, f64>::new();
+        for edge in &edges {
+            *degrees.entry(edge.0.as_str()).or_default() += edge.2;
+            *degrees.entry(edge.1.as_str()).or_default() += edge.2;
+        }
+        let start_id = match self.seed {
+            Some(ref seed) => seed.clone(),
+            None => papers
+                .iter()
+                .map(|paper| paper.paper_id.as_str())
+                .max_by(|a, b| {
+                    let degree = |id: &str| degrees.get(id).copied().unwrap_or(0.0);
+                    degree(a).total_cmp(&degree(b)).then_with(|| b.cmp(a))
+                })
+                .expect("papers is not empty")
+                .to_owned(),
+        };
+
+        let nodes = papers
+            .into_iter()
+            .map(|paper| (paper.paper_id.clone(), Paper::from(paper.clone())))
+            .collect::<HashMap<_, _>>();
+
+        let now = Utc::now();
+        let mut graph = Graph {
+            parameters: Parameter {
+                paper_id: start_id.clone(),
+                total_nodes: nodes.len() as u32,
+                num_neighbors: self.neighbors as u32,
+                spring_iterations: self.layout.iterations,
+                params_version: GRAPH_VERSION,
+                ..Default::default()
+            },
+            nodes,
+            edges,
+            start_id,
+            current_corpus_date: now.date_naive(),
+            creation_time: now.naive_utc(),
+            ..Default::default()
+        };
+        force_layout(&mut graph, &self.layout);
+        Ok(graph)
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.neighbors == 0 {
+            return Err(Error::InvalidParameter(
+                "neighbors must be at least 1".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Edges from each paper to its most similar papers, sorted by ids
+    fn edges(&self, papers: &[&S2NestedPaper]) -> Vec<Edge> {
+        let mut sharing = HashMap::<&str, Vec<usize>>::new();
+        let mut sizes = Vec::with_capacity(papers.len());
+        for (i, paper) in papers.iter().enumerate() {
+            let linked = paper
+                .citations
+                .iter()
+                .flatten()
+                .chain(paper.references.iter().flatten())
+                .map(|linked| linked.paper_id.as_str())
+                .collect::<HashSet<_>>();
+            sizes.push(linked.len());
+            for id in linked {
+                sharing.entry(id).or_default().push(i);
+            }
+        }
+
+        let mut shared = HashMap::<(usize, usize), u32>::new();
+        for indices in sharing.values() {
+            for (k, &a) in indices.iter().enumerate() {
+                for &b in &indices[k + 1..] {
+                    *shared.entry((a, b)).or_default() += 1;
+                }
+            }
+        }
+
+        let mut candidates = vec![Vec::new(); papers.len()];
+        for (&(a, b), &count) in &shared {
+            let similarity = f64::from(count) / ((sizes[a] * sizes[b]) as f64).sqrt();
+            candidates[a].push((similarity, b));
+            candidates[b].push((similarity, a));
+        }
+        let mut edges = BTreeMap::new();
+        for (a, mut candidates) in candidates.into_iter().enumerate() {
+            candidates.sort_by(|x, y| y.0.total_cmp(&x.0).then(x.1.cmp(&y.1)));
+            for (similarity, b) in candidates.into_iter().take(self.neighbors) {
+                let (source, target) = if papers[a].paper_id <= papers[b].paper_id {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                edges.insert(
+                    (
+                        papers[source].paper_id.clone(),
+                        papers[target].paper_id.clone(),
+                    ),
+                    similarity,
+                );
+            }
+        }
+        edges
+            .into_iter()
+            .map(|((source, target), similarity)| Edge(source, target, similarity))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Product, cassette::Cassette, ss::merge_selected_fields};
+    use serde_json::json;
+
+    #[test]
+    fn test_build() {
+        let papers: Vec<S2NestedPaper> = serde_json::from_value(serde_json::json!([
+            { "paperId": "a", "title": "A", "references": [{ "paperId": "x" }, { "paperId": "y" }] },
+            { "paperId": "b", "references": [{ "paperId": "x" }, { "paperId": "y" }] },
+            { "paperId": "c", "references": [{ "paperId": "y" }, { "paperId": "z" }],
+              "citations": [{ "paperId": "w" }] },
+            { "paperId": "d", "citations": [{ "paperId": "w" }] },
+        ]))
+        .unwrap();
+
+        let graph = GraphBuilder::new().build(&papers).unwrap();
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.nodes["a"].title.as_deref(), Some("A"));
+        let edges = graph
+            .edges
+            .iter()
+            .map(|edge| (edge.0.as_str(), edge.1.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(edges, vec![("a", "b"), ("a", "c"), ("b", "c"), ("c", "d")]);
+        assert_eq!(graph.edges[0].2, 1.0);
+        assert_eq!(graph.start_id, "a");
+        assert!(graph.nodes.values().all(|paper| {
+            paper
+                .pos
+                .is_some_and(|pos| pos.iter().all(|x| x.is_finite()))
+        }));
+
+        let graph = GraphBuilder::new()
+            .neighbors(1)
+            .seed("d")
+            .build(&papers)
+            .unwrap();
+        assert_eq!(graph.edges.len(), 2);
+        assert_eq!(graph.start_id, "d");
+        assert!(GraphBuilder::new().seed("e").build(&papers).is_err());
+        assert!(GraphBuilder::new().neighbors(0).build(&papers).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_missing() {
+        let dir = std::env::temp_dir().join(format!("graph-builder-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        let url = format!(
+            "{}/paper/batch?fields={}",
+            Product::Graph.default_url(),
+            merge_selected_fields(&BUILD_FIELDS, &[])
+        );
+        let ids = json!({ "ids": ["a", "missing", "b"] }).to_string();
+        let body = json!([
+            { "paperId": "a", "references": [{ "paperId": "x" }, { "paperId": "y" }] },
+            null,
+            { "paperId": "b", "references": [{ "paperId": "x" }, { "paperId": "y" }] },
+        ])
+        .to_string();
+        cassette
+            .insert("POST", &url, ids.as_bytes(), 200, &body)
+            .unwrap();
+        let client = SemanticScholar::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+
+        let papers: Vec<S2NestedPaper> = serde_json::from_value(
+            json!([{ "paperId": "a" }, { "paperId": "missing" }, { "paperId": "b" }]),
+        )
+        .unwrap();
+        let graph = GraphBuilder::new().fetch(&client, &papers).await.unwrap();
+        let mut ids = graph.nodes.keys().map(String::as_str).collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(graph.edges.len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
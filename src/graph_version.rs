@@ -0,0 +1,129 @@
+//! Graph schema versions
+//!
+//! Connected Papers tags each graph with `parameters.params_version`. Graphs of a version
+//! newer than [`GRAPH_VERSION`] are rejected with [`Error::UnsupportedGraphVersion`] rather
+//! than deserialized with their new fields silently dropped, and payloads of older versions
+//! are adjusted to the current shape first.
+
+use crate::{
+    client::Graph,
+    error::{Error, Result},
+};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+/// Newest graph schema version this crate understands
+pub const GRAPH_VERSION: u32 = 1;
+
+impl Graph {
+    /// Deserialize a graph payload, migrating it from older schema versions
+    ///
+    /// Prefer it over plain deserialization for graphs stored by other tools or older
+    /// releases: deserializing a [`Graph`] directly also rejects newer versions, but does not
+    /// adjust older payloads and reports the rejection as a JSON error.
+    pub fn from_json(mut value: Value) -> Result<Self> {
+        migrate(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Schema version of a graph payload, 0 if it has none
+pub fn schema_version(value: &Value) -> u32 {
+    value
+        .pointer("/parameters/params_version")
+        .and_then(Value::as_u64)
+        .map_or(0, |version| u32::try_from(version).unwrap_or(u32::MAX))
+}
+
+/// Adjust a graph payload to the current schema version
+pub(crate) fn migrate(value: &mut Value) -> Result<()> {
+    let version = schema_version(value);
+    if version > GRAPH_VERSION {
+        return Err(Error::UnsupportedGraphVersion(version));
+    }
+    let Some(graph) = value.as_object_mut() else {
+        return Ok(());
+    };
+    if version == 0 {
+        // Unversioned graphs, built by hand or trimmed for tests, may leave out the
+        // parameters and path lengths of the version 1 payloads
+        let start_id = graph.get("start_id").cloned().unwrap_or_default();
+        let parameters = graph
+            .entry("parameters")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(parameters) = parameters.as_object_mut() {
+            parameters.entry("paper_id").or_insert(start_id);
+            for key in [
+                "total_nodes",
+                "num_commons",
+                "max_load",
+                "num_neighbors",
+                "spring_iterations",
+            ] {
+                parameters.entry(key).or_insert(Value::from(0));
+            }
+            parameters.insert("params_version".to_owned(), Value::from(GRAPH_VERSION));
+        }
+        graph
+            .entry("path_lengths")
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    Ok(())
+}
+
+/// Deserialize `params_version`, rejecting versions newer than [`GRAPH_VERSION`]
+pub(crate) fn supported_version<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+    if version > GRAPH_VERSION {
+        return Err(serde::de::Error::custom(Error::UnsupportedGraphVersion(
+            version,
+        )));
+    }
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(version: Option<u32>) -> Value {
+        let mut value = serde_json::json!({
+            "nodes": {},
+            "start_id": "seed",
+            "current_corpus_date": "2024-01-01",
+            "creation_time": "2024-01-01T00:00:00",
+        });
+        if let Some(version) = version {
+            value["parameters"] = serde_json::json!({
+                "paper_id": "seed", "total_nodes": 0, "num_commons": 0, "max_load": 0,
+                "num_neighbors": 0, "spring_iterations": 0, "params_version": version
+            });
+            value["path_lengths"] = serde_json::json!({});
+        }
+        value
+    }
+
+    #[test]
+    fn test_graph_versions() {
+        let graph = Graph::from_json(payload(Some(1))).unwrap();
+        assert_eq!(graph.parameters.params_version, 1);
+
+        let graph = Graph::from_json(payload(None)).unwrap();
+        assert_eq!(graph.parameters.paper_id, "seed");
+        assert_eq!(graph.parameters.params_version, GRAPH_VERSION);
+        assert!(serde_json::from_value::<Graph>(payload(None)).is_err());
+
+        assert_eq!(
+            Graph::from_json(payload(Some(2))).unwrap_err(),
+            Error::UnsupportedGraphVersion(2)
+        );
+        let error = serde_json::from_value::<Graph>(payload(Some(2))).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("unsupported graph schema version 2")
+        );
+    }
+}
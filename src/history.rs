@@ -0,0 +1,170 @@
+//! Citation count history
+//!
+//! Records the citation counts of papers at successive snapshots, e.g. repeated graph fetches
+//! or searches, into a JSON file, and derives per-paper trends to tell whether an area is
+//! heating up.
+
+use crate::{Graph, PaperLike, error::Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Citation count of a paper at a given date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CitationSample {
+    pub date: NaiveDate,
+    pub citation_count: u32,
+}
+
+/// Change of the citation count of a paper over the recorded history
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CitationTrend {
+    /// Earliest sample
+    pub first: CitationSample,
+    /// Latest sample
+    pub last: CitationSample,
+    /// Citations gained between the first and the last sample
+    pub growth: i64,
+    /// Least-squares slope of the citation count, in citations per year
+    pub per_year: f64,
+}
+
+/// Citation count time series of papers, persisted as a JSON file
+#[derive(Debug, Clone, Default)]
+pub struct CitationHistory {
+    path: Option<PathBuf>,
+    series: BTreeMap<String, Vec<CitationSample>>,
+}
+
+impl CitationHistory {
+    /// Create an empty in-memory history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the history stored at `path`, starting empty if the file does not exist
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let series = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path),
+            series,
+        })
+    }
+
+    /// The file backing this history, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Write the history to its file; in-memory histories are left untouched
+    pub fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.path {
+            std::fs::write(path, serde_json::to_string_pretty(&self.series)?)?;
+        }
+        Ok(())
+    }
+
+    /// Record the citation count of a paper, replacing any sample at the same date
+    pub fn record(&mut self, paper_id: &str, date: NaiveDate, citation_count: u32) {
+        let samples = self.series.entry(paper_id.to_owned()).or_default();
+        let sample = CitationSample {
+            date,
+            citation_count,
+        };
+        match samples.binary_search_by_key(&date, |sample| sample.date) {
+            Ok(i) => samples[i] = sample,
+            Err(i) => samples.insert(i, sample),
+        }
+    }
+
+    /// Record the citation counts of papers, skipping those without one
+    pub fn record_papers<'a, P: PaperLike + 'a>(
+        &mut self,
+        papers: impl IntoIterator<Item = &'a P>,
+        date: NaiveDate,
+    ) {
+        for paper in papers {
+            if let Some(count) = paper.citation_count() {
+                self.record(paper.id(), date, count);
+            }
+        }
+    }
+
+    /// Record the citation counts of the nodes of a graph at its corpus date
+    pub fn record_graph(&mut self, graph: &Graph) {
+        self.record_papers(graph.nodes.values(), graph.current_corpus_date);
+    }
+
+    /// Recorded samples of a paper, oldest first
+    pub fn samples(&self, paper_id: &str) -> &[CitationSample] {
+        self.series
+            .get(paper_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Citation trend of a paper, if at least two samples were recorded
+    pub fn citation_trend(&self, paper_id: &str) -> Option<CitationTrend> {
+        let samples = self.samples(paper_id);
+        let (&first, &last) = (samples.first()?, samples.last()?);
+        if samples.len() < 2 {
+            return None;
+        }
+
+        let points = samples
+            .iter()
+            .map(|sample| {
+                let days = (sample.date - first.date).num_days() as f64;
+                (days / 365.25, f64::from(sample.citation_count))
+            })
+            .collect::<Vec<_>>();
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let covariance = points
+            .iter()
+            .map(|(x, y)| (x - mean_x) * (y - mean_y))
+            .sum::<f64>();
+        let variance = points
+            .iter()
+            .map(|(x, _)| (x - mean_x).powi(2))
+            .sum::<f64>();
+
+        Some(CitationTrend {
+            first,
+            last,
+            growth: i64::from(last.citation_count) - i64::from(first.citation_count),
+            per_year: covariance / variance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_citation_trend() {
+        let date = |year| NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+        let mut history = CitationHistory::new();
+        history.record("a", date(2022), 30);
+        history.record("a", date(2020), 10);
+        history.record("a", date(2021), 15);
+        history.record("a", date(2021), 20);
+
+        assert_eq!(history.samples("a").len(), 3);
+        assert!(history.citation_trend("b").is_none());
+        let trend = history.citation_trend("a").unwrap();
+        assert_eq!(trend.first.date, date(2020));
+        assert_eq!(trend.growth, 20);
+        assert!((trend.per_year - 10.0).abs() < 0.1);
+    }
+}
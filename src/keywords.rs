@@ -0,0 +1,171 @@
+//! Keyphrase extraction
+//!
+//! [`keywords`] extracts the keyphrases of a set of papers from their titles and abstracts
+//! with RAKE: candidate phrases are the runs of words between stop words and punctuation,
+//! and each word scores its degree, the number of words it co-occurs with in a phrase,
+//! over its frequency. Phrases recurring across papers rank first, which suits tags and
+//! labels describing a whole set of papers.
+
+use crate::{paper::PaperLike, utils::STOP_WORDS};
+use std::collections::{HashMap, HashSet};
+
+/// Longest phrase kept, longer runs of words are rarely keyphrases
+const MAX_WORDS: usize = 4;
+
+/// A keyphrase of a set of papers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyword {
+    /// The phrase in lowercase, e.g. `citation graph`
+    pub phrase: String,
+    /// RAKE score of the phrase times the number of papers it occurs in
+    pub score: f64,
+    /// Number of papers whose title or abstract contains the phrase
+    pub papers: usize,
+}
+
+impl Keyword {
+    /// The phrase as a tag, with hyphens between its words, e.g. `citation-graph`
+    pub fn tag(&self) -> String {
+        self.phrase.replace(' ', "-")
+    }
+}
+
+/// Extract the `limit` best keyphrases of papers from their titles and abstracts
+///
+/// Abstracts are [cleaned](PaperLike::abstract_clean) first. Ties are broken by phrase so
+/// that the result does not depend on the order of the papers.
+pub fn keywords<P: PaperLike>(papers: &[P], limit: usize) -> Vec<Keyword> {
+    let documents = papers.iter().map(paper_candidates).collect::<Vec<_>>();
+
+    let mut frequency = HashMap::<&str, f64>::new();
+    let mut degree = HashMap::<&str, f64>::new();
+    for phrase in documents.iter().flatten() {
+        for word in phrase {
+            *frequency.entry(word).or_default() += 1.0;
+            *degree.entry(word).or_default() += phrase.len() as f64;
+        }
+    }
+
+    let mut occurrences = HashMap::<Vec<&str>, usize>::new();
+    for phrases in &documents {
+        let unique = phrases
+            .iter()
+            .map(|phrase| phrase.iter().map(String::as_str).collect::<Vec<_>>())
+            .collect::<HashSet<_>>();
+        for phrase in unique {
+            *occurrences.entry(phrase).or_default() += 1;
+        }
+    }
+
+    let mut keywords = occurrences
+        .into_iter()
+        .map(|(phrase, papers)| {
+            let score = phrase
+                .iter()
+                .map(|word| degree[word] / frequency[word])
+                .sum::<f64>();
+            Keyword {
+                phrase: phrase.join(" "),
+                score: score * papers as f64,
+                papers,
+            }
+        })
+        .collect::<Vec<_>>();
+    keywords.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.phrase.cmp(&b.phrase))
+    });
+    keywords.truncate(limit);
+    keywords
+}
+
+/// Distinct keyphrases in the title and abstract of a paper
+pub(crate) fn phrases<P: PaperLike + ?Sized>(paper: &P) -> HashSet<String> {
+    paper_candidates(paper)
+        .into_iter()
+        .map(|phrase| phrase.join(" "))
+        .collect()
+}
+
+fn paper_candidates<P: PaperLike + ?Sized>(paper: &P) -> Vec<Vec<String>> {
+    let mut text = paper.title().unwrap_or_default().to_owned();
+    if let Some(abstract_) = paper.abstract_clean() {
+        text.push_str(". ");
+        text.push_str(&abstract_);
+    }
+    candidates(&text)
+}
+
+/// Candidate phrases of a text, as lists of lowercase words
+fn candidates(text: &str) -> Vec<Vec<String>> {
+    let mut phrases = Vec::new();
+    let mut phrase = Vec::new();
+    let mut flush = |phrase: &mut Vec<String>| {
+        if !phrase.is_empty() && phrase.len() <= MAX_WORDS {
+            phrases.push(std::mem::take(phrase));
+        }
+        phrase.clear();
+    };
+    for token in text.split_inclusive(|c: char| !c.is_alphanumeric() && c != '-') {
+        let word = token
+            .trim_end_matches(|c: char| !c.is_alphanumeric() && c != '-')
+            .trim_matches('-')
+            .to_lowercase();
+        let is_word = word.chars().count() > 1
+            && word.chars().any(char::is_alphabetic)
+            && !STOP_WORDS.contains(&word.as_str());
+        if is_word {
+            phrase.push(word);
+        } else if !word.is_empty() {
+            flush(&mut phrase);
+        }
+        let delimiter = token.chars().last().filter(|c| !c.is_alphanumeric());
+        if delimiter.is_some_and(|c| !c.is_whitespace() && c != '-') {
+            flush(&mut phrase);
+        }
+    }
+    flush(&mut phrase);
+    phrases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2NestedPaper;
+
+    fn paper(title: &str, abstract_: &str) -> S2NestedPaper {
+        let mut paper = S2NestedPaper::new(title);
+        paper.title = Some(title.to_owned());
+        paper.abstract_ = Some(abstract_.to_owned());
+        paper
+    }
+
+    #[test]
+    fn test_keywords() {
+        assert_eq!(
+            candidates("Graph neural networks for citation-graph analysis, in 2020."),
+            vec![
+                vec!["graph", "neural", "networks"],
+                vec!["citation-graph", "analysis"]
+            ]
+        );
+
+        let papers = [
+            paper(
+                "Graph neural networks for citation recommendation",
+                "We train graph neural networks on the citation graph.",
+            ),
+            paper(
+                "Graph neural networks at scale",
+                "<p>Graph neural networks, trained on large graphs.</p>",
+            ),
+            paper("Protein folding", "Proteins fold."),
+        ];
+        let keywords = keywords(&papers, 3);
+        assert_eq!(keywords[0].phrase, "graph neural networks");
+        assert_eq!(keywords[0].papers, 2);
+        assert_eq!(keywords[0].tag(), "graph-neural-networks");
+        assert_eq!(keywords.len(), 3);
+    }
+}
@@ -0,0 +1,155 @@
+//! Seminal and review papers
+//!
+//! Heuristics flagging the landmarks of a graph: seminal papers, old papers at the center of
+//! the graph that are cited a lot, and reviews, papers typed as reviews by Semantic Scholar or
+//! titled like surveys.
+
+use crate::{
+    client::{Graph, Paper},
+    ss::PublicationType,
+};
+use std::collections::HashMap;
+
+/// Share of the graph maximum each seminal criterion must reach
+const SEMINAL_THRESHOLD: f64 = 0.5;
+
+/// Title words of surveys and reviews
+const REVIEW_WORDS: [&str; 8] = [
+    "review",
+    "reviews",
+    "survey",
+    "surveys",
+    "overview",
+    "tutorial",
+    "meta-analysis",
+    "primer",
+];
+
+/// Paper flagged as likely seminal
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeminalPaper<'a> {
+    pub paper: &'a Paper,
+    /// Sum of the weights of the paper edges, divided by the largest sum of the graph
+    pub centrality: f64,
+    /// Mean of the age, centrality and citation criteria, in `[0, 1]`
+    pub score: f64,
+}
+
+impl Paper {
+    /// Whether the paper is likely a review
+    ///
+    /// True for papers typed as reviews or meta-analyses, or whose title contains a word
+    /// such as "survey", "review" or "overview".
+    pub fn is_likely_review(&self) -> bool {
+        let typed = self.publication_types().iter().any(|kind| {
+            matches!(
+                kind,
+                PublicationType::Review | PublicationType::MetaAnalysis
+            )
+        });
+        typed
+            || self.title.as_deref().is_some_and(|title| {
+                title
+                    .to_lowercase()
+                    .split(|c: char| !c.is_alphanumeric() && c != '-')
+                    .any(|word| REVIEW_WORDS.contains(&word))
+            })
+    }
+}
+
+impl Graph {
+    /// Papers likely seminal in the graph, highest score first
+    ///
+    /// A paper is flagged when it reaches half of the graph maximum on three criteria: its
+    /// age within the years of the graph, its centrality and the logarithm of its citation
+    /// count. Papers without a year are never flagged.
+    pub fn seminal_papers(&self) -> Vec<SeminalPaper<'_>> {
+        let mut degrees = HashMap::<&str, f64>::new();
+        for edge in &self.edges {
+            if edge.0 != edge.1 {
+                *degrees.entry(edge.0.as_str()).or_default() += edge.2;
+                *degrees.entry(edge.1.as_str()).or_default() += edge.2;
+            }
+        }
+        let max_degree = degrees.values().copied().fold(0.0, f64::max);
+        let years = self.nodes.values().filter_map(|paper| paper.year);
+        let (min_year, max_year) = years.fold((u32::MAX, u32::MIN), |(min, max), year| {
+            (min.min(year), max.max(year))
+        });
+        let log_citations = |paper: &Paper| f64::from(paper.citations_length.unwrap_or(0)).ln_1p();
+        let max_citations = self.nodes.values().map(log_citations).fold(0.0, f64::max);
+        let share = |value: f64, max: f64| if max > 0.0 { value / max } else { 0.0 };
+
+        let mut seminal = self
+            .nodes
+            .values()
+            .filter_map(|paper| {
+                let age = share(
+                    f64::from(max_year - paper.year?),
+                    f64::from(max_year - min_year),
+                );
+                let centrality = share(
+                    degrees.get(paper.id.as_str()).copied().unwrap_or(0.0),
+                    max_degree,
+                );
+                let citations = share(log_citations(paper), max_citations);
+                [age, centrality, citations]
+                    .iter()
+                    .all(|criterion| *criterion >= SEMINAL_THRESHOLD)
+                    .then_some(SeminalPaper {
+                        paper,
+                        centrality,
+                        score: (age + centrality + citations) / 3.0,
+                    })
+            })
+            .collect::<Vec<_>>();
+        seminal.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.paper.id.cmp(&b.paper.id))
+        });
+        seminal
+    }
+
+    /// Papers of the graph likely to be reviews, sorted by id
+    pub fn review_papers(&self) -> Vec<&Paper> {
+        let mut reviews = self
+            .nodes
+            .values()
+            .filter(|paper| paper.is_likely_review())
+            .collect::<Vec<_>>();
+        reviews.sort_by(|a, b| a.id.cmp(&b.id));
+        reviews
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Edge;
+
+    #[test]
+    fn test_landmarks() {
+        let mut graph = Graph::example();
+        graph
+            .edges
+            .push(Edge("older".to_owned(), "newer".to_owned(), 0.5));
+        let seminal = graph.seminal_papers();
+        assert_eq!(seminal.len(), 1);
+        assert_eq!(seminal[0].paper.id, "older");
+
+        if let Some(paper) = graph.nodes.get_mut("newer") {
+            paper.title = Some("A Survey of Literature Graphs".to_owned());
+        }
+        if let Some(paper) = graph.nodes.get_mut("seed") {
+            paper.publication_types = Some(vec![PublicationType::Review]);
+        }
+        let ids = graph
+            .review_papers()
+            .iter()
+            .map(|paper| paper.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["newer", "seed"]);
+        assert!(!graph.nodes["older"].is_likely_review());
+    }
+}
@@ -0,0 +1,166 @@
+//! Force-directed layout
+//!
+//! A small Fruchterman-Reingold spring embedder placing the nodes of a graph, for graphs that
+//! do not come with the Connected Papers layout, e.g. those of
+//! [`GraphBuilder`](crate::GraphBuilder). It sets the `pos` coordinates the renderers use.
+
+use crate::client::Graph;
+use std::collections::HashMap;
+
+/// Options of the force-directed layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutOptions {
+    /// Number of iterations
+    pub iterations: u32,
+    /// Seed of the starting positions; the same seed always gives the same layout
+    pub seed: u64,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            seed: 0,
+        }
+    }
+}
+
+impl Graph {
+    /// Lay the graph out with the default options, replacing the positions of its nodes
+    pub fn layout(&mut self) {
+        force_layout(self, &LayoutOptions::default());
+    }
+}
+
+/// Lay a graph out, replacing the positions of its nodes
+///
+/// Nodes start at pseudo-random positions in `[-1, 1]` drawn from the seed, in the order of
+/// their ids. Edges then pull their nodes together in proportion to their weight while every
+/// pair of nodes pushes apart, with moves shrinking at each iteration.
+pub fn force_layout(graph: &mut Graph, options: &LayoutOptions) {
+    let mut ids = graph.nodes.keys().cloned().collect::<Vec<_>>();
+    ids.sort_unstable();
+    let index = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i))
+        .collect::<HashMap<_, _>>();
+    let edges = graph
+        .edges
+        .iter()
+        .filter_map(
+            |edge| match (index.get(edge.0.as_str()), index.get(edge.1.as_str())) {
+                (Some(&a), Some(&b)) if a != b => Some((a, b, edge.2)),
+                _ => None,
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let n = ids.len();
+    let mut random = SplitMix64(options.seed);
+    let mut positions = (0..n)
+        .map(|_| {
+            [
+                random.next_unit() * 2.0 - 1.0,
+                random.next_unit() * 2.0 - 1.0,
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let k = (4.0 / n.max(1) as f64).sqrt();
+    for iteration in 0..options.iterations {
+        let temperature = 0.1 * (1.0 - f64::from(iteration) / f64::from(options.iterations));
+        let mut moves = vec![[0.0; 2]; n];
+        for a in 0..n {
+            for b in a + 1..n {
+                let (dx, dy, distance) = delta(&positions, a, b);
+                let force = k * k / distance;
+                moves[a][0] += dx / distance * force;
+                moves[a][1] += dy / distance * force;
+                moves[b][0] -= dx / distance * force;
+                moves[b][1] -= dy / distance * force;
+            }
+        }
+        for &(a, b, weight) in &edges {
+            let (dx, dy, distance) = delta(&positions, a, b);
+            let force = weight * distance * distance / k;
+            moves[a][0] -= dx / distance * force;
+            moves[a][1] -= dy / distance * force;
+            moves[b][0] += dx / distance * force;
+            moves[b][1] += dy / distance * force;
+        }
+        for (position, step) in positions.iter_mut().zip(&moves) {
+            let length = step[0].hypot(step[1]);
+            if length > 0.0 {
+                let scale = length.min(temperature) / length;
+                position[0] += step[0] * scale;
+                position[1] += step[1] * scale;
+            }
+        }
+    }
+
+    for (id, pos) in ids.iter().zip(positions) {
+        if let Some(paper) = graph.nodes.get_mut(id) {
+            paper.pos = Some(pos);
+        }
+    }
+}
+
+/// Offset from node `b` to node `a` and its length, never zero
+fn delta(positions: &[[f64; 2]], a: usize, b: usize) -> (f64, f64, f64) {
+    let dx = positions[a][0] - positions[b][0];
+    let dy = positions[a][1] - positions[b][1];
+    (dx, dy, dx.hypot(dy).max(1e-9))
+}
+
+/// SplitMix64 generator, enough for reproducible starting positions
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Next number in `[0, 1)`
+    fn next_unit(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_force_layout() {
+        let mut graph = Graph::example();
+        graph.layout();
+        let positions = |graph: &Graph| {
+            let mut positions = graph
+                .nodes
+                .values()
+                .map(|paper| (paper.id.clone(), paper.pos.unwrap()))
+                .collect::<Vec<_>>();
+            positions.sort_by(|a, b| a.0.cmp(&b.0));
+            positions
+        };
+        let first = positions(&graph);
+        assert!(
+            first
+                .iter()
+                .all(|(_, pos)| pos.iter().all(|x| x.is_finite()))
+        );
+
+        let mut again = Graph::example();
+        again.layout();
+        assert_eq!(positions(&again), first);
+
+        let options = LayoutOptions {
+            seed: 42,
+            ..Default::default()
+        };
+        force_layout(&mut again, &options);
+        assert_ne!(positions(&again), first);
+    }
+}
@@ -1,10 +1,147 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 
+pub mod arxiv;
+pub use arxiv::*;
+pub mod cassette;
+pub use cassette::*;
+pub mod cite;
+pub use cite::*;
+pub mod doi;
+pub use doi::*;
+pub mod enrich;
+pub use enrich::*;
 pub mod error;
 pub use error::*;
 pub mod ss;
 pub use ss::*;
+pub mod markup;
+pub use markup::*;
+pub mod paper;
+pub use paper::*;
+pub mod polite;
+pub use polite::*;
+pub mod progress;
+pub use progress::*;
+pub mod scheduler;
+pub use scheduler::*;
+#[cfg(feature = "connected-papers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "connected-papers")))]
+pub mod annotation;
+#[cfg(feature = "connected-papers")]
+pub use annotation::*;
+#[cfg(feature = "connected-papers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "connected-papers")))]
 pub mod client;
+#[cfg(feature = "connected-papers")]
 pub use client::*;
+#[cfg(feature = "connected-papers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "connected-papers")))]
+pub mod graph_version;
+#[cfg(feature = "connected-papers")]
+pub use graph_version::*;
+#[cfg(feature = "connected-papers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "connected-papers")))]
+pub mod history;
+#[cfg(feature = "connected-papers")]
+pub use history::*;
+#[cfg(feature = "connected-papers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "connected-papers")))]
+pub mod library;
+#[cfg(feature = "connected-papers")]
+pub use library::*;
+#[cfg(feature = "semantic-scholar")]
+#[cfg_attr(docsrs, doc(cfg(feature = "semantic-scholar")))]
+pub mod watch;
+#[cfg(feature = "semantic-scholar")]
+pub use watch::*;
+#[cfg(feature = "export")]
+#[cfg_attr(docsrs, doc(cfg(feature = "export")))]
+pub mod export;
+#[cfg(feature = "export")]
+pub use export::*;
+#[cfg(feature = "export")]
+#[cfg_attr(docsrs, doc(cfg(feature = "export")))]
+pub mod reading_list;
+#[cfg(feature = "export")]
+pub use reading_list::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod community;
+#[cfg(feature = "analysis")]
+pub use community::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod graph_builder;
+#[cfg(feature = "analysis")]
+pub use graph_builder::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod keywords;
+#[cfg(feature = "analysis")]
+pub use keywords::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod landmark;
+#[cfg(feature = "analysis")]
+pub use landmark::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod layout;
+#[cfg(feature = "analysis")]
+pub use layout::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod pipeline;
+#[cfg(feature = "analysis")]
+pub use pipeline::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod similarity;
+#[cfg(feature = "analysis")]
+pub use similarity::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod timeline;
+#[cfg(feature = "analysis")]
+pub use timeline::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod trend;
+#[cfg(feature = "analysis")]
+pub use trend::*;
+#[cfg(feature = "analysis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "analysis")))]
+pub mod venue;
+#[cfg(feature = "analysis")]
+pub use venue::*;
+#[cfg(feature = "csl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "csl")))]
+pub mod csl;
+#[cfg(feature = "pdf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pdf")))]
+pub mod pdf;
+#[cfg(feature = "pdf")]
+pub use pdf::*;
+#[cfg(feature = "render")]
+#[cfg_attr(docsrs, doc(cfg(feature = "render")))]
+pub mod render;
+#[cfg(all(
+    feature = "schema",
+    feature = "connected-papers",
+    feature = "semantic-scholar"
+))]
+#[cfg_attr(docsrs, doc(cfg(feature = "schema")))]
+pub mod schema;
+#[cfg(feature = "service")]
+#[cfg_attr(docsrs, doc(cfg(feature = "service")))]
+pub mod service;
+#[cfg(feature = "summary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "summary")))]
+pub mod summary;
+#[cfg(feature = "summary")]
+pub use summary::*;
+#[cfg(feature = "text")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text")))]
+pub mod text;
 pub(crate) mod utils;
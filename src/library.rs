@@ -0,0 +1,105 @@
+//! Library matching
+//!
+//! Compares the nodes of a graph with a user's library, e.g. the papers of a reference
+//! manager export or a reading list, to tell which papers of an area the user already has
+//! and which ones they are missing.
+
+use crate::{
+    client::{Graph, Paper},
+    doi::normalize_doi,
+    paper::PaperLike,
+    utils::normalize_title,
+};
+use std::collections::HashSet;
+
+/// Nodes of a graph split by whether a library holds them
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphIntersection<'a> {
+    /// Nodes already in the library, sorted by id
+    pub known: Vec<&'a Paper>,
+    /// Nodes not in the library, sorted by id
+    pub missing: Vec<&'a Paper>,
+}
+
+/// Ids, DOIs and titles of the papers of a library
+#[derive(Debug, Default)]
+struct LibraryIndex {
+    ids: HashSet<String>,
+    dois: HashSet<String>,
+    titles: HashSet<String>,
+}
+
+impl LibraryIndex {
+    fn new<P: PaperLike>(corpus: &[P]) -> Self {
+        let mut index = Self::default();
+        for paper in corpus {
+            index.ids.insert(paper.id().to_owned());
+            if let Some(doi) = paper.doi() {
+                index.dois.insert(normalize_doi(doi));
+            }
+            if let Some(title) = paper
+                .title()
+                .map(normalize_title)
+                .filter(|title| !title.is_empty())
+            {
+                index.titles.insert(title);
+            }
+        }
+        index
+    }
+
+    fn contains(&self, paper: &Paper) -> bool {
+        self.ids.contains(&paper.id)
+            || self.ids.contains(&paper.paper_id)
+            || paper
+                .doi()
+                .is_some_and(|doi| self.dois.contains(&normalize_doi(doi)))
+            || paper
+                .title()
+                .map(normalize_title)
+                .is_some_and(|title| self.titles.contains(&title))
+    }
+}
+
+impl Graph {
+    /// Split the nodes of the graph by whether they are in a library
+    ///
+    /// A node is in the library when a paper of the corpus has the same id, the same DOI or
+    /// the same title, ignoring case and punctuation.
+    pub fn intersect<P: PaperLike>(&self, corpus: &[P]) -> GraphIntersection<'_> {
+        let index = LibraryIndex::new(corpus);
+        let mut papers = self.nodes.values().collect::<Vec<_>>();
+        papers.sort_by(|a, b| a.id.cmp(&b.id));
+        let (known, missing) = papers.into_iter().partition(|paper| index.contains(paper));
+        GraphIntersection { known, missing }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2NestedPaper;
+
+    #[test]
+    fn test_intersect() {
+        let mut graph = Graph::example();
+        if let Some(paper) = graph.nodes.get_mut("older") {
+            paper.doi = Some("10.1000/older".to_owned());
+        }
+        let corpus: Vec<S2NestedPaper> = serde_json::from_value(serde_json::json!([
+            { "paperId": "seed" },
+            { "paperId": "x", "externalIds": { "DOI": "https://doi.org/10.1000/OLDER" } },
+            { "paperId": "y", "title": "NEWER paper!" },
+        ]))
+        .unwrap();
+
+        let ids = |papers: &[&Paper]| papers.iter().map(|p| p.id.clone()).collect::<Vec<_>>();
+        let intersection = graph.intersect(&corpus);
+        assert_eq!(ids(&intersection.known), vec!["newer", "older", "seed"]);
+        assert!(intersection.missing.is_empty());
+
+        let intersection = graph.intersect(&corpus[..1]);
+        assert_eq!(ids(&intersection.known), vec!["seed"]);
+        assert_eq!(ids(&intersection.missing), vec!["newer", "older"]);
+    }
+}
@@ -0,0 +1,252 @@
+//! Markup removal from abstracts
+//!
+//! Abstracts collected from publishers, Crossref in particular, often keep their JATS or HTML
+//! markup, character entities and LaTeX math. [`clean_abstract`] turns them into plain text
+//! for exports and summaries.
+
+/// Elements separating blocks of text, replaced by a space
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "sec",
+    "br",
+    "div",
+    "list",
+    "list-item",
+    "li",
+    "ul",
+    "ol",
+    "label",
+    "caption",
+    "abstract",
+];
+
+/// LaTeX commands whose argument is kept as is
+const LATEX_WRAPPERS: &[&str] = &[
+    "emph", "textit", "textbf", "textrm", "texttt", "mathrm", "mathit", "mathbf", "mathcal",
+    "text", "mbox",
+];
+
+const LATEX_SYMBOLS: &[(&str, &str)] = &[
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ε"),
+    ("theta", "θ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("pi", "π"),
+    ("sigma", "σ"),
+    ("tau", "τ"),
+    ("phi", "φ"),
+    ("omega", "ω"),
+    ("Delta", "Δ"),
+    ("Sigma", "Σ"),
+    ("Omega", "Ω"),
+    ("times", "×"),
+    ("pm", "±"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("approx", "≈"),
+    ("sim", "~"),
+    ("infty", "∞"),
+    ("to", "→"),
+    ("%", "%"),
+    ("&", "&"),
+    ("_", "_"),
+];
+
+/// Turn an abstract with markup into plain text
+///
+/// Strips JATS and HTML tags, dropping an `Abstract` heading and ending other section titles
+/// with a colon, decodes character entities, unwraps LaTeX math and formatting commands and
+/// collapses whitespace. A `<` not starting a tag, as in `p < 0.05`, is kept.
+pub fn clean_abstract(text: &str) -> String {
+    let text = decode_entities(&strip_tags(text));
+    let text = strip_latex(&text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn strip_tags(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut title_start = None;
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        let tag = &rest[start + 1..];
+        if let Some(comment) = tag.strip_prefix("!--") {
+            rest = comment
+                .find("-->")
+                .map_or("", |end| &comment[end + "-->".len()..]);
+            continue;
+        }
+        let is_tag = tag
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'));
+        let Some(end) = tag.find('>').filter(|_| is_tag) else {
+            stripped.push('<');
+            rest = tag;
+            continue;
+        };
+        let closing = tag.starts_with('/');
+        let name = tag[..end]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        let name = name.rsplit(':').next().unwrap_or_default();
+        if name.eq_ignore_ascii_case("title") {
+            if !closing {
+                stripped.push(' ');
+                title_start = Some(stripped.len());
+            } else if let Some(title_start) = title_start.take() {
+                let title = stripped[title_start..].trim().to_owned();
+                stripped.truncate(title_start);
+                if !title.eq_ignore_ascii_case("abstract") && !title.is_empty() {
+                    stripped.push_str(title.trim_end_matches([':', '.']));
+                    stripped.push_str(": ");
+                }
+            }
+        } else if BLOCK_ELEMENTS
+            .iter()
+            .any(|block| name.eq_ignore_ascii_case(block))
+        {
+            stripped.push(' ');
+        }
+        rest = &tag[end + 1..];
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end + 1]).map(|c| (c, end + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(code) = name.strip_prefix('#') {
+        let code = match code.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => code.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "minus" => '−',
+        "times" => '×',
+        "plusmn" => '±',
+        "le" => '≤',
+        "ge" => '≥',
+        _ => return None,
+    })
+}
+
+fn strip_latex(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_math = false;
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let rest = &text[i + c.len_utf8()..];
+        i += c.len_utf8();
+        match c {
+            // A lone dollar is a currency sign rather than a math delimiter
+            '$' if in_math || rest.contains('$') => in_math = !in_math,
+            '\\' => {
+                let len = match rest.chars().next() {
+                    Some(c) if c.is_ascii_alphabetic() => rest
+                        .find(|c: char| !c.is_ascii_alphabetic())
+                        .unwrap_or(rest.len()),
+                    Some(c) => c.len_utf8(),
+                    None => 0,
+                };
+                let command = &rest[..len];
+                i += len;
+                if let Some((_, symbol)) = LATEX_SYMBOLS.iter().find(|(name, _)| *name == command) {
+                    stripped.push_str(symbol);
+                } else if let Some(argument) = LATEX_WRAPPERS
+                    .contains(&command)
+                    .then(|| braced(&rest[len..]))
+                    .flatten()
+                {
+                    stripped.push_str(&strip_latex(argument));
+                    i += argument.len() + 2;
+                } else {
+                    stripped.push('\\');
+                    stripped.push_str(command);
+                }
+            }
+            '{' | '}' if in_math => {}
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// The content of the braces `text` starts with
+fn braced(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix('{')?;
+    let mut depth = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(&inner[..i]),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_abstract() {
+        assert_eq!(
+            clean_abstract(concat!(
+                "<jats:title>Abstract</jats:title>\n",
+                "<jats:sec><jats:title>Background</jats:title>",
+                "<jats:p>Graphs of <jats:italic>related</jats:italic> papers&nbsp;&amp; their ",
+                "citations&#x2014;at scale (p < 0.05).</jats:p></jats:sec>",
+                "<!-- comment --><p>We set $\\alpha \\leq 10^{3}$ and \\emph{tune} it.</p>",
+            )),
+            "Background: Graphs of related papers & their citations—at scale (p < 0.05). We set α ≤ 10^3 and tune it."
+        );
+        assert_eq!(clean_abstract("  Plain   text\n"), "Plain text");
+        assert_eq!(clean_abstract("AT&T costs $5"), "AT&T costs $5");
+    }
+}
@@ -0,0 +1,378 @@
+//! Common accessors shared by every paper model
+//!
+//! Connected Papers and Semantic Scholar describe papers with different structs.
+//! [`PaperLike`] exposes the fields they have in common so that code such as
+//! exporters, deduplication and metrics can be written once over generics.
+
+#[cfg(feature = "connected-papers")]
+use crate::{Citation, Paper, Reference};
+use crate::{
+    ExternalIds, OpenAccessPdf, S2NestedPaper, S2Paper,
+    cite::{CitationStyle, format_citation},
+    markup::clean_abstract,
+};
+use std::{
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+/// A paper-like model
+pub trait PaperLike {
+    /// The paper id
+    fn id(&self) -> &str;
+
+    /// The paper title
+    fn title(&self) -> Option<&str>;
+
+    /// The publication year
+    fn year(&self) -> Option<u32>;
+
+    /// The DOI of the paper
+    fn doi(&self) -> Option<&str>;
+
+    /// The journal or conference the paper was published in
+    fn venue(&self) -> Option<&str>;
+
+    /// The journal volume
+    fn volume(&self) -> Option<&str>;
+
+    /// The page range, e.g. `84-91`
+    fn pages(&self) -> Option<&str>;
+
+    /// The names of the authors, in order
+    fn author_names(&self) -> Vec<&str>;
+
+    /// The paper abstract
+    fn abstract_(&self) -> Option<&str>;
+
+    /// The paper abstract as plain text, see [`clean_abstract`]
+    fn abstract_clean(&self) -> Option<String> {
+        self.abstract_()
+            .map(clean_abstract)
+            .filter(|text| !text.is_empty())
+    }
+
+    /// The machine-generated TL;DR of the paper, if the API returned one
+    fn tldr(&self) -> Option<&str> {
+        None
+    }
+
+    /// A one-line summary of the paper
+    ///
+    /// The [TL;DR](PaperLike::tldr) when there is one, otherwise, with the `summary` feature,
+    /// the most central sentence of the abstract picked by [`summarize`](crate::summarize).
+    fn summary(&self) -> Option<Summary> {
+        let summary = self.tldr().map(|text| Summary {
+            text: text.to_owned(),
+            generated: false,
+        });
+        #[cfg(feature = "summary")]
+        let summary = summary.or_else(|| {
+            let text = crate::summary::summarize(&self.abstract_clean()?, 1)?;
+            Some(Summary {
+                text,
+                generated: true,
+            })
+        });
+        summary
+    }
+
+    /// The number of citations of the paper
+    fn citation_count(&self) -> Option<u32>;
+
+    /// Whether the paper is open access, if known
+    fn is_open_access(&self) -> Option<bool> {
+        None
+    }
+
+    /// The open access PDF with its license and status, if known
+    fn open_access_pdf(&self) -> Option<&OpenAccessPdf> {
+        None
+    }
+
+    /// The link to show or open for the paper
+    ///
+    /// Prefers the DOI, then the arXiv abstract page, then the Semantic Scholar page.
+    fn primary_link(&self) -> Option<String>;
+
+    /// Format the paper as a reference in the given style
+    fn cite(&self, style: CitationStyle) -> String {
+        format_citation(self, style)
+    }
+}
+
+#[cfg(feature = "connected-papers")]
+macro_rules! impl_connected_papers {
+    ($($ty:ty),*) => {
+        $(
+            impl PaperLike for $ty {
+                fn id(&self) -> &str {
+                    &self.id
+                }
+
+                fn title(&self) -> Option<&str> {
+                    self.title.as_deref()
+                }
+
+                fn year(&self) -> Option<u32> {
+                    self.year
+                }
+
+                fn doi(&self) -> Option<&str> {
+                    self.doi
+                        .as_deref()
+                        .or_else(|| self.external_ids.as_ref()?.doi.as_deref())
+                }
+
+                fn venue(&self) -> Option<&str> {
+                    self.journal_name.as_deref().or(self.venue.as_deref())
+                }
+
+                fn volume(&self) -> Option<&str> {
+                    self.journal_volume.as_deref()
+                }
+
+                fn pages(&self) -> Option<&str> {
+                    self.journal_pages.as_deref()
+                }
+
+                fn author_names(&self) -> Vec<&str> {
+                    self.authors()
+                        .iter()
+                        .filter_map(|author| author.name.as_deref())
+                        .collect()
+                }
+
+                fn abstract_(&self) -> Option<&str> {
+                    self.abstract_.as_deref()
+                }
+
+                fn tldr(&self) -> Option<&str> {
+                    self.tldr.as_deref()
+                }
+
+                fn citation_count(&self) -> Option<u32> {
+                    self.citations_length
+                }
+
+                fn is_open_access(&self) -> Option<bool> {
+                    self.is_open_access
+                }
+
+                fn primary_link(&self) -> Option<String> {
+                    let external_ids = self.external_ids.as_ref();
+                    let ids = ExternalIds {
+                        doi: self.doi().map(ToOwned::to_owned),
+                        arxiv: self
+                            .arxiv_id
+                            .clone()
+                            .or_else(|| external_ids?.arxiv.clone()),
+                        corpus_id: self.corpus_id.or_else(|| external_ids?.corpus_id),
+                        ..Default::default()
+                    };
+                    ids.doi_url()
+                        .or_else(|| ids.arxiv_url())
+                        .or_else(|| self.url.clone())
+                        .or_else(|| ids.s2_url())
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_semantic_scholar {
+    ($($ty:ty),*) => {
+        $(
+            impl PaperLike for $ty {
+                fn id(&self) -> &str {
+                    &self.paper_id
+                }
+
+                fn title(&self) -> Option<&str> {
+                    self.title.as_deref()
+                }
+
+                fn year(&self) -> Option<u32> {
+                    self.year
+                }
+
+                fn doi(&self) -> Option<&str> {
+                    self.external_ids.as_ref()?.doi.as_deref()
+                }
+
+                fn venue(&self) -> Option<&str> {
+                    self.journal
+                        .as_ref()
+                        .and_then(|journal| journal.name.as_deref())
+                        .or(self.venue.as_deref())
+                }
+
+                fn volume(&self) -> Option<&str> {
+                    self.journal.as_ref()?.volume.as_deref()
+                }
+
+                fn pages(&self) -> Option<&str> {
+                    self.journal.as_ref()?.pages.as_deref()
+                }
+
+                fn author_names(&self) -> Vec<&str> {
+                    self.authors()
+                        .iter()
+                        .filter_map(|author| author.name.as_deref())
+                        .collect()
+                }
+
+                fn abstract_(&self) -> Option<&str> {
+                    self.abstract_.as_deref()
+                }
+
+                fn tldr(&self) -> Option<&str> {
+                    self.tldr.as_ref()?.text.as_deref()
+                }
+
+                fn citation_count(&self) -> Option<u32> {
+                    self.citation_count
+                }
+
+                fn is_open_access(&self) -> Option<bool> {
+                    self.is_open_access
+                }
+
+                fn open_access_pdf(&self) -> Option<&OpenAccessPdf> {
+                    self.open_access_pdf.as_ref()
+                }
+
+                fn primary_link(&self) -> Option<String> {
+                    let ids = self.external_ids.as_ref();
+                    ids.and_then(ExternalIds::doi_url)
+                        .or_else(|| ids.and_then(ExternalIds::arxiv_url))
+                        .or_else(|| self.url.clone())
+                        .or_else(|| ids.and_then(ExternalIds::s2_url))
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "connected-papers")]
+impl_connected_papers!(Paper, Citation, Reference);
+impl_semantic_scholar!(S2NestedPaper, S2Paper);
+
+/// A one-line summary of a paper
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// The summary text
+    pub text: String,
+    /// Whether the summary was extracted locally from the abstract rather than being the
+    /// TL;DR of the API
+    pub generated: bool,
+}
+
+/// A paper compared and hashed by its id only
+///
+/// Lets papers be deduplicated in a `HashSet` or used as `HashMap` keys, even when two
+/// copies of the same paper were fetched with different fields.
+#[derive(Debug, Clone)]
+pub struct ById<P>(pub P);
+
+impl<P: PaperLike> PartialEq for ById<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.id() == other.0.id()
+    }
+}
+
+impl<P: PaperLike> Eq for ById<P> {}
+
+impl<P: PaperLike> Hash for ById<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.id().hash(state);
+    }
+}
+
+impl<P> Deref for ById<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "connected-papers")]
+    fn describe(paper: &impl PaperLike) -> String {
+        format!(
+            "{} {} ({}) by {}",
+            paper.id(),
+            paper.title().unwrap_or_default(),
+            paper.year().unwrap_or_default(),
+            paper.author_names().join(", ")
+        )
+    }
+
+    #[cfg(feature = "connected-papers")]
+    #[test]
+    fn test_paper_like() {
+        let paper: Paper = serde_json::from_value(serde_json::json!({
+            "id": "abc",
+            "paperId": "abc",
+            "title": "A paper",
+            "year": 2020,
+            "doi": "10.1000/xyz",
+            "authors": [{ "name": "Ada Lovelace" }, { "name": "Alan Turing" }],
+            "citations_length": 10
+        }))
+        .unwrap();
+        let nested = S2NestedPaper::from(paper.clone());
+
+        assert_eq!(describe(&paper), describe(&nested));
+        assert_eq!(paper.doi(), nested.doi());
+        assert_eq!(nested.citation_count(), Some(10));
+    }
+
+    #[cfg(feature = "connected-papers")]
+    #[test]
+    fn test_primary_link() {
+        let mut paper = Paper::new("abc");
+        assert_eq!(paper.primary_link(), None);
+
+        paper.url = Some("https://www.semanticscholar.org/paper/abc".to_owned());
+        paper.arxiv_id = Some("1805.02262".to_owned());
+        assert_eq!(
+            paper.primary_link().as_deref(),
+            Some("https://arxiv.org/abs/1805.02262")
+        );
+
+        paper.doi = Some("10.18653/v1/N18-3011".to_owned());
+        assert_eq!(
+            paper.primary_link().as_deref(),
+            Some("https://doi.org/10.18653/v1/N18-3011")
+        );
+
+        let ids = ExternalIds {
+            corpus_id: Some(19170988),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids.best_link().as_deref(),
+            Some("https://api.semanticscholar.org/CorpusID:19170988")
+        );
+    }
+
+    #[test]
+    fn test_by_id() {
+        let a: S2NestedPaper =
+            serde_json::from_value(serde_json::json!({ "paperId": "a", "title": "A" })).unwrap();
+        let mut b = a.clone();
+        b.year = Some(2020);
+        assert_ne!(a, b);
+
+        let set = [a.clone(), b, a]
+            .into_iter()
+            .map(ById)
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(set.len(), 1);
+    }
+}
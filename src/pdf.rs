@@ -0,0 +1,280 @@
+//! License-aware PDF downloads
+//!
+//! Semantic Scholar reports the license and open access status of the PDF it links to.
+//! [`PdfDownloader`] only fetches PDFs a [`PdfPolicy`] allows, permissively licensed ones by
+//! default, and logs the papers it skips through `tracing`, so bulk downloads stay within
+//! what an institution may store and share.
+
+use crate::{
+    OpenAccessPdf,
+    error::{ApiError, Error, Result},
+    paper::PaperLike,
+    polite::Politeness,
+    utils::HttpConfig,
+};
+use reqwest::Client;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Licenses allowed by [`PdfPolicy::default`]
+pub const PERMISSIVE_LICENSES: &[&str] = &["CC-BY", "CC-BY-SA", "CC0", "public-domain"];
+
+/// Licenses and open access statuses of the PDFs to download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdfPolicy {
+    licenses: Vec<String>,
+    statuses: Option<Vec<String>>,
+}
+
+impl Default for PdfPolicy {
+    /// Allow the [permissive licenses](PERMISSIVE_LICENSES) whatever the status
+    fn default() -> Self {
+        Self {
+            licenses: PERMISSIVE_LICENSES
+                .iter()
+                .map(|license| normalize_license(license))
+                .collect(),
+            statuses: None,
+        }
+    }
+}
+
+impl PdfPolicy {
+    /// Create a policy allowing no license, to build an allowlist from scratch
+    pub fn empty() -> Self {
+        Self {
+            licenses: Vec::new(),
+            statuses: None,
+        }
+    }
+
+    /// Also allow a license, e.g. `CC-BY-NC`
+    ///
+    /// Licenses are compared ignoring case, punctuation and the version of Creative Commons
+    /// licenses, so `CCBY`, `cc-by` and `CC BY 4.0` are the same license.
+    pub fn allow_license(&mut self, license: &str) -> &mut Self {
+        let license = normalize_license(license);
+        if !self.licenses.contains(&license) {
+            self.licenses.push(license);
+        }
+        self
+    }
+
+    /// Only allow PDFs with one of the allowed statuses, e.g. `GOLD` or `GREEN`
+    ///
+    /// Any status is allowed until the first call.
+    pub fn allow_status(&mut self, status: &str) -> &mut Self {
+        let status = status.to_uppercase();
+        let statuses = self.statuses.get_or_insert_with(Vec::new);
+        if !statuses.contains(&status) {
+            statuses.push(status);
+        }
+        self
+    }
+
+    /// The URL to download a PDF from, or why the policy skips it
+    pub fn check<'a>(
+        &self,
+        pdf: Option<&'a OpenAccessPdf>,
+    ) -> std::result::Result<&'a str, SkipReason> {
+        let Some((pdf, url)) = pdf.and_then(|pdf| Some((pdf, pdf.url.as_deref()?))) else {
+            return Err(SkipReason::NoPdf);
+        };
+        if let Some(ref statuses) = self.statuses {
+            let allowed = pdf
+                .status
+                .as_ref()
+                .is_some_and(|status| statuses.contains(&status.to_uppercase()));
+            if !allowed {
+                return Err(SkipReason::Status(pdf.status.clone()));
+            }
+        }
+        let allowed = pdf
+            .license
+            .as_ref()
+            .is_some_and(|license| self.licenses.contains(&normalize_license(license)));
+        if !allowed {
+            return Err(SkipReason::License(pdf.license.clone()));
+        }
+        Ok(url)
+    }
+}
+
+/// Why a PDF was not downloaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The paper has no open access PDF
+    NoPdf,
+    /// The license, if any, is not allowed
+    License(Option<String>),
+    /// The open access status, if any, is not allowed
+    Status(Option<String>),
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::NoPdf => write!(f, "no open access PDF"),
+            SkipReason::License(Some(license)) => write!(f, "license {} not allowed", license),
+            SkipReason::License(None) => write!(f, "unknown license"),
+            SkipReason::Status(Some(status)) => write!(f, "status {} not allowed", status),
+            SkipReason::Status(None) => write!(f, "unknown open access status"),
+        }
+    }
+}
+
+/// Outcome of a PDF download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PdfDownload<T> {
+    /// The PDF was downloaded
+    Downloaded(T),
+    /// The policy skipped the PDF
+    Skipped(SkipReason),
+}
+
+/// Downloader of the open access PDFs of papers allowed by a policy
+///
+/// Downloads go through a [`Politeness`] layer, as PDFs are hosted by publishers and
+/// repositories rather than by the APIs.
+#[derive(Debug, Clone)]
+pub struct PdfDownloader {
+    client: Client,
+    policy: PdfPolicy,
+    politeness: Politeness,
+}
+
+impl Default for PdfDownloader {
+    fn default() -> Self {
+        Self::new(PdfPolicy::default())
+    }
+}
+
+impl PdfDownloader {
+    /// Create a downloader applying a policy
+    pub fn new(policy: PdfPolicy) -> Self {
+        let politeness = Politeness::default();
+        Self {
+            client: build_client(&politeness),
+            policy,
+            politeness,
+        }
+    }
+
+    /// Share a politeness layer, sending its contact email if any
+    pub fn with_politeness(mut self, politeness: Politeness) -> Self {
+        self.client = build_client(&politeness);
+        self.politeness = politeness;
+        self
+    }
+
+    /// The policy applied
+    pub fn policy(&self) -> &PdfPolicy {
+        &self.policy
+    }
+
+    /// Download the PDF of a paper if the policy allows it
+    pub async fn download<P: PaperLike + ?Sized>(&self, paper: &P) -> Result<PdfDownload<Vec<u8>>> {
+        let url = match self.policy.check(paper.open_access_pdf()) {
+            Ok(url) => url,
+            Err(reason) => {
+                tracing::info!(paper = paper.id(), %reason, "skipped PDF download");
+                return Ok(PdfDownload::Skipped(reason));
+            }
+        };
+        let _permit = self.politeness.acquire(url).await?;
+        let resp = self.client.get(url).send().await?;
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(Error::RequestFailed(ApiError {
+                status: status.as_u16(),
+                message: None,
+                body: resp.text().await.unwrap_or_default(),
+                request_id: None,
+            }));
+        }
+        Ok(PdfDownload::Downloaded(resp.bytes().await?.to_vec()))
+    }
+
+    /// Download the PDF of a paper into a directory as `<paper id>.pdf`
+    pub async fn download_to<P: PaperLike + ?Sized>(
+        &self,
+        paper: &P,
+        dir: impl AsRef<Path>,
+    ) -> Result<PdfDownload<PathBuf>> {
+        Ok(match self.download(paper).await? {
+            PdfDownload::Downloaded(bytes) => {
+                let path = dir.as_ref().join(format!("{}.pdf", paper.id()));
+                std::fs::write(&path, bytes)?;
+                PdfDownload::Downloaded(path)
+            }
+            PdfDownload::Skipped(reason) => PdfDownload::Skipped(reason),
+        })
+    }
+}
+
+fn build_client(politeness: &Politeness) -> Client {
+    HttpConfig::default()
+        .build(&politeness.user_agent(), DEFAULT_TIMEOUT)
+        .unwrap()
+}
+
+/// License in lowercase without punctuation nor Creative Commons version, e.g. `ccby`
+fn normalize_license(license: &str) -> String {
+    let license = license
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase();
+    match license.strip_prefix("cc") {
+        Some(terms) if terms.starts_with(|c: char| c.is_ascii_alphabetic()) => license
+            .trim_end_matches(|c: char| c.is_ascii_digit())
+            .to_owned(),
+        _ => license,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pdf(license: Option<&str>, status: &str) -> OpenAccessPdf {
+        OpenAccessPdf {
+            url: Some("https://example.org/a.pdf".to_owned()),
+            status: Some(status.to_owned()),
+            license: license.map(ToOwned::to_owned),
+            legal_disclaimer: None,
+        }
+    }
+
+    #[test]
+    fn test_pdf_policy() {
+        let policy = PdfPolicy::default();
+        assert_eq!(
+            policy.check(Some(&pdf(Some("CCBY"), "GOLD"))),
+            Ok("https://example.org/a.pdf")
+        );
+        assert!(policy.check(Some(&pdf(Some("CC BY 4.0"), "GREEN"))).is_ok());
+        assert!(policy.check(Some(&pdf(Some("CC0"), "GREEN"))).is_ok());
+        assert_eq!(
+            policy.check(Some(&pdf(Some("CCBYNC"), "GOLD"))),
+            Err(SkipReason::License(Some("CCBYNC".to_owned())))
+        );
+        assert_eq!(
+            policy.check(Some(&pdf(None, "BRONZE"))),
+            Err(SkipReason::License(None))
+        );
+        assert_eq!(policy.check(None), Err(SkipReason::NoPdf));
+
+        let mut policy = PdfPolicy::empty();
+        policy.allow_license("cc-by-nc").allow_status("gold");
+        assert!(policy.check(Some(&pdf(Some("CCBYNC"), "GOLD"))).is_ok());
+        assert_eq!(
+            policy.check(Some(&pdf(Some("CCBYNC"), "GREEN"))),
+            Err(SkipReason::Status(Some("GREEN".to_owned())))
+        );
+    }
+}
@@ -0,0 +1,596 @@
+//! Literature map pipeline
+//!
+//! Wraps the whole workflow in one call: resolve a seed paper, fetch its Connected Papers
+//! graph, enrich the nodes with Semantic Scholar details, group them into topic clusters,
+//! rank the papers most similar to the seed and export the result.
+
+use crate::{
+    client::{ConnectedPapers, Graph},
+    community::{distinctive_keywords, keyword_counts, label},
+    error::{Error, Result},
+    export::{ExportFormat, to_bibtex, to_csv, to_dublin_core_records, to_markdown, to_pandoc},
+    progress::{ProgressEvent, ProgressSink},
+    scheduler::{Api, Scheduler},
+    ss::{
+        FieldOfStudy, MatchedPaper, PaperBatchParam, PaperClustering, PaperField, PaperId,
+        PaperTitleSearchParamBuilder, RankedPaper, S2NestedPaper, SemanticScholar, cluster_papers,
+        field_of_study_counts, paper_id::PaperIdSearchParam, rerank,
+    },
+};
+use futures::{StreamExt, stream};
+use std::{fmt::Write, future::Future};
+
+/// Number of papers the batch endpoint accepts per request
+pub(crate) const MAX_BATCH_SIZE: usize = 500;
+
+/// Fields requested for the graph nodes
+const ENRICH_FIELDS: [PaperField; 9] = [
+    PaperField::Title,
+    PaperField::Year,
+    PaperField::Authors,
+    PaperField::ExternalIds,
+    PaperField::Abstract,
+    PaperField::CitationCount,
+    PaperField::FieldsOfStudy,
+    PaperField::S2FieldsOfStudy,
+    PaperField::Embedding,
+];
+
+/// Seed paper of a literature map
+#[derive(Debug, Clone, PartialEq)]
+pub enum Seed {
+    /// Paper title, resolved with the title match endpoint
+    Title(String),
+    /// Paper ID of any kind, e.g. a DOI
+    Id(PaperId),
+}
+
+/// Result of a [`Pipeline`] run
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiteratureMap {
+    /// Connected Papers graph of the seed paper
+    pub graph: Graph,
+    /// Graph nodes with their Semantic Scholar details, ordered by paper id
+    pub papers: Vec<S2NestedPaper>,
+    /// Ids of the graph nodes Semantic Scholar does not know, left out of `papers`
+    pub missing: Vec<String>,
+    /// Number of papers in each field of study, external or S2 classified, most frequent first
+    pub fields_of_study: Vec<(FieldOfStudy, usize)>,
+    /// Topic clusters of the papers, `None` if no paper has an embedding
+    pub clustering: Option<PaperClustering>,
+    /// Papers most similar to the seed, most similar first
+    pub similar: Vec<RankedPaper>,
+    /// The map in the chosen export format
+    pub export: String,
+}
+
+/// Pipeline turning a seed paper into a literature map
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    connected_papers: ConnectedPapers,
+    semantic_scholar: SemanticScholar,
+    clusters: usize,
+    top_similar: usize,
+    format: ExportFormat,
+    fresh_only: bool,
+    batch_size: usize,
+    concurrency: usize,
+    scheduler: Option<Scheduler>,
+    progress: ProgressSink,
+}
+
+impl Pipeline {
+    /// Build the literature map of a seed paper
+    ///
+    /// Returns `None` if the seed matches no paper, and an [`Error::GraphUnavailable`] if
+    /// Connected Papers has no graph ready for it yet. Progress is reported to the sink set
+    /// with [`PipelineBuilder::progress`].
+    pub async fn run(&self, seed: &Seed) -> Result<Option<LiteratureMap>> {
+        let mut requests = 0;
+        if !matches!(seed, Seed::Id(PaperId::S2Id(_))) {
+            requests += 1;
+        }
+        let Some(paper_id) = self.resolve(seed).await? else {
+            return Ok(None);
+        };
+        let response = self
+            .schedule(Api::ConnectedPapers, || {
+                self.connected_papers.get_graph(&paper_id, self.fresh_only)
+            })
+            .await?;
+        requests += 1;
+        match ProgressEvent::from_graph_response(&response) {
+            Some(ProgressEvent::Completed) | None => {}
+            Some(event) => self.progress.emit(event),
+        }
+        let graph = response
+            .graph_json
+            .ok_or(Error::GraphUnavailable(response.status))?;
+        self.progress.emit(ProgressEvent::PapersEnriched {
+            papers: 0,
+            total: graph.nodes.len(),
+            requests,
+        });
+
+        let (papers, missing) = self.enrich(&graph, requests).await?;
+        let embedded = papers.iter().filter(|paper| has_vector(paper)).count();
+        let clustering = if embedded == 0 {
+            None
+        } else {
+            Some(cluster_papers(&papers, self.clusters.min(embedded))?)
+        };
+        let similar = self.similar(&papers, &graph.start_id)?;
+        let fields_of_study =
+            field_of_study_counts(papers.iter().map(S2NestedPaper::all_fields_of_study));
+
+        let export = match self.format {
+            ExportFormat::Markdown => markdown(&graph, &papers, clustering.as_ref(), &similar),
+            ExportFormat::GraphMl => graph.to_graphml(),
+            ExportFormat::Bibtex => to_bibtex(&papers),
+            ExportFormat::Pandoc => to_pandoc(&papers),
+            ExportFormat::Csv => to_csv(&papers),
+            ExportFormat::DublinCore => to_dublin_core_records(&papers),
+        };
+        self.progress.emit(ProgressEvent::Completed);
+        Ok(Some(LiteratureMap {
+            graph,
+            papers,
+            missing,
+            fields_of_study,
+            clustering,
+            similar,
+            export,
+        }))
+    }
+
+    /// Send a request through the scheduler, if any
+    async fn schedule<T, F, Fut>(&self, api: Api, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        match self.scheduler {
+            Some(ref scheduler) => scheduler.run(api, request).await,
+            None => request().await,
+        }
+    }
+
+    /// Semantic Scholar ID of the seed paper
+    async fn resolve(&self, seed: &Seed) -> Result<Option<String>> {
+        match seed {
+            Seed::Id(PaperId::S2Id(id)) => Ok(Some(id.clone())),
+            Seed::Id(id) => {
+                let mut param = PaperIdSearchParam::new(id);
+                param.add_field(PaperField::Title);
+                let paper = self
+                    .schedule(Api::SemanticScholar, || self.semantic_scholar.query(&param))
+                    .await?;
+                Ok(paper.map(|paper| paper.paper_id))
+            }
+            Seed::Title(title) => {
+                let param = PaperTitleSearchParamBuilder::new(title)
+                    .field(PaperField::Title)
+                    .build()?;
+                let matched = self
+                    .schedule(Api::SemanticScholar, || self.semantic_scholar.query(&param))
+                    .await?;
+                Ok(matched.map(|MatchedPaper { paper, .. }| paper.paper_id))
+            }
+        }
+    }
+
+    /// Semantic Scholar details of the graph nodes, fetched in concurrent batches, and the ids
+    /// of the nodes it does not know
+    async fn enrich(
+        &self,
+        graph: &Graph,
+        mut requests: usize,
+    ) -> Result<(Vec<S2NestedPaper>, Vec<String>)> {
+        let mut ids = graph.nodes.keys().collect::<Vec<_>>();
+        ids.sort();
+        let mut batches = stream::iter(ids.chunks(self.batch_size))
+            .map(|chunk| {
+                let param = PaperBatchParam {
+                    ids: chunk.iter().map(|&id| PaperId::id(id)).collect(),
+                    fields: Some(ENRICH_FIELDS.to_vec()),
+                    nested: Vec::new(),
+                };
+                async move {
+                    let batch = self
+                        .schedule(Api::SemanticScholar, || self.semantic_scholar.query(&param))
+                        .await?;
+                    Ok::<_, Error>(chunk.iter().zip(batch).collect::<Vec<_>>())
+                }
+            })
+            .buffered(self.concurrency);
+
+        let mut papers = Vec::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        let mut pages = 0;
+        while let Some(batch) = batches.next().await {
+            let batch = batch?;
+            pages += 1;
+            requests += 1;
+            self.progress.emit(ProgressEvent::PageFetched { n: pages });
+            self.progress.emit(ProgressEvent::PapersEnriched {
+                papers: papers.len() + missing.len() + batch.len(),
+                total: ids.len(),
+                requests,
+            });
+            for (&id, paper) in batch {
+                match paper {
+                    Some(paper) => papers.push(paper),
+                    None => missing.push(id.clone()),
+                }
+            }
+        }
+        Ok((papers, missing))
+    }
+
+    /// Papers ranked by the cosine similarity of their embedding to the seed embedding
+    fn similar(&self, papers: &[S2NestedPaper], seed_id: &str) -> Result<Vec<RankedPaper>> {
+        let Some(seed) = papers
+            .iter()
+            .find(|paper| paper.paper_id == seed_id && has_vector(paper))
+            .and_then(|paper| paper.embedding.as_ref())
+        else {
+            return Ok(Vec::new());
+        };
+        let others = papers
+            .iter()
+            .filter(|paper| paper.paper_id != seed_id && has_vector(paper))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut ranked = rerank(&others, seed, 1.0)?;
+        ranked.truncate(self.top_similar);
+        Ok(ranked)
+    }
+}
+
+/// Builder for [`Pipeline`]
+#[derive(Debug, Clone)]
+pub struct PipelineBuilder {
+    connected_papers: ConnectedPapers,
+    semantic_scholar: SemanticScholar,
+    clusters: usize,
+    top_similar: usize,
+    format: ExportFormat,
+    fresh_only: bool,
+    batch_size: usize,
+    concurrency: usize,
+    scheduler: Option<Scheduler>,
+    progress: ProgressSink,
+}
+
+impl PipelineBuilder {
+    /// Create a builder using the given clients
+    pub fn new(connected_papers: ConnectedPapers, semantic_scholar: SemanticScholar) -> Self {
+        Self {
+            connected_papers,
+            semantic_scholar,
+            clusters: 5,
+            top_similar: 10,
+            format: ExportFormat::Markdown,
+            fresh_only: false,
+            batch_size: MAX_BATCH_SIZE,
+            concurrency: 1,
+            scheduler: None,
+            progress: ProgressSink::default(),
+        }
+    }
+
+    /// Set the number of topic clusters, 5 by default
+    pub fn clusters(&mut self, clusters: usize) -> &mut Self {
+        self.clusters = clusters;
+        self
+    }
+
+    /// Set the number of most similar papers to keep, 10 by default
+    pub fn top_similar(&mut self, top_similar: usize) -> &mut Self {
+        self.top_similar = top_similar;
+        self
+    }
+
+    /// Set the export format, Markdown by default
+    pub fn format(&mut self, format: ExportFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Force a fresh graph rebuild instead of accepting a cached graph
+    pub fn fresh_only(&mut self) -> &mut Self {
+        self.fresh_only = true;
+        self
+    }
+
+    /// Set the number of papers enriched per batch request, at most and by default 500
+    ///
+    /// Smaller batches keep responses with embeddings well below the 10 MB limit of the
+    /// batch endpoint and let several requests run at once.
+    pub fn batch_size(&mut self, batch_size: usize) -> &mut Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set the number of batch requests in flight at once, 1 by default
+    pub fn concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Send every request through a scheduler, e.g. one shared with other runs
+    ///
+    /// The scheduler retries transient failures and spaces out the calls to each API; see
+    /// [`Scheduler`]. Without one, requests are sent as soon as the pipeline needs them.
+    pub fn scheduler(&mut self, scheduler: Scheduler) -> &mut Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Report the graph status, every enriched batch with the papers enriched and requests
+    /// spent so far, and the end of a run as progress events
+    pub fn progress(&mut self, progress: ProgressSink) -> &mut Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Build the pipeline
+    pub fn build(&self) -> Result<Pipeline> {
+        if self.clusters == 0 {
+            return Err(Error::InvalidParameter(
+                "clusters must be at least 1".to_owned(),
+            ));
+        }
+        if !(1..=MAX_BATCH_SIZE).contains(&self.batch_size) {
+            return Err(Error::InvalidParameter(format!(
+                "batch size must be between 1 and {}",
+                MAX_BATCH_SIZE
+            )));
+        }
+        if self.concurrency == 0 {
+            return Err(Error::InvalidParameter(
+                "concurrency must be at least 1".to_owned(),
+            ));
+        }
+        Ok(Pipeline {
+            connected_papers: self.connected_papers.clone(),
+            semantic_scholar: self.semantic_scholar.clone(),
+            clusters: self.clusters,
+            top_similar: self.top_similar,
+            format: self.format,
+            fresh_only: self.fresh_only,
+            batch_size: self.batch_size,
+            concurrency: self.concurrency,
+            scheduler: self.scheduler.clone(),
+            progress: self.progress.clone(),
+        })
+    }
+}
+
+fn has_vector(paper: &S2NestedPaper) -> bool {
+    paper
+        .embedding
+        .as_ref()
+        .is_some_and(|embedding| embedding.vector.is_some())
+}
+
+/// Markdown report with the most similar, seminal and review papers followed by one section
+/// per cluster
+fn markdown(
+    graph: &Graph,
+    papers: &[S2NestedPaper],
+    clustering: Option<&PaperClustering>,
+    similar: &[RankedPaper],
+) -> String {
+    let title = graph
+        .nodes
+        .get(&graph.start_id)
+        .and_then(|paper| paper.title.as_deref())
+        .unwrap_or(graph.start_id.as_str());
+    let mut markdown = format!("# Literature map of {}\n", title);
+
+    if !similar.is_empty() {
+        let papers = similar
+            .iter()
+            .map(|ranked| ranked.paper.clone())
+            .collect::<Vec<_>>();
+        let _ = write!(
+            markdown,
+            "\n## Most similar papers\n\n{}",
+            to_markdown(&papers)
+        );
+    }
+    let seminal = graph
+        .seminal_papers()
+        .into_iter()
+        .map(|seminal| seminal.paper.clone())
+        .collect::<Vec<_>>();
+    if !seminal.is_empty() {
+        let _ = write!(markdown, "\n## Seminal papers\n\n{}", to_markdown(&seminal));
+    }
+    let reviews = graph
+        .review_papers()
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    if !reviews.is_empty() {
+        let _ = write!(markdown, "\n## Reviews\n\n{}", to_markdown(&reviews));
+    }
+    match clustering {
+        Some(clustering) => {
+            let counts = keyword_counts(papers);
+            for cluster in &clustering.clusters {
+                let _ = write!(markdown, "\n## Cluster {}", cluster.label + 1);
+                if let Some(label) = label(&distinctive_keywords(&cluster.papers, &counts)) {
+                    let _ = write!(markdown, ": {}", label);
+                }
+                let _ = write!(markdown, "\n\n{}", to_markdown(&cluster.papers));
+            }
+            if !clustering.unassigned.is_empty() {
+                let _ = write!(
+                    markdown,
+                    "\n## Unclustered papers\n\n{}",
+                    to_markdown(&clustering.unassigned)
+                );
+            }
+        }
+        None => {
+            let _ = write!(markdown, "\n## Papers\n\n{}", to_markdown(papers));
+        }
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Product,
+        cassette::Cassette,
+        client::{GraphResponse, GraphResponseType},
+        ss::merge_selected_fields,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn test_pipeline_builder() {
+        let mut builder =
+            PipelineBuilder::new(ConnectedPapers::default(), SemanticScholar::default());
+        builder.format(ExportFormat::Bibtex).top_similar(3);
+        let pipeline = builder.build().unwrap();
+        assert_eq!(pipeline.format, ExportFormat::Bibtex);
+        assert_eq!(pipeline.top_similar, 3);
+        assert!(builder.batch_size(501).build().is_err());
+        assert!(builder.batch_size(100).concurrency(0).build().is_err());
+        assert!(builder.concurrency(4).clusters(0).build().is_err());
+    }
+
+    /// Builder of a pipeline replaying the example graph, with the batch responses to `ids`
+    fn replay(
+        dir: &std::path::Path,
+        batches: &[(Vec<&str>, serde_json::Value)],
+    ) -> PipelineBuilder {
+        let cassette = Cassette::replay(dir);
+        let response = GraphResponse {
+            status: GraphResponseType::FreshGraph,
+            graph_json: Some(Graph::example()),
+            progress: None,
+            remaining_requests: None,
+        };
+        let url = "https://rest.prod.connectedpapers.com/papers-api/graph/0/seed";
+        let body = serde_json::to_string(&response).unwrap();
+        cassette.insert("GET", url, b"", 200, &body).unwrap();
+        let url = format!(
+            "{}/paper/batch?fields={}",
+            Product::Graph.default_url(),
+            merge_selected_fields(&ENRICH_FIELDS, &[])
+        );
+        for (ids, papers) in batches {
+            let ids = json!({ "ids": ids }).to_string();
+            cassette
+                .insert("POST", &url, ids.as_bytes(), 200, &papers.to_string())
+                .unwrap();
+        }
+        let connected_papers = ConnectedPapers::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+        let semantic_scholar = SemanticScholar::builder()
+            .cassette(Cassette::replay(dir))
+            .build()
+            .unwrap();
+        PipelineBuilder::new(connected_papers, semantic_scholar)
+    }
+
+    #[tokio::test]
+    async fn test_missing_papers() {
+        let dir = std::env::temp_dir().join(format!("pipeline-missing-{}", std::process::id()));
+        let papers = json!([
+            {
+                "paperId": "newer",
+                "title": "Newer paper",
+                "fieldsOfStudy": ["Computer Science"],
+                "s2FieldsOfStudy": [{ "category": "Linguistics", "source": "s2-fos-model" }],
+            },
+            null,
+            {
+                "paperId": "seed",
+                "title": "Seed paper",
+                "s2FieldsOfStudy": [{ "category": "Computer Science", "source": "s2-fos-model" }],
+            },
+        ]);
+        let pipeline = replay(&dir, &[(vec!["newer", "older", "seed"], papers)])
+            .build()
+            .unwrap();
+
+        let seed = Seed::Id(PaperId::S2Id("seed".to_owned()));
+        let map = pipeline.run(&seed).await.unwrap().unwrap();
+        let ids = map.papers.iter().map(|paper| paper.paper_id.as_str());
+        assert_eq!(ids.collect::<Vec<_>>(), ["newer", "seed"]);
+        assert_eq!(map.missing, ["older"]);
+        assert_eq!(
+            map.fields_of_study,
+            [
+                (FieldOfStudy::ComputerScience, 2),
+                (FieldOfStudy::Linguistics, 1)
+            ]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_enrichment() {
+        let dir = std::env::temp_dir().join(format!("pipeline-enrich-{}", std::process::id()));
+        let batches = ["newer", "older", "seed"]
+            .map(|id| (vec![id], json!([{ "paperId": id, "title": id }])));
+        let (sink, events) = ProgressSink::channel();
+        let pipeline = replay(&dir, &batches)
+            .batch_size(1)
+            .concurrency(3)
+            .progress(sink)
+            .build()
+            .unwrap();
+
+        let seed = Seed::Id(PaperId::S2Id("seed".to_owned()));
+        let map = pipeline.run(&seed).await.unwrap().unwrap();
+        let ids = map.papers.iter().map(|paper| paper.paper_id.as_str());
+        assert_eq!(ids.collect::<Vec<_>>(), ["newer", "older", "seed"]);
+        drop(pipeline);
+        let enriched = |papers, requests| ProgressEvent::PapersEnriched {
+            papers,
+            total: 3,
+            requests,
+        };
+        let events = events.collect::<Vec<_>>().await;
+        assert_eq!(
+            events,
+            [
+                enriched(0, 1),
+                ProgressEvent::PageFetched { n: 1 },
+                enriched(1, 2),
+                ProgressEvent::PageFetched { n: 2 },
+                enriched(2, 3),
+                ProgressEvent::PageFetched { n: 3 },
+                enriched(3, 4),
+                ProgressEvent::Completed,
+            ]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_run() {
+        let pipeline = PipelineBuilder::new(
+            ConnectedPapers::from_env().unwrap(),
+            SemanticScholar::default(),
+        )
+        .build()
+        .unwrap();
+        let seed =
+            Seed::Title("Construction of the Literature Graph in Semantic Scholar".to_owned());
+        let map = pipeline.run(&seed).await.unwrap().unwrap();
+        assert!(!map.papers.is_empty());
+        println!("{}", map.export);
+    }
+}
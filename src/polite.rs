@@ -0,0 +1,271 @@
+//! Politeness towards third-party services
+//!
+//! Besides the two APIs the crate is built for, enrichers and downloaders reach services such
+//! as Crossref, Unpaywall, arXiv or publisher sites, each with its own etiquette. A
+//! [`Politeness`] shared by all of them spaces out the requests to each host, caps the
+//! connections open to it and identifies the crate with a contact address in its user agent.
+
+use crate::{
+    error::{Error, Result},
+    scheduler::RateLimiter,
+};
+use reqwest::Url;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+static APP_USER_AGENT: &str =
+    concat!("RS", env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
+
+/// Limits of hosts with a published etiquette, applied unless overridden
+///
+/// arXiv asks for no more than one request every three seconds on a single connection.
+const KNOWN_HOSTS: &[(&str, HostLimits)] = &[(
+    "arxiv.org",
+    HostLimits {
+        interval: Duration::from_secs(3),
+        max_connections: 1,
+    },
+)];
+
+/// Limits on the requests to a host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostLimits {
+    /// Minimum interval between requests
+    pub interval: Duration,
+    /// Connections open at once
+    pub max_connections: usize,
+}
+
+impl Default for HostLimits {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(200),
+            max_connections: 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Host {
+    limiter: RateLimiter,
+    connections: Arc<Semaphore>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    contact: Option<String>,
+    default_limits: HostLimits,
+    limits: Vec<(String, HostLimits)>,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+}
+
+/// Per-host limits and identification for requests to third-party services
+///
+/// Clones share the same limits, so one value can be handed to every enricher and
+/// downloader of a run.
+#[derive(Debug, Clone)]
+pub struct Politeness {
+    inner: Arc<Inner>,
+}
+
+impl Default for Politeness {
+    fn default() -> Self {
+        PolitenessBuilder::default()
+            .build()
+            .expect("default politeness is valid")
+    }
+}
+
+/// Connection to a host, released when dropped
+#[derive(Debug)]
+pub struct HostPermit {
+    _connection: OwnedSemaphorePermit,
+}
+
+impl Politeness {
+    /// Create a builder with the default limits
+    pub fn builder() -> PolitenessBuilder {
+        PolitenessBuilder::default()
+    }
+
+    /// The contact email, if any
+    pub fn contact(&self) -> Option<&str> {
+        self.inner.contact.as_deref()
+    }
+
+    /// User agent naming the crate and the contact email, e.g.
+    /// `RSconnected-papers/0.1.1 (mailto:me@example.org)`
+    pub fn user_agent(&self) -> String {
+        match self.inner.contact {
+            Some(ref contact) => format!("{} (mailto:{})", APP_USER_AGENT, contact),
+            None => APP_USER_AGENT.to_owned(),
+        }
+    }
+
+    /// The limits applied to a host
+    ///
+    /// A host takes the limits configured for it or for a parent domain, e.g. `export.arxiv.org`
+    /// those of `arxiv.org`, the most specific domain winning.
+    pub fn limits(&self, host: &str) -> HostLimits {
+        let host = host.to_lowercase();
+        // On a tie the last domain wins, so configured limits override known ones
+        KNOWN_HOSTS
+            .iter()
+            .map(|(domain, limits)| (*domain, *limits))
+            .chain(
+                self.inner
+                    .limits
+                    .iter()
+                    .map(|(domain, limits)| (domain.as_str(), *limits)),
+            )
+            .filter(|(domain, _)| {
+                host == *domain
+                    || host
+                        .strip_suffix(domain)
+                        .is_some_and(|sub| sub.ends_with('.'))
+            })
+            .max_by_key(|(domain, _)| domain.len())
+            .map_or(self.inner.default_limits, |(_, limits)| limits)
+    }
+
+    /// Wait until a request to the host of a URL is allowed, holding a connection meanwhile
+    pub async fn acquire(&self, url: &str) -> Result<HostPermit> {
+        let url =
+            Url::parse(url).map_err(|e| Error::InvalidParameter(format!("invalid URL: {}", e)))?;
+        let name = url
+            .host_str()
+            .ok_or_else(|| Error::InvalidParameter(format!("URL without host: {}", url)))?
+            .to_lowercase();
+        let host = {
+            let mut hosts = self.inner.hosts.lock().unwrap_or_else(|e| e.into_inner());
+            hosts
+                .entry(name.clone())
+                .or_insert_with(|| {
+                    let limits = self.limits(&name);
+                    Arc::new(Host {
+                        limiter: RateLimiter::new(limits.interval),
+                        connections: Arc::new(Semaphore::new(limits.max_connections)),
+                    })
+                })
+                .clone()
+        };
+        let connection = host
+            .connections
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("host semaphores are never closed");
+        host.limiter.acquire().await;
+        Ok(HostPermit {
+            _connection: connection,
+        })
+    }
+}
+
+/// Builder for [`Politeness`]
+#[derive(Debug, Clone, Default)]
+pub struct PolitenessBuilder {
+    contact: Option<String>,
+    default_limits: HostLimits,
+    limits: Vec<(String, HostLimits)>,
+}
+
+impl PolitenessBuilder {
+    /// Set the contact email sent in the user agent, which services such as Crossref and
+    /// Unpaywall ask for
+    pub fn contact(&mut self, email: &str) -> &mut Self {
+        self.contact = Some(email.to_owned());
+        self
+    }
+
+    /// Set the limits of hosts without limits of their own, 5 requests per second on 2
+    /// connections by default
+    pub fn default_limits(&mut self, limits: HostLimits) -> &mut Self {
+        self.default_limits = limits;
+        self
+    }
+
+    /// Set the limits of a host and its subdomains, e.g. `api.crossref.org`
+    pub fn host(&mut self, host: &str, limits: HostLimits) -> &mut Self {
+        let host = host.to_lowercase();
+        self.limits.retain(|(domain, _)| *domain != host);
+        self.limits.push((host, limits));
+        self
+    }
+
+    /// Build the politeness layer
+    pub fn build(&self) -> Result<Politeness> {
+        let limits = self
+            .limits
+            .iter()
+            .map(|(_, limits)| limits)
+            .chain([&self.default_limits]);
+        for limits in limits {
+            if limits.max_connections == 0 {
+                return Err(Error::InvalidParameter(
+                    "max connections must be at least 1".to_owned(),
+                ));
+            }
+        }
+        Ok(Politeness {
+            inner: Arc::new(Inner {
+                contact: self.contact.clone(),
+                default_limits: self.default_limits,
+                limits: self.limits.clone(),
+                hosts: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_politeness() {
+        let fast = HostLimits {
+            interval: Duration::ZERO,
+            max_connections: 1,
+        };
+        let politeness = Politeness::builder()
+            .contact("me@example.org")
+            .host("example.org", fast)
+            .build()
+            .unwrap();
+        assert_eq!(
+            politeness.user_agent(),
+            concat!(
+                "RS",
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION"),
+                " (mailto:me@example.org)"
+            )
+        );
+        assert_eq!(politeness.limits("export.arxiv.org").max_connections, 1);
+        assert_eq!(politeness.limits("api.example.org"), fast);
+        assert_eq!(politeness.limits("notexample.org"), HostLimits::default());
+
+        let permit = politeness
+            .acquire("https://example.org/a.pdf")
+            .await
+            .unwrap();
+        let start = Instant::now();
+        let second = tokio::spawn({
+            let politeness = politeness.clone();
+            async move { politeness.acquire("https://EXAMPLE.org/b.pdf").await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!second.is_finished());
+        drop(permit);
+        second.await.unwrap().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert!(politeness.acquire("not a url").await.is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Progress events
+//!
+//! Long operations, i.e. graph builds, bulk crawls, scheduled retries and pipeline runs,
+//! report their progress as [`ProgressEvent`]s sent to a [`ProgressSink`], either a callback
+//! or a channel, so that command lines, terminal interfaces and MCP servers can render the
+//! same events.
+
+#[cfg(feature = "connected-papers")]
+use crate::{
+    client::{ConnectedPapers, GraphResponse, GraphResponseType},
+    error::Result,
+};
+use futures::channel::mpsc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Step of a long operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// Connected Papers queued the graph build
+    GraphQueued,
+    /// Connected Papers is building the graph, with its completion in percent
+    GraphBuilding(f64),
+    /// A page or batch was fetched, `n` counting them from 1
+    PageFetched { n: usize },
+    /// Graph nodes enriched so far out of `total`, with the API requests sent so far
+    PapersEnriched {
+        papers: usize,
+        total: usize,
+        requests: usize,
+    },
+    /// A request failed transiently and is sent again, `attempt` counting retries from 1
+    ChunkRetried { attempt: u32 },
+    /// The operation completed
+    Completed,
+}
+
+#[cfg(feature = "connected-papers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "connected-papers")))]
+impl ProgressEvent {
+    /// Event matching the status of a graph response, `None` for failures
+    pub fn from_graph_response(response: &GraphResponse) -> Option<Self> {
+        match response.status {
+            GraphResponseType::Queued => Some(ProgressEvent::GraphQueued),
+            GraphResponseType::InProgress => Some(ProgressEvent::GraphBuilding(
+                response.progress.unwrap_or_default(),
+            )),
+            GraphResponseType::FreshGraph | GraphResponseType::OldGraph => {
+                Some(ProgressEvent::Completed)
+            }
+            _ => None,
+        }
+    }
+}
+
+type Callback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// Destination of progress events, discarding them by default
+#[derive(Clone, Default)]
+pub struct ProgressSink {
+    callback: Option<Callback>,
+}
+
+impl std::fmt::Debug for ProgressSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressSink")
+            .field("enabled", &self.callback.is_some())
+            .finish()
+    }
+}
+
+impl ProgressSink {
+    /// Call `callback` with every event
+    pub fn callback(callback: impl Fn(ProgressEvent) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Some(Arc::new(callback)),
+        }
+    }
+
+    /// Send every event to a channel, returning its receiving end
+    ///
+    /// Events sent after the receiver is dropped are discarded.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<ProgressEvent>) {
+        let (sender, receiver) = mpsc::unbounded();
+        let sink = Self::callback(move |event| {
+            let _ = sender.unbounded_send(event);
+        });
+        (sink, receiver)
+    }
+
+    /// Report an event
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(ref callback) = self.callback {
+            callback(event);
+        }
+    }
+}
+
+#[cfg(feature = "connected-papers")]
+#[cfg_attr(docsrs, doc(cfg(feature = "connected-papers")))]
+impl ConnectedPapers {
+    /// Wait until the graph of a paper is built, reporting each status update as an event
+    ///
+    /// Returns the last update of [`ConnectedPapers::graph_poller`], carrying the newest
+    /// graph seen. [`ProgressEvent::Completed`] is reported once, when that response
+    /// holds a graph.
+    pub async fn wait_for_graph(
+        &self,
+        id: &str,
+        fresh_only: bool,
+        progress: &ProgressSink,
+    ) -> Result<Option<GraphResponse>> {
+        let mut poller = self.graph_poller(id, fresh_only, true);
+        let mut last = None;
+        while let Some(response) = poller.next_update().await? {
+            match ProgressEvent::from_graph_response(&response) {
+                Some(ProgressEvent::Completed) | None => {}
+                Some(event) => progress.emit(event),
+            }
+            last = Some(response);
+        }
+        if last.as_ref().is_some_and(|last| last.graph_json.is_some()) {
+            progress.emit(ProgressEvent::Completed);
+        }
+        Ok(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_progress_channel() {
+        let (sink, receiver) = ProgressSink::channel();
+        sink.emit(ProgressEvent::GraphBuilding(42.0));
+        sink.emit(ProgressEvent::Completed);
+        drop(sink);
+        let events = futures::executor::block_on(receiver.collect::<Vec<_>>());
+        assert_eq!(
+            events,
+            vec![ProgressEvent::GraphBuilding(42.0), ProgressEvent::Completed]
+        );
+        assert_eq!(
+            serde_json::to_value(&events[0]).unwrap(),
+            serde_json::json!({"event": "graph_building", "data": 42.0})
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "connected-papers")]
+    fn test_graph_response_events() {
+        let response = GraphResponse {
+            status: GraphResponseType::Queued,
+            graph_json: None,
+            progress: None,
+            remaining_requests: None,
+        };
+        assert_eq!(
+            ProgressEvent::from_graph_response(&response),
+            Some(ProgressEvent::GraphQueued)
+        );
+    }
+}
@@ -0,0 +1,355 @@
+//! Reading lists
+//!
+//! A [`ReadingList`] collects papers from searches and graphs into a JSON file, together with
+//! their [`Annotations`], and orders what is left to read by similarity to the graph seed or
+//! by citations. It exports to the Markdown and CSV formats of [`export`](crate::export).
+
+use crate::{
+    Graph,
+    annotation::Annotations,
+    error::Result,
+    export::{to_csv, to_markdown},
+    paper::PaperLike,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Paper of a reading list, with the metadata needed to cite and rank it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadingEntry {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub venue: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abstract_: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub citation_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// Connected Papers similarity to the seed of the graph the paper was added from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f64>,
+}
+
+impl ReadingEntry {
+    /// Copy the metadata of a paper
+    pub fn from_paper<P: PaperLike + ?Sized>(paper: &P) -> Self {
+        let owned = |value: Option<&str>| value.map(ToOwned::to_owned);
+        Self {
+            id: paper.id().to_owned(),
+            title: owned(paper.title()),
+            year: paper.year(),
+            authors: paper
+                .author_names()
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect(),
+            doi: owned(paper.doi()),
+            venue: owned(paper.venue()),
+            volume: owned(paper.volume()),
+            pages: owned(paper.pages()),
+            abstract_: owned(paper.abstract_()),
+            citation_count: paper.citation_count(),
+            link: paper.primary_link(),
+            similarity: None,
+        }
+    }
+}
+
+impl PaperLike for ReadingEntry {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn year(&self) -> Option<u32> {
+        self.year
+    }
+
+    fn doi(&self) -> Option<&str> {
+        self.doi.as_deref()
+    }
+
+    fn venue(&self) -> Option<&str> {
+        self.venue.as_deref()
+    }
+
+    fn volume(&self) -> Option<&str> {
+        self.volume.as_deref()
+    }
+
+    fn pages(&self) -> Option<&str> {
+        self.pages.as_deref()
+    }
+
+    fn author_names(&self) -> Vec<&str> {
+        self.authors.iter().map(String::as_str).collect()
+    }
+
+    fn abstract_(&self) -> Option<&str> {
+        self.abstract_.as_deref()
+    }
+
+    fn citation_count(&self) -> Option<u32> {
+        self.citation_count
+    }
+
+    fn primary_link(&self) -> Option<String> {
+        self.link.clone()
+    }
+}
+
+/// Order of the papers of a reading list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Priority {
+    /// In the order they were added
+    #[default]
+    Added,
+    /// Most similar to the graph seed first, papers without similarity last
+    Similarity,
+    /// Most cited first
+    Citations,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Stored {
+    #[serde(default)]
+    entries: Vec<ReadingEntry>,
+    #[serde(default)]
+    annotations: Annotations,
+}
+
+/// Papers to read with their annotations, persisted as a JSON file
+#[derive(Debug, Clone, Default)]
+pub struct ReadingList {
+    path: Option<PathBuf>,
+    entries: Vec<ReadingEntry>,
+    annotations: Annotations,
+}
+
+impl ReadingList {
+    /// Create an empty in-memory reading list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the reading list stored at `path`, starting empty if the file does not exist
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let stored = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Stored::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path),
+            entries: stored.entries,
+            annotations: stored.annotations,
+        })
+    }
+
+    /// The file backing this reading list, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Write the reading list to its file; in-memory lists are left untouched
+    pub fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.path {
+            let stored = Stored {
+                entries: self.entries.clone(),
+                annotations: self.annotations.clone(),
+            };
+            std::fs::write(path, serde_json::to_string_pretty(&stored)?)?;
+        }
+        Ok(())
+    }
+
+    /// Add a paper, returning `false` if it is already in the list
+    pub fn add<P: PaperLike + ?Sized>(&mut self, paper: &P) -> bool {
+        if self.get(paper.id()).is_some() {
+            return false;
+        }
+        self.entries.push(ReadingEntry::from_paper(paper));
+        true
+    }
+
+    /// Add papers, e.g. search results, returning how many were not in the list yet
+    pub fn add_papers<'a, P: PaperLike + 'a>(
+        &mut self,
+        papers: impl IntoIterator<Item = &'a P>,
+    ) -> usize {
+        papers.into_iter().filter(|paper| self.add(*paper)).count()
+    }
+
+    /// Add the nodes of a graph but its seed, with their similarity to the seed
+    ///
+    /// Papers already in the list get the similarity if they had none. Returns how many
+    /// papers were not in the list yet.
+    pub fn add_graph(&mut self, graph: &Graph) -> usize {
+        let mut added = 0;
+        let mut ids = graph.nodes.keys().collect::<Vec<_>>();
+        ids.sort();
+        for id in ids {
+            if *id == graph.start_id {
+                continue;
+            }
+            if self.add(&graph.nodes[id]) {
+                added += 1;
+            }
+            let similarity = graph
+                .edges
+                .iter()
+                .find(|edge| {
+                    (edge.0 == graph.start_id && edge.1 == *id)
+                        || (edge.1 == graph.start_id && edge.0 == *id)
+                })
+                .map(|edge| edge.2);
+            if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == *id) {
+                entry.similarity = entry.similarity.or(similarity);
+            }
+        }
+        added
+    }
+
+    /// Remove a paper and its annotation, returning `false` if it was not in the list
+    pub fn remove(&mut self, paper_id: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.id != paper_id);
+        self.annotations.remove(paper_id);
+        self.entries.len() != len
+    }
+
+    /// The entry of a paper, if in the list
+    pub fn get(&self, paper_id: &str) -> Option<&ReadingEntry> {
+        self.entries.iter().find(|entry| entry.id == paper_id)
+    }
+
+    /// Number of papers in the list
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the list has no paper
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Mark a paper as read or unread
+    pub fn set_read(&mut self, paper_id: &str, read: bool) {
+        self.annotations.set_read(paper_id, read);
+    }
+
+    /// Whether a paper was marked as read
+    pub fn is_read(&self, paper_id: &str) -> bool {
+        self.annotations
+            .get(paper_id)
+            .is_some_and(|annotation| annotation.read)
+    }
+
+    /// Tags, notes and read flags of the papers
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Mutable tags, notes and read flags of the papers
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.annotations
+    }
+
+    /// All papers, in the given order
+    pub fn entries(&self, priority: Priority) -> Vec<&ReadingEntry> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        match priority {
+            Priority::Added => (),
+            Priority::Similarity => entries.sort_by(|a, b| {
+                let similarity = |entry: &ReadingEntry| entry.similarity.unwrap_or(f64::MIN);
+                similarity(b).total_cmp(&similarity(a))
+            }),
+            Priority::Citations => {
+                entries.sort_by_key(|entry| std::cmp::Reverse(entry.citation_count.unwrap_or(0)))
+            }
+        }
+        entries
+    }
+
+    /// Papers not marked as read, in the given order
+    pub fn unread(&self, priority: Priority) -> Vec<&ReadingEntry> {
+        self.entries(priority)
+            .into_iter()
+            .filter(|entry| !self.is_read(&entry.id))
+            .collect()
+    }
+
+    /// Write the papers as a Markdown reading list, in the given order
+    pub fn to_markdown(&self, priority: Priority) -> String {
+        to_markdown(&self.cloned_entries(priority))
+    }
+
+    /// Write the papers as CSV for reference managers, in the given order
+    pub fn to_csv(&self, priority: Priority) -> String {
+        to_csv(&self.cloned_entries(priority))
+    }
+
+    fn cloned_entries(&self, priority: Priority) -> Vec<ReadingEntry> {
+        self.entries(priority).into_iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_list() {
+        let graph = Graph::example();
+        let mut list = ReadingList::new();
+        assert_eq!(list.add_graph(&graph), 2);
+        assert_eq!(list.add_graph(&graph), 0);
+        assert!(list.add(&graph.nodes["seed"]));
+        assert_eq!(list.len(), 3);
+
+        let ids = |entries: Vec<&ReadingEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| entry.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(list.entries(Priority::Similarity)),
+            vec!["newer", "older", "seed"]
+        );
+        assert_eq!(
+            ids(list.entries(Priority::Citations)),
+            vec!["older", "seed", "newer"]
+        );
+
+        list.set_read("newer", true);
+        assert!(list.is_read("newer"));
+        assert_eq!(
+            ids(list.unread(Priority::Similarity)),
+            vec!["older", "seed"]
+        );
+        assert!(list.to_markdown(Priority::Added).contains("*Newer paper*"));
+        assert_eq!(list.to_csv(Priority::Added).lines().count(), 4);
+
+        assert!(list.remove("newer"));
+        assert!(!list.is_read("newer"));
+        assert!(!list.remove("newer"));
+    }
+}
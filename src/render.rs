@@ -0,0 +1,438 @@
+//! Rendering of Connected Papers graphs
+//!
+//! Draws a [`Graph`] to SVG using the layout coordinates (`pos`) returned by the API.
+//! Nodes are sized by citation count and colored by publication year, like on the
+//! Connected Papers website. The HTML output embeds the SVG with hover tooltips and
+//! needs no JavaScript, while the interactive HTML output embeds the graph JSON and
+//! draws it with [vis-network](https://visjs.github.io/vis-network/), loaded from a CDN
+//! unless a copy is inlined through [`RenderOptions::vis_network`].
+
+use crate::{
+    client::{Graph, Paper},
+    error::Result,
+    utils::escape,
+};
+use std::{collections::HashMap, fmt::Write};
+
+/// Options for rendering a graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Width of the drawing in pixels
+    pub width: f64,
+    /// Height of the drawing in pixels
+    pub height: f64,
+    /// Radius of the least cited node
+    pub min_radius: f64,
+    /// Radius of the most cited node
+    pub max_radius: f64,
+    /// Color of the oldest papers, as RGB
+    pub old_color: [u8; 3],
+    /// Color of the newest papers, as RGB
+    pub new_color: [u8; 3],
+    /// Where the interactive page gets vis-network from, the CDN by default
+    pub vis_network: ScriptSource,
+}
+
+/// URL of the vis-network script on the unpkg CDN
+pub const VIS_NETWORK_URL: &str =
+    "https://unpkg.com/vis-network@9/standalone/umd/vis-network.min.js";
+
+/// Source of a script used by a rendered page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptSource {
+    /// Load the script from a URL, so the page needs network access when opened
+    Url(String),
+    /// Embed the script itself, e.g. a downloaded copy, so the page also works offline
+    Inline(String),
+}
+
+impl Default for ScriptSource {
+    fn default() -> Self {
+        ScriptSource::Url(VIS_NETWORK_URL.to_owned())
+    }
+}
+
+impl ScriptSource {
+    /// The `script` element loading or embedding the script
+    fn element(&self) -> String {
+        match self {
+            ScriptSource::Url(url) => format!(r#"<script src="{}"></script>"#, escape(url)),
+            // `</script` would end the element early
+            ScriptSource::Inline(script) => {
+                format!(
+                    "<script>{}</script>",
+                    script.replace("</script", "<\\/script")
+                )
+            }
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: 960.0,
+            height: 720.0,
+            min_radius: 4.0,
+            max_radius: 24.0,
+            old_color: [217, 240, 211],
+            new_color: [27, 120, 55],
+            vis_network: ScriptSource::default(),
+        }
+    }
+}
+
+/// Node placed on the canvas
+struct PlacedNode<'a> {
+    paper: &'a Paper,
+    x: f64,
+    y: f64,
+    radius: f64,
+    color: String,
+}
+
+impl Graph {
+    /// Render the graph to an SVG document with the default options
+    pub fn to_svg(&self) -> String {
+        svg(self, &RenderOptions::default())
+    }
+
+    /// Render the graph to a self-contained HTML page with the default options
+    pub fn to_html(&self) -> String {
+        html(self, &RenderOptions::default())
+    }
+
+    /// Render the graph to a single interactive HTML page with the default options
+    pub fn to_interactive_html(&self) -> Result<String> {
+        interactive_html(self, &RenderOptions::default())
+    }
+}
+
+/// Render a graph to an SVG document
+///
+/// Nodes without layout coordinates are skipped.
+pub fn svg(graph: &Graph, options: &RenderOptions) -> String {
+    let nodes = place_nodes(graph, options);
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = options.width,
+        h = options.height
+    );
+    svg.push_str(r##"<g class="edges" stroke="#9e9e9e">"##);
+    let by_id = nodes
+        .iter()
+        .map(|node| (node.paper.id.as_str(), node))
+        .collect::<HashMap<_, _>>();
+    for edge in &graph.edges {
+        let source = by_id.get(edge.0.as_str());
+        let target = by_id.get(edge.1.as_str());
+        if let (Some(source), Some(target)) = (source, target) {
+            let _ = write!(
+                svg,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke-opacity="{:.3}"/>"#,
+                source.x,
+                source.y,
+                target.x,
+                target.y,
+                edge.2.clamp(0.05, 1.0)
+            );
+        }
+    }
+    svg.push_str("</g><g class=\"nodes\">");
+    for node in &nodes {
+        let stroke = if node.paper.id == graph.start_id {
+            r##" stroke="#000" stroke-width="2""##
+        } else {
+            ""
+        };
+        let _ = write!(
+            svg,
+            r#"<circle class="node" cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"{}><title>{}</title></circle>"#,
+            node.x,
+            node.y,
+            node.radius,
+            node.color,
+            stroke,
+            escape(&tooltip(node.paper))
+        );
+    }
+    svg.push_str("</g></svg>");
+    svg
+}
+
+/// Render a graph to a self-contained HTML page
+///
+/// Hovering a node shows its title, authors, year and citation count.
+pub fn html(graph: &Graph, options: &RenderOptions) -> String {
+    let title = graph
+        .nodes
+        .get(&graph.start_id)
+        .and_then(|paper| paper.title.as_deref())
+        .unwrap_or(graph.start_id.as_str());
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+svg {{ border: 1px solid #e0e0e0; }}
+.node:hover {{ stroke: #d32f2f; stroke-width: 3; cursor: pointer; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{svg}
+</body>
+</html>
+"#,
+        title = escape(title),
+        svg = svg(graph, options)
+    )
+}
+
+/// Render a graph to a single interactive HTML page
+///
+/// The page embeds the full graph JSON and draws it with vis-network, so it can be shared
+/// with collaborators who only have a browser. Nodes can be dragged, zoomed and hovered for
+/// details. vis-network is loaded from [`VIS_NETWORK_URL`] by default, which needs network
+/// access when the page is opened; set [`RenderOptions::vis_network`] to another URL or to
+/// an inlined copy for offline use.
+pub fn interactive_html(graph: &Graph, options: &RenderOptions) -> Result<String> {
+    let title = graph
+        .nodes
+        .get(&graph.start_id)
+        .and_then(|paper| paper.title.as_deref())
+        .unwrap_or(graph.start_id.as_str());
+    // `</` would end the script element early, so it is escaped in the embedded JSON
+    let data = serde_json::to_string(graph)?.replace("</", "<\\/");
+    let color = |rgb: [u8; 3]| format!("[{}, {}, {}]", rgb[0], rgb[1], rgb[2]);
+    Ok(format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+{vis_network}
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+#graph {{ width: {width}px; height: {height}px; border: 1px solid #e0e0e0; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<div id="graph"></div>
+<script type="application/json" id="graph-data">{data}</script>
+<script>
+const graph = JSON.parse(document.getElementById("graph-data").textContent);
+const papers = Object.values(graph.nodes);
+const years = papers.map(p => p.year).filter(y => y);
+const minYear = Math.min(...years), maxYear = Math.max(...years);
+const maxCitations = Math.max(1, ...papers.map(p => p.citations_length || 0));
+const oldColor = {old_color}, newColor = {new_color};
+const color = year => {{
+  const t = year && maxYear > minYear ? (year - minYear) / (maxYear - minYear) : 1;
+  return "rgb(" + oldColor.map((c, i) => Math.round(c + (newColor[i] - c) * t)).join(",") + ")";
+}};
+const nodes = papers.map(p => ({{
+  id: p.id,
+  label: (p.authors && p.authors[0] && p.authors[0].name ? p.authors[0].name.split(" ").pop() : "") + (p.year ? ", " + p.year : ""),
+  title: (p.title || p.id) + (p.year ? " (" + p.year + ")" : "") + "\n" + (p.authors || []).map(a => a.name).filter(n => n).join(", ") + "\nCitations: " + (p.citations_length || 0),
+  x: p.pos ? p.pos[0] * {width} : undefined,
+  y: p.pos ? p.pos[1] * {height} : undefined,
+  size: {min_radius} + ({max_radius} - {min_radius}) * Math.sqrt((p.citations_length || 0) / maxCitations),
+  color: color(p.year),
+  borderWidth: p.id === graph.start_id ? 3 : 1,
+  shape: "dot",
+  url: p.url,
+}}));
+const edges = graph.edges.map(([from, to, weight]) => ({{ from, to, value: weight, color: {{ color: "#9e9e9e" }} }}));
+const network = new vis.Network(
+  document.getElementById("graph"),
+  {{ nodes: new vis.DataSet(nodes), edges: new vis.DataSet(edges) }},
+  {{ physics: false, interaction: {{ hover: true }} }}
+);
+network.on("doubleClick", event => {{
+  const node = nodes.find(n => n.id === event.nodes[0]);
+  if (node && node.url) window.open(node.url, "_blank");
+}});
+</script>
+</body>
+</html>
+"##,
+        title = escape(title),
+        vis_network = options.vis_network.element(),
+        width = options.width,
+        height = options.height,
+        min_radius = options.min_radius,
+        max_radius = options.max_radius,
+        old_color = color(options.old_color),
+        new_color = color(options.new_color),
+    ))
+}
+
+/// Map node positions to the canvas, sizes to citation counts and colors to years
+fn place_nodes<'a>(graph: &'a Graph, options: &RenderOptions) -> Vec<PlacedNode<'a>> {
+    let mut papers = graph
+        .nodes
+        .values()
+        .filter(|paper| paper.pos.is_some())
+        .collect::<Vec<_>>();
+    papers.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let positions = papers
+        .iter()
+        .filter_map(|paper| paper.pos)
+        .collect::<Vec<_>>();
+    let (min_x, max_x) = bounds(positions.iter().map(|pos| pos[0]));
+    let (min_y, max_y) = bounds(positions.iter().map(|pos| pos[1]));
+    let max_citations = papers
+        .iter()
+        .filter_map(|paper| paper.citations_length)
+        .max()
+        .unwrap_or(0);
+    let (min_year, max_year) = bounds(papers.iter().filter_map(|paper| paper.year.map(f64::from)));
+
+    let padding = options.max_radius * 1.5;
+    let scale = |value: f64, min: f64, max: f64, size: f64| {
+        if max > min {
+            padding + (value - min) / (max - min) * (size - 2.0 * padding)
+        } else {
+            size / 2.0
+        }
+    };
+
+    papers
+        .into_iter()
+        .map(|paper| {
+            let [x, y] = paper.pos.unwrap_or_default();
+            let citations = paper.citations_length.unwrap_or(0);
+            let share = if max_citations > 0 {
+                (f64::from(citations) / f64::from(max_citations)).sqrt()
+            } else {
+                0.0
+            };
+            let age = match paper.year {
+                Some(year) if max_year > min_year => {
+                    (f64::from(year) - min_year) / (max_year - min_year)
+                }
+                _ => 1.0,
+            };
+            PlacedNode {
+                paper,
+                x: scale(x, min_x, max_x, options.width),
+                y: scale(y, min_y, max_y, options.height),
+                radius: options.min_radius + (options.max_radius - options.min_radius) * share,
+                color: interpolate(options.old_color, options.new_color, age),
+            }
+        })
+        .collect()
+}
+
+/// Minimum and maximum of the values, `(0, 0)` if there are none
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values
+        .fold(None, |acc: Option<(f64, f64)>, value| match acc {
+            Some((min, max)) => Some((min.min(value), max.max(value))),
+            None => Some((value, value)),
+        })
+        .unwrap_or((0.0, 0.0))
+}
+
+/// Linear interpolation between two RGB colors as a hex string
+fn interpolate(from: [u8; 3], to: [u8; 3], t: f64) -> String {
+    let channel = |i: usize| {
+        let value = f64::from(from[i]) + (f64::from(to[i]) - f64::from(from[i])) * t;
+        value.round().clamp(0.0, 255.0) as u8
+    };
+    format!("#{:02x}{:02x}{:02x}", channel(0), channel(1), channel(2))
+}
+
+/// Tooltip text of a node
+fn tooltip(paper: &Paper) -> String {
+    let mut text = paper.title.clone().unwrap_or_else(|| paper.id.clone());
+    if let Some(year) = paper.year {
+        let _ = write!(text, " ({})", year);
+    }
+    let names = paper
+        .authors()
+        .iter()
+        .filter_map(|author| author.name.as_deref())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !names.is_empty() {
+        let _ = write!(text, "\n{}", names);
+    }
+    if let Some(citations) = paper.citations_length {
+        let _ = write!(text, "\nCitations: {}", citations);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        serde_json::from_value(serde_json::json!({
+            "nodes": {
+                "a": { "id": "a", "paperId": "a", "title": "Seed <paper>", "year": 2010, "citations_length": 100, "pos": [0.0, 0.0] },
+                "b": { "id": "b", "paperId": "b", "title": "Other", "year": 2020, "citations_length": 4, "pos": [1.0, 2.0] },
+                "c": { "id": "c", "paperId": "c", "title": "Unplaced" }
+            },
+            "edges": [["a", "b", 0.5], ["a", "c", 0.2]],
+            "common_citations": [],
+            "common_references": [],
+            "common_authors": [],
+            "parameters": {
+                "paper_id": "a", "total_nodes": 3, "num_commons": 0, "max_load": 0,
+                "num_neighbors": 0, "spring_iterations": 0, "params_version": 1
+            },
+            "path_lengths": {},
+            "start_id": "a",
+            "current_corpus_date": "2024-01-01",
+            "creation_time": "2024-01-01T00:00:00"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_svg() {
+        let svg = graph().to_svg();
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert!(svg.contains("Seed &lt;paper&gt; (2010)"));
+        assert!(svg.contains(r##"fill="#1b7837""##));
+    }
+
+    #[test]
+    fn test_html() {
+        let html = graph().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Seed &lt;paper&gt;</title>"));
+        assert!(html.contains("<svg"));
+    }
+
+    #[test]
+    fn test_interactive_html() {
+        let mut graph = graph();
+        graph.nodes.get_mut("b").unwrap().title = Some("</script><b>".to_owned());
+        let html = graph.to_interactive_html().unwrap();
+        assert!(html.contains(r#""start_id":"a""#));
+        assert!(html.contains(r#"<\/script><b>"#));
+        assert_eq!(html.matches("</script>").count(), 3);
+        assert!(html.contains(&format!(r#"<script src="{}">"#, VIS_NETWORK_URL)));
+
+        let options = RenderOptions {
+            vis_network: ScriptSource::Inline("var vis = {}; // </script>".to_owned()),
+            ..Default::default()
+        };
+        let html = interactive_html(&graph, &options).unwrap();
+        assert!(!html.contains("<script src="));
+        assert!(html.contains(r#"<script>var vis = {}; // <\/script></script>"#));
+        assert_eq!(html.matches("</script>").count(), 3);
+    }
+}
@@ -0,0 +1,466 @@
+//! Request scheduling across both APIs
+//!
+//! A [`Scheduler`] is shared by every stage of a run, e.g. a [`Pipeline`](crate::Pipeline)
+//! resolving a seed on Semantic Scholar, fetching its graph from Connected Papers and
+//! enriching the nodes in batches. It caps the requests in flight across both APIs, hands
+//! free slots to each API in turn so that one stage cannot starve the other, spaces out the
+//! calls to each API and retries failed requests out of a budget shared by the whole run.
+
+use crate::{
+    error::{Error, Result},
+    progress::{ProgressEvent, ProgressSink},
+};
+use futures::channel::oneshot;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// API called by a scheduled request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Api {
+    ConnectedPapers,
+    SemanticScholar,
+}
+
+impl Api {
+    fn index(self) -> usize {
+        match self {
+            Api::ConnectedPapers => 0,
+            Api::SemanticScholar => 1,
+        }
+    }
+}
+
+/// Whether a failed request may succeed when sent again
+///
+/// Rate limiting, server errors and transport failures are transient; any other error,
+/// e.g. an invalid parameter or a missing paper, would fail again.
+pub fn is_retryable(error: &Error) -> bool {
+    match error.inner() {
+        Error::RequestFailed(error) => error.status == 429 || error.status >= 500,
+        Error::ReqwestError(_) => true,
+        _ => false,
+    }
+}
+
+/// Spaces out calls to an API by a minimum interval
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Reserve the next slot and wait for it
+    pub(crate) async fn acquire(&self) {
+        if self.interval.is_zero() {
+            return;
+        }
+        let wait = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + self.interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    running: usize,
+    retries: usize,
+    waiting: [VecDeque<oneshot::Sender<Slot>>; 2],
+    turn: usize,
+}
+
+impl State {
+    /// Next waiting request, taking each API in turn
+    fn next_waiter(&mut self) -> Option<oneshot::Sender<Slot>> {
+        for index in [self.turn, 1 - self.turn] {
+            if let Some(waiter) = self.waiting[index].pop_front() {
+                self.turn = 1 - index;
+                return Some(waiter);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    max_concurrency: usize,
+    max_retries: u32,
+    retry_budget: usize,
+    backoff: Duration,
+    limiters: [RateLimiter; 2],
+    progress: ProgressSink,
+    state: Mutex<State>,
+}
+
+impl Inner {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A request slot, handed over to the next waiting request when dropped
+#[derive(Debug)]
+struct Slot {
+    scheduler: Option<Arc<Inner>>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let Some(inner) = self.scheduler.take() else {
+            return;
+        };
+        let mut slot = Slot {
+            scheduler: Some(inner.clone()),
+        };
+        let mut state = inner.lock();
+        while let Some(waiter) = state.next_waiter() {
+            match waiter.send(slot) {
+                Ok(()) => return,
+                Err(returned) => slot = returned,
+            }
+        }
+        state.running -= 1;
+        slot.scheduler = None;
+    }
+}
+
+/// Shared limits on the requests of a run
+///
+/// Clones share the same limits, so a scheduler can be handed to every stage of a run.
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    inner: Arc<Inner>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        SchedulerBuilder::default()
+            .build()
+            .expect("default scheduler is valid")
+    }
+}
+
+impl Scheduler {
+    /// Create a builder with the default limits
+    pub fn builder() -> SchedulerBuilder {
+        SchedulerBuilder::default()
+    }
+
+    /// Retries spent so far out of the shared budget
+    pub fn retries(&self) -> usize {
+        self.inner.lock().retries
+    }
+
+    /// Run a request to an API within the limits, retrying it while it fails transiently
+    ///
+    /// `request` is called once per attempt. A retry waits an exponential backoff, counts
+    /// against the shared budget and releases its slot to other waiting requests meanwhile.
+    /// Errors carry the number of the attempt that failed in their context.
+    pub async fn run<T, F, Fut>(&self, api: Api, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let slot = self.acquire(api).await;
+            self.inner.limiters[api.index()].acquire().await;
+            let result = request().await.map_err(|e| e.with_attempt(attempt + 1));
+            drop(slot);
+            match result {
+                Err(e) if is_retryable(&e) && attempt < self.inner.max_retries => {
+                    if !self.spend_retry() {
+                        return Err(e);
+                    }
+                    self.inner.progress.emit(ProgressEvent::ChunkRetried {
+                        attempt: attempt + 1,
+                    });
+                    let backoff = self
+                        .inner
+                        .backoff
+                        .saturating_mul(2u32.saturating_pow(attempt));
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Take a retry out of the shared budget, `false` if it is exhausted
+    fn spend_retry(&self) -> bool {
+        let mut state = self.inner.lock();
+        if state.retries >= self.inner.retry_budget {
+            return false;
+        }
+        state.retries += 1;
+        true
+    }
+
+    /// Wait for a free slot, queuing behind the requests already waiting for the API
+    async fn acquire(&self, api: Api) -> Slot {
+        let receiver = {
+            let mut state = self.inner.lock();
+            let queued = state.waiting.iter().any(|queue| !queue.is_empty());
+            if state.running < self.inner.max_concurrency && !queued {
+                state.running += 1;
+                return Slot {
+                    scheduler: Some(self.inner.clone()),
+                };
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiting[api.index()].push_back(sender);
+            receiver
+        };
+        receiver
+            .await
+            .expect("waiting requests are only dropped with the scheduler")
+    }
+}
+
+/// Builder for [`Scheduler`]
+#[derive(Debug, Clone)]
+pub struct SchedulerBuilder {
+    max_concurrency: usize,
+    connected_papers_interval: Duration,
+    semantic_scholar_interval: Duration,
+    max_retries: u32,
+    retry_budget: usize,
+    backoff: Duration,
+    progress: ProgressSink,
+}
+
+impl Default for SchedulerBuilder {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 4,
+            connected_papers_interval: Duration::ZERO,
+            semantic_scholar_interval: Duration::from_secs(1),
+            max_retries: 3,
+            retry_budget: 10,
+            backoff: Duration::from_secs(1),
+            progress: ProgressSink::default(),
+        }
+    }
+}
+
+impl SchedulerBuilder {
+    /// Set the number of requests in flight at once across both APIs, 4 by default
+    pub fn max_concurrency(&mut self, max_concurrency: usize) -> &mut Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Set the minimum interval between Connected Papers calls, none by default
+    pub fn connected_papers_interval(&mut self, interval: Duration) -> &mut Self {
+        self.connected_papers_interval = interval;
+        self
+    }
+
+    /// Set the minimum interval between Semantic Scholar calls, 1 second by default
+    pub fn semantic_scholar_interval(&mut self, interval: Duration) -> &mut Self {
+        self.semantic_scholar_interval = interval;
+        self
+    }
+
+    /// Set the number of retries of a single request, 3 by default
+    pub fn max_retries(&mut self, max_retries: u32) -> &mut Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the number of retries shared by all requests of the run, 10 by default
+    pub fn retry_budget(&mut self, retry_budget: usize) -> &mut Self {
+        self.retry_budget = retry_budget;
+        self
+    }
+
+    /// Set the delay before the first retry, doubled at each further retry, 1 second by default
+    pub fn backoff(&mut self, backoff: Duration) -> &mut Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Report every retry as a [`ProgressEvent::ChunkRetried`]
+    pub fn progress(&mut self, progress: ProgressSink) -> &mut Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Build the scheduler
+    pub fn build(&self) -> Result<Scheduler> {
+        if self.max_concurrency == 0 {
+            return Err(Error::InvalidParameter(
+                "max concurrency must be at least 1".to_owned(),
+            ));
+        }
+        Ok(Scheduler {
+            inner: Arc::new(Inner {
+                max_concurrency: self.max_concurrency,
+                max_retries: self.max_retries,
+                retry_budget: self.retry_budget,
+                backoff: self.backoff,
+                limiters: [
+                    RateLimiter::new(self.connected_papers_interval),
+                    RateLimiter::new(self.semantic_scholar_interval),
+                ],
+                progress: self.progress.clone(),
+                state: Mutex::new(State::default()),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use futures::future::join_all;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn too_many_requests() -> Error {
+        Error::RequestFailed(ApiError {
+            status: 429,
+            message: None,
+            body: "Too Many Requests".to_owned(),
+            request_id: None,
+        })
+    }
+
+    fn scheduler(max_concurrency: usize, retry_budget: usize) -> Scheduler {
+        Scheduler::builder()
+            .max_concurrency(max_concurrency)
+            .semantic_scholar_interval(Duration::ZERO)
+            .retry_budget(retry_budget)
+            .backoff(Duration::ZERO)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget() {
+        let scheduler = scheduler(1, 3);
+        let calls = AtomicUsize::new(0);
+        let result = scheduler
+            .run(Api::SemanticScholar, || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err(too_many_requests()),
+                    _ => Ok(()),
+                }
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(scheduler.retries(), 2);
+
+        let result = scheduler
+            .run(Api::SemanticScholar, || async {
+                Err::<(), _>(too_many_requests())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(scheduler.retries(), 3);
+
+        let calls = AtomicUsize::new(0);
+        let result = scheduler
+            .run(Api::ConnectedPapers, || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Error::InvalidParameter("id".to_owned()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency() {
+        let scheduler = scheduler(2, 0);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let requests = (0..8).map(|i| {
+            let api = if i % 2 == 0 {
+                Api::ConnectedPapers
+            } else {
+                Api::SemanticScholar
+            };
+            scheduler.run(api, || async {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            })
+        });
+        assert!(join_all(requests).await.iter().all(Result::is_ok));
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(scheduler.inner.lock().running, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(Duration::from_millis(20));
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[cfg(feature = "semantic-scholar")]
+    #[tokio::test]
+    async fn test_retry_attempt() {
+        use crate::{SemanticScholar, cassette::Cassette, ss::Product};
+
+        let dir = std::env::temp_dir().join(format!("retry-attempt-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        let url = format!("{}/paper/abc", Product::Graph.default_url());
+        cassette
+            .insert("GET", &url, b"", 429, r#"{"message": "Too Many Requests"}"#)
+            .unwrap();
+        let client = SemanticScholar::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+        let scheduler = Scheduler::builder()
+            .semantic_scholar_interval(Duration::ZERO)
+            .backoff(Duration::ZERO)
+            .max_retries(2)
+            .build()
+            .unwrap();
+        let error = scheduler
+            .run(Api::SemanticScholar, || async {
+                client
+                    .send_raw(crate::Method::Get, "/paper/abc", &[], None)
+                    .await?
+                    .error_for_status()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(scheduler.retries(), 2);
+        assert_eq!(error.context().unwrap().attempt, 3);
+        assert!(
+            error
+                .to_string()
+                .ends_with("(in GET /graph/v1/paper/abc, attempt 3)")
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
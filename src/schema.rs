@@ -0,0 +1,50 @@
+//! JSON Schema of the serialized models
+//!
+//! Graphs, papers and search responses serialize to the JSON layout described by these
+//! schemas, so that consumers in other languages can validate exported files or generate
+//! code from them. The layout is versioned by [`SCHEMA_VERSION`], which is bumped whenever
+//! a change could break such a consumer.
+
+use crate::{Graph, Paper, PaperBulkSearchResponse, PaperSearchResponse, S2NestedPaper};
+use schemars::{JsonSchema, Schema, schema_for};
+use std::collections::BTreeMap;
+
+/// Version of the serialized layout
+pub const SCHEMA_VERSION: &str = "1.0.0";
+
+/// JSON Schema of a model, tagged with [`SCHEMA_VERSION`] under the `x-version` keyword
+pub fn schema<T: JsonSchema>() -> Schema {
+    let mut schema = schema_for!(T);
+    schema.insert("x-version".to_owned(), SCHEMA_VERSION.into());
+    schema
+}
+
+/// JSON Schemas of the exported models, keyed by file stem
+pub fn schemas() -> BTreeMap<&'static str, Schema> {
+    BTreeMap::from([
+        ("graph", schema::<Graph>()),
+        ("paper", schema::<Paper>()),
+        ("s2_paper", schema::<S2NestedPaper>()),
+        ("paper_search_response", schema::<PaperSearchResponse>()),
+        (
+            "paper_bulk_search_response",
+            schema::<PaperBulkSearchResponse>(),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_schema() {
+        let schema = serde_json::to_value(schema::<Graph>()).unwrap();
+        assert_eq!(schema["x-version"], SCHEMA_VERSION);
+        let graph = serde_json::to_value(Graph::example()).unwrap();
+        for key in graph.as_object().unwrap().keys() {
+            assert!(schema["properties"].get(key).is_some(), "{} missing", key);
+        }
+        assert_eq!(schemas().len(), 5);
+    }
+}
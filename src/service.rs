@@ -0,0 +1,402 @@
+//! Tower service
+//!
+//! Exposes the main operations of both clients as a single [`tower::Service`], taking a
+//! [`ServiceRequest`] and answering with a [`ServiceResponse`], so that web backends can mount
+//! the crate behind their own endpoints. Requests and responses are serializable, calls to
+//! each API are spaced out by a minimum interval and successful responses are cached.
+
+use crate::{
+    client::{ConnectedPapers, GraphResponse},
+    error::{Error, Result},
+    scheduler::RateLimiter,
+    ss::{
+        PaperBatchParamBuilder, PaperField, PaperId, PaperSearchParamBuilder,
+        PaperTitleSearchParamBuilder, S2NestedPaper, SemanticScholar, paper_id::PaperIdSearchParam,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// Operation handled by a [`PapersService`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum ServiceRequest {
+    /// Connected Papers graph of a paper
+    Graph {
+        paper_id: String,
+        #[serde(default)]
+        fresh_only: bool,
+    },
+    /// Number of remaining Connected Papers API calls, never cached
+    RemainingUsages,
+    /// Semantic Scholar relevance search
+    SearchPapers { query: String, limit: Option<u8> },
+    /// Semantic Scholar paper best matching a title
+    MatchTitle { title: String },
+    /// Semantic Scholar details of a paper, e.g. by S2 ID or `DOI:10.18653/v1/N18-3011`
+    Paper { paper_id: String },
+    /// Semantic Scholar details of up to 500 papers
+    Papers { paper_ids: Vec<String> },
+}
+
+/// Answer of a [`PapersService`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ServiceResponse {
+    /// Answer to [`ServiceRequest::Graph`]
+    Graph(Box<GraphResponse>),
+    /// Answer to [`ServiceRequest::RemainingUsages`]
+    RemainingUsages(u64),
+    /// Answer to [`ServiceRequest::SearchPapers`]
+    Papers(Vec<S2NestedPaper>),
+    /// Answer to [`ServiceRequest::Papers`], in the order of the IDs with `None` for unknown ones
+    Batch(Vec<Option<S2NestedPaper>>),
+    /// Answer to [`ServiceRequest::MatchTitle`] and [`ServiceRequest::Paper`]
+    Paper(Option<Box<S2NestedPaper>>),
+}
+
+impl ServiceResponse {
+    /// Whether the response may be served again from the cache
+    fn is_cacheable(&self) -> bool {
+        match self {
+            ServiceResponse::Graph(response) => response.graph_json.is_some(),
+            ServiceResponse::RemainingUsages(_) => false,
+            ServiceResponse::Papers(_) | ServiceResponse::Batch(_) | ServiceResponse::Paper(_) => {
+                true
+            }
+        }
+    }
+}
+
+/// Responses kept for a time to live, evicting the oldest entry when full
+#[derive(Debug)]
+struct ResponseCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<ServiceRequest, (Instant, ServiceResponse)>>,
+}
+
+impl ResponseCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, request: &ServiceRequest) -> Option<ServiceResponse> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(request)
+            .filter(|(stored, _)| stored.elapsed() < self.ttl)
+            .map(|(_, response)| response.clone())
+    }
+
+    fn insert(&self, request: ServiceRequest, response: ServiceResponse) {
+        if self.capacity == 0 || self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= self.capacity && !entries.contains_key(&request) {
+            entries.retain(|_, (stored, _)| stored.elapsed() < self.ttl);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (stored, _))| *stored)
+                    .map(|(request, _)| request.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(request, (Instant::now(), response));
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    connected_papers: ConnectedPapers,
+    semantic_scholar: SemanticScholar,
+    connected_papers_limiter: RateLimiter,
+    semantic_scholar_limiter: RateLimiter,
+    cache: ResponseCache,
+}
+
+impl Inner {
+    async fn handle(&self, request: ServiceRequest) -> Result<ServiceResponse> {
+        if let Some(response) = self.cache.get(&request) {
+            return Ok(response);
+        }
+        let response = self.dispatch(&request).await?;
+        if response.is_cacheable() {
+            self.cache.insert(request, response.clone());
+        }
+        Ok(response)
+    }
+
+    async fn dispatch(&self, request: &ServiceRequest) -> Result<ServiceResponse> {
+        match request {
+            ServiceRequest::Graph {
+                paper_id,
+                fresh_only,
+            } => {
+                self.connected_papers_limiter.acquire().await;
+                let response = self
+                    .connected_papers
+                    .get_graph(paper_id, *fresh_only)
+                    .await?;
+                Ok(ServiceResponse::Graph(Box::new(response)))
+            }
+            ServiceRequest::RemainingUsages => {
+                self.connected_papers_limiter.acquire().await;
+                let usages = self.connected_papers.get_remaining_usages().await?;
+                Ok(ServiceResponse::RemainingUsages(usages))
+            }
+            ServiceRequest::SearchPapers { query, limit } => {
+                let mut builder = PaperSearchParamBuilder::new(query);
+                for field in PaperField::SUMMARY {
+                    builder.field(field);
+                }
+                if let Some(limit) = limit {
+                    builder.limit(*limit);
+                }
+                let param = builder.build()?;
+                self.semantic_scholar_limiter.acquire().await;
+                let response = self.semantic_scholar.query(&param).await?;
+                Ok(ServiceResponse::Papers(response.data().to_vec()))
+            }
+            ServiceRequest::MatchTitle { title } => {
+                let mut builder = PaperTitleSearchParamBuilder::new(title);
+                for field in PaperField::SUMMARY {
+                    builder.field(field);
+                }
+                let param = builder.build()?;
+                self.semantic_scholar_limiter.acquire().await;
+                let matched = self.semantic_scholar.query(&param).await?;
+                Ok(ServiceResponse::Paper(
+                    matched.map(|matched| Box::new(matched.paper)),
+                ))
+            }
+            ServiceRequest::Paper { paper_id } => {
+                let mut param = PaperIdSearchParam::new(&PaperId::id(paper_id.as_str()));
+                for field in PaperField::SUMMARY {
+                    param.add_field(field);
+                }
+                self.semantic_scholar_limiter.acquire().await;
+                let paper = self.semantic_scholar.query(&param).await?;
+                Ok(ServiceResponse::Paper(paper.map(Box::new)))
+            }
+            ServiceRequest::Papers { paper_ids } => {
+                let mut builder = PaperBatchParamBuilder::default();
+                for paper_id in paper_ids {
+                    builder.id(PaperId::id(paper_id.as_str()));
+                }
+                for field in PaperField::SUMMARY {
+                    builder.field(field);
+                }
+                let param = builder.build()?;
+                self.semantic_scholar_limiter.acquire().await;
+                let papers = self.semantic_scholar.query(&param).await?;
+                Ok(ServiceResponse::Batch(papers))
+            }
+        }
+    }
+}
+
+/// [`tower::Service`] over both clients, with rate limiting and caching
+///
+/// Clones share the rate limiters and the cache, so the service can be cloned into every
+/// handler of a web framework.
+#[derive(Debug, Clone)]
+pub struct PapersService {
+    inner: Arc<Inner>,
+}
+
+impl PapersService {
+    /// Create a builder using the given clients
+    pub fn builder(
+        connected_papers: ConnectedPapers,
+        semantic_scholar: SemanticScholar,
+    ) -> PapersServiceBuilder {
+        PapersServiceBuilder::new(connected_papers, semantic_scholar)
+    }
+
+    /// Handle a request without going through [`tower::Service`]
+    pub async fn handle(&self, request: ServiceRequest) -> Result<ServiceResponse> {
+        self.inner.handle(request).await
+    }
+}
+
+impl tower::Service<ServiceRequest> for PapersService {
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<ServiceResponse>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: ServiceRequest) -> Self::Future {
+        let inner = self.inner.clone();
+        Box::pin(async move { inner.handle(request).await })
+    }
+}
+
+/// Builder for [`PapersService`]
+#[derive(Debug, Clone)]
+pub struct PapersServiceBuilder {
+    connected_papers: ConnectedPapers,
+    semantic_scholar: SemanticScholar,
+    connected_papers_interval: Duration,
+    semantic_scholar_interval: Duration,
+    cache_ttl: Duration,
+    cache_capacity: usize,
+}
+
+impl PapersServiceBuilder {
+    /// Create a builder using the given clients
+    pub fn new(connected_papers: ConnectedPapers, semantic_scholar: SemanticScholar) -> Self {
+        Self {
+            connected_papers,
+            semantic_scholar,
+            connected_papers_interval: Duration::ZERO,
+            semantic_scholar_interval: Duration::from_secs(1),
+            cache_ttl: Duration::from_secs(3600),
+            cache_capacity: 1024,
+        }
+    }
+
+    /// Set the minimum interval between Connected Papers calls, none by default
+    pub fn connected_papers_interval(&mut self, interval: Duration) -> &mut Self {
+        self.connected_papers_interval = interval;
+        self
+    }
+
+    /// Set the minimum interval between Semantic Scholar calls, 1 second by default
+    ///
+    /// One request per second is the rate granted to API keys; anonymous access shares a
+    /// global pool and may need a longer interval.
+    pub fn semantic_scholar_interval(&mut self, interval: Duration) -> &mut Self {
+        self.semantic_scholar_interval = interval;
+        self
+    }
+
+    /// Set how long responses are cached, 1 hour by default; zero disables the cache
+    pub fn cache_ttl(&mut self, ttl: Duration) -> &mut Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Set the number of cached responses, 1024 by default; zero disables the cache
+    pub fn cache_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Build the service
+    pub fn build(&self) -> Result<PapersService> {
+        Ok(PapersService {
+            inner: Arc::new(Inner {
+                connected_papers: self.connected_papers.clone(),
+                semantic_scholar: self.semantic_scholar.clone(),
+                connected_papers_limiter: RateLimiter::new(self.connected_papers_interval),
+                semantic_scholar_limiter: RateLimiter::new(self.semantic_scholar_interval),
+                cache: ResponseCache::new(self.cache_ttl, self.cache_capacity),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ss::merge_paper_fields;
+
+    fn search(query: &str) -> ServiceRequest {
+        ServiceRequest::SearchPapers {
+            query: query.to_owned(),
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn test_service_request_json() {
+        let request: ServiceRequest =
+            serde_json::from_str(r#"{"operation": "graph", "paper_id": "abc"}"#).unwrap();
+        assert_eq!(
+            request,
+            ServiceRequest::Graph {
+                paper_id: "abc".to_owned(),
+                fresh_only: false,
+            }
+        );
+        let response = serde_json::to_value(ServiceResponse::RemainingUsages(3)).unwrap();
+        assert_eq!(
+            response,
+            serde_json::json!({"type": "remaining_usages", "data": 3})
+        );
+    }
+
+    #[test]
+    fn test_response_cache() {
+        let cache = ResponseCache::new(Duration::from_secs(60), 2);
+        for query in ["a", "b", "c"] {
+            cache.insert(search(query), ServiceResponse::Paper(None));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(cache.get(&search("a")).is_none());
+        assert_eq!(cache.get(&search("b")), Some(ServiceResponse::Paper(None)));
+        assert!(cache.get(&search("c")).is_some());
+
+        let disabled = ResponseCache::new(Duration::ZERO, 2);
+        disabled.insert(search("a"), ServiceResponse::Paper(None));
+        assert!(disabled.get(&search("a")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_unknown_ids() {
+        use crate::{cassette::Cassette, ss::Product};
+
+        let dir = std::env::temp_dir().join(format!("service-batch-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        let url = format!(
+            "{}/paper/batch?fields={}",
+            Product::Graph.default_url(),
+            merge_paper_fields(&PaperField::SUMMARY)
+        );
+        let body = serde_json::json!({ "ids": ["a", "unknown", "b"] }).to_string();
+        let papers = serde_json::json!([{ "paperId": "a" }, null, { "paperId": "b" }]);
+        cassette
+            .insert("POST", &url, body.as_bytes(), 200, &papers.to_string())
+            .unwrap();
+        let semantic_scholar = SemanticScholar::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+        let service = PapersService::builder(ConnectedPapers::default(), semantic_scholar)
+            .semantic_scholar_interval(Duration::ZERO)
+            .build()
+            .unwrap();
+
+        let request = ServiceRequest::Papers {
+            paper_ids: vec!["a".to_owned(), "unknown".to_owned(), "b".to_owned()],
+        };
+        let ServiceResponse::Batch(papers) = service.handle(request).await.unwrap() else {
+            panic!("batch answered with another response");
+        };
+        let ids = papers
+            .iter()
+            .map(|paper| paper.as_ref().map(|paper| paper.paper_id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [Some("a"), None, Some("b")]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Hybrid similarity
+//!
+//! Connected Papers links papers by co-citation and bibliographic coupling, which misses a
+//! paper directly citing another one. [`hybrid_similarity`] combines the edge weights of a
+//! graph with the direct citation and reference links of its nodes fetched from Semantic
+//! Scholar into a single score per pair of nodes.
+
+use crate::{
+    client::Graph,
+    error::{Error, Result},
+    ss::S2NestedPaper,
+};
+use std::collections::{BTreeMap, HashSet};
+
+/// Pair of graph nodes with its similarity scores
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridEdge {
+    /// Smaller id of the pair
+    pub source: String,
+    /// Larger id of the pair
+    pub target: String,
+    /// Connected Papers edge weight, divided by the largest weight of the graph, in `[0, 1]`
+    pub co_citation: f64,
+    /// Whether one paper of the pair cites the other
+    pub direct: bool,
+    /// Combined score used for ordering, in `[0, 1]`
+    pub score: f64,
+}
+
+/// Score every linked pair of graph nodes by edge weight and direct citation
+///
+/// `papers` are the nodes of the graph enriched with their
+/// [`PaperField::Citations`](crate::PaperField::Citations) or
+/// [`PaperField::References`](crate::PaperField::References), e.g. by the batch endpoint;
+/// links to papers outside the graph are ignored. The score is
+/// `(1 - direct_weight) * co_citation + direct_weight * direct`, so `direct_weight` tunes
+/// how much direct citation matters against co-citation and must be in `[0, 1]`.
+///
+/// Pairs are returned from the highest score down.
+pub fn hybrid_similarity(
+    graph: &Graph,
+    papers: &[S2NestedPaper],
+    direct_weight: f64,
+) -> Result<Vec<HybridEdge>> {
+    if !(0.0..=1.0).contains(&direct_weight) {
+        return Err(Error::InvalidParameter(
+            "direct weight must be in [0, 1]".to_owned(),
+        ));
+    }
+
+    let pair = |a: &str, b: &str| {
+        if a <= b {
+            (a.to_owned(), b.to_owned())
+        } else {
+            (b.to_owned(), a.to_owned())
+        }
+    };
+
+    let max_weight = graph.edges.iter().map(|edge| edge.2).fold(0.0, f64::max);
+    let mut co_citation = BTreeMap::new();
+    for edge in &graph.edges {
+        if edge.0 == edge.1
+            || !graph.nodes.contains_key(&edge.0)
+            || !graph.nodes.contains_key(&edge.1)
+        {
+            continue;
+        }
+        let weight = if max_weight > 0.0 {
+            edge.2 / max_weight
+        } else {
+            0.0
+        };
+        let entry = co_citation.entry(pair(&edge.0, &edge.1)).or_insert(0.0);
+        *entry = weight.max(*entry);
+    }
+
+    let mut direct = HashSet::new();
+    for paper in papers {
+        if !graph.nodes.contains_key(&paper.paper_id) {
+            continue;
+        }
+        let citations = paper.citations.iter().flatten();
+        for linked in citations.chain(paper.references.iter().flatten()) {
+            if linked.paper_id != paper.paper_id && graph.nodes.contains_key(&linked.paper_id) {
+                direct.insert(pair(&paper.paper_id, &linked.paper_id));
+            }
+        }
+    }
+
+    let mut pairs = co_citation.keys().cloned().collect::<HashSet<_>>();
+    pairs.extend(direct.iter().cloned());
+    let mut edges = pairs
+        .into_iter()
+        .map(|key| {
+            let co_citation = co_citation.get(&key).copied().unwrap_or(0.0);
+            let direct = direct.contains(&key);
+            let link = if direct { 1.0 } else { 0.0 };
+            let (source, target) = key;
+            HybridEdge {
+                source,
+                target,
+                co_citation,
+                direct,
+                score: (1.0 - direct_weight) * co_citation + direct_weight * link,
+            }
+        })
+        .collect::<Vec<_>>();
+    edges.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.source.cmp(&b.source))
+            .then_with(|| a.target.cmp(&b.target))
+    });
+    Ok(edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hybrid_similarity() {
+        let graph = Graph::example();
+        let papers: Vec<S2NestedPaper> = serde_json::from_value(serde_json::json!([
+            { "paperId": "newer", "references": [{ "paperId": "older" }, { "paperId": "outside" }] },
+            { "paperId": "older" },
+        ]))
+        .unwrap();
+
+        let edges = hybrid_similarity(&graph, &papers, 0.5).unwrap();
+        let pairs = edges
+            .iter()
+            .map(|edge| (edge.source.as_str(), edge.target.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            vec![("newer", "older"), ("newer", "seed"), ("older", "seed")]
+        );
+        assert!(edges[0].direct);
+        assert_eq!(edges[0].co_citation, 0.0);
+        assert_eq!(edges[1].score, 0.5);
+        assert!((edges[2].score - 0.5 * 0.4 / 0.6).abs() < 1e-9);
+
+        let edges = hybrid_similarity(&graph, &papers, 0.0).unwrap();
+        assert_eq!(edges[0].score, 1.0);
+        assert!(hybrid_similarity(&graph, &papers, 1.5).is_err());
+    }
+}
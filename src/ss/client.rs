@@ -1,37 +1,78 @@
 //! Semantic Scholar Client
 
-use crate::{error::Result, utils::APIKey};
+use crate::{
+    cassette::Cassette,
+    error::{Error, Result},
+    ss::PaperField,
+    utils::{APIKey, Coalescer, HttpConfig, Transport},
+};
 use reqwest::Client;
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 static APP_USER_AGENT: &str =
     concat!("RS", env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Semantic Scholar product, each served under its own base URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Product {
+    /// Academic Graph API
+    Graph,
+    /// Recommendations API
+    Recommendations,
+    /// Datasets API
+    Datasets,
+}
+
+impl Product {
+    /// Public base URL of the product
+    pub fn default_url(self) -> &'static str {
+        match self {
+            Product::Graph => "https://api.semanticscholar.org/graph/v1",
+            Product::Recommendations => "https://api.semanticscholar.org/recommendations/v1",
+            Product::Datasets => "https://api.semanticscholar.org/datasets/v1",
+        }
+    }
+}
+
 /// Client
+///
+/// Clones share the connection pool, the settings and the requests in flight, and cost a few
+/// reference count increments.
 #[derive(Debug, Clone)]
 pub struct SemanticScholar {
-    api_key: Option<String>,
+    api_key: Option<Arc<str>>,
     client: Client,
+    transport: Transport,
+    default_fields: Option<Arc<[PaperField]>>,
+    base_urls: Arc<HashMap<Product, String>>,
 }
 
 impl Default for SemanticScholar {
     fn default() -> Self {
         Self {
             api_key: None,
-            client: Client::builder()
-                .timeout(Duration::from_secs(30))
-                .user_agent(APP_USER_AGENT)
-                .build()
+            client: HttpConfig::default()
+                .build(APP_USER_AGENT, DEFAULT_TIMEOUT)
                 .unwrap(),
+            transport: Transport::default(),
+            default_fields: None,
+            base_urls: Arc::default(),
         }
     }
 }
 
 impl SemanticScholar {
+    /// Create a builder to configure the client
+    pub fn builder() -> SemanticScholarBuilder {
+        SemanticScholarBuilder::default()
+    }
+
     /// Create a new client with the given API key
     pub fn with_api_key(api_key: &str) -> Self {
         Self {
-            api_key: Some(api_key.to_owned()),
+            api_key: Some(Arc::from(api_key)),
             ..Self::default()
         }
     }
@@ -42,10 +83,22 @@ impl SemanticScholar {
         Ok(Self::with_api_key(&api_key))
     }
 
+    /// Create a client sharing the HTTP client and transport of another API client
+    #[cfg(feature = "connected-papers")]
+    pub(crate) fn with_transport(client: Client, transport: Transport) -> Self {
+        Self {
+            api_key: None,
+            client,
+            transport,
+            default_fields: None,
+            base_urls: Arc::default(),
+        }
+    }
+
     pub(crate) fn api_key(&self) -> Option<APIKey> {
         self.api_key.as_ref().map(|key| APIKey {
             header: "x-api-key".to_owned(),
-            value: key.to_owned(),
+            value: key.to_string(),
         })
     }
 
@@ -53,12 +106,128 @@ impl SemanticScholar {
         &self.client
     }
 
+    pub(crate) fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    /// Fields requested when a query does not select any
+    pub fn default_fields(&self) -> Option<&[PaperField]> {
+        self.default_fields.as_deref()
+    }
+
+    /// Base URL requests to a product are sent to
+    pub fn base_url(&self, product: Product) -> &str {
+        self.base_urls
+            .get(&product)
+            .map_or(product.default_url(), String::as_str)
+    }
+
+    pub(crate) fn graph_url(&self) -> &str {
+        self.base_url(Product::Graph)
+    }
+
     /// Query the Semantic Scholar API
     pub async fn query<Q: Query>(&self, query: &Q) -> Result<Q::Response> {
         query.query(self).await
     }
 }
 
+/// Builder for [`SemanticScholar`]
+#[derive(Debug, Clone, Default)]
+pub struct SemanticScholarBuilder {
+    api_key: Option<String>,
+    http: HttpConfig,
+    transport: Transport,
+    default_fields: Option<Vec<PaperField>>,
+    base_urls: HashMap<Product, String>,
+}
+
+impl SemanticScholarBuilder {
+    /// Set the API key
+    pub fn api_key(&mut self, api_key: &str) -> &mut Self {
+        self.api_key = Some(api_key.to_owned());
+        self
+    }
+
+    /// Set the request timeout, 30 seconds by default
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.http.timeout = Some(timeout);
+        self
+    }
+
+    /// Route all requests through the given HTTP(S) proxy, e.g. `http://proxy.example.org:8080`
+    pub fn proxy(&mut self, url: &str) -> &mut Self {
+        self.http.proxy = Some(url.to_owned());
+        self
+    }
+
+    /// Trust an additional PEM-encoded root certificate
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "rustls-tls", feature = "native-tls"))))]
+    pub fn root_certificate(&mut self, pem: &[u8]) -> &mut Self {
+        self.http.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Record responses to, or replay them from, the given cassette
+    pub fn cassette(&mut self, cassette: Cassette) -> &mut Self {
+        self.transport.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Reject responses larger than the given number of bytes
+    ///
+    /// Unlimited by default. Requests selecting large fields, such as embeddings together
+    /// with citations, can otherwise exhaust memory when many run concurrently.
+    pub fn max_response_size(&mut self, bytes: u64) -> &mut Self {
+        self.transport.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Send identical requests issued concurrently only once, sharing the response
+    ///
+    /// Clones of the built client share the requests in flight, so concurrent tasks asking
+    /// for the same resource, e.g. the same paper, cost a single request of the quota.
+    pub fn coalesce_requests(&mut self) -> &mut Self {
+        self.transport.coalescer = Some(Coalescer::default());
+        self
+    }
+
+    /// Request the given fields whenever a query does not select any
+    ///
+    /// Without this, Semantic Scholar only returns the paper id and title. Fields an endpoint
+    /// does not support are left out of its requests.
+    pub fn default_fields(&mut self, fields: &[PaperField]) -> &mut Self {
+        self.default_fields = Some(fields.to_vec());
+        self
+    }
+
+    /// Send the requests to a product to another base URL, e.g. a partner or proxy deployment
+    ///
+    /// The URL replaces the [public one](Product::default_url), version path included, e.g.
+    /// `https://s2.partner.example.org/graph/v1`.
+    pub fn base_url(&mut self, product: Product, url: &str) -> &mut Self {
+        self.base_urls
+            .insert(product, url.trim_end_matches('/').to_owned());
+        self
+    }
+
+    /// Build the client
+    pub fn build(&self) -> Result<SemanticScholar> {
+        for url in self.base_urls.values() {
+            reqwest::Url::parse(url)
+                .map_err(|e| Error::InvalidParameter(format!("invalid base URL {}: {}", url, e)))?;
+        }
+        Ok(SemanticScholar {
+            api_key: self.api_key.as_deref().map(Arc::from),
+            client: self.http.build(APP_USER_AGENT, DEFAULT_TIMEOUT)?,
+            transport: self.transport.clone(),
+            default_fields: self.default_fields.as_deref().map(Arc::from),
+            base_urls: Arc::new(self.base_urls.clone()),
+        })
+    }
+}
+
 /// Query trait
 pub trait Query {
     type Response;
@@ -68,3 +237,102 @@ pub trait Query {
         client: &SemanticScholar,
     ) -> impl std::future::Future<Output = Result<Self::Response>> + Send;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cheap_clone() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<SemanticScholar>();
+
+        let client = SemanticScholar::builder()
+            .api_key("TEST_TOKEN")
+            .default_fields(&[PaperField::Title])
+            .build()
+            .unwrap();
+        let clone = client.clone();
+        assert!(Arc::ptr_eq(
+            client.api_key.as_ref().unwrap(),
+            clone.api_key.as_ref().unwrap()
+        ));
+        assert!(Arc::ptr_eq(
+            client.default_fields.as_ref().unwrap(),
+            clone.default_fields.as_ref().unwrap()
+        ));
+        assert!(Arc::ptr_eq(&client.base_urls, &clone.base_urls));
+    }
+
+    #[test]
+    fn test_base_url() {
+        let client = SemanticScholar::builder()
+            .base_url(Product::Graph, "https://s2.example.org/graph/v1/")
+            .build()
+            .unwrap();
+        assert_eq!(client.graph_url(), "https://s2.example.org/graph/v1");
+        assert_eq!(
+            client.base_url(Product::Datasets),
+            Product::Datasets.default_url()
+        );
+        assert!(
+            SemanticScholar::builder()
+                .base_url(Product::Recommendations, "not a url")
+                .build()
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_default_fields() {
+        let dir = std::env::temp_dir().join(format!("default-fields-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        let graph = Product::Graph.default_url();
+        for (url, body) in [
+            (
+                // `tldr` is left out of the relevance search defaults
+                format!(
+                    "{}/paper/search?query=fish&fields=title,citations,year",
+                    graph
+                ),
+                r#"{"total": 0, "data": []}"#,
+            ),
+            (
+                // and `citations` too out of the bulk search ones
+                format!("{}/paper/search/bulk?query=fish&fields=title,year", graph),
+                r#"{"total": 0, "data": []}"#,
+            ),
+            (
+                format!("{}/paper/search?query=fish&fields=abstract", graph),
+                r#"{"total": 0, "data": []}"#,
+            ),
+        ] {
+            cassette.insert("GET", &url, b"", 200, body).unwrap();
+        }
+        let client = SemanticScholar::builder()
+            .cassette(cassette)
+            .default_fields(&[
+                PaperField::Title,
+                PaperField::Citations,
+                PaperField::Tldr,
+                PaperField::Year,
+            ])
+            .build()
+            .unwrap();
+
+        let search = crate::PaperSearchParamBuilder::new("fish").build().unwrap();
+        client.query(&search).await.unwrap();
+        let bulk = crate::PaperBulkSearchParamBuilder::default()
+            .query(&crate::QueryExpr::term("fish"))
+            .build()
+            .unwrap();
+        client.query(&bulk).await.unwrap();
+        // Fields of the query replace the defaults
+        let search = crate::PaperSearchParamBuilder::new("fish")
+            .field(PaperField::Abstract)
+            .build()
+            .unwrap();
+        client.query(&search).await.unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,245 @@
+//! Author disambiguation
+//!
+//! Many authors share a name. These helpers search Semantic Scholar authors by name and rank
+//! the candidates by name similarity, affiliation and overlap with a corpus of papers known
+//! to belong to the author.
+
+use crate::{
+    error::Result,
+    paper::PaperLike,
+    ss::{AuthorField, AuthorSearchParamBuilder, S2Author, SemanticScholar},
+};
+use std::collections::HashSet;
+
+/// Number of authors fetched from the author search
+const CANDIDATES: u16 = 20;
+
+/// Fields requested for the candidates
+const CANDIDATE_FIELDS: [AuthorField; 7] = [
+    AuthorField::Name,
+    AuthorField::Affiliations,
+    AuthorField::Homepage,
+    AuthorField::PaperCount,
+    AuthorField::CitationCount,
+    AuthorField::HIndex,
+    AuthorField::Papers,
+];
+
+/// Author with its disambiguation scores
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorCandidate {
+    pub author: S2Author,
+    /// Similarity between the searched name and the author's name, in `[0, 1]`
+    pub name_similarity: f64,
+    /// Number of seed papers written by the author
+    pub paper_overlap: usize,
+    /// Whether one of the author's affiliations matches the affiliation hint
+    pub affiliation_match: bool,
+    /// Combined score used for ordering
+    pub score: f64,
+}
+
+/// Similarity between two person names, in `[0, 1]`
+///
+/// Names with different last names score zero. Otherwise the given names are compared in
+/// order, an initial such as `W.` counting as a partial match of `Waleed`, so that
+/// `W. Ammar` is close to `Waleed Ammar` but not as close as the full name. Names written
+/// last name first, as in `Ammar, Waleed`, are read as `Waleed Ammar`.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let tokens = |name: &str| {
+        let (given, last) = match name.split_once(',') {
+            Some((last, given)) => (given, last),
+            None => (name, ""),
+        };
+        [given, last]
+            .into_iter()
+            .flat_map(|part| {
+                part.split(|c: char| c.is_whitespace() || matches!(c, '.' | '-' | ','))
+            })
+            .filter(|token| !token.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (tokens(a), tokens(b));
+    let (Some((last_a, given_a)), Some((last_b, given_b))) = (a.split_last(), b.split_last())
+    else {
+        return 0.0;
+    };
+    if last_a != last_b {
+        return 0.0;
+    }
+
+    let matched = given_a
+        .iter()
+        .zip(given_b)
+        .map(|(x, y)| {
+            if x == y {
+                1.0
+            } else if (x.chars().count() == 1 && y.starts_with(x.as_str()))
+                || (y.chars().count() == 1 && x.starts_with(y.as_str()))
+            {
+                0.75
+            } else {
+                0.0
+            }
+        })
+        .sum::<f64>();
+    (1.0 + matched) / (1.0 + given_a.len().max(given_b.len()) as f64)
+}
+
+/// Rank author candidates for a name
+///
+/// The combined score is `name_similarity + 2 * overlap + 0.5 * affiliation`, where the
+/// overlap is the share of `seed` papers listed among the author's papers and the
+/// affiliation term is one when an affiliation contains the hint or the other way round,
+/// ignoring case. Authors whose name does not match at all are dropped.
+///
+/// # Arguments
+///
+/// * `authors` - Candidates from the author search, requested with their papers and affiliations
+/// * `name` - Name of the author to find
+/// * `affiliation` - Optional hint such as `Allen Institute`
+/// * `seed` - Papers known to be written by the author, may be empty
+pub fn rank_authors<P: PaperLike>(
+    authors: &[S2Author],
+    name: &str,
+    affiliation: Option<&str>,
+    seed: &[P],
+) -> Vec<AuthorCandidate> {
+    let seed_ids = seed.iter().map(PaperLike::id).collect::<HashSet<_>>();
+    let affiliation = affiliation
+        .map(|hint| hint.trim().to_lowercase())
+        .filter(|hint| !hint.is_empty());
+
+    let mut ranked = authors
+        .iter()
+        .filter_map(|author| {
+            let name_similarity = name_similarity(name, author.name.as_deref()?);
+            if name_similarity == 0.0 {
+                return None;
+            }
+            let paper_overlap = author
+                .papers()
+                .iter()
+                .filter(|paper| seed_ids.contains(paper.paper_id.as_str()))
+                .count();
+            let affiliation_match = affiliation.as_ref().is_some_and(|hint| {
+                author.affiliations().iter().any(|affiliation| {
+                    let affiliation = affiliation.to_lowercase();
+                    affiliation.contains(hint.as_str()) || hint.contains(affiliation.as_str())
+                })
+            });
+            let overlap = if seed_ids.is_empty() {
+                0.0
+            } else {
+                paper_overlap as f64 / seed_ids.len() as f64
+            };
+            let score = name_similarity + 2.0 * overlap + if affiliation_match { 0.5 } else { 0.0 };
+            Some(AuthorCandidate {
+                author: author.clone(),
+                name_similarity,
+                paper_overlap,
+                affiliation_match,
+                score,
+            })
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.author.paper_count.cmp(&a.author.paper_count))
+    });
+    ranked
+}
+
+impl SemanticScholar {
+    /// Search authors by name and rank them with [`rank_authors`]
+    ///
+    /// The candidates come with their affiliations, homepage, metrics and papers.
+    pub async fn author_candidates<P: PaperLike>(
+        &self,
+        name: &str,
+        affiliation: Option<&str>,
+        seed: &[P],
+    ) -> Result<Vec<AuthorCandidate>> {
+        let mut builder = AuthorSearchParamBuilder::new(name);
+        for field in CANDIDATE_FIELDS {
+            builder.field(field);
+        }
+        let param = builder.limit(CANDIDATES).build()?;
+        let response = self.query(&param).await?;
+        Ok(rank_authors(response.data(), name, affiliation, seed))
+    }
+
+    /// Semantic Scholar ID of the author best matching a name, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the author to find
+    /// * `affiliation` - Optional hint such as `Allen Institute`
+    /// * `seed` - Papers known to be written by the author, may be empty
+    pub async fn disambiguate_author<P: PaperLike>(
+        &self,
+        name: &str,
+        affiliation: Option<&str>,
+        seed: &[P],
+    ) -> Result<Option<String>> {
+        let candidates = self.author_candidates(name, affiliation, seed).await?;
+        Ok(candidates
+            .into_iter()
+            .find_map(|candidate| candidate.author.author_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2NestedPaper;
+
+    fn author(id: &str, name: &str, affiliations: &[&str], papers: &[&str]) -> S2Author {
+        serde_json::from_value(serde_json::json!({
+            "authorId": id,
+            "name": name,
+            "affiliations": affiliations,
+            "papers": papers.iter().map(|id| serde_json::json!({ "paperId": id })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_name_similarity() {
+        assert_eq!(name_similarity("Waleed Ammar", "waleed  ammar"), 1.0);
+        assert_eq!(name_similarity("Waleed Ammar", "W. Ammar"), 0.875);
+        assert_eq!(name_similarity("Waleed Ammar", "Ammar"), 0.5);
+        assert_eq!(name_similarity("Waleed Ammar", "Waleed Smith"), 0.0);
+        assert_eq!(name_similarity("", "Ammar"), 0.0);
+        assert_eq!(name_similarity("Ammar, Waleed", "Waleed Ammar"), 1.0);
+        assert_eq!(name_similarity("Waleed Ammar", "Ammar, W."), 0.875);
+        assert_eq!(name_similarity("Ammar, Waleed", "Smith, Waleed"), 0.0);
+    }
+
+    #[test]
+    fn test_rank_authors() {
+        let authors = vec![
+            author("1", "Waleed Ammar", &[], &["x"]),
+            author("2", "W. Ammar", &["Allen Institute for AI"], &["a", "b"]),
+            author("3", "Someone Else", &[], &["a"]),
+        ];
+        let seed = vec![S2NestedPaper::new("a"), S2NestedPaper::new("b")];
+
+        let ranked = rank_authors(&authors, "Waleed Ammar", None, &seed);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].author.author_id.as_deref(), Some("2"));
+        assert_eq!(ranked[0].paper_overlap, 2);
+
+        let ranked = rank_authors(
+            &authors,
+            "Waleed Ammar",
+            Some("allen institute"),
+            &[] as &[S2NestedPaper],
+        );
+        assert_eq!(ranked[0].author.author_id.as_deref(), Some("2"));
+        assert!(ranked[0].affiliation_match);
+        assert!(!ranked[1].affiliation_match);
+    }
+}
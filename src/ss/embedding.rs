@@ -0,0 +1,433 @@
+//! Embedding utilities
+//!
+//! Helpers working on the SPECTER vectors returned with [`PaperField::Embedding`](crate::PaperField::Embedding).
+
+use crate::{
+    error::{Error, Result},
+    ss::{Embedding, S2NestedPaper},
+};
+use std::collections::HashMap;
+
+/// Cosine similarity between two vectors
+///
+/// Returns `None` if the vectors have different lengths or one of them has zero norm.
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let dot = a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>();
+    let norm_a = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        None
+    } else {
+        Some(dot / (norm_a * norm_b))
+    }
+}
+
+/// Paper with its reranking scores
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedPaper {
+    pub paper: S2NestedPaper,
+    /// Relevance derived from the position in the API results, in `[0, 1]`
+    pub relevance: f64,
+    /// Cosine similarity to the seed embedding, if the paper has an embedding
+    pub similarity: Option<f64>,
+    /// Combined score used for ordering
+    pub score: f64,
+}
+
+/// Re-order relevance search results by semantic similarity to a seed paper
+///
+/// The combined score is `(1 - weight) * relevance + weight * similarity`, where the
+/// relevance decreases linearly with the position in `papers` and the cosine similarity
+/// is rescaled to `[0, 1]`. Papers without an embedding get a similarity of zero.
+///
+/// # Arguments
+///
+/// * `papers` - Results in API relevance order, requested with the embedding field
+/// * `seed` - Embedding of the seed paper
+/// * `weight` - Share of the semantic similarity in the combined score, in `[0, 1]`
+pub fn rerank(papers: &[S2NestedPaper], seed: &Embedding, weight: f64) -> Result<Vec<RankedPaper>> {
+    if !(0.0..=1.0).contains(&weight) {
+        return Err(Error::InvalidParameter(
+            "weight must be between 0 and 1".to_owned(),
+        ));
+    }
+    let Some(ref seed) = seed.vector else {
+        return Err(Error::InvalidParameter(
+            "seed embedding has no vector".to_owned(),
+        ));
+    };
+
+    let total = papers.len() as f64;
+    let mut ranked = papers
+        .iter()
+        .enumerate()
+        .map(|(rank, paper)| {
+            let relevance = 1.0 - rank as f64 / total;
+            let similarity = paper
+                .embedding
+                .as_ref()
+                .and_then(|embedding| embedding.vector.as_deref())
+                .and_then(|vector| cosine_similarity(seed, vector));
+            let semantic = similarity.map_or(0.0, |s| (s + 1.0) / 2.0);
+            RankedPaper {
+                paper: paper.clone(),
+                relevance,
+                similarity,
+                score: (1.0 - weight) * relevance + weight * semantic,
+            }
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(ranked)
+}
+
+/// Squared Euclidean distance between two vectors of the same length
+fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Group vectors into `k` clusters with Lloyd's k-means
+///
+/// Centroids are seeded deterministically with farthest-point initialization starting from
+/// the first vector, so repeated runs on the same input return the same labels.
+/// Returns the cluster label of every vector.
+pub fn kmeans(vectors: &[Vec<f64>], k: usize, max_iterations: usize) -> Result<Vec<usize>> {
+    if k == 0 || k > vectors.len() {
+        return Err(Error::InvalidParameter(format!(
+            "k must be between 1 and the number of vectors ({})",
+            vectors.len()
+        )));
+    }
+    let dimension = vectors[0].len();
+    if vectors.iter().any(|vector| vector.len() != dimension) {
+        return Err(Error::InvalidParameter(
+            "all vectors must have the same dimension".to_owned(),
+        ));
+    }
+
+    let mut centroids = vec![vectors[0].clone()];
+    while centroids.len() < k {
+        let farthest = vectors
+            .iter()
+            .max_by(|a, b| {
+                let da = nearest(&centroids, a).1;
+                let db = nearest(&centroids, b).1;
+                da.total_cmp(&db)
+            })
+            .unwrap();
+        centroids.push(farthest.clone());
+    }
+
+    let mut labels = vec![usize::MAX; vectors.len()];
+    for _ in 0..max_iterations.max(1) {
+        let assignments = vectors
+            .iter()
+            .map(|vector| nearest(&centroids, vector).0)
+            .collect::<Vec<_>>();
+        if assignments == labels {
+            break;
+        }
+        labels = assignments;
+
+        for (label, centroid) in centroids.iter_mut().enumerate() {
+            let members = vectors
+                .iter()
+                .zip(&labels)
+                .filter(|(_, l)| **l == label)
+                .map(|(vector, _)| vector)
+                .collect::<Vec<_>>();
+            if members.is_empty() {
+                continue;
+            }
+            for (d, value) in centroid.iter_mut().enumerate() {
+                *value = members.iter().map(|vector| vector[d]).sum::<f64>() / members.len() as f64;
+            }
+        }
+    }
+    Ok(labels)
+}
+
+/// Index of and squared distance to the nearest centroid
+fn nearest(centroids: &[Vec<f64>], vector: &[f64]) -> (usize, f64) {
+    centroids
+        .iter()
+        .map(|centroid| squared_distance(centroid, vector))
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+/// Mean silhouette coefficient of a clustering, in `[-1, 1]`
+///
+/// Higher values mean tighter, better separated clusters. Single-cluster labelings and
+/// singleton clusters contribute a coefficient of zero.
+pub fn silhouette_score(vectors: &[Vec<f64>], labels: &[usize]) -> f64 {
+    if vectors.is_empty() || vectors.len() != labels.len() {
+        return 0.0;
+    }
+    let clusters = labels.iter().copied().max().unwrap_or(0) + 1;
+    let total = vectors
+        .iter()
+        .zip(labels)
+        .map(|(vector, &label)| {
+            let mut sums = vec![0.0; clusters];
+            let mut counts = vec![0usize; clusters];
+            for (other, &other_label) in vectors.iter().zip(labels) {
+                sums[other_label] += squared_distance(vector, other).sqrt();
+                counts[other_label] += 1;
+            }
+            if counts[label] <= 1 {
+                return 0.0;
+            }
+            let a = sums[label] / (counts[label] - 1) as f64;
+            let b = (0..clusters)
+                .filter(|&c| c != label && counts[c] > 0)
+                .map(|c| sums[c] / counts[c] as f64)
+                .min_by(f64::total_cmp);
+            match b {
+                Some(b) if a.max(b) > 0.0 => (b - a) / a.max(b),
+                _ => 0.0,
+            }
+        })
+        .sum::<f64>();
+    total / vectors.len() as f64
+}
+
+/// Cluster of papers sharing a k-means label
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperCluster {
+    pub label: usize,
+    pub papers: Vec<S2NestedPaper>,
+}
+
+/// Result of clustering papers by their embeddings
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperClustering {
+    /// Clusters ordered by label
+    pub clusters: Vec<PaperCluster>,
+    /// Papers without an embedding vector
+    pub unassigned: Vec<S2NestedPaper>,
+    /// Mean silhouette coefficient of the clustering
+    pub silhouette: f64,
+}
+
+/// Group papers into `k` topic clusters by their SPECTER embeddings
+///
+/// Papers must have been requested with the embedding field; papers without a vector are
+/// returned in [`PaperClustering::unassigned`].
+pub fn cluster_papers(papers: &[S2NestedPaper], k: usize) -> Result<PaperClustering> {
+    let (embedded, unassigned): (Vec<_>, Vec<_>) = papers.iter().partition(|paper| {
+        paper
+            .embedding
+            .as_ref()
+            .is_some_and(|embedding| embedding.vector.is_some())
+    });
+    let vectors = embedded
+        .iter()
+        .filter_map(|paper| paper.embedding.as_ref()?.vector.clone())
+        .collect::<Vec<_>>();
+
+    let labels = kmeans(&vectors, k, 100)?;
+    let silhouette = silhouette_score(&vectors, &labels);
+    let mut clusters = (0..k)
+        .map(|label| PaperCluster {
+            label,
+            papers: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    for (paper, label) in embedded.into_iter().zip(labels) {
+        clusters[label].papers.push(paper.clone());
+    }
+
+    Ok(PaperClustering {
+        clusters,
+        unassigned: unassigned.into_iter().cloned().collect(),
+        silhouette,
+    })
+}
+
+/// Project vectors onto their first two principal components
+///
+/// The components are found by power iteration with deflation from a fixed start vector,
+/// so the projection is deterministic. The coordinates have the same `[x, y]` shape as
+/// [`Paper::pos`](crate::Paper::pos) in Connected Papers graphs.
+pub fn project_2d(vectors: &[Vec<f64>]) -> Result<Vec<[f64; 2]>> {
+    let Some(first) = vectors.first() else {
+        return Ok(Vec::new());
+    };
+    let dimension = first.len();
+    if vectors.iter().any(|vector| vector.len() != dimension) {
+        return Err(Error::InvalidParameter(
+            "all vectors must have the same dimension".to_owned(),
+        ));
+    }
+
+    let n = vectors.len() as f64;
+    let mean = (0..dimension)
+        .map(|d| vectors.iter().map(|vector| vector[d]).sum::<f64>() / n)
+        .collect::<Vec<_>>();
+    let centered = vectors
+        .iter()
+        .map(|vector| vector.iter().zip(&mean).map(|(x, m)| x - m).collect())
+        .collect::<Vec<Vec<f64>>>();
+
+    let first_component = principal_component(&centered, &[]);
+    let second_component = principal_component(&centered, &[first_component.as_slice()]);
+    Ok(centered
+        .iter()
+        .map(|vector| {
+            [
+                dot(vector, &first_component),
+                dot(vector, &second_component),
+            ]
+        })
+        .collect())
+}
+
+/// Project papers with embeddings to 2D coordinates keyed by paper id
+///
+/// Papers without an embedding vector are skipped.
+pub fn project_papers(papers: &[S2NestedPaper]) -> Result<HashMap<String, [f64; 2]>> {
+    let (ids, vectors): (Vec<_>, Vec<_>) = papers
+        .iter()
+        .filter_map(|paper| {
+            let vector = paper.embedding.as_ref()?.vector.clone()?;
+            Some((paper.paper_id.clone(), vector))
+        })
+        .unzip();
+    Ok(ids.into_iter().zip(project_2d(&vectors)?).collect())
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Dominant direction of the rows orthogonal to the given components
+fn principal_component(rows: &[Vec<f64>], orthogonal_to: &[&[f64]]) -> Vec<f64> {
+    let dimension = rows.first().map_or(0, Vec::len);
+    let orthogonalize = |vector: &mut Vec<f64>| {
+        for component in orthogonal_to {
+            let projection = dot(vector, component);
+            for (value, c) in vector.iter_mut().zip(component.iter()) {
+                *value -= projection * c;
+            }
+        }
+        let norm = dot(vector, vector).sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|value| *value /= norm);
+        }
+    };
+
+    let mut vector = (0..dimension)
+        .map(|d| 1.0 + (d as f64 * 0.618).fract())
+        .collect::<Vec<_>>();
+    orthogonalize(&mut vector);
+    for _ in 0..100 {
+        let mut next = vec![0.0; dimension];
+        for row in rows {
+            let weight = dot(row, &vector);
+            for (value, x) in next.iter_mut().zip(row) {
+                *value += weight * x;
+            }
+        }
+        orthogonalize(&mut next);
+        let delta = squared_distance(&next, &vector);
+        vector = next;
+        if delta < 1e-18 {
+            break;
+        }
+    }
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper(id: &str, vector: Option<Vec<f64>>) -> S2NestedPaper {
+        serde_json::from_value(serde_json::json!({
+            "paperId": id,
+            "embedding": vector.map(|v| serde_json::json!({ "model": "specter_v2", "vector": v })),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]), Some(1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), Some(0.0));
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_rerank() {
+        let papers = vec![
+            paper("far", Some(vec![0.0, 1.0])),
+            paper("none", None),
+            paper("near", Some(vec![1.0, 0.1])),
+        ];
+        let seed = Embedding {
+            model: None,
+            vector: Some(vec![1.0, 0.0]),
+        };
+        let ranked = rerank(&papers, &seed, 1.0).unwrap();
+        assert_eq!(ranked[0].paper.paper_id, "near");
+        assert_eq!(ranked[2].paper.paper_id, "none");
+
+        let ranked = rerank(&papers, &seed, 0.0).unwrap();
+        assert_eq!(ranked[0].paper.paper_id, "far");
+        assert!(rerank(&papers, &seed, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_kmeans_and_silhouette() {
+        let vectors = vec![
+            vec![0.0, 0.0],
+            vec![0.1, 0.0],
+            vec![10.0, 10.0],
+            vec![10.1, 10.0],
+        ];
+        let labels = kmeans(&vectors, 2, 10).unwrap();
+        assert_eq!(labels[0], labels[1]);
+        assert_eq!(labels[2], labels[3]);
+        assert_ne!(labels[0], labels[2]);
+        assert!(silhouette_score(&vectors, &labels) > 0.9);
+        assert!(kmeans(&vectors, 5, 10).is_err());
+    }
+
+    #[test]
+    fn test_cluster_papers() {
+        let papers = vec![
+            paper("a", Some(vec![0.0, 0.0])),
+            paper("b", Some(vec![10.0, 10.0])),
+            paper("c", None),
+            paper("d", Some(vec![0.2, 0.1])),
+        ];
+        let clustering = cluster_papers(&papers, 2).unwrap();
+        assert_eq!(clustering.clusters.len(), 2);
+        assert_eq!(clustering.unassigned.len(), 1);
+        assert_eq!(clustering.clusters[0].papers.len(), 2);
+        assert_eq!(clustering.clusters[1].papers[0].paper_id, "b");
+    }
+
+    #[test]
+    fn test_project_2d() {
+        let vectors = vec![
+            vec![0.0, 0.0, 0.0],
+            vec![1.0, 0.0, 0.0],
+            vec![2.0, 0.1, 0.0],
+            vec![3.0, 0.0, 0.0],
+        ];
+        let positions = project_2d(&vectors).unwrap();
+        assert_eq!(positions.len(), 4);
+        let spread_x = (positions[3][0] - positions[0][0]).abs();
+        let spread_y = positions.iter().map(|pos| pos[1].abs()).fold(0.0, f64::max);
+        assert!((spread_x - 3.0).abs() < 1e-3);
+        assert!(spread_y < 0.1);
+        assert!(project_2d(&[vec![1.0], vec![1.0, 2.0]]).is_err());
+    }
+}
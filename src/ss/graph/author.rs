@@ -0,0 +1,184 @@
+//! Author search
+//!
+//! `Get /author/search`
+//!
+//! `/author/search?query={name}`
+//!
+//! ## Limitations
+//! - Can only return up to 1,000 results.
+//! - Can only return up to 10 MB of data at a time.
+//! - No special query syntax is supported; hyphenated names yield no matches.
+
+use crate::{
+    error::{Error, Result},
+    ss::{
+        AuthorField, S2Author,
+        client::{Query, SemanticScholar},
+        merge_author_fields, non_empty,
+    },
+    utils::{Method, build_request, encode_query_value, list_accessors, send},
+};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// Query parameters for the author search
+#[derive(Debug, Clone)]
+pub struct AuthorSearchParam {
+    /// A plain-text search query string, the name of the author.
+    query: String,
+    /// A comma-separated list of the fields to be returned.
+    fields: Option<Vec<AuthorField>>,
+    /// Used for pagination. When returning a list of results, start with the element at this position in the list (default: 0).
+    offset: Option<u32>,
+    /// The maximum number of results to return (default: 100).
+    ///
+    /// Must be <= 1000.
+    limit: Option<u16>,
+}
+
+impl AuthorSearchParam {
+    pub(crate) fn query_string(&self) -> String {
+        let mut query_string = format!("query={}", encode_query_value(&self.query));
+        if let Some(fields) = non_empty(&self.fields) {
+            let fields_string = merge_author_fields(fields);
+            query_string.push_str(&format!("&fields={}", fields_string));
+        }
+
+        if let Some(offset) = self.offset {
+            query_string.push_str(&format!("&offset={}", offset));
+        }
+
+        if let Some(limit) = self.limit {
+            query_string.push_str(&format!("&limit={}", limit));
+        }
+
+        query_string
+    }
+}
+
+impl Query for AuthorSearchParam {
+    type Response = AuthorSearchResponse;
+
+    async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
+        let url = format!(
+            "{}/author/search?{}",
+            client.graph_url(),
+            self.query_string()
+        );
+        let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
+
+        let resp = send(req_builder, client.transport()).await?;
+        match resp.status {
+            StatusCode::OK => resp.json(),
+            _ => Err(resp.into_error()),
+        }
+    }
+}
+
+/// Builder for the author search parameters
+#[derive(Debug, Clone, Default)]
+pub struct AuthorSearchParamBuilder {
+    query: String,
+    fields: Option<Vec<AuthorField>>,
+    offset: Option<u32>,
+    limit: Option<u16>,
+}
+
+impl AuthorSearchParamBuilder {
+    /// Create a new builder with the given author name
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Add a field to the author search parameters
+    pub fn field(&mut self, field: AuthorField) -> &mut Self {
+        if let Some(ref mut fields) = self.fields {
+            fields.push(field);
+        } else {
+            self.fields = Some(vec![field]);
+        }
+        self
+    }
+
+    /// Used for pagination. When returning a list of results, start with the element at this position in the list (default: 0).
+    pub fn offset(&mut self, offset: u32) -> &mut Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The maximum number of results to return (default: 100), at most 1000.
+    pub fn limit(&mut self, limit: u16) -> &mut Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Build the author search parameters
+    pub fn build(&self) -> Result<AuthorSearchParam> {
+        if self.query.trim().is_empty() {
+            return Err(Error::InvalidParameter("query must be set".to_owned()));
+        }
+        if self.limit.is_some_and(|limit| limit > 1000) {
+            return Err(Error::InvalidParameter(
+                "limit must be at most 1000".to_owned(),
+            ));
+        }
+
+        Ok(AuthorSearchParam {
+            query: self.query.trim().to_owned(),
+            fields: self.fields.clone(),
+            offset: self.offset,
+            limit: self.limit,
+        })
+    }
+}
+
+/// Response for the author search
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AuthorSearchResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<S2Author>>,
+}
+
+list_accessors!(AuthorSearchResponse { data: S2Author });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_author_search_param_builder() {
+        let mut builder = AuthorSearchParamBuilder::new(" Waleed Ammar ");
+        builder.field(AuthorField::Name).limit(10);
+        let param = builder.build().unwrap();
+        assert_eq!(
+            param.query_string(),
+            "query=Waleed%20Ammar&fields=name&limit=10"
+        );
+
+        builder.limit(1001);
+        assert!(builder.build().is_err());
+        assert!(AuthorSearchParamBuilder::new(" ").build().is_err());
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_author_search() {
+        let client = SemanticScholar::default();
+        let param = AuthorSearchParamBuilder::new("Waleed Ammar")
+            .field(AuthorField::Name)
+            .field(AuthorField::PaperCount)
+            .build()
+            .unwrap();
+        let response = client.query(&param).await.unwrap();
+        println!("{:?}", response);
+    }
+}
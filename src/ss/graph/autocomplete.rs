@@ -5,17 +5,21 @@
 //!
 //! `GET /paper/autocomplete`
 //!
+//! The API only reads the first 100 characters of the query. Longer queries are truncated
+//! before sending, at a grapheme cluster boundary so that no accented letter or emoji is cut
+//! in half, and the result reports the query actually searched.
 
 use crate::{
-    error::{Error, Result},
-    ss::{
-        client::{Query, SemanticScholar},
-        graph::BASE_URL,
-    },
-    utils::{Method, build_request},
+    error::Result,
+    ss::client::{Query, SemanticScholar},
+    utils::{Method, build_request, send},
 };
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Maximum number of characters of an autocomplete query the API reads
+pub const AUTOCOMPLETE_MAX_CHARS: usize = 100;
 
 /// Parameters for the autocomplete query
 #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -31,6 +35,29 @@ impl PaperAutocompleteParam {
             query: query.to_string(),
         }
     }
+
+    /// The query sent to the API, truncated to [`AUTOCOMPLETE_MAX_CHARS`] characters
+    pub fn searched_query(&self) -> &str {
+        truncate_graphemes(&self.query, AUTOCOMPLETE_MAX_CHARS)
+    }
+}
+
+/// Result of an autocomplete query
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutocompleteResult {
+    /// The query actually searched, see [`PaperAutocompleteParam::searched_query`]
+    pub query: String,
+    /// Set when the query was truncated
+    pub warning: Option<String>,
+    /// Matching papers
+    pub matches: Vec<AutocompletePaper>,
+}
+
+impl AutocompleteResult {
+    /// Whether the query was truncated before searching
+    pub fn is_truncated(&self) -> bool {
+        self.warning.is_some()
+    }
 }
 
 /// Response for autocomplete query
@@ -71,23 +98,61 @@ impl AutocompletePaper {
 }
 
 impl Query for PaperAutocompleteParam {
-    type Response = Vec<AutocompletePaper>;
+    type Response = AutocompleteResult;
 
     async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
-        let url = format!("{}/paper/autocomplete", BASE_URL);
+        let url = format!("{}/paper/autocomplete", client.graph_url());
+        let query = self.searched_query();
         let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
-        let res = req_builder.query(self).send().await?;
-        match res.status() {
-            StatusCode::OK => Ok(res.json::<PaperAutocompleteResponse>().await?.matches),
-            _ => Err(Error::RequestFailed(res.text().await?)),
+        let res = send(req_builder.query(&[("query", query)]), client.transport()).await?;
+        match res.status {
+            StatusCode::OK => Ok(AutocompleteResult {
+                query: query.to_owned(),
+                warning: (query.len() < self.query.len()).then(|| {
+                    format!(
+                        "query truncated to its first {} characters",
+                        query.chars().count()
+                    )
+                }),
+                matches: res.json::<PaperAutocompleteResponse>()?.matches,
+            }),
+            _ => Err(res.into_error()),
+        }
+    }
+}
+
+/// Longest prefix of whole grapheme clusters with at most `max_chars` characters
+fn truncate_graphemes(text: &str, max_chars: usize) -> &str {
+    let mut chars = 0;
+    let mut end = 0;
+    for (start, grapheme) in text.grapheme_indices(true) {
+        chars += grapheme.chars().count();
+        if chars > max_chars {
+            break;
         }
+        end = start + grapheme.len();
     }
+    &text[..end]
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_truncate_graphemes() {
+        assert_eq!(truncate_graphemes("graph", 100), "graph");
+        assert_eq!(truncate_graphemes(&"a".repeat(120), 100).len(), 100);
+        // "e" followed by a combining acute accent counts two characters
+        assert_eq!(truncate_graphemes("cafe\u{301} au lait", 4), "caf");
+        assert_eq!(truncate_graphemes("cafe\u{301} au lait", 5), "cafe\u{301}");
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(truncate_graphemes(&format!("ab{}", family), 6), "ab");
+
+        let param = PaperAutocompleteParam::new(&"\u{e9}".repeat(150));
+        assert_eq!(param.searched_query().chars().count(), 100);
+    }
+
     #[ignore]
     #[tokio::test]
     async fn test_autocomplete() {
@@ -96,6 +161,7 @@ mod tests {
             query: "semantic".to_string(),
         };
         let res = client.query(&query).await.unwrap();
-        println!("{:?}", res);
+        assert!(!res.is_truncated());
+        println!("{:?}", res.matches);
     }
 }
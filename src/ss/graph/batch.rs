@@ -10,12 +10,12 @@
 use crate::{
     error::{Error, Result},
     ss::{
-        PaperField, PaperId, S2NestedPaper,
+        FieldSpec, PaperField, PaperId, S2NestedPaper,
         client::{Query, SemanticScholar},
-        graph::BASE_URL,
-        merge_paper_fields,
+        graph::Endpoint,
+        merge_selected_fields, non_empty,
     },
-    utils::{Method, build_request},
+    utils::{Method, build_request, send},
 };
 use reqwest::StatusCode;
 use serde::Serialize;
@@ -25,6 +25,8 @@ use serde::Serialize;
 pub struct PaperBatchParam {
     pub ids: Vec<PaperId>,
     pub fields: Option<Vec<PaperField>>,
+    /// Fields restricted to some of their subfields
+    pub nested: Vec<FieldSpec>,
 }
 
 /// Builder for the paper batch query parameters
@@ -32,6 +34,7 @@ pub struct PaperBatchParam {
 pub struct PaperBatchParamBuilder {
     ids: Vec<PaperId>,
     fields: Option<Vec<PaperField>>,
+    nested: Vec<FieldSpec>,
 }
 
 impl PaperBatchParamBuilder {
@@ -51,14 +54,30 @@ impl PaperBatchParamBuilder {
         self
     }
 
+    /// Add fields with their subfields, e.g. built with [`fields!`](crate::fields)
+    pub fn select(&mut self, specs: impl IntoIterator<Item = FieldSpec>) -> &mut Self {
+        for spec in specs {
+            self.field(spec.field);
+            if !spec.subfields.is_empty() {
+                self.nested.push(spec);
+            }
+        }
+        self
+    }
+
     /// Build the paper batch query parameters
     pub fn build(&self) -> Result<PaperBatchParam> {
+        Endpoint::PaperBatch.check_fields(self.fields.as_deref())?;
+        for id in &self.ids {
+            id.validate()?;
+        }
         if self.ids.is_empty() {
             Err(Error::InvalidParameter("ids is empty".to_string()))
         } else {
             Ok(PaperBatchParam {
                 ids: self.ids.clone(),
                 fields: self.fields.clone(),
+                nested: self.nested.clone(),
             })
         }
     }
@@ -71,30 +90,33 @@ struct PaperIds {
 }
 
 impl Query for PaperBatchParam {
-    type Response = Vec<S2NestedPaper>;
+    /// Papers in the order of the ids, `None` for the ids Semantic Scholar does not know
+    type Response = Vec<Option<S2NestedPaper>>;
 
     async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
         let paper_ids = PaperIds {
             ids: self.ids.clone(),
         };
-        let url = if let Some(ref fields) = self.fields
-            && !fields.is_empty()
-        {
+        let fields = match self.fields {
+            Some(ref fields) => Some(fields.clone()),
+            None => Endpoint::PaperBatch.default_fields(client.default_fields()),
+        };
+        let url = if let Some(fields) = non_empty(&fields) {
             format!(
                 "{}/paper/batch?fields={}",
-                BASE_URL,
-                merge_paper_fields(fields)
+                client.graph_url(),
+                merge_selected_fields(fields, &self.nested)
             )
         } else {
-            format!("{}/paper/batch", BASE_URL)
+            format!("{}/paper/batch", client.graph_url())
         };
 
         let req_builder = build_request(client.client(), Method::Post, &url, client.api_key());
 
-        let resp = req_builder.json(&paper_ids).send().await?;
-        match resp.status() {
-            StatusCode::OK => Ok(resp.json().await?),
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+        let resp = send(req_builder.json(&paper_ids), client.transport()).await?;
+        match resp.status {
+            StatusCode::OK => resp.json(),
+            _ => Err(resp.into_error()),
         }
     }
 }
@@ -124,6 +146,7 @@ mod tests {
         let param = PaperBatchParam {
             ids,
             fields: Some(fields),
+            nested: Vec::new(),
         };
 
         let client = SemanticScholar::default();
@@ -0,0 +1,333 @@
+//! Citations of a paper
+//!
+//! `Get /paper/{paperId}/citations`
+//!
+//! `/paper/{paperId}/citations?fields={fields}`
+//!
+//! Fetch details about the papers that cite this paper (i.e. papers in whose bibliography this paper appears).
+//!
+//! ## Limitations
+//! - Can only return up to 1,000 citations per call, use `offset` to page through the rest.
+//! - Can only return up to 10 MB of data at a time.
+//! - Intent and influence filters are applied to each page once fetched, so filtered pages
+//!   may hold fewer citations than `limit`.
+//! - Citing papers missing from the Semantic Scholar corpus come without an id, and are
+//!   returned as citations without a citing paper.
+
+use crate::{
+    error::{Error, Result},
+    ss::{
+        PaperField, PaperId, S2Paper,
+        client::{Query, SemanticScholar},
+        graph::Endpoint,
+        merge_paper_fields, non_empty,
+    },
+    utils::{Method, build_request, list_accessors, send},
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer, de};
+
+/// Query parameters for the citations of a paper
+#[derive(Debug, Clone)]
+pub struct PaperCitationsParam {
+    /// The paper whose citations are fetched.
+    id: PaperId,
+    /// A comma-separated list of the fields of the citing papers to be returned.
+    fields: Option<Vec<PaperField>>,
+    /// Also return the citation contexts, intents and whether the citation is influential.
+    context: bool,
+    /// Keep only the influential citations.
+    influential_only: bool,
+    /// Keep only the citations with one of these intents, all of them if empty.
+    intents: Vec<CitationIntent>,
+    /// Used for pagination. When returning a list of results, start with the element at this position in the list (default: 0).
+    pub(crate) offset: Option<u32>,
+    /// The maximum number of results to return (default: 100).
+    ///
+    /// Must be <= 1000.
+    limit: Option<u16>,
+}
+
+impl PaperCitationsParam {
+    pub(crate) fn query_string(&self) -> String {
+        let mut fields = non_empty(&self.fields)
+            .map(merge_paper_fields)
+            .into_iter()
+            .collect::<Vec<_>>();
+        if self.context || self.influential_only || !self.intents.is_empty() {
+            fields.push("contexts,intents,isInfluential".to_owned());
+        }
+
+        let mut params = Vec::new();
+        if !fields.is_empty() {
+            params.push(format!("fields={}", fields.join(",")));
+        }
+
+        if let Some(offset) = self.offset {
+            params.push(format!("offset={}", offset));
+        }
+
+        if let Some(limit) = self.limit {
+            params.push(format!("limit={}", limit));
+        }
+
+        if params.is_empty() {
+            format!("{}/citations", self.id)
+        } else {
+            format!("{}/citations?{}", self.id, params.join("&"))
+        }
+    }
+
+    /// Whether a citation passes the influence and intent filters
+    fn keeps(&self, citation: &S2Citation) -> bool {
+        (!self.influential_only || citation.is_influential == Some(true))
+            && (self.intents.is_empty()
+                || citation
+                    .intents()
+                    .iter()
+                    .any(|intent| self.intents.contains(intent)))
+    }
+}
+
+impl Query for PaperCitationsParam {
+    type Response = PaperCitationsResponse;
+
+    async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
+        let mut param = self.clone();
+        if param.fields.is_none() {
+            param.fields = Endpoint::PaperCitations.default_fields(client.default_fields());
+        }
+        let url = format!("{}/paper/{}", client.graph_url(), param.query_string());
+        let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
+
+        let resp = send(req_builder, client.transport()).await?;
+        match resp.status {
+            StatusCode::OK => {
+                let mut response: PaperCitationsResponse = resp.json()?;
+                if let Some(ref mut data) = response.data {
+                    data.retain(|citation| param.keeps(citation));
+                }
+                Ok(response)
+            }
+            _ => Err(resp.into_error()),
+        }
+    }
+}
+
+/// Builder for the citations parameters
+#[derive(Debug, Clone)]
+pub struct PaperCitationsParamBuilder {
+    id: PaperId,
+    fields: Option<Vec<PaperField>>,
+    context: bool,
+    influential_only: bool,
+    intents: Vec<CitationIntent>,
+    offset: Option<u32>,
+    limit: Option<u16>,
+}
+
+impl PaperCitationsParamBuilder {
+    /// Create a new builder for the citations of the given paper
+    pub fn new(id: &PaperId) -> Self {
+        Self {
+            id: id.to_owned(),
+            fields: None,
+            context: false,
+            influential_only: false,
+            intents: Vec::new(),
+            offset: None,
+            limit: None,
+        }
+    }
+
+    /// Add a field of the citing papers to the parameters
+    pub fn field(&mut self, field: PaperField) -> &mut Self {
+        if let Some(ref mut fields) = self.fields {
+            fields.push(field);
+        } else {
+            self.fields = Some(vec![field]);
+        }
+        self
+    }
+
+    /// Also return the citation contexts, intents and whether the citation is influential
+    pub fn context(&mut self) -> &mut Self {
+        self.context = true;
+        self
+    }
+
+    /// Keep only the influential citations, requesting the citation context
+    pub fn only_influential(&mut self) -> &mut Self {
+        self.influential_only = true;
+        self
+    }
+
+    /// Keep only the citations with this intent, or any other intent added, requesting the
+    /// citation context
+    pub fn intent(&mut self, intent: CitationIntent) -> &mut Self {
+        if !self.intents.contains(&intent) {
+            self.intents.push(intent);
+        }
+        self
+    }
+
+    /// Used for pagination. When returning a list of results, start with the element at this position in the list (default: 0).
+    pub fn offset(&mut self, offset: u32) -> &mut Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The maximum number of results to return (default: 100), at most 1000.
+    pub fn limit(&mut self, limit: u16) -> &mut Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Build the citations parameters
+    pub fn build(&self) -> Result<PaperCitationsParam> {
+        Endpoint::PaperCitations.check_fields(self.fields.as_deref())?;
+        self.id.validate()?;
+        if self.limit.is_some_and(|limit| limit > 1000) {
+            return Err(Error::InvalidParameter(
+                "limit must be at most 1000".to_owned(),
+            ));
+        }
+
+        Ok(PaperCitationsParam {
+            id: self.id.clone(),
+            fields: self.fields.clone(),
+            context: self.context,
+            influential_only: self.influential_only,
+            intents: self.intents.clone(),
+            offset: self.offset,
+            limit: self.limit,
+        })
+    }
+}
+
+/// Response for the citations of a paper
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PaperCitationsResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<S2Citation>>,
+}
+
+list_accessors!(PaperCitationsResponse { data: S2Citation });
+
+/// A citation of a paper
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S2Citation {
+    /// The paper citing the requested paper, `None` when Semantic Scholar has no id for it.
+    #[serde(default, deserialize_with = "paper_with_id")]
+    pub citing_paper: Option<S2Paper>,
+    /// Text snippets where the citation occurs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contexts: Option<Vec<String>>,
+    /// Intents of the citation, e.g. `methodology` or `background`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intents: Option<Vec<CitationIntent>>,
+    /// Whether the citation is influential: <https://www.semanticscholar.org/faq#influential-citations>.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_influential: Option<bool>,
+}
+
+list_accessors!(S2Citation {
+    contexts: String,
+    intents: CitationIntent,
+});
+
+/// Deserialize a citing paper, `None` when it is `null` or its `paperId` is
+fn paper_with_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<S2Paper>, D::Error> {
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(paper) if paper.get("paperId").is_some_and(|id| !id.is_null()) => {
+            S2Paper::deserialize(paper)
+                .map(Some)
+                .map_err(de::Error::custom)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Intent of a citation, as classified by Semantic Scholar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CitationIntent {
+    /// The citing paper gives the cited paper as background
+    Background,
+    /// The citing paper uses the methods, tools or data of the cited paper
+    Methodology,
+    /// The citing paper compares its results with those of the cited paper
+    Result,
+    /// An intent not known to this crate
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paper_citations_param_builder() {
+        let mut builder = PaperCitationsParamBuilder::new(&PaperId::id("abc"));
+        builder.field(PaperField::Title).context().limit(10);
+        let param = builder.build().unwrap();
+        assert_eq!(
+            param.query_string(),
+            "abc/citations?fields=title,contexts,intents,isInfluential&limit=10"
+        );
+
+        builder.field(PaperField::Citations);
+        assert!(matches!(builder.build(), Err(Error::UnsupportedFields(_))));
+    }
+
+    #[test]
+    fn test_citation_filters() {
+        let param = PaperCitationsParamBuilder::new(&PaperId::id("abc"))
+            .only_influential()
+            .intent(CitationIntent::Methodology)
+            .build()
+            .unwrap();
+        assert_eq!(
+            param.query_string(),
+            "abc/citations?fields=contexts,intents,isInfluential"
+        );
+
+        let citations: Vec<S2Citation> = serde_json::from_value(serde_json::json!([
+            { "citingPaper": { "paperId": "a" }, "intents": ["methodology"], "isInfluential": true },
+            { "citingPaper": { "paperId": "b" }, "intents": ["background"], "isInfluential": true },
+            { "citingPaper": { "paperId": "c" }, "intents": ["methodology"], "isInfluential": false },
+            { "citingPaper": { "paperId": "d" }, "intents": ["unknown"] },
+        ]))
+        .unwrap();
+        assert_eq!(citations[3].intents(), [CitationIntent::Other]);
+        let kept = citations
+            .iter()
+            .filter(|citation| param.keeps(citation))
+            .filter_map(|citation| citation.citing_paper.as_ref())
+            .map(|paper| paper.paper_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(kept, vec!["a"]);
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_query() {
+        let param = PaperCitationsParamBuilder::new(&PaperId::id(
+            "649def34f8be52c8b66281af98ae884c09aef38b",
+        ))
+        .field(PaperField::Title)
+        .build()
+        .unwrap();
+        let client = SemanticScholar::default();
+        let response = client.query(&param).await.unwrap();
+        println!("{:#?}", response);
+    }
+}
@@ -0,0 +1,101 @@
+//! Paper fields supported by each endpoint
+//!
+//! Not every endpoint can return every [`PaperField`]. Builders check the requested fields
+//! against this table so that an invalid selection fails before any request is sent, and
+//! default fields of the client are filtered the same way.
+
+use crate::{
+    error::{Error, Result},
+    ss::PaperField,
+};
+
+/// Graph API endpoint returning papers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    /// `GET /paper/search`
+    PaperSearch,
+    /// `GET /paper/search/bulk`
+    PaperBulkSearch,
+    /// `GET /paper/search/match`
+    PaperTitleSearch,
+    /// `GET /paper/{paperId}`
+    PaperDetails,
+    /// `POST /paper/batch`
+    PaperBatch,
+    /// `GET /paper/{paperId}/citations`
+    PaperCitations,
+}
+
+impl Endpoint {
+    /// Fields the endpoint cannot return
+    pub fn unsupported_fields(self) -> &'static [PaperField] {
+        match self {
+            Endpoint::PaperSearch | Endpoint::PaperTitleSearch => &[PaperField::Tldr],
+            Endpoint::PaperBulkSearch | Endpoint::PaperCitations => &[
+                PaperField::Citations,
+                PaperField::References,
+                PaperField::Embedding,
+                PaperField::Tldr,
+            ],
+            Endpoint::PaperDetails | Endpoint::PaperBatch => &[],
+        }
+    }
+
+    /// Whether the endpoint can return the field
+    pub fn supports(self, field: PaperField) -> bool {
+        !self.unsupported_fields().contains(&field)
+    }
+
+    /// Check the requested fields, listing every unsupported one in the error
+    pub fn check_fields(self, fields: Option<&[PaperField]>) -> Result<()> {
+        let unsupported = fields
+            .unwrap_or_default()
+            .iter()
+            .filter(|field| !self.supports(**field))
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedFields(unsupported))
+        }
+    }
+
+    /// Default fields of the client restricted to the ones the endpoint supports
+    pub(crate) fn default_fields(self, fields: Option<&[PaperField]>) -> Option<Vec<PaperField>> {
+        fields.map(|fields| {
+            fields
+                .iter()
+                .copied()
+                .filter(|field| self.supports(*field))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_fields() {
+        let fields = [PaperField::Title, PaperField::Citations, PaperField::Tldr];
+        assert_eq!(
+            Endpoint::PaperBulkSearch.check_fields(Some(&fields)),
+            Err(Error::UnsupportedFields(vec![
+                "citations".to_owned(),
+                "tldr".to_owned()
+            ]))
+        );
+        assert_eq!(
+            Endpoint::PaperSearch.check_fields(Some(&fields)),
+            Err(Error::UnsupportedFields(vec!["tldr".to_owned()]))
+        );
+        assert!(Endpoint::PaperBatch.check_fields(Some(&fields)).is_ok());
+        assert!(Endpoint::PaperBulkSearch.check_fields(None).is_ok());
+        assert_eq!(
+            Endpoint::PaperTitleSearch.default_fields(Some(&fields)),
+            Some(vec![PaperField::Title, PaperField::Citations])
+        );
+    }
+}
@@ -1,10 +1,20 @@
 //! Semantic Scholar Graph API
 
-const BASE_URL: &str = "https://api.semanticscholar.org/graph/v1";
-
+pub mod author;
+pub use author::*;
 pub mod autocomplete;
 pub use autocomplete::*;
 pub mod batch;
 pub use batch::*;
+pub mod citations;
+pub use citations::*;
+pub mod fields;
+pub use fields::*;
+pub mod paper_authors;
+pub use paper_authors::*;
+#[cfg(test)]
+mod proptests;
+pub mod resolve;
+pub use resolve::*;
 pub mod search;
 pub use search::*;
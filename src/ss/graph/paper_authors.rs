@@ -0,0 +1,186 @@
+//! Authors of a paper
+//!
+//! `Get /paper/{paperId}/authors`
+//!
+//! `/paper/{paperId}/authors?fields={fields}`
+//!
+//! Fetch details about the authors of a paper without fetching the whole paper details.
+//!
+//! ## Limitations
+//! - Can only return up to 1,000 authors per call, use `offset` to page through the rest.
+//! - Can only return up to 10 MB of data at a time.
+
+use crate::{
+    error::{Error, Result},
+    ss::{
+        AuthorField, PaperId, S2Author,
+        client::{Query, SemanticScholar},
+        merge_author_fields, non_empty,
+    },
+    utils::{Method, build_request, list_accessors, send},
+};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// Query parameters for the authors of a paper
+#[derive(Debug, Clone)]
+pub struct PaperAuthorsParam {
+    /// The paper whose authors are fetched.
+    id: PaperId,
+    /// A comma-separated list of the author fields to be returned.
+    fields: Option<Vec<AuthorField>>,
+    /// Used for pagination. When returning a list of results, start with the element at this position in the list (default: 0).
+    pub(crate) offset: Option<u32>,
+    /// The maximum number of results to return (default: 100).
+    ///
+    /// Must be <= 1000.
+    limit: Option<u16>,
+}
+
+impl PaperAuthorsParam {
+    pub(crate) fn query_string(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(fields) = non_empty(&self.fields) {
+            params.push(format!("fields={}", merge_author_fields(fields)));
+        }
+
+        if let Some(offset) = self.offset {
+            params.push(format!("offset={}", offset));
+        }
+
+        if let Some(limit) = self.limit {
+            params.push(format!("limit={}", limit));
+        }
+
+        if params.is_empty() {
+            format!("{}/authors", self.id)
+        } else {
+            format!("{}/authors?{}", self.id, params.join("&"))
+        }
+    }
+}
+
+impl Query for PaperAuthorsParam {
+    type Response = PaperAuthorsResponse;
+
+    async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
+        let url = format!("{}/paper/{}", client.graph_url(), self.query_string());
+        let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
+
+        let resp = send(req_builder, client.transport()).await?;
+        match resp.status {
+            StatusCode::OK => resp.json(),
+            _ => Err(resp.into_error()),
+        }
+    }
+}
+
+/// Builder for the paper authors parameters
+#[derive(Debug, Clone)]
+pub struct PaperAuthorsParamBuilder {
+    id: PaperId,
+    fields: Option<Vec<AuthorField>>,
+    offset: Option<u32>,
+    limit: Option<u16>,
+}
+
+impl PaperAuthorsParamBuilder {
+    /// Create a new builder for the authors of the given paper
+    pub fn new(id: &PaperId) -> Self {
+        Self {
+            id: id.to_owned(),
+            fields: None,
+            offset: None,
+            limit: None,
+        }
+    }
+
+    /// Add an author field to the parameters
+    pub fn field(&mut self, field: AuthorField) -> &mut Self {
+        if let Some(ref mut fields) = self.fields {
+            fields.push(field);
+        } else {
+            self.fields = Some(vec![field]);
+        }
+        self
+    }
+
+    /// Used for pagination. When returning a list of results, start with the element at this position in the list (default: 0).
+    pub fn offset(&mut self, offset: u32) -> &mut Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// The maximum number of results to return (default: 100), at most 1000.
+    pub fn limit(&mut self, limit: u16) -> &mut Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Build the paper authors parameters
+    pub fn build(&self) -> Result<PaperAuthorsParam> {
+        self.id.validate()?;
+        if self.limit.is_some_and(|limit| limit > 1000) {
+            return Err(Error::InvalidParameter(
+                "limit must be at most 1000".to_owned(),
+            ));
+        }
+
+        Ok(PaperAuthorsParam {
+            id: self.id.clone(),
+            fields: self.fields.clone(),
+            offset: self.offset,
+            limit: self.limit,
+        })
+    }
+}
+
+/// Response for the authors of a paper
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PaperAuthorsResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<S2Author>>,
+}
+
+list_accessors!(PaperAuthorsResponse { data: S2Author });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paper_authors_param_builder() {
+        let mut builder = PaperAuthorsParamBuilder::new(&PaperId::doi("10.18653/v1/N18-3011"));
+        assert_eq!(
+            builder.build().unwrap().query_string(),
+            "DOI:10.18653/v1/n18-3011/authors"
+        );
+
+        builder.field(AuthorField::HIndex).offset(10).limit(5);
+        assert_eq!(
+            builder.build().unwrap().query_string(),
+            "DOI:10.18653/v1/n18-3011/authors?fields=hIndex&offset=10&limit=5"
+        );
+
+        builder.limit(1001);
+        assert!(matches!(builder.build(), Err(Error::InvalidParameter(_))));
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_query() {
+        let param =
+            PaperAuthorsParamBuilder::new(&PaperId::id("649def34f8be52c8b66281af98ae884c09aef38b"))
+                .field(AuthorField::Name)
+                .field(AuthorField::Affiliations)
+                .build()
+                .unwrap();
+        let client = SemanticScholar::default();
+        let response = client.query(&param).await.unwrap();
+        println!("{:#?}", response);
+    }
+}
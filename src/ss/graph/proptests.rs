@@ -0,0 +1,528 @@
+//! Property tests of the query strings built by the search parameters
+//!
+//! Every generated builder must yield a query string where each set parameter appears exactly
+//! once, in the canonical order of its endpoint, with values percent-encoded so that they
+//! decode back to the inputs.
+
+use crate::ss::{
+    AuthorField, AuthorSearchParamBuilder, CitationIntent, FieldOfStudy, FieldsOfStudySource,
+    PaperAuthorsParamBuilder, PaperBulkSearchParamBuilder, PaperCitationsParamBuilder, PaperField,
+    PaperId, PaperSearchParamBuilder, PaperTitleSearchParamBuilder, PublicationType, QueryExpr,
+    SortBy, SortOrder, graph::Endpoint,
+};
+use chrono::NaiveDate;
+use proptest::{prelude::*, sample::select};
+use std::collections::HashSet;
+
+const PAPER_FIELDS: [PaperField; 24] = [
+    PaperField::CorpusId,
+    PaperField::ExternalIds,
+    PaperField::URL,
+    PaperField::Title,
+    PaperField::Abstract,
+    PaperField::Venue,
+    PaperField::PublicationVenue,
+    PaperField::Year,
+    PaperField::ReferenceCount,
+    PaperField::CitationCount,
+    PaperField::InfluentialCitationCount,
+    PaperField::IsOpenAccess,
+    PaperField::OpenAccessPDF,
+    PaperField::FieldsOfStudy,
+    PaperField::S2FieldsOfStudy,
+    PaperField::PublicationTypes,
+    PaperField::PublicationDate,
+    PaperField::Journal,
+    PaperField::CitationStyles,
+    PaperField::Authors,
+    PaperField::Citations,
+    PaperField::References,
+    PaperField::Embedding,
+    PaperField::Tldr,
+];
+
+const AUTHOR_FIELDS: [AuthorField; 9] = [
+    AuthorField::ExternalIds,
+    AuthorField::URL,
+    AuthorField::Name,
+    AuthorField::Affiliations,
+    AuthorField::Homepage,
+    AuthorField::PaperCount,
+    AuthorField::CitationCount,
+    AuthorField::HIndex,
+    AuthorField::Papers,
+];
+
+const PUBLICATION_TYPES: [PublicationType; 13] = [
+    PublicationType::Review,
+    PublicationType::JournalArticle,
+    PublicationType::CaseReport,
+    PublicationType::ClinicalTrial,
+    PublicationType::Conference,
+    PublicationType::Dataset,
+    PublicationType::Editorial,
+    PublicationType::LettersAndComments,
+    PublicationType::MetaAnalysis,
+    PublicationType::News,
+    PublicationType::Study,
+    PublicationType::Book,
+    PublicationType::BookSection,
+];
+
+/// A parameter of a query string, `None` for a flag, the list items otherwise
+type Pair = (String, Option<Vec<String>>);
+
+/// Publication period filter, years and dates being mutually exclusive
+#[derive(Debug, Clone)]
+enum Published {
+    Any,
+    Years(Option<u32>, Option<u32>),
+    Dates(Option<NaiveDate>, Option<NaiveDate>),
+}
+
+/// Filters shared by the paper searches
+#[derive(Debug, Clone)]
+struct Filters {
+    publication_types: Vec<PublicationType>,
+    open_access_pdf: bool,
+    min_citation_count: Option<u32>,
+    published: Published,
+    fields_of_study: Vec<FieldOfStudy>,
+    source: FieldsOfStudySource,
+    venues: Vec<String>,
+}
+
+fn paper_fields(endpoint: Endpoint) -> impl Strategy<Value = Vec<PaperField>> {
+    prop::collection::vec(select(&PAPER_FIELDS[..]), 0..6).prop_map(move |fields| {
+        fields
+            .into_iter()
+            .filter(|field| endpoint.supports(*field))
+            .collect()
+    })
+}
+
+fn author_fields() -> impl Strategy<Value = Vec<AuthorField>> {
+    prop::collection::vec(select(&AUTHOR_FIELDS[..]), 0..4)
+}
+
+fn date() -> impl Strategy<Value = NaiveDate> {
+    (0..73_000u64)
+        .prop_map(|days| NaiveDate::from_ymd_opt(1900, 1, 1).unwrap() + chrono::Days::new(days))
+}
+
+fn published() -> impl Strategy<Value = Published> {
+    prop_oneof![
+        Just(Published::Any),
+        (any::<bool>(), any::<bool>(), 1900..2100u32, 1900..2100u32).prop_map(
+            |(from, to, a, b)| {
+                let (start, end) = (a.min(b), a.max(b));
+                Published::Years(from.then_some(start), to.then_some(end))
+            }
+        ),
+        (any::<bool>(), any::<bool>(), date(), date()).prop_map(|(from, to, a, b)| {
+            let (start, end) = (a.min(b), a.max(b));
+            Published::Dates(from.then_some(start), to.then_some(end))
+        }),
+    ]
+}
+
+fn filters() -> impl Strategy<Value = Filters> {
+    (
+        prop::collection::vec(select(&PUBLICATION_TYPES[..]), 0..4),
+        any::<bool>(),
+        any::<Option<u32>>(),
+        published(),
+        prop::collection::vec(select(&FieldOfStudy::ALL[..]), 0..4),
+        prop_oneof![
+            Just(FieldsOfStudySource::External),
+            Just(FieldsOfStudySource::S2)
+        ],
+        prop::collection::vec(any::<String>(), 0..3),
+    )
+        .prop_map(
+            |(
+                publication_types,
+                open_access_pdf,
+                min_citation_count,
+                published,
+                fields_of_study,
+                source,
+                venues,
+            )| Filters {
+                publication_types,
+                open_access_pdf,
+                min_citation_count,
+                published,
+                fields_of_study,
+                source,
+                venues,
+            },
+        )
+}
+
+fn sort() -> impl Strategy<Value = Option<SortBy>> {
+    let order = prop_oneof![Just(SortOrder::Ascending), Just(SortOrder::Descending)];
+    prop::option::of(order.prop_flat_map(|order| {
+        prop_oneof![
+            Just(SortBy::PaperId(order)),
+            Just(SortBy::PublicationDate(order)),
+            Just(SortBy::CitationCount(order)),
+        ]
+    }))
+}
+
+/// Apply the filters to any of the paper search builders
+macro_rules! apply_filters {
+    ($builder:expr, $filters:expr) => {{
+        let builder = $builder;
+        let filters: &Filters = $filters;
+        for type_ in &filters.publication_types {
+            builder.publication_type(*type_);
+        }
+        if filters.open_access_pdf {
+            builder.open_access_pdf();
+        }
+        if let Some(count) = filters.min_citation_count {
+            builder.min_citation_count(count);
+        }
+        match filters.published {
+            Published::Any => (),
+            Published::Years(start, end) => {
+                if let Some(start) = start {
+                    builder.from_year(start);
+                }
+                if let Some(end) = end {
+                    builder.to_year(end);
+                }
+            }
+            Published::Dates(start, end) => {
+                if let Some(start) = start {
+                    builder.from_naive_date(start);
+                }
+                if let Some(end) = end {
+                    builder.to_naive_date(end);
+                }
+            }
+        }
+        for field_of_study in &filters.fields_of_study {
+            builder.field_of_study(*field_of_study);
+        }
+        builder.fields_of_study_source(filters.source);
+        for venue in &filters.venues {
+            builder.venue(venue);
+        }
+    }};
+}
+
+/// Distinct items in order of first occurrence, `None` if there are none
+fn distinct<T: ToString>(items: &[T]) -> Option<Vec<String>> {
+    let mut distinct = Vec::new();
+    for item in items.iter().map(ToString::to_string) {
+        if !distinct.contains(&item) {
+            distinct.push(item);
+        }
+    }
+    (!distinct.is_empty()).then_some(distinct)
+}
+
+/// A parameter with a single value
+fn pair(key: &str, value: impl ToString) -> Pair {
+    (key.to_owned(), Some(vec![value.to_string()]))
+}
+
+/// A parameter holding a list, skipped if the list is empty
+fn list_pair<T: ToString>(pairs: &mut Vec<Pair>, key: &str, items: &[T]) {
+    if let Some(items) = distinct(items) {
+        pairs.push((key.to_owned(), Some(items)));
+    }
+}
+
+/// Expected parameters of the filters, in canonical order
+fn filter_pairs(filters: &Filters) -> Vec<Pair> {
+    let mut pairs = Vec::new();
+    list_pair(&mut pairs, "publicationTypes", &filters.publication_types);
+    if filters.open_access_pdf {
+        pairs.push(("openAccessPdf".to_owned(), None));
+    }
+    if let Some(count) = filters.min_citation_count {
+        pairs.push(pair("minCitationCount", count));
+    }
+    let show = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+    match filters.published {
+        Published::Any => (),
+        Published::Years(None, None) | Published::Dates(None, None) => (),
+        Published::Years(Some(start), Some(end)) if start == end => {
+            pairs.push(pair("year", start));
+        }
+        Published::Years(start, end) => {
+            pairs.push(pair("year", format!("{}-{}", show(start), show(end))));
+        }
+        Published::Dates(start, end) => {
+            let show = |date: Option<NaiveDate>| date.map(|d| d.to_string()).unwrap_or_default();
+            let value = format!("{}:{}", show(start), show(end));
+            pairs.push(pair("publicationDate", value));
+        }
+    }
+    list_pair(
+        &mut pairs,
+        filters.source.query_param(),
+        &filters.fields_of_study,
+    );
+    list_pair(&mut pairs, "venue", &filters.venues);
+    pairs
+}
+
+/// Decode a percent-encoded value, which must only hold unreserved characters and escapes
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .unwrap_or_else(|| panic!("truncated escape in {:?}", value));
+                assert!(
+                    hex.bytes()
+                        .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase()),
+                    "escape %{} in {:?} is not upper case hexadecimal",
+                    hex,
+                    value
+                );
+                decoded.push(u8::from_str_radix(hex, 16).unwrap());
+                i += 3;
+            }
+            b @ (b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':') => {
+                decoded.push(b);
+                i += 1;
+            }
+            b => panic!("unencoded byte {:?} in {:?}", char::from(b), value),
+        }
+    }
+    String::from_utf8(decoded).unwrap()
+}
+
+/// Split a query string into its parameters, decoding every list item
+fn pairs(query_string: &str) -> Vec<Pair> {
+    query_string
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (key.to_owned(), Some(value.split(',').map(decode).collect())),
+            None => (pair.to_owned(), None),
+        })
+        .collect()
+}
+
+/// Check the query string against the expected parameters
+fn check(query_string: &str, expected: &[Pair]) -> Result<(), TestCaseError> {
+    let actual = pairs(query_string);
+    let keys = actual.iter().map(|(key, _)| key).collect::<HashSet<_>>();
+    prop_assert_eq!(keys.len(), actual.len(), "repeated key in {}", query_string);
+    prop_assert_eq!(&actual[..], expected, "in {}", query_string);
+    Ok(())
+}
+
+/// Split the path of a paper sub-resource from its query string, empty if there is none
+fn split_path(path: &str) -> (&str, &str) {
+    path.split_once('?').unwrap_or((path, ""))
+}
+
+proptest! {
+    #[test]
+    fn test_relevance_search_query_string(
+        query in any::<String>(),
+        fields in paper_fields(Endpoint::PaperSearch),
+        filters in filters(),
+        offset in any::<Option<u32>>(),
+        limit in any::<Option<u8>>(),
+    ) {
+        let mut builder = PaperSearchParamBuilder::new(&query);
+        for field in &fields {
+            builder.field(*field);
+        }
+        apply_filters!(&mut builder, &filters);
+        if let Some(offset) = offset {
+            builder.offset(offset);
+        }
+        if let Some(limit) = limit {
+            builder.limit(limit);
+        }
+        let query_string = builder.build().unwrap().query_string();
+        prop_assert_eq!(&builder.build().unwrap().query_string(), &query_string);
+
+        let mut expected = vec![pair("query", &query)];
+        list_pair(&mut expected, "fields", &fields);
+        expected.extend(filter_pairs(&filters));
+        expected.extend(offset.map(|offset| pair("offset", offset)));
+        expected.extend(limit.map(|limit| pair("limit", limit)));
+        check(&query_string, &expected)?;
+    }
+
+    #[test]
+    fn test_bulk_search_query_string(
+        term in any::<String>(),
+        token in any::<Option<String>>(),
+        sort in sort(),
+        fields in paper_fields(Endpoint::PaperBulkSearch),
+        filters in filters(),
+    ) {
+        let query = QueryExpr::term(&term);
+        let mut builder = PaperBulkSearchParamBuilder::default();
+        builder.query(&query);
+        if let Some(ref token) = token {
+            builder.token(token);
+        }
+        if let Some(sort) = sort {
+            builder.sort_by(sort);
+        }
+        for field in &fields {
+            builder.field(*field);
+        }
+        apply_filters!(&mut builder, &filters);
+        if token.is_some() && sort.is_some() {
+            prop_assert!(builder.build().is_err());
+            return Ok(());
+        }
+        let query_string = builder.build().unwrap().query_string();
+        prop_assert_eq!(&builder.build().unwrap().query_string(), &query_string);
+
+        let mut expected = vec![pair("query", &query)];
+        expected.extend(token.map(|token| pair("token", token)));
+        expected.extend(sort.map(|sort| pair("sort", sort)));
+        list_pair(&mut expected, "fields", &fields);
+        expected.extend(filter_pairs(&filters));
+        check(&query_string, &expected)?;
+    }
+
+    #[test]
+    fn test_title_search_query_string(
+        query in any::<String>(),
+        fields in paper_fields(Endpoint::PaperTitleSearch),
+        filters in filters(),
+    ) {
+        let mut builder = PaperTitleSearchParamBuilder::new(&query);
+        for field in &fields {
+            builder.field(*field);
+        }
+        apply_filters!(&mut builder, &filters);
+        let query_string = builder.build().unwrap().query_string();
+        prop_assert_eq!(&builder.build().unwrap().query_string(), &query_string);
+
+        let mut expected = vec![pair("query", &query)];
+        list_pair(&mut expected, "fields", &fields);
+        expected.extend(filter_pairs(&filters));
+        check(&query_string, &expected)?;
+    }
+
+    #[test]
+    fn test_author_search_query_string(
+        name in any::<String>().prop_filter("blank name", |name| !name.trim().is_empty()),
+        fields in author_fields(),
+        offset in any::<Option<u32>>(),
+        limit in prop::option::of(0..=1000u16),
+    ) {
+        let mut builder = AuthorSearchParamBuilder::new(&name);
+        for field in &fields {
+            builder.field(*field);
+        }
+        if let Some(offset) = offset {
+            builder.offset(offset);
+        }
+        if let Some(limit) = limit {
+            builder.limit(limit);
+        }
+        let query_string = builder.build().unwrap().query_string();
+        prop_assert_eq!(&builder.build().unwrap().query_string(), &query_string);
+
+        let mut expected = vec![pair("query", name.trim())];
+        list_pair(&mut expected, "fields", &fields);
+        expected.extend(offset.map(|offset| pair("offset", offset)));
+        expected.extend(limit.map(|limit| pair("limit", limit)));
+        check(&query_string, &expected)?;
+    }
+
+    #[test]
+    fn test_citations_query_string(
+        corpus_id in any::<u64>(),
+        fields in paper_fields(Endpoint::PaperCitations),
+        context in any::<bool>(),
+        intents in prop::collection::vec(
+            select(&[CitationIntent::Background, CitationIntent::Methodology, CitationIntent::Result][..]),
+            0..3,
+        ),
+        offset in any::<Option<u32>>(),
+        limit in prop::option::of(0..=1000u16),
+    ) {
+        let id = PaperId::corpus(corpus_id);
+        let mut builder = PaperCitationsParamBuilder::new(&id);
+        for field in &fields {
+            builder.field(*field);
+        }
+        if context {
+            builder.context();
+        }
+        for intent in &intents {
+            builder.intent(*intent);
+        }
+        if let Some(offset) = offset {
+            builder.offset(offset);
+        }
+        if let Some(limit) = limit {
+            builder.limit(limit);
+        }
+        let path = builder.build().unwrap().query_string();
+        prop_assert_eq!(&builder.build().unwrap().query_string(), &path);
+
+        let (resource, query_string) = split_path(&path);
+        prop_assert_eq!(resource, format!("{}/citations", id));
+        let mut requested = distinct(&fields).unwrap_or_default();
+        if context || !intents.is_empty() {
+            requested.extend(["contexts", "intents", "isInfluential"].map(String::from));
+        }
+        let mut expected = Vec::new();
+        list_pair(&mut expected, "fields", &requested);
+        expected.extend(offset.map(|offset| pair("offset", offset)));
+        expected.extend(limit.map(|limit| pair("limit", limit)));
+        if expected.is_empty() {
+            prop_assert!(query_string.is_empty());
+        } else {
+            check(query_string, &expected)?;
+        }
+    }
+
+    #[test]
+    fn test_paper_authors_query_string(
+        corpus_id in any::<u64>(),
+        fields in author_fields(),
+        offset in any::<Option<u32>>(),
+        limit in prop::option::of(0..=1000u16),
+    ) {
+        let id = PaperId::corpus(corpus_id);
+        let mut builder = PaperAuthorsParamBuilder::new(&id);
+        for field in &fields {
+            builder.field(*field);
+        }
+        if let Some(offset) = offset {
+            builder.offset(offset);
+        }
+        if let Some(limit) = limit {
+            builder.limit(limit);
+        }
+        let path = builder.build().unwrap().query_string();
+        prop_assert_eq!(&builder.build().unwrap().query_string(), &path);
+
+        let (resource, query_string) = split_path(&path);
+        prop_assert_eq!(resource, format!("{}/authors", id));
+        let mut expected = Vec::new();
+        list_pair(&mut expected, "fields", &fields);
+        expected.extend(offset.map(|offset| pair("offset", offset)));
+        expected.extend(limit.map(|limit| pair("limit", limit)));
+        if expected.is_empty() {
+            prop_assert!(query_string.is_empty());
+        } else {
+            check(query_string, &expected)?;
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Translate paper ids of any kind into Semantic Scholar ids
+//!
+//! `POST /paper/batch?fields=externalIds`
+//!
+//! ## Limitations
+//! - Can only process 500 paper ids at a time, larger lists are split.
+
+use crate::{
+    error::Result,
+    ss::{ExternalIds, PaperId, client::SemanticScholar},
+    utils::{Method, build_request, null_as_default, send},
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+/// Paper ids sent per request
+const MAX_IDS: usize = 500;
+
+/// Identifiers of a resolved paper
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedId {
+    /// Semantic Scholar ID
+    pub paper_id: String,
+    /// Ids of the paper in external sources, the corpus ID included
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub external_ids: ExternalIds,
+}
+
+impl ResolvedId {
+    /// Semantic Scholar's numerical ID
+    pub fn corpus_id(&self) -> Option<u64> {
+        self.external_ids.corpus_id
+    }
+}
+
+/// Semantic Scholar ids of a list of paper ids
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdMapping {
+    /// Resolved ids, in the order given
+    pub resolved: Vec<(PaperId, ResolvedId)>,
+    /// Ids Semantic Scholar does not know, in the order given
+    pub unresolved: Vec<PaperId>,
+}
+
+impl IdMapping {
+    /// The resolution of an id
+    pub fn get(&self, id: &PaperId) -> Option<&ResolvedId> {
+        self.resolved
+            .iter()
+            .find(|(resolved, _)| resolved == id)
+            .map(|(_, resolved)| resolved)
+    }
+}
+
+#[derive(Serialize)]
+struct PaperIds<'a> {
+    ids: &'a [PaperId],
+}
+
+impl SemanticScholar {
+    /// Translate DOIs, arXiv IDs, PMIDs or any other paper ids into Semantic Scholar and
+    /// corpus IDs in bulk
+    ///
+    /// Only the external ids of the papers are requested, by batches of 500. Ids are checked
+    /// with [`PaperId::validate`] before any request.
+    pub async fn resolve_ids(&self, ids: &[PaperId]) -> Result<IdMapping> {
+        for id in ids {
+            id.validate()?;
+        }
+        let url = format!("{}/paper/batch?fields=externalIds", self.graph_url());
+        let mut mapping = IdMapping::default();
+        for chunk in ids.chunks(MAX_IDS) {
+            let req_builder = build_request(self.client(), Method::Post, &url, self.api_key())
+                .json(&PaperIds { ids: chunk });
+            let resp = send(req_builder, self.transport()).await?;
+            let papers = match resp.status {
+                StatusCode::OK => resp.json::<Vec<Option<ResolvedId>>>()?,
+                _ => return Err(resp.into_error()),
+            };
+            add_resolved(&mut mapping, chunk, papers);
+        }
+        Ok(mapping)
+    }
+}
+
+/// Pair ids with the papers returned for them, `null` for unknown ids
+fn add_resolved(mapping: &mut IdMapping, ids: &[PaperId], papers: Vec<Option<ResolvedId>>) {
+    for (id, paper) in ids.iter().zip(papers) {
+        match paper {
+            Some(paper) => mapping.resolved.push((id.clone(), paper)),
+            None => mapping.unresolved.push(id.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_resolved() {
+        let ids = [PaperId::doi("10.18653/v1/N18-3011"), PaperId::pubmed(1)];
+        let papers = serde_json::from_str(
+            r#"[{"paperId": "649def34", "externalIds": {"CorpusId": 215416146, "DOI": "10.18653/v1/N18-3011"}}, null]"#,
+        )
+        .unwrap();
+        let mut mapping = IdMapping::default();
+        add_resolved(&mut mapping, &ids, papers);
+        let resolved = mapping.get(&ids[0]).unwrap();
+        assert_eq!(resolved.paper_id, "649def34");
+        assert_eq!(resolved.corpus_id(), Some(215416146));
+        assert_eq!(mapping.unresolved, vec![PaperId::pubmed(1)]);
+    }
+}
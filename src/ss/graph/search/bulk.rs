@@ -20,17 +20,19 @@
 use crate::{
     error::{Error, Result},
     ss::{
-        _Date, Date, FieldOfStudy, PaperField, PublicationType, S2Paper,
+        _Date, Date, FieldOfStudy, FieldsOfStudySource, PaperField, PublicationType, S2Paper,
         client::{Query, SemanticScholar},
-        graph::BASE_URL,
-        merge_fields_of_study, merge_paper_fields, merge_publication_types,
+        graph::Endpoint,
+        merge_fields_of_study, merge_paper_fields, merge_publication_types, merge_venues,
+        non_empty, validate_date_filters,
     },
-    utils::{Method, build_request},
+    utils::{Method, build_request, encode_query_value, list_accessors, send},
 };
+use chrono::NaiveDate;
 use reqwest::StatusCode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum QueryExpr {
     Term(String),                  // word
     Phrase(String),                // "word phrase"
@@ -139,6 +141,197 @@ impl std::fmt::Display for QueryExpr {
     }
 }
 
+impl std::str::FromStr for QueryExpr {
+    type Err = Error;
+
+    /// Parse a query in the syntax documented on [`PaperBulkSearchParam::query`]
+    ///
+    /// `+` binds tighter than `|`, and terms separated by whitespace only are joined with AND.
+    /// A `~` without a distance after a phrase means a distance of 2.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = QueryParser {
+            query: s,
+            chars: s.char_indices().peekable(),
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        parser.skip_whitespace();
+        match parser.chars.peek() {
+            None => Ok(expr),
+            Some(&(i, c)) => Err(parser.error(i, &format!("unexpected `{}`", c))),
+        }
+    }
+}
+
+/// Characters with a meaning in queries, never part of a term
+const QUERY_SYNTAX: &[char] = &['+', '|', '"', '(', ')', '*', '~'];
+
+/// Deepest nesting of groups and negations accepted
+const MAX_QUERY_DEPTH: usize = 64;
+
+/// Recursive descent parser of bulk search queries
+struct QueryParser<'a> {
+    query: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    depth: usize,
+}
+
+impl QueryParser<'_> {
+    fn error(&self, at: usize, message: &str) -> Error {
+        Error::InvalidParameter(format!(
+            "invalid query {:?} at {}: {}",
+            self.query, at, message
+        ))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(self.query.len(), |&(i, _)| i)
+    }
+
+    /// `and ('|' and)*`
+    fn or(&mut self) -> Result<QueryExpr> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.and()? {
+                QueryExpr::Or(inner) => nodes.extend(inner),
+                node => nodes.push(node),
+            }
+            if self.peek() != Some('|') {
+                return Ok(join(nodes, QueryExpr::Or));
+            }
+            self.chars.next();
+        }
+    }
+
+    /// `unary (['+'] unary)*`
+    fn and(&mut self) -> Result<QueryExpr> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.unary()? {
+                QueryExpr::And(inner) => nodes.extend(inner),
+                node => nodes.push(node),
+            }
+            match self.peek() {
+                Some('+') => {
+                    self.chars.next();
+                }
+                Some('|' | ')') | None => return Ok(join(nodes, QueryExpr::And)),
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// `'-' unary | '(' or ')' | phrase | word`
+    fn unary(&mut self) -> Result<QueryExpr> {
+        let at = self.position();
+        match self.peek() {
+            Some('-' | '(') if self.depth == MAX_QUERY_DEPTH => {
+                Err(self.error(at, "nested too deeply"))
+            }
+            Some('-') => {
+                self.chars.next();
+                self.depth += 1;
+                let expr = self.unary()?.not();
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Some('(') => {
+                self.chars.next();
+                self.depth += 1;
+                let expr = self.or()?;
+                self.depth -= 1;
+                if self.peek() != Some(')') {
+                    let at = self.position();
+                    return Err(self.error(at, "missing `)`"));
+                }
+                self.chars.next();
+                Ok(expr)
+            }
+            Some('"') => self.phrase(),
+            Some(c) if !QUERY_SYNTAX.contains(&c) => self.word(),
+            Some(c) => {
+                let at = self.position();
+                Err(self.error(at, &format!("unexpected `{}`", c)))
+            }
+            None => Err(self.error(at, "missing term")),
+        }
+    }
+
+    /// `'"' text '"' ['~' [N]]`
+    fn phrase(&mut self) -> Result<QueryExpr> {
+        let start = self.position();
+        self.chars.next();
+        let mut phrase = String::new();
+        loop {
+            match self.chars.next() {
+                Some((_, '"')) => break,
+                Some((_, c)) => phrase.push(c),
+                None => return Err(self.error(start, "unterminated phrase")),
+            }
+        }
+        if phrase.trim().is_empty() {
+            return Err(self.error(start, "empty phrase"));
+        }
+        if self.chars.next_if(|&(_, c)| c == '~').is_none() {
+            return Ok(QueryExpr::Phrase(phrase));
+        }
+        let distance = self.distance()?.unwrap_or(2);
+        Ok(QueryExpr::ProximityPhrase(phrase, distance))
+    }
+
+    /// `text ['*' | '~' [N]]`, the text being non-empty
+    fn word(&mut self) -> Result<QueryExpr> {
+        let mut word = String::new();
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|&(_, c)| !c.is_whitespace() && !QUERY_SYNTAX.contains(&c))
+        {
+            word.push(c);
+        }
+        if self.chars.next_if(|&(_, c)| c == '*').is_some() {
+            return Ok(QueryExpr::Prefix(word));
+        }
+        if self.chars.next_if(|&(_, c)| c == '~').is_some() {
+            return Ok(QueryExpr::FuzzyTerm(word, self.distance()?));
+        }
+        Ok(QueryExpr::Term(word))
+    }
+
+    /// Digits following a `~`, if any
+    fn distance(&mut self) -> Result<Option<u8>> {
+        let start = self.position();
+        let mut digits = String::new();
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| c.is_ascii_digit()) {
+            digits.push(c);
+        }
+        if digits.is_empty() {
+            return Ok(None);
+        }
+        digits
+            .parse()
+            .map(Some)
+            .map_err(|_| self.error(start, "distance out of range"))
+    }
+}
+
+/// A single node, or the nodes joined by `join`
+fn join(mut nodes: Vec<QueryExpr>, join: fn(Vec<QueryExpr>) -> QueryExpr) -> QueryExpr {
+    if nodes.len() == 1 {
+        nodes.pop().unwrap()
+    } else {
+        join(nodes)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortOrder {
     Ascending,
@@ -199,47 +392,42 @@ pub struct PaperBulkSearchParam {
     /// - `"fish ladder"~3` matches papers that contain the phrase "fish ladder" or "fish is on a ladder"
     query: String,
     /// Used for pagination. This string token is provided when the original query returns, and is used to fetch the next batch of papers. Each call will return a new token.
-    token: Option<String>,
+    pub(crate) token: Option<String>,
     fields: Option<Vec<PaperField>>,
-    sort: Option<SortBy>,
+    pub(crate) sort: Option<SortBy>,
     publication_types: Option<Vec<PublicationType>>,
     open_access_pdf: Option<bool>,
     min_citation_count: Option<u32>,
     publication_date: Option<(Option<Date>, Option<Date>)>,
-    year: Option<(Option<u32>, Option<u32>)>,
+    pub(crate) year: Option<(Option<u32>, Option<u32>)>,
     fields_of_study: Option<Vec<FieldOfStudy>>,
+    fields_of_study_source: FieldsOfStudySource,
     venue: Option<Vec<String>>,
 }
 
 impl PaperBulkSearchParam {
     pub(crate) fn query_string(&self) -> String {
-        let mut query_string = format!("query={}", &self.query);
+        let mut query_string = format!("query={}", encode_query_value(&self.query));
 
         if let Some(ref token) = self.token {
-            query_string.push_str(&format!("&token={}", token));
+            query_string.push_str(&format!("&token={}", encode_query_value(token)));
         }
 
         if let Some(sort_by) = self.sort {
             query_string.push_str(&format!("&sort={}", sort_by));
         }
 
-        if let Some(ref fields) = self.fields
-            && !fields.is_empty()
-        {
+        if let Some(fields) = non_empty(&self.fields) {
             let fields_string = merge_paper_fields(fields);
             query_string.push_str(&format!("&fields={}", fields_string));
         }
 
-        if let Some(ref publication_types) = self.publication_types
-            && !publication_types.is_empty()
-        {
+        if let Some(publication_types) = non_empty(&self.publication_types) {
             let publication_types_string = merge_publication_types(publication_types);
             query_string.push_str(&format!("&publicationTypes={}", publication_types_string));
         }
 
-        if let Some(open_access) = self.open_access_pdf
-            && open_access
-        {
+        if self.open_access_pdf == Some(true) {
             query_string.push_str("&openAccessPdf");
         }
 
@@ -277,18 +465,17 @@ impl PaperBulkSearchParam {
             }
         }
 
-        if let Some(ref fields_of_study) = self.fields_of_study
-            && !fields_of_study.is_empty()
-        {
+        if let Some(fields_of_study) = non_empty(&self.fields_of_study) {
             let fields_of_study_string = merge_fields_of_study(fields_of_study);
-            query_string.push_str(&format!("&fieldsOfStudy={}", fields_of_study_string));
+            query_string.push_str(&format!(
+                "&{}={}",
+                self.fields_of_study_source.query_param(),
+                fields_of_study_string
+            ));
         }
 
-        if let Some(ref venue) = self.venue
-            && !venue.is_empty()
-        {
-            let venue_string = venue.join(",");
-            query_string.push_str(&format!("&venue={}", venue_string));
+        if let Some(venue) = non_empty(&self.venue) {
+            query_string.push_str(&format!("&venue={}", merge_venues(venue)));
         }
 
         query_string
@@ -299,14 +486,26 @@ impl Query for PaperBulkSearchParam {
     type Response = PaperBulkSearchResponse;
 
     async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
-        let url = format!("{}/paper/search/bulk?{}", BASE_URL, self.query_string());
-        let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
-
-        let resp = req_builder.send().await?;
-        match resp.status() {
-            StatusCode::OK => Ok(resp.json().await?),
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+        let mut param = self.clone();
+        if param.fields.is_none() {
+            param.fields = Endpoint::PaperBulkSearch.default_fields(client.default_fields());
         }
+        bulk_search(client, &param.query_string()).await
+    }
+}
+
+/// Run a bulk search with an already encoded query string
+pub(crate) async fn bulk_search(
+    client: &SemanticScholar,
+    query_string: &str,
+) -> Result<PaperBulkSearchResponse> {
+    let url = format!("{}/paper/search/bulk?{}", client.graph_url(), query_string);
+    let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
+
+    let resp = send(req_builder, client.transport()).await?;
+    match resp.status {
+        StatusCode::OK => resp.json(),
+        _ => Err(resp.into_error()),
     }
 }
 
@@ -323,6 +522,7 @@ pub struct PaperBulkSearchParamBuilder {
     publication_date: Option<(Option<_Date>, Option<_Date>)>,
     year: Option<(Option<u32>, Option<u32>)>,
     fields_of_study: Option<Vec<FieldOfStudy>>,
+    fields_of_study_source: FieldsOfStudySource,
     venue: Option<Vec<String>>,
 }
 
@@ -411,6 +611,28 @@ impl PaperBulkSearchParamBuilder {
         self
     }
 
+    /// Restricts results to papers published on or after the given date.
+    pub fn from_naive_date(&mut self, date: NaiveDate) -> &mut Self {
+        let date = _Date::from(date);
+        if let Some((ref mut start, _)) = self.publication_date {
+            *start = Some(date);
+        } else {
+            self.publication_date = Some((Some(date), None));
+        }
+        self
+    }
+
+    /// Restricts results to papers published on or before the given date.
+    pub fn to_naive_date(&mut self, date: NaiveDate) -> &mut Self {
+        let date = _Date::from(date);
+        if let Some((_, ref mut end)) = self.publication_date {
+            *end = Some(date);
+        } else {
+            self.publication_date = Some((None, Some(date)));
+        }
+        self
+    }
+
     /// Restricts results to the given publication year range (inclusive).
     pub fn from_year(&mut self, year: u32) -> &mut Self {
         if let Some((ref mut start, _)) = self.year {
@@ -445,6 +667,12 @@ impl PaperBulkSearchParamBuilder {
         self
     }
 
+    /// Apply the fields of study filter to the external or the S2 classification
+    pub fn fields_of_study_source(&mut self, source: FieldsOfStudySource) -> &mut Self {
+        self.fields_of_study_source = source;
+        self
+    }
+
     /// Add a venue to the paper search parameters
     pub fn venue(&mut self, venue: &str) -> &mut Self {
         if let Some(ref mut venues) = self.venue {
@@ -460,45 +688,16 @@ impl PaperBulkSearchParamBuilder {
         if self.query.is_none() {
             return Err(Error::InvalidParameter("query must be set".to_owned()));
         }
-
-        if let Some(ref fields) = self.fields {
-            let unsupported_fields = vec![
-                PaperField::Citations,
-                PaperField::References,
-                PaperField::Embedding,
-                PaperField::Tldr,
-            ];
-            for field in unsupported_fields {
-                if fields.contains(&field) {
-                    return Err(Error::InvalidParameter(format!(
-                        "{} is not supported",
-                        field
-                    )));
-                }
-            }
-        }
-
-        if let Some(year) = self.year
-            && let Some(start) = year.0
-            && let Some(end) = year.1
-            && start > end
-        {
+        if self.token.is_some() && self.sort.is_some() {
             return Err(Error::InvalidParameter(
-                "start year must be less than or equal to end year".to_string(),
+                "sort cannot be combined with a continuation token, set it on the first request only"
+                    .to_owned(),
             ));
         }
 
-        let publication_date = match self.publication_date {
-            Some((ref start, ref end)) => match (start.as_ref(), end.as_ref()) {
-                (Some(start), Some(end)) => {
-                    Some((Some(Date::try_from(start)?), Some(Date::try_from(end)?)))
-                }
-                (Some(start), None) => Some((Some(Date::try_from(start)?), None)),
-                (None, Some(end)) => Some((None, Some(Date::try_from(end)?))),
-                (None, None) => None,
-            },
-            None => None,
-        };
+        Endpoint::PaperBulkSearch.check_fields(self.fields.as_deref())?;
+
+        let publication_date = validate_date_filters(self.publication_date.as_ref(), self.year)?;
 
         Ok(PaperBulkSearchParam {
             query: self.query.clone().unwrap().to_string(),
@@ -511,13 +710,15 @@ impl PaperBulkSearchParamBuilder {
             publication_date,
             year: self.year,
             fields_of_study: self.fields_of_study.clone(),
+            fields_of_study_source: self.fields_of_study_source,
             venue: self.venue.clone(),
         })
     }
 }
 
 /// Response for the paper search
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PaperBulkSearchResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total: Option<u32>,
@@ -527,9 +728,12 @@ pub struct PaperBulkSearchResponse {
     pub data: Option<Vec<S2Paper>>,
 }
 
+list_accessors!(PaperBulkSearchResponse { data: S2Paper });
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ss::TokenPaginated;
 
     #[test]
     fn test_paper_bulk_search_param_builder() {
@@ -555,5 +759,58 @@ mod tests {
             param.fields_of_study,
             Some(vec![FieldOfStudy::ComputerScience])
         );
+
+        let sort = SortBy::CitationCount(SortOrder::Descending);
+        let param = builder.sort_by(sort).build().unwrap();
+        let next = param.with_token("next");
+        assert_eq!((next.token.as_deref(), next.sort), (Some("next"), None));
+        assert!(matches!(
+            builder.token("next").build(),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+    #[test]
+    fn test_parse_query_expr() {
+        let expr = "fish -(ladder | \"salmon run\"~) + out*"
+            .parse::<QueryExpr>()
+            .unwrap();
+        assert_eq!(
+            expr,
+            QueryExpr::term("fish")
+                .and(
+                    QueryExpr::term("ladder")
+                        .or(QueryExpr::proximity("salmon run", 2))
+                        .not()
+                )
+                .and(QueryExpr::prefix("out"))
+        );
+        assert_eq!(
+            expr.to_string(),
+            "fish + -(ladder | \"salmon run\"~2) + out*"
+        );
+        assert_eq!(expr.to_string().parse::<QueryExpr>().unwrap(), expr);
+
+        assert_eq!(
+            "(a + b) c | d~".parse::<QueryExpr>().unwrap(),
+            QueryExpr::And(vec![
+                QueryExpr::term("a"),
+                QueryExpr::term("b"),
+                QueryExpr::term("c")
+            ])
+            .or(QueryExpr::fuzzy("d", None))
+        );
+        assert_eq!(
+            "state-of-the-art".parse::<QueryExpr>().unwrap(),
+            QueryExpr::term("state-of-the-art")
+        );
+        for invalid in ["", "a +", "(a", "a)", "\"a", "\"\"", "a~300", "*"] {
+            assert!(invalid.parse::<QueryExpr>().is_err(), "{:?}", invalid);
+        }
+        assert!(
+            format!("{}a", "-".repeat(100))
+                .parse::<QueryExpr>()
+                .is_err()
+        );
+        assert!(format!("{}a", "-".repeat(10)).parse::<QueryExpr>().is_ok());
     }
 }
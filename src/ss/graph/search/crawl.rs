@@ -0,0 +1,319 @@
+//! Resumable Bulk Crawl
+//!
+//! Walks every page of a [bulk search](super::bulk) and writes a checkpoint file after each
+//! page, holding the encoded filters, the continuation token and progress counters. An
+//! interrupted crawl picks up where it stopped with [`BulkCrawl::resume`].
+//!
+//! Continuation tokens expire. The checkpoint records when its token was issued and a hash of
+//! the query it belongs to; when the token is rejected or too old, the crawl re-issues the
+//! query and skips the papers it already fetched. Resuming with other filters than the
+//! checkpointed ones is an error, as the counters would not match the new query.
+
+use crate::{
+    error::{Error, Result},
+    progress::{ProgressEvent, ProgressSink},
+    ss::{
+        S2Paper,
+        client::SemanticScholar,
+        graph::search::{PaperBulkSearchParam, PaperBulkSearchResponse, bulk::bulk_search},
+    },
+    utils::{encode_query_value, fnv1a},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// State of a bulk crawl, as stored in its checkpoint file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkCheckpoint {
+    /// Encoded search filters, without the continuation token
+    pub filters: String,
+    /// Continuation token of the next page
+    pub token: Option<String>,
+    /// When the token was issued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_issued_at: Option<DateTime<Utc>>,
+    /// Hash of the filters the token was issued for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_hash: Option<String>,
+    /// Number of pages fetched so far
+    pub pages: u64,
+    /// Number of papers fetched so far
+    pub papers: u64,
+    /// Whether every page has been fetched
+    pub done: bool,
+}
+
+/// A bulk search crawl that checkpoints its progress to a file
+#[derive(Debug, Clone)]
+pub struct BulkCrawl {
+    path: PathBuf,
+    checkpoint: BulkCheckpoint,
+    progress: ProgressSink,
+    max_token_age: Option<Duration>,
+}
+
+impl BulkCrawl {
+    /// Start a new crawl of the given search, checkpointing to `path`
+    pub fn new(param: &PaperBulkSearchParam, path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            checkpoint: BulkCheckpoint {
+                filters: encode_filters(param),
+                token: param.token.clone(),
+                token_issued_at: None,
+                query_hash: None,
+                pages: 0,
+                papers: 0,
+                done: false,
+            },
+            progress: ProgressSink::default(),
+            max_token_age: None,
+        }
+    }
+
+    /// Resume a crawl of the given search from its checkpoint file
+    ///
+    /// Returns an [`Error::InvalidParameter`] if the checkpoint was written for other filters
+    /// than those of `param`, ignoring its continuation token.
+    pub fn resume(param: &PaperBulkSearchParam, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let checkpoint: BulkCheckpoint = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        let recorded = match checkpoint.query_hash {
+            Some(ref hash) => hash.clone(),
+            None => query_hash(&checkpoint.filters),
+        };
+        if recorded != query_hash(&encode_filters(param)) {
+            return Err(Error::InvalidParameter(format!(
+                "checkpoint {} belongs to the search {}",
+                path.display(),
+                checkpoint.filters
+            )));
+        }
+        Ok(Self {
+            path,
+            checkpoint,
+            progress: ProgressSink::default(),
+            max_token_age: None,
+        })
+    }
+
+    /// Report every fetched page, and the end of the crawl, as progress events
+    pub fn progress(&mut self, progress: ProgressSink) -> &mut Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Re-issue the query instead of using tokens older than `max_age`
+    ///
+    /// Tokens of unknown age, e.g. from checkpoints written by older versions, are tried
+    /// anyway.
+    pub fn max_token_age(&mut self, max_age: Duration) -> &mut Self {
+        self.max_token_age = Some(max_age);
+        self
+    }
+
+    /// Fetch the next page and checkpoint the progress
+    ///
+    /// A rejected or stale continuation token is replaced by re-issuing the query and
+    /// skipping the papers already fetched, which costs one request per skipped page.
+    /// Returns `None` once the crawl is complete.
+    pub async fn next_page(&mut self, client: &SemanticScholar) -> Result<Option<Vec<S2Paper>>> {
+        if self.checkpoint.done {
+            return Ok(None);
+        }
+
+        let response = match self.checkpoint.token.clone() {
+            None => bulk_search(client, &self.checkpoint.filters).await?,
+            Some(token) if self.token_is_usable() => {
+                match bulk_search(client, &self.with_token(&token)).await {
+                    Err(e) if rejects_token(&e) => self.reissue(client).await?,
+                    response => response?,
+                }
+            }
+            Some(_) => self.reissue(client).await?,
+        };
+        let papers = response.data.unwrap_or_default();
+
+        self.checkpoint.pages += 1;
+        self.checkpoint.papers += papers.len() as u64;
+        self.checkpoint.done = response.token.is_none() || papers.is_empty();
+        self.checkpoint.token_issued_at = response.token.as_ref().map(|_| Utc::now());
+        self.checkpoint.query_hash = response
+            .token
+            .as_ref()
+            .map(|_| query_hash(&self.checkpoint.filters));
+        self.checkpoint.token = response.token;
+        self.save()?;
+        self.progress.emit(ProgressEvent::PageFetched {
+            n: self.checkpoint.pages as usize,
+        });
+        if self.checkpoint.done {
+            self.progress.emit(ProgressEvent::Completed);
+        }
+        Ok(Some(papers))
+    }
+
+    /// The current checkpoint
+    pub fn checkpoint(&self) -> &BulkCheckpoint {
+        &self.checkpoint
+    }
+
+    /// The checkpoint file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether every page has been fetched
+    pub fn is_done(&self) -> bool {
+        self.checkpoint.done
+    }
+
+    /// Whether the token was issued for the current filters and is recent enough
+    fn token_is_usable(&self) -> bool {
+        let same_query = self
+            .checkpoint
+            .query_hash
+            .as_ref()
+            .is_none_or(|hash| *hash == query_hash(&self.checkpoint.filters));
+        let fresh = match (self.max_token_age, self.checkpoint.token_issued_at) {
+            (Some(max_age), Some(issued_at)) => (Utc::now() - issued_at)
+                .to_std()
+                .ok()
+                .is_none_or(|age| age <= max_age),
+            _ => true,
+        };
+        same_query && fresh
+    }
+
+    /// Filters continued with a token, without the sort the API may reject alongside it
+    fn with_token(&self, token: &str) -> String {
+        let filters = self
+            .checkpoint
+            .filters
+            .split('&')
+            .filter(|param| !param.starts_with("sort="))
+            .collect::<Vec<_>>();
+        format!("{}&token={}", filters.join("&"), encode_query_value(token))
+    }
+
+    /// Run the query from its first page, dropping the papers fetched before
+    async fn reissue(&self, client: &SemanticScholar) -> Result<PaperBulkSearchResponse> {
+        let mut skipped = 0;
+        let mut response = bulk_search(client, &self.checkpoint.filters).await?;
+        loop {
+            let papers = response.data.take().unwrap_or_default();
+            let len = papers.len() as u64;
+            match response.token.clone() {
+                Some(token) if !papers.is_empty() && skipped + len <= self.checkpoint.papers => {
+                    skipped += len;
+                    response = bulk_search(client, &self.with_token(&token)).await?;
+                }
+                _ => {
+                    let fetched = (self.checkpoint.papers - skipped).min(len) as usize;
+                    response.data = Some(papers.into_iter().skip(fetched).collect());
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    /// Write the checkpoint, replacing the previous one atomically
+    fn save(&self) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.checkpoint)?)?;
+        std::fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Encoded search filters, without the continuation token
+fn encode_filters(param: &PaperBulkSearchParam) -> String {
+    let mut filters = param.clone();
+    filters.token = None;
+    filters.query_string()
+}
+
+/// Hash of the encoded filters a token belongs to
+fn query_hash(filters: &str) -> String {
+    fnv1a(&[filters.as_bytes()])
+}
+
+/// Whether an error is the API refusing a continuation token, e.g. an expired one
+fn rejects_token(error: &Error) -> bool {
+    matches!(error.inner(), Error::RequestFailed(e) if e.status == 400 || e.status == 410)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PaperBulkSearchParamBuilder, QueryExpr, SortBy, SortOrder};
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let param = PaperBulkSearchParamBuilder::default()
+            .query(&QueryExpr::term("fish"))
+            .build()
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("crawl-{}.json", std::process::id()));
+        let mut crawl = BulkCrawl::new(&param, &path);
+        crawl.checkpoint.token = Some("next".to_owned());
+        crawl.checkpoint.pages = 1;
+        crawl.save().unwrap();
+
+        let resumed = BulkCrawl::resume(&param, &path).unwrap();
+        assert_eq!(resumed.checkpoint().filters, "query=fish");
+        assert_eq!(resumed.checkpoint().token.as_deref(), Some("next"));
+        assert_eq!(resumed.checkpoint().pages, 1);
+        assert!(resumed.token_is_usable());
+
+        let other = PaperBulkSearchParamBuilder::default()
+            .query(&QueryExpr::term("ladder"))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            BulkCrawl::resume(&other, &path),
+            Err(Error::InvalidParameter(_))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_token_metadata() {
+        let param = PaperBulkSearchParamBuilder::default()
+            .query(&QueryExpr::term("fish"))
+            .build()
+            .unwrap();
+        let mut crawl = BulkCrawl::new(&param, std::env::temp_dir().join("unused.json"));
+        crawl.checkpoint.token = Some("next".to_owned());
+        assert!(crawl.token_is_usable());
+
+        crawl.checkpoint.query_hash = Some(query_hash("query=fish"));
+        crawl.checkpoint.token_issued_at = Some(Utc::now() - chrono::TimeDelta::hours(2));
+        assert!(crawl.token_is_usable());
+        crawl.max_token_age(Duration::from_secs(3600));
+        assert!(!crawl.token_is_usable());
+
+        crawl.checkpoint.token_issued_at = Some(Utc::now());
+        crawl.checkpoint.query_hash = Some(query_hash("query=ladder"));
+        assert!(!crawl.token_is_usable());
+
+        let legacy: BulkCheckpoint = serde_json::from_str(
+            r#"{"filters":"query=fish","token":"t","pages":1,"papers":1000,"done":false}"#,
+        )
+        .unwrap();
+        assert_eq!(legacy.token_issued_at, None);
+
+        let param = PaperBulkSearchParamBuilder::default()
+            .query(&QueryExpr::term("fish"))
+            .sort_by(SortBy::PaperId(SortOrder::Ascending))
+            .build()
+            .unwrap();
+        let crawl = BulkCrawl::new(&param, std::env::temp_dir().join("unused.json"));
+        assert_eq!(crawl.checkpoint.filters, "query=fish&sort=paperId:asc");
+        assert_eq!(crawl.with_token("a b"), "query=fish&token=a%20b");
+    }
+}
@@ -5,6 +5,10 @@ pub mod relevance;
 pub use relevance::*;
 pub mod bulk;
 pub use bulk::*;
+pub mod crawl;
+pub use crawl::*;
+pub mod sample;
+pub use sample::*;
 pub mod paper_id;
 pub mod title;
 pub use title::*;
@@ -8,14 +8,14 @@
 //! - Can only return up to 10 MB of data at a time.
 
 use crate::{
-    error::{Error, Result},
+    error::Result,
     ss::{
-        S2NestedPaper, PaperField, PaperId,
+        FieldSpec, PaperField, PaperId, S2NestedPaper,
         client::{Query, SemanticScholar},
-        graph::BASE_URL,
-        merge_paper_fields,
+        graph::Endpoint,
+        merge_selected_fields, non_empty,
     },
-    utils::{Method, build_request},
+    utils::{Method, build_request, send},
 };
 use reqwest::StatusCode;
 
@@ -29,6 +29,8 @@ pub struct PaperIdSearchParam {
     id: PaperId,
     /// A comma-separated list of the fields to be returned.
     fields: Option<Vec<PaperField>>,
+    /// Fields restricted to some of their subfields
+    nested: Vec<FieldSpec>,
 }
 
 impl PaperIdSearchParam {
@@ -36,6 +38,7 @@ impl PaperIdSearchParam {
         Self {
             id: id.to_owned(),
             fields: None,
+            nested: Vec::new(),
         }
     }
 
@@ -48,12 +51,21 @@ impl PaperIdSearchParam {
         self
     }
 
+    /// Add fields with their subfields, e.g. built with [`fields!`](crate::fields)
+    pub fn select(&mut self, specs: impl IntoIterator<Item = FieldSpec>) -> &mut Self {
+        for spec in specs {
+            self.add_field(spec.field);
+            if !spec.subfields.is_empty() {
+                self.nested.push(spec);
+            }
+        }
+        self
+    }
+
     pub(crate) fn query_string(&self) -> String {
         let mut query_string = self.id.to_string();
-        if let Some(ref fields) = self.fields
-            && !fields.is_empty()
-        {
-            let fields_string = merge_paper_fields(fields);
+        if let Some(fields) = non_empty(&self.fields) {
+            let fields_string = merge_selected_fields(fields, &self.nested);
             query_string.push_str(&format!("?fields={}", fields_string));
         }
 
@@ -65,14 +77,20 @@ impl Query for PaperIdSearchParam {
     type Response = Option<S2NestedPaper>;
 
     async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
-        let url = format!("{}/paper/{}", BASE_URL, self.query_string());
+        Endpoint::PaperDetails.check_fields(self.fields.as_deref())?;
+        self.id.validate()?;
+        let mut param = self.clone();
+        if param.fields.is_none() {
+            param.fields = Endpoint::PaperDetails.default_fields(client.default_fields());
+        }
+        let url = format!("{}/paper/{}", client.graph_url(), param.query_string());
         let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
 
-        let resp = req_builder.send().await?;
-        match resp.status() {
-            StatusCode::OK => Ok(Some(resp.json().await?)),
+        let resp = send(req_builder, client.transport()).await?;
+        match resp.status {
+            StatusCode::OK => Ok(Some(resp.json()?)),
             StatusCode::NOT_FOUND => Ok(None),
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+            _ => Err(resp.into_error()),
         }
     }
 }
@@ -9,17 +9,32 @@
 //! - Can only return up to 10 MB of data at a time.
 
 use crate::{
-    error::{Error, Result},
+    error::Result,
     ss::{
-        _Date, Date, FieldOfStudy, S2NestedPaper, PaperField, PublicationType,
+        _Date, Date, FieldOfStudy, FieldsOfStudySource, PaperField, PublicationType, S2NestedPaper,
         client::{Query, SemanticScholar},
-        graph::BASE_URL,
-        merge_fields_of_study, merge_paper_fields, merge_publication_types,
+        graph::Endpoint,
+        merge_fields_of_study, merge_paper_fields, merge_publication_types, merge_venues,
+        non_empty, validate_date_filters,
     },
-    utils::{Method, build_request},
+    utils::{Method, build_request, encode_query_value, list_accessors, send},
 };
+#[cfg(feature = "stream")]
+use async_stream::stream;
+use chrono::NaiveDate;
+#[cfg(feature = "stream")]
+use futures::Stream;
 use reqwest::StatusCode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+
+/// Maximum number of relevance-ranked results the API can return for a query
+pub const RELEVANCE_SEARCH_MAX_RESULTS: u32 = 1000;
+
+/// Default page size of the relevance search
+const DEFAULT_PAGE_SIZE: u32 = 100;
 
 /// Query parameters for the paper search
 #[derive(Debug, Clone)]
@@ -62,12 +77,14 @@ pub struct PaperSearchParam {
     year: Option<(Option<u32>, Option<u32>)>,
     /// Restricts results to papers in the given fields of study, formatted as a comma-separated list.
     fields_of_study: Option<Vec<FieldOfStudy>>,
+    /// Classification the fields of study filter applies to (default: external).
+    fields_of_study_source: FieldsOfStudySource,
     /// Restricts results to papers published in the given venues, formatted as a comma-separated list.
     ///
     /// Input could also be an ISO4 abbreviation.
     venue: Option<Vec<String>>,
     /// Used for pagination. When returning a list of results, start with the element at this position in the list (default: 0).
-    offset: Option<u32>,
+    pub(crate) offset: Option<u32>,
     /// The maximum number of results to return (default: 100).
     ///
     /// Must be <= 100.
@@ -75,25 +92,30 @@ pub struct PaperSearchParam {
 }
 
 impl PaperSearchParam {
+    /// Parameters of the page starting at `offset`, its size cut to stay within the first
+    /// [`RELEVANCE_SEARCH_MAX_RESULTS`] results
+    pub(crate) fn at_offset(&self, offset: u32) -> Self {
+        let page_size = self.limit.map_or(DEFAULT_PAGE_SIZE, u32::from);
+        let limit = page_size.min(RELEVANCE_SEARCH_MAX_RESULTS.saturating_sub(offset));
+        let mut param = self.clone();
+        param.offset = Some(offset);
+        param.limit = Some(limit as u8);
+        param
+    }
+
     pub(crate) fn query_string(&self) -> String {
-        let mut query_string = format!("query={}", &self.query);
-        if let Some(ref fields) = self.fields
-            && !fields.is_empty()
-        {
+        let mut query_string = format!("query={}", encode_query_value(&self.query));
+        if let Some(fields) = non_empty(&self.fields) {
             let fields_string = merge_paper_fields(fields);
             query_string.push_str(&format!("&fields={}", fields_string));
         }
 
-        if let Some(ref publication_types) = self.publication_types
-            && !publication_types.is_empty()
-        {
+        if let Some(publication_types) = non_empty(&self.publication_types) {
             let publication_types_string = merge_publication_types(publication_types);
             query_string.push_str(&format!("&publicationTypes={}", publication_types_string));
         }
 
-        if let Some(open_access) = self.open_access_pdf
-            && open_access
-        {
+        if self.open_access_pdf == Some(true) {
             query_string.push_str("&openAccessPdf");
         }
 
@@ -131,18 +153,17 @@ impl PaperSearchParam {
             }
         }
 
-        if let Some(ref fields_of_study) = self.fields_of_study
-            && !fields_of_study.is_empty()
-        {
+        if let Some(fields_of_study) = non_empty(&self.fields_of_study) {
             let fields_of_study_string = merge_fields_of_study(fields_of_study);
-            query_string.push_str(&format!("&fieldsOfStudy={}", fields_of_study_string));
+            query_string.push_str(&format!(
+                "&{}={}",
+                self.fields_of_study_source.query_param(),
+                fields_of_study_string
+            ));
         }
 
-        if let Some(ref venue) = self.venue
-            && !venue.is_empty()
-        {
-            let venue_string = venue.join(",");
-            query_string.push_str(&format!("&venue={}", venue_string));
+        if let Some(venue) = non_empty(&self.venue) {
+            query_string.push_str(&format!("&venue={}", merge_venues(venue)));
         }
 
         if let Some(offset) = self.offset {
@@ -161,13 +182,21 @@ impl Query for PaperSearchParam {
     type Response = PaperSearchResponse;
 
     async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
-        let url = format!("{}/paper/search?{}", BASE_URL, self.query_string());
+        let mut param = self.clone();
+        if param.fields.is_none() {
+            param.fields = Endpoint::PaperSearch.default_fields(client.default_fields());
+        }
+        let url = format!(
+            "{}/paper/search?{}",
+            client.graph_url(),
+            param.query_string()
+        );
         let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
 
-        let resp = req_builder.send().await?;
-        match resp.status() {
-            StatusCode::OK => Ok(resp.json().await?),
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+        let resp = send(req_builder, client.transport()).await?;
+        match resp.status {
+            StatusCode::OK => resp.json(),
+            _ => Err(resp.into_error()),
         }
     }
 }
@@ -183,6 +212,7 @@ pub struct PaperSearchParamBuilder {
     publication_date: Option<(Option<_Date>, Option<_Date>)>,
     year: Option<(Option<u32>, Option<u32>)>,
     fields_of_study: Option<Vec<FieldOfStudy>>,
+    fields_of_study_source: FieldsOfStudySource,
     venue: Option<Vec<String>>,
     offset: Option<u32>,
     limit: Option<u8>,
@@ -266,6 +296,28 @@ impl PaperSearchParamBuilder {
         self
     }
 
+    /// Restricts results to papers published on or after the given date.
+    pub fn from_naive_date(&mut self, date: NaiveDate) -> &mut Self {
+        let date = _Date::from(date);
+        if let Some((ref mut start, _)) = self.publication_date {
+            *start = Some(date);
+        } else {
+            self.publication_date = Some((Some(date), None));
+        }
+        self
+    }
+
+    /// Restricts results to papers published on or before the given date.
+    pub fn to_naive_date(&mut self, date: NaiveDate) -> &mut Self {
+        let date = _Date::from(date);
+        if let Some((_, ref mut end)) = self.publication_date {
+            *end = Some(date);
+        } else {
+            self.publication_date = Some((None, Some(date)));
+        }
+        self
+    }
+
     /// Restricts results to the given publication year range (inclusive).
     pub fn from_year(&mut self, year: u32) -> &mut Self {
         if let Some((ref mut start, _)) = self.year {
@@ -300,6 +352,12 @@ impl PaperSearchParamBuilder {
         self
     }
 
+    /// Apply the fields of study filter to the external or the S2 classification
+    pub fn fields_of_study_source(&mut self, source: FieldsOfStudySource) -> &mut Self {
+        self.fields_of_study_source = source;
+        self
+    }
+
     /// Add a venue to the paper search parameters
     pub fn venue(&mut self, venue: &str) -> &mut Self {
         if let Some(ref mut venues) = self.venue {
@@ -325,27 +383,8 @@ impl PaperSearchParamBuilder {
 
     /// Build the paper search parameters
     pub fn build(&self) -> Result<PaperSearchParam> {
-        if let Some(year) = self.year
-            && let Some(start) = year.0
-            && let Some(end) = year.1
-            && start > end
-        {
-            return Err(Error::InvalidParameter(
-                "start year must be less than or equal to end year".to_string(),
-            ));
-        }
-
-        let publication_date = match self.publication_date {
-            Some((ref start, ref end)) => match (start.as_ref(), end.as_ref()) {
-                (Some(start), Some(end)) => {
-                    Some((Some(Date::try_from(start)?), Some(Date::try_from(end)?)))
-                }
-                (Some(start), None) => Some((Some(Date::try_from(start)?), None)),
-                (None, Some(end)) => Some((None, Some(Date::try_from(end)?))),
-                (None, None) => None,
-            },
-            None => None,
-        };
+        Endpoint::PaperSearch.check_fields(self.fields.as_deref())?;
+        let publication_date = validate_date_filters(self.publication_date.as_ref(), self.year)?;
 
         Ok(PaperSearchParam {
             query: self.query.clone(),
@@ -356,6 +395,7 @@ impl PaperSearchParamBuilder {
             publication_date,
             year: self.year,
             fields_of_study: self.fields_of_study.clone(),
+            fields_of_study_source: self.fields_of_study_source,
             venue: self.venue.clone(),
             offset: self.offset,
             limit: self.limit,
@@ -364,7 +404,8 @@ impl PaperSearchParamBuilder {
 }
 
 /// Response for the paper search
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PaperSearchResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total: Option<u32>,
@@ -376,6 +417,108 @@ pub struct PaperSearchResponse {
     pub data: Option<Vec<S2NestedPaper>>,
 }
 
+list_accessors!(PaperSearchResponse {
+    data: S2NestedPaper,
+});
+
+/// Paginates a relevance search, skipping papers already returned by previous pages
+///
+/// Relevance-ranked pages may overlap, so the pager remembers the ids it has yielded and
+/// stops once the API reports no further page or the 1,000-result ceiling is reached.
+#[derive(Debug, Clone)]
+pub struct PaperSearchPager<'a> {
+    client: &'a SemanticScholar,
+    param: PaperSearchParam,
+    offset: Option<u32>,
+    seen: HashSet<String>,
+}
+
+impl<'a> PaperSearchPager<'a> {
+    /// Create a new pager starting at the offset of the given parameters
+    pub fn new(client: &'a SemanticScholar, param: &PaperSearchParam) -> Self {
+        Self {
+            client,
+            param: param.clone(),
+            offset: Some(param.offset.unwrap_or(0)),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Fetch the next page, returning only papers that have not been seen before
+    ///
+    /// Returns `None` once the search is exhausted.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<S2NestedPaper>>> {
+        let Some(offset) = self.offset else {
+            return Ok(None);
+        };
+        if offset >= RELEVANCE_SEARCH_MAX_RESULTS {
+            self.offset = None;
+            return Ok(None);
+        }
+
+        let param = self.param.at_offset(offset);
+        let response = self.client.query(&param).await?;
+        let data = response.data.unwrap_or_default();
+        self.offset = match response.next {
+            Some(next) if !data.is_empty() && next > offset => Some(next),
+            _ => None,
+        };
+        Ok(Some(dedup_papers(&mut self.seen, data)))
+    }
+
+    /// Number of unique papers returned so far
+    pub fn unique_count(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Whether the search has been exhausted
+    pub fn is_done(&self) -> bool {
+        self.offset.is_none()
+    }
+
+    /// Fetch the remaining pages and return their unique papers
+    ///
+    /// At most 1,000 papers are returned, so collecting a search in memory stays cheap when
+    /// the `stream` feature is disabled.
+    pub async fn collect_papers(mut self) -> Result<Vec<S2NestedPaper>> {
+        let mut papers = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            papers.extend(page);
+        }
+        Ok(papers)
+    }
+
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    /// Turn the pager into a stream of unique papers
+    pub fn into_stream(mut self) -> Pin<Box<dyn Stream<Item = Result<S2NestedPaper>> + Send + 'a>> {
+        Box::pin(stream! {
+            loop {
+                match self.next_page().await {
+                    Ok(Some(papers)) => {
+                        for paper in papers {
+                            yield Ok(paper);
+                        }
+                    }
+                    Ok(None) => return,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Keep the papers whose ids have not been seen yet, recording them as seen
+fn dedup_papers(seen: &mut HashSet<String>, papers: Vec<S2NestedPaper>) -> Vec<S2NestedPaper> {
+    papers
+        .into_iter()
+        .filter(|paper| seen.insert(paper.paper_id.clone()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -404,4 +547,40 @@ mod tests {
             Some(vec![FieldOfStudy::ComputerScience])
         );
     }
+
+    #[test]
+    fn test_fields_of_study_source() {
+        let mut builder = PaperSearchParamBuilder::new("test");
+        builder.field_of_study(FieldOfStudy::Physics);
+        let param = builder.build().unwrap();
+        assert!(param.query_string().contains("&fieldsOfStudy=Physics"));
+
+        builder.fields_of_study_source(FieldsOfStudySource::S2);
+        let param = builder.build().unwrap();
+        assert!(param.query_string().contains("&s2FieldsOfStudy=Physics"));
+    }
+
+    #[test]
+    fn test_dedup_papers() {
+        let page = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| {
+                    serde_json::from_value::<S2NestedPaper>(serde_json::json!({ "paperId": id }))
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        let ids = |papers: Vec<S2NestedPaper>| {
+            papers
+                .into_iter()
+                .map(|paper| paper.paper_id)
+                .collect::<Vec<_>>()
+        };
+        let mut seen = HashSet::new();
+        let first = dedup_papers(&mut seen, page(&["a", "b", "a"]));
+        let second = dedup_papers(&mut seen, page(&["b", "c"]));
+        assert_eq!(ids(first), vec!["a", "b"]);
+        assert_eq!(ids(second), vec!["c"]);
+        assert_eq!(seen.len(), 3);
+    }
 }
@@ -0,0 +1,152 @@
+//! Bulk Search Sampling
+//!
+//! Draws a representative subset of a [bulk search](super::bulk) while paginating, without
+//! downloading every matching record.
+//!
+//! Paper ids are content hashes, so the papers sorted by id come in an order unrelated to
+//! their content: the first papers of that order are a uniform sample of the whole result
+//! set. Stratified samples query each year separately, splitting the sample size by the
+//! estimated number of papers per year, and top-cited samples sort by citation count.
+
+use crate::{
+    error::{Error, Result},
+    ss::{
+        Cursor, FetchBudget, S2Paper, TokenPaginated,
+        client::SemanticScholar,
+        graph::search::{PaperBulkSearchParam, SortBy, SortOrder},
+    },
+};
+
+/// How papers are picked from a bulk search
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SampleStrategy {
+    /// Every matching paper has the same chance to be picked
+    #[default]
+    Uniform,
+    /// Each year of the search year range gets a share proportional to its number of papers
+    StratifiedByYear,
+    /// The most cited papers
+    TopCited,
+}
+
+impl SemanticScholar {
+    /// Sample `n` papers from a bulk search
+    ///
+    /// Only the pages holding the sampled papers are fetched. The sort order of the search is
+    /// replaced by the one of the strategy. [`SampleStrategy::StratifiedByYear`] needs a
+    /// search restricted to a range of years with both ends set, and sends at least one
+    /// request per year.
+    pub async fn sample(
+        &self,
+        param: &PaperBulkSearchParam,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<S2Paper>> {
+        let mut param = param.clone();
+        param.token = None;
+        match strategy {
+            SampleStrategy::Uniform => {
+                param.sort = Some(SortBy::PaperId(SortOrder::Ascending));
+                self.first(&param, n).await
+            }
+            SampleStrategy::TopCited => {
+                param.sort = Some(SortBy::CitationCount(SortOrder::Descending));
+                self.first(&param, n).await
+            }
+            SampleStrategy::StratifiedByYear => {
+                let Some((Some(start), Some(end))) = param.year else {
+                    return Err(Error::InvalidParameter(
+                        "stratified sampling needs a year range with both ends".to_owned(),
+                    ));
+                };
+                param.sort = Some(SortBy::PaperId(SortOrder::Ascending));
+                self.stratified(&param, n, start..=end).await
+            }
+        }
+    }
+
+    /// The first `n` papers of a search
+    async fn first(&self, param: &PaperBulkSearchParam, n: usize) -> Result<Vec<S2Paper>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        let budget = FetchBudget {
+            max_items: Some(n),
+            ..Default::default()
+        };
+        Ok(self.fetch_all(param, budget).await?.items)
+    }
+
+    /// Papers of each year, as many as the year's share of `n`
+    async fn stratified(
+        &self,
+        param: &PaperBulkSearchParam,
+        n: usize,
+        years: std::ops::RangeInclusive<u32>,
+    ) -> Result<Vec<S2Paper>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        let mut strata = Vec::new();
+        for year in years {
+            let mut param = param.clone();
+            param.year = Some((Some(year), Some(year)));
+            let page = self.page(&param).await?;
+            let total = page
+                .total_estimate
+                .map_or(page.items.len(), |total| total as usize);
+            strata.push((param, page.items, page.next, total));
+        }
+
+        let quotas = allocate(n, &strata.iter().map(|s| s.3).collect::<Vec<_>>());
+        let mut sample = Vec::new();
+        for ((param, mut papers, next, _), quota) in strata.into_iter().zip(quotas) {
+            if let Some(Cursor::Token(token)) = next.filter(|_| papers.len() < quota) {
+                let rest = param.with_token(&token);
+                papers.extend(self.first(&rest, quota - papers.len()).await?);
+            }
+            papers.truncate(quota);
+            sample.extend(papers);
+        }
+        Ok(sample)
+    }
+}
+
+/// Split `n` into shares proportional to `totals` by largest remainder, capped by each total
+fn allocate(n: usize, totals: &[usize]) -> Vec<usize> {
+    let sum = totals.iter().sum::<usize>();
+    if sum <= n {
+        return totals.to_vec();
+    }
+    let exact = totals
+        .iter()
+        .map(|&total| (n * total) as f64 / sum as f64)
+        .collect::<Vec<_>>();
+    let mut quotas = exact
+        .iter()
+        .map(|share| *share as usize)
+        .collect::<Vec<_>>();
+    let mut order = (0..totals.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        let remainder = |i: usize| exact[i] - quotas[i] as f64;
+        remainder(b).total_cmp(&remainder(a)).then(a.cmp(&b))
+    });
+    let left = n - quotas.iter().sum::<usize>();
+    for &i in order.iter().take(left) {
+        quotas[i] += 1;
+    }
+    quotas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate() {
+        assert_eq!(allocate(10, &[50, 30, 20]), vec![5, 3, 2]);
+        assert_eq!(allocate(10, &[1, 1, 1]), vec![1, 1, 1]);
+        assert_eq!(allocate(4, &[10, 10, 10]), vec![2, 1, 1]);
+        assert_eq!(allocate(3, &[100, 0, 1]), vec![3, 0, 0]);
+    }
+}
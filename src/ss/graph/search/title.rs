@@ -5,16 +5,17 @@
 //! `/paper/search/match?query={query}`
 
 use crate::{
-    error::{Error, Result},
+    error::Result,
     ss::{
-        _Date, S2Author, CitationStyles, Date, Embedding, ExternalIds, FieldOfStudy, Journal,
-        OpenAccessPdf, PaperField, PublicationType, PublicationVenue, S2FieldsOfStudy,
-        S2NestedPaper, S2Paper,
+        _Date, CitationStyles, Date, Embedding, ExternalIds, FieldOfStudy, FieldsOfStudySource,
+        Journal, OpenAccessPdf, PaperField, PublicationType, PublicationVenue, S2Author,
+        S2FieldsOfStudy, S2NestedPaper, S2Paper, Tldr,
         client::{Query, SemanticScholar},
-        graph::BASE_URL,
-        merge_fields_of_study, merge_paper_fields, merge_publication_types,
+        graph::Endpoint,
+        merge_fields_of_study, merge_paper_fields, merge_publication_types, merge_venues,
+        non_empty, validate_date_filters,
     },
-    utils::{Method, build_request},
+    utils::{Method, build_request, encode_query_value, null_as_default, send},
 };
 use chrono::NaiveDate;
 use reqwest::StatusCode;
@@ -31,29 +32,24 @@ pub struct PaperTitleSearchParam {
     publication_date: Option<(Option<Date>, Option<Date>)>,
     year: Option<(Option<u32>, Option<u32>)>,
     fields_of_study: Option<Vec<FieldOfStudy>>,
+    fields_of_study_source: FieldsOfStudySource,
     venue: Option<Vec<String>>,
 }
 
 impl PaperTitleSearchParam {
     pub(crate) fn query_string(&self) -> String {
-        let mut query_string = format!("query={}", &self.query);
-        if let Some(ref fields) = self.fields
-            && !fields.is_empty()
-        {
+        let mut query_string = format!("query={}", encode_query_value(&self.query));
+        if let Some(fields) = non_empty(&self.fields) {
             let fields_string = merge_paper_fields(fields);
             query_string.push_str(&format!("&fields={}", fields_string));
         }
 
-        if let Some(ref publication_types) = self.publication_types
-            && !publication_types.is_empty()
-        {
+        if let Some(publication_types) = non_empty(&self.publication_types) {
             let publication_types_string = merge_publication_types(publication_types);
             query_string.push_str(&format!("&publicationTypes={}", publication_types_string));
         }
 
-        if let Some(open_access) = self.open_access_pdf
-            && open_access
-        {
+        if self.open_access_pdf == Some(true) {
             query_string.push_str("&openAccessPdf");
         }
 
@@ -91,18 +87,17 @@ impl PaperTitleSearchParam {
             }
         }
 
-        if let Some(ref fields_of_study) = self.fields_of_study
-            && !fields_of_study.is_empty()
-        {
+        if let Some(fields_of_study) = non_empty(&self.fields_of_study) {
             let fields_of_study_string = merge_fields_of_study(fields_of_study);
-            query_string.push_str(&format!("&fieldsOfStudy={}", fields_of_study_string));
+            query_string.push_str(&format!(
+                "&{}={}",
+                self.fields_of_study_source.query_param(),
+                fields_of_study_string
+            ));
         }
 
-        if let Some(ref venue) = self.venue
-            && !venue.is_empty()
-        {
-            let venue_string = venue.join(",");
-            query_string.push_str(&format!("&venue={}", venue_string));
+        if let Some(venue) = non_empty(&self.venue) {
+            query_string.push_str(&format!("&venue={}", merge_venues(venue)));
         }
 
         query_string
@@ -113,13 +108,21 @@ impl Query for PaperTitleSearchParam {
     type Response = Option<MatchedPaper>;
 
     async fn query(&self, client: &SemanticScholar) -> Result<Self::Response> {
-        let url = format!("{}/paper/search/match?{}", BASE_URL, self.query_string());
+        let mut param = self.clone();
+        if param.fields.is_none() {
+            param.fields = Endpoint::PaperTitleSearch.default_fields(client.default_fields());
+        }
+        let url = format!(
+            "{}/paper/search/match?{}",
+            client.graph_url(),
+            param.query_string()
+        );
         let req_builder = build_request(client.client(), Method::Get, &url, client.api_key());
 
-        let resp = req_builder.send().await?;
-        match resp.status() {
+        let resp = send(req_builder, client.transport()).await?;
+        match resp.status {
             StatusCode::OK => {
-                let result = resp.json::<PaperTitleSearchResponse>().await?;
+                let result = resp.json::<PaperTitleSearchResponse>()?;
                 if let Some(paper) = result.data.first() {
                     Ok(Some(paper.clone().into()))
                 } else {
@@ -127,7 +130,7 @@ impl Query for PaperTitleSearchParam {
                 }
             }
             StatusCode::NOT_FOUND => Ok(None),
-            _ => Err(Error::RequestFailed(resp.text().await?)),
+            _ => Err(resp.into_error()),
         }
     }
 }
@@ -143,6 +146,7 @@ pub struct PaperTitleSearchParamBuilder {
     publication_date: Option<(Option<_Date>, Option<_Date>)>,
     year: Option<(Option<u32>, Option<u32>)>,
     fields_of_study: Option<Vec<FieldOfStudy>>,
+    fields_of_study_source: FieldsOfStudySource,
     venue: Option<Vec<String>>,
 }
 
@@ -224,6 +228,28 @@ impl PaperTitleSearchParamBuilder {
         self
     }
 
+    /// Restricts results to papers published on or after the given date.
+    pub fn from_naive_date(&mut self, date: NaiveDate) -> &mut Self {
+        let date = _Date::from(date);
+        if let Some((ref mut start, _)) = self.publication_date {
+            *start = Some(date);
+        } else {
+            self.publication_date = Some((Some(date), None));
+        }
+        self
+    }
+
+    /// Restricts results to papers published on or before the given date.
+    pub fn to_naive_date(&mut self, date: NaiveDate) -> &mut Self {
+        let date = _Date::from(date);
+        if let Some((_, ref mut end)) = self.publication_date {
+            *end = Some(date);
+        } else {
+            self.publication_date = Some((None, Some(date)));
+        }
+        self
+    }
+
     /// Restricts results to the given publication year range (inclusive).
     pub fn from_year(&mut self, year: u32) -> &mut Self {
         if let Some((ref mut start, _)) = self.year {
@@ -258,6 +284,12 @@ impl PaperTitleSearchParamBuilder {
         self
     }
 
+    /// Apply the fields of study filter to the external or the S2 classification
+    pub fn fields_of_study_source(&mut self, source: FieldsOfStudySource) -> &mut Self {
+        self.fields_of_study_source = source;
+        self
+    }
+
     /// Add a venue to the paper search parameters
     pub fn venue(&mut self, venue: &str) -> &mut Self {
         if let Some(ref mut venues) = self.venue {
@@ -270,27 +302,8 @@ impl PaperTitleSearchParamBuilder {
 
     /// Build the paper search parameters
     pub fn build(&self) -> Result<PaperTitleSearchParam> {
-        if let Some(year) = self.year
-            && let Some(start) = year.0
-            && let Some(end) = year.1
-            && start > end
-        {
-            return Err(Error::InvalidParameter(
-                "start year must be less than or equal to end year".to_string(),
-            ));
-        }
-
-        let publication_date = match self.publication_date {
-            Some((ref start, ref end)) => match (start.as_ref(), end.as_ref()) {
-                (Some(start), Some(end)) => {
-                    Some((Some(Date::try_from(start)?), Some(Date::try_from(end)?)))
-                }
-                (Some(start), None) => Some((Some(Date::try_from(start)?), None)),
-                (None, Some(end)) => Some((None, Some(Date::try_from(end)?))),
-                (None, None) => None,
-            },
-            None => None,
-        };
+        Endpoint::PaperTitleSearch.check_fields(self.fields.as_deref())?;
+        let publication_date = validate_date_filters(self.publication_date.as_ref(), self.year)?;
 
         Ok(PaperTitleSearchParam {
             query: self.query.clone(),
@@ -301,6 +314,7 @@ impl PaperTitleSearchParamBuilder {
             publication_date,
             year: self.year,
             fields_of_study: self.fields_of_study.clone(),
+            fields_of_study_source: self.fields_of_study_source,
             venue: self.venue.clone(),
         })
     }
@@ -308,10 +322,11 @@ impl PaperTitleSearchParamBuilder {
 
 #[derive(Debug, Clone, Deserialize)]
 struct PaperTitleSearchResponse {
+    #[serde(default, deserialize_with = "null_as_default")]
     data: Vec<InnerPaperTitleSearchResponse>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MatchedPaper {
     pub score: f64,
     pub paper: S2NestedPaper,
@@ -363,6 +378,8 @@ struct InnerPaperTitleSearchResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     citation_styles: Option<CitationStyles>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    tldr: Option<Tldr>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     authors: Option<Vec<S2Author>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     citations: Option<Vec<S2Paper>>,
@@ -399,6 +416,7 @@ impl From<InnerPaperTitleSearchResponse> for MatchedPaper {
                 publication_date: response.publication_date,
                 journal: response.journal,
                 citation_styles: response.citation_styles,
+                tldr: response.tldr,
                 authors: response.authors,
                 text_availability: response.text_availability,
                 citations: response.citations,
@@ -0,0 +1,146 @@
+//! Concurrent queries
+//!
+//! [`SemanticScholar::query_many`] runs a set of queries at once through a [`Scheduler`],
+//! which bounds the requests in flight, spaces them out and retries transient failures, and
+//! gathers their responses according to an [`ErrorPolicy`].
+
+use crate::{
+    error::{Error, Result},
+    scheduler::{Api, Scheduler},
+    ss::client::{Query, SemanticScholar},
+};
+use futures::{StreamExt, stream::FuturesUnordered};
+
+/// What to do with the queries that fail
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Return the first error, cancelling the queries still running
+    #[default]
+    FailFast,
+    /// Run every query and report the errors next to the responses
+    CollectErrors,
+    /// Run every query and drop the errors
+    SkipErrors,
+}
+
+/// Responses and errors of concurrent queries
+///
+/// Both lists hold the index of the query in the given parameters and are sorted by it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryResults<T> {
+    /// Responses of the successful queries
+    pub responses: Vec<(usize, T)>,
+    /// Errors of the failed queries, always empty with [`ErrorPolicy::SkipErrors`]
+    pub errors: Vec<(usize, Error)>,
+}
+
+impl<T> QueryResults<T> {
+    /// Whether every query succeeded
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Responses without their indices
+    pub fn into_responses(self) -> Vec<T> {
+        self.responses
+            .into_iter()
+            .map(|(_, response)| response)
+            .collect()
+    }
+}
+
+impl SemanticScholar {
+    /// Run queries concurrently within the limits of a scheduler
+    ///
+    /// Queries of different kinds can run together through an enum implementing [`Query`].
+    /// Transient failures are retried by the scheduler first; the errors left are handled
+    /// according to `policy`, and only [`ErrorPolicy::FailFast`] returns an error.
+    pub async fn query_many<Q: Query>(
+        &self,
+        scheduler: &Scheduler,
+        params: &[Q],
+        policy: ErrorPolicy,
+    ) -> Result<QueryResults<Q::Response>> {
+        let mut pending = params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| async move {
+                let result = scheduler
+                    .run(Api::SemanticScholar, || self.query(param))
+                    .await;
+                (i, result)
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        let mut results = QueryResults {
+            responses: Vec::with_capacity(params.len()),
+            errors: Vec::new(),
+        };
+        while let Some((i, result)) = pending.next().await {
+            match (result, policy) {
+                (Ok(response), _) => results.responses.push((i, response)),
+                (Err(e), ErrorPolicy::FailFast) => return Err(e),
+                (Err(e), ErrorPolicy::CollectErrors) => results.errors.push((i, e)),
+                (Err(_), ErrorPolicy::SkipErrors) => {}
+            }
+        }
+        results.responses.sort_unstable_by_key(|(i, _)| *i);
+        results.errors.sort_unstable_by_key(|(i, _)| *i);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Query answering its number, failing for odd ones
+    struct Even(u32);
+
+    impl Query for Even {
+        type Response = u32;
+
+        async fn query(&self, _client: &SemanticScholar) -> Result<u32> {
+            if self.0 % 2 == 0 {
+                Ok(self.0)
+            } else {
+                Err(Error::InvalidParameter(format!("{} is odd", self.0)))
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_many() {
+        let client = SemanticScholar::default();
+        let scheduler = Scheduler::builder()
+            .semantic_scholar_interval(Duration::ZERO)
+            .build()
+            .unwrap();
+        let params = (0..6).map(Even).collect::<Vec<_>>();
+
+        let results = client
+            .query_many(&scheduler, &params, ErrorPolicy::CollectErrors)
+            .await
+            .unwrap();
+        assert_eq!(results.responses, vec![(0, 0), (2, 2), (4, 4)]);
+        assert_eq!(
+            results.errors.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![1, 3, 5]
+        );
+
+        let results = client
+            .query_many(&scheduler, &params, ErrorPolicy::SkipErrors)
+            .await
+            .unwrap();
+        assert!(results.is_complete());
+        assert_eq!(results.into_responses(), vec![0, 2, 4]);
+
+        assert!(
+            client
+                .query_many(&scheduler, &params, ErrorPolicy::FailFast)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,8 +1,39 @@
 //! Semantic Scholar API
+//!
+//! The models are shared with the Connected Papers client and always compiled; the client and
+//! its endpoints need the `semantic-scholar` feature.
 
+#[cfg(feature = "semantic-scholar")]
 pub mod client;
+#[cfg(feature = "semantic-scholar")]
 pub use client::*;
+#[cfg(feature = "semantic-scholar")]
+pub mod disambiguation;
+#[cfg(feature = "semantic-scholar")]
+pub use disambiguation::*;
+#[cfg(feature = "semantic-scholar")]
+pub mod embedding;
+#[cfg(feature = "semantic-scholar")]
+pub use embedding::*;
+#[cfg(feature = "semantic-scholar")]
 pub mod graph;
+#[cfg(feature = "semantic-scholar")]
 pub use graph::*;
+#[cfg(feature = "semantic-scholar")]
+pub mod many;
+#[cfg(feature = "semantic-scholar")]
+pub use many::*;
 pub mod models;
 pub use models::*;
+#[cfg(feature = "semantic-scholar")]
+pub mod page;
+#[cfg(feature = "semantic-scholar")]
+pub use page::*;
+#[cfg(feature = "semantic-scholar")]
+pub mod raw;
+#[cfg(feature = "semantic-scholar")]
+pub use raw::*;
+#[cfg(feature = "semantic-scholar")]
+pub mod select;
+#[cfg(feature = "semantic-scholar")]
+pub use select::*;
@@ -1,11 +1,35 @@
 //! Models for the Semantic Scholar Graph API
 //!
 
-use crate::error::{Error, Result};
+#[cfg(feature = "semantic-scholar")]
+use crate::utils::encode_query_value;
+use crate::{
+    doi::{normalize_doi, parse_doi},
+    error::{Error, Result},
+    utils::list_accessors,
+};
+#[cfg(feature = "semantic-scholar")]
+use chrono::Months;
 use chrono::{Datelike, NaiveDate};
+use reqwest::Url;
 use serde::{Deserialize, Serialize, Serializer};
 use std::collections::HashSet;
 
+/// Hosts of the paper URLs Semantic Scholar resolves, subdomains included
+pub const SUPPORTED_URL_HOSTS: &[&str] = &[
+    "semanticscholar.org",
+    "arxiv.org",
+    "aclweb.org",
+    "aclanthology.org",
+    "acm.org",
+    "biorxiv.org",
+];
+
+/// Tracking parameters dropped from paper URLs, `utm_` ones included
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "msclkid", "mc_cid", "mc_eid", "_ga", "_hsenc", "_hsmi",
+];
+
 /// Paper ID
 #[derive(Debug, Clone, PartialEq)]
 pub enum PaperId {
@@ -42,10 +66,12 @@ impl PaperId {
         PaperId::CorpusId(id)
     }
 
-    /// Create a DOI from a string-like value
+    /// Create a DOI from a string-like value, normalized with [`normalize_doi`]
+    ///
+    /// The syntax is checked when queried, see [`PaperId::checked_doi`] to check it upfront.
     #[inline]
     pub fn doi<S: Into<String>>(s: S) -> Self {
-        PaperId::DOI(s.into())
+        PaperId::DOI(normalize_doi(&s.into()))
     }
 
     /// Create an arXiv ID from a string-like value
@@ -61,11 +87,34 @@ impl PaperId {
     }
 
     /// Create a URL from a string-like value
+    ///
+    /// The URL is checked against [`SUPPORTED_URL_HOSTS`] when queried, see
+    /// [`PaperId::checked_url`] to check and normalize it upfront.
     #[inline]
     pub fn url<S: Into<String>>(s: S) -> Self {
         PaperId::URL(s.into())
     }
 
+    /// Create a URL after checking its host and normalizing it
+    ///
+    /// The URL is switched to HTTPS and loses its fragment and tracking parameters such as
+    /// `utm_source`; arXiv PDF, HTML and mirror links become the abstract page, e.g.
+    /// `http://export.arxiv.org/pdf/2106.15928v1.pdf` becomes
+    /// `https://arxiv.org/abs/2106.15928v1`. Fails with [`Error::UnsupportedUrlHost`] for
+    /// a host Semantic Scholar does not resolve.
+    pub fn checked_url(url: &str) -> Result<Self> {
+        normalize_paper_url(url).map(PaperId::URL)
+    }
+
+    /// Check the syntax of a DOI and the host of a URL, other IDs being always valid
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            PaperId::DOI(doi) => parse_doi(doi).map(drop),
+            PaperId::URL(url) => normalize_paper_url(url).map(drop),
+            _ => Ok(()),
+        }
+    }
+
     /// Create a PubMed ID from a numerical value
     #[inline]
     pub fn pubmed(id: u64) -> Self {
@@ -85,6 +134,55 @@ impl PaperId {
     }
 }
 
+/// Check the host of a paper URL and normalize it, see [`PaperId::checked_url`]
+fn normalize_paper_url(url: &str) -> Result<String> {
+    let url = url.trim();
+    let mut parsed = Url::parse(url)
+        .or_else(|_| Url::parse(&format!("https://{}", url)))
+        .map_err(|e| Error::InvalidParameter(format!("invalid paper URL {}: {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(Error::InvalidParameter(format!(
+            "paper URL must use HTTP or HTTPS: {}",
+            url
+        )));
+    }
+    let host = parsed.host_str().unwrap_or_default().to_owned();
+    let is_within = |domain: &str| {
+        host == domain
+            || host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.'))
+    };
+    if !SUPPORTED_URL_HOSTS.iter().any(|domain| is_within(domain)) {
+        return Err(Error::UnsupportedUrlHost(host));
+    }
+
+    if is_within("arxiv.org") {
+        let path = parsed.path().trim_end_matches('/');
+        let id = ["/abs/", "/pdf/", "/html/", "/format/"]
+            .iter()
+            .find_map(|prefix| path.strip_prefix(prefix))
+            .map(|id| id.trim_end_matches(".pdf"));
+        if let Some(id) = id.filter(|id| !id.is_empty()) {
+            return Ok(format!("https://arxiv.org/abs/{}", id));
+        }
+    }
+
+    let _ = parsed.set_scheme("https");
+    parsed.set_fragment(None);
+    let params = parsed
+        .query_pairs()
+        .filter(|(key, _)| !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_ref()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    if params.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(params);
+    }
+    Ok(parsed.into())
+}
+
 impl std::fmt::Display for PaperId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -170,7 +268,53 @@ impl std::fmt::Display for PaperField {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+impl PaperField {
+    /// Fields summarizing a paper, as requested by the service and the language bindings
+    pub const SUMMARY: [PaperField; 9] = [
+        PaperField::Title,
+        PaperField::Year,
+        PaperField::Authors,
+        PaperField::ExternalIds,
+        PaperField::Abstract,
+        PaperField::Venue,
+        PaperField::CitationCount,
+        PaperField::URL,
+        PaperField::OpenAccessPDF,
+    ];
+}
+
+/// Author field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuthorField {
+    ExternalIds,
+    URL,
+    Name,
+    Affiliations,
+    Homepage,
+    PaperCount,
+    CitationCount,
+    HIndex,
+    Papers,
+}
+
+impl std::fmt::Display for AuthorField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthorField::ExternalIds => write!(f, "externalIds"),
+            AuthorField::URL => write!(f, "url"),
+            AuthorField::Name => write!(f, "name"),
+            AuthorField::Affiliations => write!(f, "affiliations"),
+            AuthorField::Homepage => write!(f, "homepage"),
+            AuthorField::PaperCount => write!(f, "paperCount"),
+            AuthorField::CitationCount => write!(f, "citationCount"),
+            AuthorField::HIndex => write!(f, "hIndex"),
+            AuthorField::Papers => write!(f, "papers"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "PascalCase")]
 pub enum FieldOfStudy {
     #[serde(rename = "Computer Science")]
@@ -235,44 +379,228 @@ impl std::fmt::Display for FieldOfStudy {
     }
 }
 
-/// Merge paper fields into a comma-separated string
-pub(crate) fn merge_paper_fields(fields: &[PaperField]) -> String {
-    fields
-        .iter()
-        .copied()
-        .collect::<HashSet<_>>()
+impl FieldOfStudy {
+    /// Every field of study
+    pub const ALL: [FieldOfStudy; 23] = [
+        FieldOfStudy::ComputerScience,
+        FieldOfStudy::Medicine,
+        FieldOfStudy::Chemistry,
+        FieldOfStudy::Biology,
+        FieldOfStudy::MaterialsScience,
+        FieldOfStudy::Physics,
+        FieldOfStudy::Geology,
+        FieldOfStudy::Psychology,
+        FieldOfStudy::Art,
+        FieldOfStudy::History,
+        FieldOfStudy::Geography,
+        FieldOfStudy::Sociology,
+        FieldOfStudy::Business,
+        FieldOfStudy::PoliticalScience,
+        FieldOfStudy::Economics,
+        FieldOfStudy::Philosophy,
+        FieldOfStudy::Mathematics,
+        FieldOfStudy::Engineering,
+        FieldOfStudy::EnvironmentalScience,
+        FieldOfStudy::AgriculturalAndFoodSciences,
+        FieldOfStudy::Education,
+        FieldOfStudy::Law,
+        FieldOfStudy::Linguistics,
+    ];
+}
+
+/// Classification a fields of study filter applies to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FieldsOfStudySource {
+    /// Fields of study from external sources, filtered with `fieldsOfStudy`
+    #[default]
+    External,
+    /// Fields of study from Semantic Scholar's own classifier, filtered with `s2FieldsOfStudy`
+    S2,
+}
+
+impl FieldsOfStudySource {
+    /// Name of the query parameter for the filter
+    #[cfg(feature = "semantic-scholar")]
+    pub(crate) fn query_param(self) -> &'static str {
+        match self {
+            FieldsOfStudySource::External => "fieldsOfStudy",
+            FieldsOfStudySource::S2 => "s2FieldsOfStudy",
+        }
+    }
+}
+
+impl TryFrom<&str> for FieldOfStudy {
+    type Error = Error;
+
+    /// Parse a category name such as `Computer Science`, ignoring case and spacing
+    fn try_from(value: &str) -> Result<Self> {
+        let normalize = |s: &str| {
+            s.chars()
+                .filter(|c| !c.is_whitespace())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        };
+        let value_normalized = normalize(value);
+        FieldOfStudy::ALL
+            .into_iter()
+            .find(|field| normalize(&field.to_string()) == value_normalized)
+            .ok_or_else(|| Error::InvalidParameter(format!("unknown field of study: {}", value)))
+    }
+}
+
+/// A field of study together with the classifier that assigned it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LabeledFieldOfStudy {
+    pub field: FieldOfStudy,
+    /// Source of the classification, e.g. `external` or `s2-fos-model`
+    pub source: String,
+}
+
+/// Merge the external and S2 classifications of a paper, dropping duplicates and unknown categories
+fn all_fields_of_study(
+    fields_of_study: &[FieldOfStudy],
+    s2_fields_of_study: &[S2FieldsOfStudy],
+) -> Vec<LabeledFieldOfStudy> {
+    let external = fields_of_study.iter().map(|&field| LabeledFieldOfStudy {
+        field,
+        source: "external".to_owned(),
+    });
+    let s2 = s2_fields_of_study.iter().filter_map(|s2| {
+        Some(LabeledFieldOfStudy {
+            field: FieldOfStudy::try_from(s2.category.as_deref()?).ok()?,
+            source: s2.source.clone().unwrap_or_else(|| "s2".to_owned()),
+        })
+    });
+    let mut labeled = Vec::new();
+    for field in external.chain(s2) {
+        if !labeled.contains(&field) {
+            labeled.push(field);
+        }
+    }
+    labeled
+}
+
+/// Count the papers classified under each field of study, most frequent first
+///
+/// A paper counts once per field, whatever the number of sources agreeing on it.
+pub fn field_of_study_counts(
+    papers: impl IntoIterator<Item = Vec<LabeledFieldOfStudy>>,
+) -> Vec<(FieldOfStudy, usize)> {
+    let mut counts = std::collections::HashMap::new();
+    for labeled in papers {
+        let fields = labeled
+            .into_iter()
+            .map(|labeled| labeled.field)
+            .collect::<HashSet<_>>();
+        for field in fields {
+            *counts.entry(field).or_insert(0) += 1;
+        }
+    }
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| {
+        b.1.cmp(&a.1)
+            .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+    });
+    counts
+}
+
+impl S2NestedPaper {
+    /// Create a paper with the given id and no other field set
+    pub fn new(paper_id: &str) -> Self {
+        Self {
+            paper_id: paper_id.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// External and S2 fields of study of the paper, labeled with their source
+    pub fn all_fields_of_study(&self) -> Vec<LabeledFieldOfStudy> {
+        all_fields_of_study(self.fields_of_study(), self.s2_fields_of_study())
+    }
+}
+
+impl S2Paper {
+    /// External and S2 fields of study of the paper, labeled with their source
+    pub fn all_fields_of_study(&self) -> Vec<LabeledFieldOfStudy> {
+        all_fields_of_study(self.fields_of_study(), self.s2_fields_of_study())
+    }
+}
+
+list_accessors!(S2NestedPaper {
+    authors: S2Author,
+    fields_of_study: FieldOfStudy,
+    s2_fields_of_study: S2FieldsOfStudy,
+    publication_types: PublicationType,
+    citations: S2Paper,
+    references: S2Paper,
+});
+
+list_accessors!(S2Paper {
+    authors: S2Author,
+    fields_of_study: FieldOfStudy,
+    s2_fields_of_study: S2FieldsOfStudy,
+    publication_types: PublicationType,
+});
+
+list_accessors!(S2Author {
+    affiliations: String,
+    papers: S2Paper,
+});
+
+/// The items of an optional list, `None` if the list is missing or empty
+#[cfg(feature = "semantic-scholar")]
+pub(crate) fn non_empty<T>(list: &Option<Vec<T>>) -> Option<&[T]> {
+    list.as_deref().filter(|list| !list.is_empty())
+}
+
+/// Join the distinct items with commas, in the order they were first given
+///
+/// The order must not depend on hashing, so that the same parameters always produce the
+/// same query string, e.g. for the crawl checkpoints keyed by it.
+#[cfg(feature = "semantic-scholar")]
+fn merge_distinct<T: PartialEq>(items: &[T], show: impl Fn(&T) -> String) -> String {
+    items
         .iter()
-        .map(|f| f.to_string())
-        .collect::<Vec<String>>()
+        .enumerate()
+        .filter(|(i, item)| !items[..*i].contains(item))
+        .map(|(_, item)| show(item))
+        .collect::<Vec<_>>()
         .join(",")
 }
 
+/// Merge paper fields into a comma-separated string
+#[cfg(feature = "semantic-scholar")]
+pub(crate) fn merge_paper_fields(fields: &[PaperField]) -> String {
+    merge_distinct(fields, PaperField::to_string)
+}
+
 /// Merge paper publication types into a comma-separated string
+#[cfg(feature = "semantic-scholar")]
 pub(crate) fn merge_publication_types(types: &[PublicationType]) -> String {
-    types
-        .iter()
-        .copied()
-        .collect::<HashSet<_>>()
-        .iter()
-        .map(|f| f.to_string())
-        .collect::<Vec<String>>()
-        .join(",")
+    merge_distinct(types, PublicationType::to_string)
+}
+
+/// Merge author fields into a comma-separated string
+#[cfg(feature = "semantic-scholar")]
+pub(crate) fn merge_author_fields(fields: &[AuthorField]) -> String {
+    merge_distinct(fields, AuthorField::to_string)
 }
 
-/// Merge paper fields of study into a comma-separated string
+/// Merge paper fields of study into a comma-separated string, encoding their spaces
+#[cfg(feature = "semantic-scholar")]
 pub(crate) fn merge_fields_of_study(fields: &[FieldOfStudy]) -> String {
-    fields
-        .iter()
-        .copied()
-        .collect::<HashSet<_>>()
-        .iter()
-        .map(|f| f.to_string())
-        .collect::<Vec<String>>()
-        .join(",")
+    merge_distinct(fields, |field| encode_query_value(&field.to_string()))
+}
+
+/// Merge venues into a comma-separated string, encoding each of them
+#[cfg(feature = "semantic-scholar")]
+pub(crate) fn merge_venues(venues: &[String]) -> String {
+    merge_distinct(venues, |venue| encode_query_value(venue))
 }
 
 /// Inner struct for the paper/batch query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct S2NestedPaper {
     /// Semantic Scholar's primary unique identifier for a paper.
@@ -349,6 +677,9 @@ pub struct S2NestedPaper {
     /// The BibTex bibliographical citation of the paper.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub citation_styles: Option<CitationStyles>,
+    /// Machine-generated summary of the paper, requested with [`PaperField::Tldr`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tldr: Option<Tldr>,
     /// Array of authors info.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authors: Option<Vec<S2Author>>,
@@ -367,7 +698,8 @@ pub struct S2NestedPaper {
 }
 
 /// Inner struct for the embedding field in the paper/batch query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Embedding {
     /// The Spector vector embedding model version: <https://github.com/allenai/spector>.
@@ -379,7 +711,8 @@ pub struct Embedding {
 }
 
 /// Inner struct for the tldr field in the paper/batch query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Tldr {
     /// The tldr model version number: <https://github.com/allenai/scitldr>.
@@ -391,14 +724,16 @@ pub struct Tldr {
 }
 
 /// Inner struct for the citation styles field in the paper/batch query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CitationStyles {
     pub bibtex: Option<String>,
 }
 
 /// Inner struct for the associated paper field in the paper/batch query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct S2Paper {
     /// Semantic Scholar's primary unique identifier for a paper.
@@ -475,13 +810,17 @@ pub struct S2Paper {
     /// The BibTex bibliographical citation of the paper.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub citation_styles: Option<CitationStyles>,
+    /// Machine-generated summary of the paper, requested with [`PaperField::Tldr`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tldr: Option<Tldr>,
     /// Array of authors info.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authors: Option<Vec<S2Author>>,
 }
 
 /// Inner struct for the author field in the paper query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct S2Author {
     /// Semantic Scholar's unique ID for the author.
@@ -504,17 +843,21 @@ pub struct S2Author {
     pub homepage: Option<String>,
     /// The author's total publications count.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub paper_count: Option<String>,
+    pub paper_count: Option<u32>,
     /// The author's total citations count.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub citation_count: Option<String>,
+    pub citation_count: Option<u32>,
     /// The author’s h-index, which is a measure of the productivity and citation impact of the author’s publications: <https://www.semanticscholar.org/faq#h-index>.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub h_index: Option<String>,
+    pub h_index: Option<u32>,
+    /// The author's papers, only returned by the author endpoints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub papers: Option<Vec<S2Paper>>,
 }
 
 /// Inner struct for the author external ids field in the paper query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "UPPERCASE")]
 pub struct AuthorExternalIds {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -524,7 +867,8 @@ pub struct AuthorExternalIds {
 }
 
 /// Inner struct for the journal field in the paper query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Journal {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -536,7 +880,8 @@ pub struct Journal {
 }
 
 /// Inner struct for the s2 fields of study field in the paper query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct S2FieldsOfStudy {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -546,7 +891,8 @@ pub struct S2FieldsOfStudy {
 }
 
 /// Inner struct for the open access pdf field in the paper query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct OpenAccessPdf {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -560,7 +906,8 @@ pub struct OpenAccessPdf {
 }
 
 /// Publication type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "PascalCase")]
 pub enum PublicationType {
     Review,
@@ -599,7 +946,8 @@ impl std::fmt::Display for PublicationType {
 }
 
 /// Inner struct for the publication venue field in the paper query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct PublicationVenue {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -616,7 +964,8 @@ pub struct PublicationVenue {
 }
 
 /// Inner struct for the external ids field in the paper query response
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ExternalIds {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "CorpusId")]
@@ -647,16 +996,55 @@ pub struct ExternalIds {
     pub medline: Option<String>,
 }
 
+impl ExternalIds {
+    /// Link resolving the DOI, e.g. `https://doi.org/10.18653/v1/N18-3011`
+    pub fn doi_url(&self) -> Option<String> {
+        self.doi
+            .as_deref()
+            .map(|doi| format!("https://doi.org/{}", doi))
+    }
+
+    /// Link to the arXiv abstract page, e.g. `https://arxiv.org/abs/1805.02262`
+    pub fn arxiv_url(&self) -> Option<String> {
+        self.arxiv
+            .as_deref()
+            .map(|arxiv| format!("https://arxiv.org/abs/{}", arxiv))
+    }
+
+    /// Link to the Semantic Scholar page, resolved from the corpus id
+    pub fn s2_url(&self) -> Option<String> {
+        self.corpus_id
+            .map(|id| format!("https://api.semanticscholar.org/CorpusID:{}", id))
+    }
+
+    /// The most stable link among the ids, preferring the DOI, then arXiv, then Semantic Scholar
+    pub fn best_link(&self) -> Option<String> {
+        self.doi_url()
+            .or_else(|| self.arxiv_url())
+            .or_else(|| self.s2_url())
+    }
+}
+
+#[cfg(feature = "semantic-scholar")]
 #[derive(Debug, Clone)]
 pub(crate) struct Date {
     pub(crate) inner: NaiveDate,
     pub(crate) whole_month: bool,
 }
 
+#[cfg(feature = "semantic-scholar")]
 impl Date {
     pub(crate) fn new(year: i32, month: u32, day: u32) -> Result<Self> {
-        let inner = NaiveDate::from_ymd_opt(year, month, day)
-            .ok_or(Error::InvalidParameter("invalid date".to_string()))?;
+        let first = first_of_month(year, month)?;
+        let inner = first.with_day(day).ok_or_else(|| {
+            Error::InvalidParameter(format!(
+                "invalid date {}-{:02}-{:02}: day must be between 1 and {}",
+                year,
+                month,
+                day,
+                Self::whole_month_of(first).last_day().day()
+            ))
+        })?;
         Ok(Self {
             inner,
             whole_month: false,
@@ -664,15 +1052,30 @@ impl Date {
     }
 
     pub(crate) fn whole_month(year: i32, month: u32) -> Result<Self> {
-        let inner = NaiveDate::from_ymd_opt(year, month, 1)
-            .ok_or(Error::InvalidParameter("invalid date".to_string()))?;
-        Ok(Self {
-            inner,
+        Ok(Self::whole_month_of(first_of_month(year, month)?))
+    }
+
+    fn whole_month_of(first: NaiveDate) -> Self {
+        Self {
+            inner: first,
             whole_month: true,
-        })
+        }
+    }
+
+    /// The last day covered by this date
+    pub(crate) fn last_day(&self) -> NaiveDate {
+        if self.whole_month {
+            self.inner
+                .checked_add_months(Months::new(1))
+                .and_then(|date| date.pred_opt())
+                .unwrap_or(self.inner)
+        } else {
+            self.inner
+        }
     }
 }
 
+#[cfg(feature = "semantic-scholar")]
 impl std::fmt::Display for Date {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.whole_month {
@@ -683,9 +1086,30 @@ impl std::fmt::Display for Date {
     }
 }
 
+/// Returns the first day of the month, explaining which component is invalid
+#[cfg(feature = "semantic-scholar")]
+fn first_of_month(year: i32, month: u32) -> Result<NaiveDate> {
+    if !(1..=12).contains(&month) {
+        return Err(Error::InvalidParameter(format!(
+            "invalid month {} in {}-{:02}: month must be between 1 and 12",
+            month, year, month
+        )));
+    }
+    NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| {
+        Error::InvalidParameter(format!("year {} is out of the supported range", year))
+    })
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct _Date(pub(crate) i32, pub(crate) u32, pub(crate) Option<u32>);
 
+impl From<NaiveDate> for _Date {
+    fn from(date: NaiveDate) -> Self {
+        _Date(date.year(), date.month(), Some(date.day()))
+    }
+}
+
+#[cfg(feature = "semantic-scholar")]
 impl TryFrom<&_Date> for Date {
     type Error = Error;
 
@@ -699,6 +1123,50 @@ impl TryFrom<&_Date> for Date {
     }
 }
 
+/// Validates the publication date and year filters of a search builder.
+///
+/// The two filters are mutually exclusive, and the start of each range must not
+/// come after its end.
+#[cfg(feature = "semantic-scholar")]
+pub(crate) fn validate_date_filters(
+    publication_date: Option<&(Option<_Date>, Option<_Date>)>,
+    year: Option<(Option<u32>, Option<u32>)>,
+) -> Result<Option<(Option<Date>, Option<Date>)>> {
+    let has_dates = publication_date.is_some_and(|(start, end)| start.is_some() || end.is_some());
+    if has_dates && year.is_some() {
+        return Err(Error::InvalidParameter(
+            "publication date and year filters are mutually exclusive".to_string(),
+        ));
+    }
+
+    if let Some((Some(start), Some(end))) = year {
+        if start > end {
+            return Err(Error::InvalidParameter(format!(
+                "start year {} must be less than or equal to end year {}",
+                start, end
+            )));
+        }
+    }
+
+    let Some((start, end)) = publication_date else {
+        return Ok(None);
+    };
+    let start = start.as_ref().map(Date::try_from).transpose()?;
+    let end = end.as_ref().map(Date::try_from).transpose()?;
+    if let (Some(start), Some(end)) = (&start, &end) {
+        if start.inner > end.last_day() {
+            return Err(Error::InvalidParameter(format!(
+                "start date {} must not be after end date {}",
+                start, end
+            )));
+        }
+    }
+    match (start, end) {
+        (None, None) => Ok(None),
+        range => Ok(Some(range)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -716,7 +1184,7 @@ mod tests {
         assert_eq!(corpus_id_serialized, "\"CorpusId:215416146\"");
         let doi = PaperId::doi("10.18653/v1/N18-3011");
         let doi_serialized = serde_json::to_string(&doi).unwrap();
-        assert_eq!(doi_serialized, "\"DOI:10.18653/v1/N18-3011\"");
+        assert_eq!(doi_serialized, "\"DOI:10.18653/v1/n18-3011\"");
         let arxiv = PaperId::arxiv("2106.15928");
         let arxiv_serialized = serde_json::to_string(&arxiv).unwrap();
         assert_eq!(arxiv_serialized, "\"ARXIV:2106.15928\"");
@@ -736,4 +1204,114 @@ mod tests {
         let url_serialized = serde_json::to_string(&url).unwrap();
         assert_eq!(url_serialized, "\"URL:https://arxiv.org/abs/2106.15928v1\"");
     }
+
+    #[test]
+    fn test_checked_url() {
+        assert_eq!(
+            PaperId::checked_url("http://export.arxiv.org/pdf/2106.15928v1.pdf").unwrap(),
+            PaperId::url("https://arxiv.org/abs/2106.15928v1")
+        );
+        assert_eq!(
+            PaperId::checked_url(
+                "www.semanticscholar.org/paper/649def34?utm_source=x&sort=relevance#cite"
+            )
+            .unwrap(),
+            PaperId::url("https://www.semanticscholar.org/paper/649def34?sort=relevance")
+        );
+        assert_eq!(
+            PaperId::checked_url("https://dl.acm.org/doi/10.1145/3442188.3445922?fbclid=1")
+                .unwrap(),
+            PaperId::url("https://dl.acm.org/doi/10.1145/3442188.3445922")
+        );
+        assert_eq!(
+            PaperId::checked_url("https://notarxiv.org/abs/2106.15928"),
+            Err(Error::UnsupportedUrlHost("notarxiv.org".to_owned()))
+        );
+        assert!(
+            PaperId::url("https://example.org/paper")
+                .validate()
+                .is_err()
+        );
+        assert!(PaperId::doi("10.1000/xyz").validate().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "semantic-scholar")]
+    fn test_validate_date_filters() {
+        let range = (Some(_Date(2020, 6, Some(15))), Some(_Date(2020, 6, None)));
+        let dates = validate_date_filters(Some(&range), None).unwrap().unwrap();
+        assert_eq!(
+            dates.1.unwrap().last_day(),
+            NaiveDate::from_ymd_opt(2020, 6, 30).unwrap()
+        );
+
+        let reversed = (Some(_Date(2021, 1, None)), Some(_Date(2020, 12, Some(31))));
+        assert!(validate_date_filters(Some(&reversed), None).is_err());
+        assert!(validate_date_filters(None, Some((Some(2021), Some(2020)))).is_err());
+        assert!(validate_date_filters(Some(&range), Some((Some(2020), None))).is_err());
+        assert!(
+            validate_date_filters(Some(&(None, None)), Some((Some(2020), None)))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "semantic-scholar")]
+    fn test_date_diagnostics() {
+        let err = Date::try_from(&_Date(2020, 2, Some(31))).unwrap_err();
+        assert_eq!(
+            err,
+            Error::InvalidParameter(
+                "invalid date 2020-02-31: day must be between 1 and 29".to_string()
+            )
+        );
+        let err = Date::try_from(&_Date(2020, 13, None)).unwrap_err();
+        assert_eq!(
+            err,
+            Error::InvalidParameter(
+                "invalid month 13 in 2020-13: month must be between 1 and 12".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_all_fields_of_study() {
+        assert_eq!(
+            FieldOfStudy::try_from("computer science").unwrap(),
+            FieldOfStudy::ComputerScience
+        );
+        assert!(FieldOfStudy::try_from("Alchemy").is_err());
+
+        let paper: S2NestedPaper = serde_json::from_value(serde_json::json!({
+            "paperId": "a",
+            "fieldsOfStudy": ["Computer Science"],
+            "s2FieldsOfStudy": [
+                { "category": "Computer Science", "source": "external" },
+                { "category": "Mathematics", "source": "s2-fos-model" },
+                { "category": "Alchemy", "source": "s2-fos-model" }
+            ]
+        }))
+        .unwrap();
+        let labeled = paper.all_fields_of_study();
+        assert_eq!(labeled.len(), 2);
+        assert_eq!(labeled[1].source, "s2-fos-model");
+
+        let counts = field_of_study_counts([labeled.clone(), labeled[..1].to_vec()]);
+        assert_eq!(counts[0], (FieldOfStudy::ComputerScience, 2));
+        assert_eq!(counts[1], (FieldOfStudy::Mathematics, 1));
+    }
+
+    #[test]
+    fn test_list_accessors() {
+        let paper: S2NestedPaper = serde_json::from_value(serde_json::json!({
+            "paperId": "a",
+            "authors": null,
+            "publicationTypes": []
+        }))
+        .unwrap();
+        assert!(paper.authors().is_empty());
+        assert!(paper.publication_types().is_empty());
+        assert!(paper.fields_of_study().is_empty());
+    }
 }
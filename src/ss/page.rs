@@ -0,0 +1,610 @@
+//! Unified pagination over the list endpoints
+//!
+//! Relevance search, citations and paper authors page by offset, bulk search by continuation
+//! token. [`Page`] and [`Cursor`] describe both, so pagination helpers and streams can be
+//! written once. Endpoints added outside this crate get the same helpers by implementing
+//! [`OffsetPaginated`] or [`TokenPaginated`] next to [`Query`].
+
+use crate::{
+    PaperAuthorsParam, PaperAuthorsResponse, PaperBulkSearchParam, PaperBulkSearchResponse,
+    PaperCitationsParam, PaperCitationsResponse, PaperSearchParam, PaperSearchResponse,
+    RELEVANCE_SEARCH_MAX_RESULTS, S2Author, S2Citation, S2NestedPaper, S2Paper,
+    error::Result,
+    ss::client::{Query, SemanticScholar},
+};
+#[cfg(feature = "stream")]
+use async_stream::stream;
+#[cfg(feature = "stream")]
+use futures::Stream;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+use std::{
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+/// Position of the next page of a list endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cursor {
+    /// Offset of the first item of the next page
+    Offset(u32),
+    /// Continuation token returned by the previous page
+    Token(String),
+}
+
+/// A page of results from a list endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    /// Items of this page
+    pub items: Vec<T>,
+    /// Estimated total number of matching items
+    pub total_estimate: Option<u32>,
+    /// Cursor of the next page, `None` on the last page
+    pub next: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Whether this is the last page
+    pub fn is_last(&self) -> bool {
+        self.next.is_none()
+    }
+}
+
+/// Limits on the work done by [`SemanticScholar::fetch_all`] and [`SemanticScholar::pages`]
+///
+/// Each limit is optional, and the default budget is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FetchBudget {
+    /// Maximum number of items to collect
+    pub max_items: Option<usize>,
+    /// Maximum number of requests to send
+    pub max_requests: Option<usize>,
+    /// Maximum time to spend, checked before each request
+    pub max_duration: Option<Duration>,
+}
+
+/// A list endpoint paged by offset, like the relevance search or the citations of a paper
+pub trait OffsetPaginated: Query + Clone + Sync {
+    /// Item type of the pages
+    type Item;
+
+    /// Parameters that fetch the page starting at `offset`
+    fn with_offset(&self, offset: u32) -> Self;
+
+    /// Offset of the first item of the page these parameters fetch
+    fn offset(&self) -> u32;
+
+    /// Convert a raw response into a page, its next cursor being a [`Cursor::Offset`]
+    fn into_page(response: Self::Response) -> Page<Self::Item>;
+}
+
+/// A list endpoint paged by continuation token, like the bulk search
+pub trait TokenPaginated: Query + Clone + Sync {
+    /// Item type of the pages
+    type Item;
+
+    /// Parameters that fetch the page continuing with `token`
+    fn with_token(&self, token: &str) -> Self;
+
+    /// Token these parameters continue with, `None` for the first page
+    fn token(&self) -> Option<&str>;
+
+    /// Convert a raw response into a page, its next cursor being a [`Cursor::Token`]
+    fn into_page(response: Self::Response) -> Page<Self::Item>;
+}
+
+/// Marker of the endpoints paged by offset, see [`Paginated`]
+#[derive(Debug)]
+pub enum ByOffset {}
+
+/// Marker of the endpoints paged by continuation token, see [`Paginated`]
+#[derive(Debug)]
+pub enum ByToken {}
+
+mod private {
+    pub trait Sealed<K> {}
+}
+
+/// A list endpoint whose results can be fetched page by page
+///
+/// Implemented for every [`OffsetPaginated`] endpoint with `K` = [`ByOffset`] and every
+/// [`TokenPaginated`] one with `K` = [`ByToken`]; `K` is inferred by the helpers taking it.
+pub trait Paginated<K>: Query + Clone + Sync + private::Sealed<K> {
+    /// Item type of the pages
+    type Item;
+
+    /// Parameters that fetch the page at the given cursor
+    fn at_cursor(&self, cursor: &Cursor) -> Self;
+
+    /// Cursor of the page these parameters fetch, `None` for the first page of a token search
+    fn cursor(&self) -> Option<Cursor>;
+
+    /// Convert a raw response into a page
+    fn to_page(response: Self::Response) -> Page<Self::Item>;
+}
+
+impl<P: OffsetPaginated> private::Sealed<ByOffset> for P {}
+
+impl<P: OffsetPaginated> Paginated<ByOffset> for P {
+    type Item = P::Item;
+
+    fn at_cursor(&self, cursor: &Cursor) -> Self {
+        match cursor {
+            Cursor::Offset(offset) => self.with_offset(*offset),
+            Cursor::Token(_) => self.clone(),
+        }
+    }
+
+    fn cursor(&self) -> Option<Cursor> {
+        Some(Cursor::Offset(self.offset()))
+    }
+
+    fn to_page(response: Self::Response) -> Page<Self::Item> {
+        P::into_page(response)
+    }
+}
+
+impl<P: TokenPaginated> private::Sealed<ByToken> for P {}
+
+impl<P: TokenPaginated> Paginated<ByToken> for P {
+    type Item = P::Item;
+
+    fn at_cursor(&self, cursor: &Cursor) -> Self {
+        match cursor {
+            Cursor::Token(token) => self.with_token(token),
+            Cursor::Offset(_) => self.clone(),
+        }
+    }
+
+    fn cursor(&self) -> Option<Cursor> {
+        self.token().map(|token| Cursor::Token(token.to_owned()))
+    }
+
+    fn to_page(response: Self::Response) -> Page<Self::Item> {
+        P::into_page(response)
+    }
+}
+
+/// Stream of the pages of a list endpoint, see [`SemanticScholar::pages`]
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub type PageStream<'a, T> = Pin<Box<dyn Stream<Item = Result<Page<T>>> + Send + 'a>>;
+
+/// Walks the pages of a list endpoint within a budget
+struct PageWalker<'a, K, P> {
+    client: &'a SemanticScholar,
+    param: Option<P>,
+    budget: FetchBudget,
+    start: Instant,
+    requests: usize,
+    items: usize,
+    kind: PhantomData<fn() -> K>,
+}
+
+impl<'a, K, P: Paginated<K>> PageWalker<'a, K, P> {
+    fn new(client: &'a SemanticScholar, param: &P, budget: FetchBudget) -> Self {
+        Self {
+            client,
+            param: Some(param.clone()),
+            budget,
+            start: Instant::now(),
+            requests: 0,
+            items: 0,
+            kind: PhantomData,
+        }
+    }
+
+    /// Fetch the next page, `None` once the endpoint is exhausted or the budget runs out
+    ///
+    /// A page paged by offset and cut short by `max_items` gets the cursor of its first item
+    /// left out, while a page paged by token is kept whole since a token cannot resume inside
+    /// it. When the budget runs out, the parameters of the page not fetched are kept.
+    async fn next_page(&mut self) -> Result<Option<Page<P::Item>>> {
+        let Some(param) = self.param.take() else {
+            return Ok(None);
+        };
+        let budget = self.budget;
+        if budget.max_items.is_some_and(|max| self.items >= max)
+            || budget.max_requests.is_some_and(|max| self.requests >= max)
+            || budget
+                .max_duration
+                .is_some_and(|max| self.start.elapsed() >= max)
+        {
+            self.param = Some(param);
+            return Ok(None);
+        }
+
+        let mut page = self.client.page(&param).await?;
+        self.requests += 1;
+        let empty = page.items.is_empty();
+        let left = budget.max_items.map(|max| max - self.items);
+        match (left, param.cursor()) {
+            (Some(left), Some(Cursor::Offset(offset))) if left < page.items.len() => {
+                page.items.truncate(left);
+                page.next = Some(Cursor::Offset(offset + left as u32));
+            }
+            _ => {}
+        }
+        self.items += page.items.len();
+        match page.next {
+            Some(ref cursor) if !empty => self.param = Some(param.at_cursor(cursor)),
+            _ => page.next = None,
+        }
+        Ok(Some(page))
+    }
+}
+
+impl SemanticScholar {
+    /// Fetch a single page of a list endpoint
+    pub async fn page<K, P: Paginated<K>>(&self, param: &P) -> Result<Page<P::Item>> {
+        Ok(P::to_page(param.query(self).await?))
+    }
+
+    /// Fetch the pages of a list endpoint until it is exhausted or the budget runs out
+    ///
+    /// The items of all pages are collected into a single page. Its `next` cursor is set when
+    /// the budget stopped the fetch early, so it can be resumed with [`Paginated::at_cursor`]
+    /// without losing items. When `max_items` falls inside a page paged by offset, the cursor
+    /// points at the first item left out; a page paged by token is collected whole instead.
+    pub async fn fetch_all<K, P: Paginated<K>>(
+        &self,
+        param: &P,
+        budget: FetchBudget,
+    ) -> Result<Page<P::Item>> {
+        let mut walker = PageWalker::new(self, param, budget);
+        let mut result = Page {
+            items: Vec::new(),
+            total_estimate: None,
+            next: None,
+        };
+        while let Some(page) = walker.next_page().await? {
+            result.items.extend(page.items);
+            result.total_estimate = page.total_estimate.or(result.total_estimate);
+        }
+        result.next = walker.param.as_ref().and_then(Paginated::cursor);
+        Ok(result)
+    }
+
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    /// Stream the pages of a list endpoint until it is exhausted or the budget runs out
+    ///
+    /// The pages are those [`SemanticScholar::fetch_all`] would collect, and the stream ends
+    /// after the first error.
+    pub fn pages<'a, K: 'a, P>(&'a self, param: &P, budget: FetchBudget) -> PageStream<'a, P::Item>
+    where
+        P: Paginated<K> + Send + 'a,
+        P::Item: Send,
+    {
+        let mut walker = PageWalker::new(self, param, budget);
+        Box::pin(stream! {
+            loop {
+                match walker.next_page().await {
+                    Ok(Some(page)) => yield Ok(page),
+                    Ok(None) => return,
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl OffsetPaginated for PaperSearchParam {
+    type Item = S2NestedPaper;
+
+    /// Fetch the page at the offset, cut short to end at the 1,000-result ceiling
+    fn with_offset(&self, offset: u32) -> Self {
+        self.at_offset(offset)
+    }
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    /// Convert a response, without a next page past the 1,000-result ceiling
+    fn into_page(response: PaperSearchResponse) -> Page<S2NestedPaper> {
+        Page {
+            items: response.data.unwrap_or_default(),
+            total_estimate: response.total,
+            next: response
+                .next
+                .filter(|&next| next < RELEVANCE_SEARCH_MAX_RESULTS)
+                .map(Cursor::Offset),
+        }
+    }
+}
+
+impl TokenPaginated for PaperBulkSearchParam {
+    type Item = S2Paper;
+
+    /// Continue with the token, without the sort the API may reject alongside it
+    fn with_token(&self, token: &str) -> Self {
+        let mut param = self.clone();
+        param.token = Some(token.to_owned());
+        param.sort = None;
+        param
+    }
+
+    fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    fn into_page(response: PaperBulkSearchResponse) -> Page<S2Paper> {
+        Page {
+            items: response.data.unwrap_or_default(),
+            total_estimate: response.total,
+            next: response.token.map(Cursor::Token),
+        }
+    }
+}
+
+impl OffsetPaginated for PaperCitationsParam {
+    type Item = S2Citation;
+
+    fn with_offset(&self, offset: u32) -> Self {
+        let mut param = self.clone();
+        param.offset = Some(offset);
+        param
+    }
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn into_page(response: PaperCitationsResponse) -> Page<S2Citation> {
+        Page {
+            items: response.data.unwrap_or_default(),
+            total_estimate: None,
+            next: response.next.map(Cursor::Offset),
+        }
+    }
+}
+
+impl OffsetPaginated for PaperAuthorsParam {
+    type Item = S2Author;
+
+    fn with_offset(&self, offset: u32) -> Self {
+        let mut param = self.clone();
+        param.offset = Some(offset);
+        param
+    }
+
+    fn offset(&self) -> u32 {
+        self.offset.unwrap_or(0)
+    }
+
+    fn into_page(response: PaperAuthorsResponse) -> Page<S2Author> {
+        Page {
+            items: response.data.unwrap_or_default(),
+            total_estimate: None,
+            next: response.next.map(Cursor::Offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PaperField, PaperSearchParamBuilder, cassette::Cassette, ss::Product};
+    use std::path::PathBuf;
+
+    /// Endpoint defined outside the crate serving numbers in pages of three
+    #[derive(Debug, Clone)]
+    struct Numbers {
+        offset: u32,
+    }
+
+    impl Query for Numbers {
+        type Response = (Vec<u32>, Option<u32>);
+
+        async fn query(&self, _client: &SemanticScholar) -> Result<Self::Response> {
+            let items = (self.offset..10).take(3).collect::<Vec<_>>();
+            let next = Some(self.offset + 3).filter(|&next| next < 10);
+            Ok((items, next))
+        }
+    }
+
+    impl OffsetPaginated for Numbers {
+        type Item = u32;
+
+        fn with_offset(&self, offset: u32) -> Self {
+            Numbers { offset }
+        }
+
+        fn offset(&self) -> u32 {
+            self.offset
+        }
+
+        fn into_page((items, next): Self::Response) -> Page<u32> {
+            Page {
+                items,
+                total_estimate: Some(10),
+                next: next.map(Cursor::Offset),
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_page() {
+        let response: PaperSearchResponse = serde_json::from_value(serde_json::json!({
+            "total": 120,
+            "offset": 0,
+            "next": 100,
+            "data": [{ "paperId": "a" }]
+        }))
+        .unwrap();
+        let page = PaperSearchParam::into_page(response);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.total_estimate, Some(120));
+        assert_eq!(page.next, Some(Cursor::Offset(100)));
+
+        let response: PaperBulkSearchResponse =
+            serde_json::from_value(serde_json::json!({ "total": 1, "data": [] })).unwrap();
+        assert!(PaperBulkSearchParam::into_page(response).is_last());
+    }
+
+    #[tokio::test]
+    async fn test_custom_endpoint() {
+        let client = SemanticScholar::default();
+        let param = Numbers { offset: 0 };
+        let all = client
+            .fetch_all(&param, FetchBudget::default())
+            .await
+            .unwrap();
+        assert_eq!(all.items, (0..10).collect::<Vec<_>>());
+        assert!(all.is_last());
+
+        let budget = FetchBudget {
+            max_items: Some(4),
+            ..Default::default()
+        };
+        let first = client.fetch_all(&param, budget).await.unwrap();
+        assert_eq!(first.items, [0, 1, 2, 3]);
+        assert_eq!(first.next, Some(Cursor::Offset(4)));
+
+        let budget = FetchBudget {
+            max_requests: Some(1),
+            ..Default::default()
+        };
+        let rest = param.at_cursor(&Cursor::Offset(9));
+        let last = client.fetch_all(&rest, budget).await.unwrap();
+        assert_eq!((last.items, last.next), (vec![9], None));
+
+        #[cfg(feature = "stream")]
+        {
+            use futures::StreamExt;
+
+            let pages = client
+                .pages(&param, FetchBudget::default())
+                .map(|page| page.unwrap().items.len())
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(pages, [3, 3, 3, 1]);
+        }
+    }
+
+    /// Client replaying relevance search pages of papers `p{offset}`, keyed by query string,
+    /// and the directory of its cassette
+    fn search_pages(
+        test: &str,
+        pages: &[(&str, u32, u32, Option<u32>)],
+    ) -> (SemanticScholar, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("{}-{}", test, std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        for &(query, offset, count, next) in pages {
+            let url = format!("{}/paper/search?{}", Product::Graph.default_url(), query);
+            let data = (offset..offset + count)
+                .map(|i| serde_json::json!({ "paperId": format!("p{}", i) }))
+                .collect::<Vec<_>>();
+            let body =
+                serde_json::json!({ "total": 5000, "offset": offset, "next": next, "data": data });
+            cassette
+                .insert("GET", &url, b"", 200, &body.to_string())
+                .unwrap();
+        }
+        let client = SemanticScholar::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+        (client, dir)
+    }
+
+    fn ids(page: &Page<S2NestedPaper>) -> Vec<&str> {
+        page.items
+            .iter()
+            .map(|paper| paper.paper_id.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_budget() {
+        let (client, dir) = search_pages(
+            "fetch-budget",
+            &[
+                ("query=fish&fields=title&limit=2", 0, 2, Some(2)),
+                ("query=fish&fields=title&offset=2&limit=2", 2, 2, Some(4)),
+                ("query=fish&fields=title&offset=3&limit=2", 3, 2, Some(5)),
+            ],
+        );
+        let param = PaperSearchParamBuilder::new("fish")
+            .field(PaperField::Title)
+            .limit(2)
+            .build()
+            .unwrap();
+
+        let budget = FetchBudget {
+            max_requests: Some(2),
+            ..Default::default()
+        };
+        let page = client.fetch_all(&param, budget).await.unwrap();
+        assert_eq!(ids(&page), ["p0", "p1", "p2", "p3"]);
+        assert_eq!(page.total_estimate, Some(5000));
+        assert_eq!(page.next, Some(Cursor::Offset(4)));
+
+        // Stopping in the middle of the second page resumes inside it
+        let budget = FetchBudget {
+            max_items: Some(3),
+            ..Default::default()
+        };
+        let page = client.fetch_all(&param, budget).await.unwrap();
+        assert_eq!(ids(&page), ["p0", "p1", "p2"]);
+        assert_eq!(page.next, Some(Cursor::Offset(3)));
+
+        let budget = FetchBudget {
+            max_requests: Some(1),
+            ..Default::default()
+        };
+        let rest = param.at_cursor(page.next.as_ref().unwrap());
+        let rest = client.fetch_all(&rest, budget).await.unwrap();
+        assert_eq!(ids(&rest), ["p3", "p4"]);
+        assert_eq!(rest.next, Some(Cursor::Offset(5)));
+
+        // A budget exhausted before the first request keeps the cursor
+        let budget = FetchBudget {
+            max_duration: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let page = client.fetch_all(&param, budget).await.unwrap();
+        assert!(page.items.is_empty());
+        assert_eq!(page.next, Some(Cursor::Offset(0)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_relevance_ceiling() {
+        let (client, dir) = search_pages(
+            "relevance-ceiling",
+            &[
+                (
+                    "query=fish&fields=title&offset=996&limit=3",
+                    996,
+                    3,
+                    Some(999),
+                ),
+                (
+                    "query=fish&fields=title&offset=999&limit=1",
+                    999,
+                    1,
+                    Some(1000),
+                ),
+            ],
+        );
+        let param = PaperSearchParamBuilder::new("fish")
+            .field(PaperField::Title)
+            .offset(996)
+            .limit(3)
+            .build()
+            .unwrap();
+        let budget = FetchBudget {
+            max_items: Some(2000),
+            ..Default::default()
+        };
+        let page = client.fetch_all(&param, budget).await.unwrap();
+        assert_eq!(ids(&page), ["p996", "p997", "p998", "p999"]);
+        assert!(page.is_last());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+//! Raw requests to endpoints without a model
+//!
+//! [`SemanticScholar::send_raw`] sends a request through the same transport as the modeled
+//! endpoints, so the cassette, the response size limit, the coalescing of identical requests
+//! and the error context all apply. The API key is only sent to the client's own base URLs.
+//!
+//! The requests are neither rate limited nor retried, as the client does neither by itself.
+//! Wrap them in [`Scheduler::run`](crate::Scheduler::run) to space out and retry the calls
+//! like the rest of a run.
+
+use crate::{
+    error::{ErrorContext, Result},
+    ss::client::{Product, SemanticScholar},
+    utils::{Response, build_request, decode, encode_query_value, parse_json, send},
+};
+use encoding_rs::Encoding;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+
+pub use crate::utils::http::Method;
+
+/// Status and body of a raw request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// Response body as received
+    ///
+    /// A body replayed from a cassette is the UTF-8 text it was recorded as.
+    pub body: Vec<u8>,
+    /// Server-side request identifier, if the response carried one
+    pub request_id: Option<String>,
+    encoding: &'static Encoding,
+    context: ErrorContext,
+}
+
+impl RawResponse {
+    /// Whether the status is in the 2xx range
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Body decoded with the charset of its `Content-Type`, UTF-8 by default
+    pub fn text(&self) -> Cow<'_, str> {
+        decode(&self.body, self.encoding)
+    }
+
+    /// Deserialize the JSON body
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        parse_json(&self.text()).map_err(|e| e.with_context(self.context.clone()))
+    }
+
+    /// Turn an unsuccessful response into the error a modeled endpoint would return
+    ///
+    /// Rate limits and server errors then count as retryable for a
+    /// [`Scheduler`](crate::Scheduler).
+    pub fn error_for_status(self) -> Result<Self> {
+        if self.is_success() {
+            return Ok(self);
+        }
+        let response = Response {
+            status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            body: self.body,
+            encoding: self.encoding,
+            request_id: self.request_id,
+            context: self.context,
+        };
+        Err(response.into_error())
+    }
+}
+
+impl SemanticScholar {
+    /// Send a request to an endpoint the crate does not model
+    ///
+    /// `path` is relative to the Graph API base URL, e.g. `/paper/search/match`; a full URL,
+    /// such as one built from [`base_url`](Self::base_url), is used as is. The API key is only
+    /// sent along when the URL is under one of the client's base URLs, so other hosts never
+    /// see it. Query values are percent-encoded and `body` is sent as JSON. Unsuccessful
+    /// statuses are returned rather than turned into errors, see
+    /// [`RawResponse::error_for_status`]. The request is not rate limited nor retried.
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path: &str,
+        query_pairs: &[(&str, &str)],
+        body: Option<&serde_json::Value>,
+    ) -> Result<RawResponse> {
+        let mut url = if path.starts_with("http://") || path.starts_with("https://") {
+            path.to_owned()
+        } else {
+            format!("{}/{}", self.graph_url(), path.trim_start_matches('/'))
+        };
+        for (key, value) in query_pairs {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&encode_query_value(key));
+            url.push('=');
+            url.push_str(&encode_query_value(value));
+        }
+
+        let api_key = self.api_key().filter(|_| self.is_own_url(&url));
+        let mut req_builder = build_request(self.client(), method, &url, api_key);
+        if let Some(body) = body {
+            req_builder = req_builder.json(body);
+        }
+        let resp = send(req_builder, self.transport()).await?;
+        Ok(RawResponse {
+            status: resp.status.as_u16(),
+            body: resp.body,
+            request_id: resp.request_id,
+            encoding: resp.encoding,
+            context: resp.context,
+        })
+    }
+
+    /// Whether the URL is under the base URL of one of the products
+    fn is_own_url(&self, url: &str) -> bool {
+        [Product::Graph, Product::Recommendations, Product::Datasets]
+            .into_iter()
+            .any(|product| {
+                url.strip_prefix(self.base_url(product))
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, cassette::Cassette, ss::Product};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_send_raw() {
+        let dir = std::env::temp_dir().join(format!("send-raw-{}", std::process::id()));
+        let cassette = Cassette::replay(&dir);
+        let graph = Product::Graph.default_url();
+        let url = format!(
+            "{}/paper/search/match?query=deep%20learning&fields=title",
+            graph
+        );
+        cassette
+            .insert(
+                "GET",
+                &url,
+                b"",
+                200,
+                r#"{"data": [{"title": "Deep Learning"}]}"#,
+            )
+            .unwrap();
+        let body = json!({ "positivePaperIds": ["abc"] });
+        let url = format!("{}/papers", Product::Recommendations.default_url());
+        cassette
+            .insert(
+                "POST",
+                &url,
+                body.to_string().as_bytes(),
+                429,
+                r#"{"message": "Too Many Requests"}"#,
+            )
+            .unwrap();
+        let client = SemanticScholar::builder()
+            .cassette(cassette)
+            .build()
+            .unwrap();
+
+        let query = [("query", "deep learning"), ("fields", "title")];
+        let response = client
+            .send_raw(Method::Get, "/paper/search/match", &query, None)
+            .await
+            .unwrap();
+        assert!(response.is_success());
+        let value: serde_json::Value = response.json().unwrap();
+        assert_eq!(value["data"][0]["title"], "Deep Learning");
+
+        let response = client
+            .send_raw(Method::Post, &url, &[], Some(&body))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 429);
+        let error = response.error_for_status().unwrap_err();
+        assert!(matches!(error.inner(), Error::RequestFailed(failure) if failure.status == 429));
+        assert_eq!(
+            error.context().unwrap().endpoint,
+            "POST /recommendations/v1/papers"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_own_url() {
+        let client = SemanticScholar::builder()
+            .base_url(Product::Graph, "https://s2.example.org/graph/v1/")
+            .build()
+            .unwrap();
+        assert!(client.is_own_url("https://s2.example.org/graph/v1/paper/abc"));
+        assert!(client.is_own_url("https://s2.example.org/graph/v1?query=fish"));
+        assert!(client.is_own_url(&format!(
+            "{}/papers",
+            Product::Recommendations.default_url()
+        )));
+        // The key never reaches other hosts, even ones sharing a prefix
+        assert!(!client.is_own_url("https://s2.example.org/graph/v10/paper"));
+        assert!(!client.is_own_url("https://s2.example.org.evil.com/graph/v1/paper"));
+        assert!(!client.is_own_url("https://api.semanticscholar.org/graph/v1/paper"));
+        assert!(!client.is_own_url("https://example.com/paper"));
+    }
+}
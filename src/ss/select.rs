@@ -0,0 +1,295 @@
+//! Nested field selection
+//!
+//! The paper details and batch endpoints accept subfields of the nested authors, citations
+//! and references, e.g. `fields=title,authors.name,citations.title`. A [`FieldSpec`] pairs a
+//! [`PaperField`] with such subfields, and the [`fields!`](crate::fields) macro builds a list
+//! of them from a GraphQL-like selection whose names are checked at compile time.
+
+use crate::ss::{AuthorField, PaperField, merge_paper_fields};
+
+/// Subfield of a nested paper field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubField {
+    /// Field of the nested authors
+    Author(AuthorField),
+    /// Field of the nested citations or references
+    Paper(PaperField),
+}
+
+impl std::fmt::Display for SubField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubField::Author(field) => field.fmt(f),
+            SubField::Paper(field) => field.fmt(f),
+        }
+    }
+}
+
+/// Paper field with the subfields to return, all of them if there is none
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldSpec {
+    pub field: PaperField,
+    pub subfields: Vec<SubField>,
+}
+
+impl FieldSpec {
+    /// Select only some subfields of a nested field
+    pub fn nested(field: PaperField, subfields: Vec<SubField>) -> Self {
+        Self { field, subfields }
+    }
+}
+
+impl From<PaperField> for FieldSpec {
+    fn from(field: PaperField) -> Self {
+        Self {
+            field,
+            subfields: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for FieldSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.subfields.is_empty() {
+            return self.field.fmt(f);
+        }
+        let subfields = self
+            .subfields
+            .iter()
+            .map(|subfield| format!("{}.{}", self.field, subfield))
+            .collect::<Vec<_>>();
+        write!(f, "{}", subfields.join(","))
+    }
+}
+
+/// Merge paper fields into a comma-separated string, expanding those with subfields
+pub(crate) fn merge_selected_fields(fields: &[PaperField], nested: &[FieldSpec]) -> String {
+    let plain = fields
+        .iter()
+        .copied()
+        .filter(|field| !nested.iter().any(|spec| spec.field == *field))
+        .collect::<Vec<_>>();
+    std::iter::once(merge_paper_fields(&plain))
+        .filter(|merged| !merged.is_empty())
+        .chain(nested.iter().map(ToString::to_string))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Build a list of [`FieldSpec`] from a GraphQL-like selection
+///
+/// Fields are written in snake case. The nested `authors` take [`AuthorField`] names and the
+/// nested `citations` and `references` take [`PaperField`] names; any other name fails to
+/// compile.
+///
+/// ```
+/// use connected_papers::{AuthorField, FieldSpec, PaperField, SubField, fields};
+///
+/// let specs = fields! { title, year, authors { name }, citations { title } };
+/// assert_eq!(specs[0], FieldSpec::from(PaperField::Title));
+/// assert_eq!(
+///     specs[2],
+///     FieldSpec::nested(PaperField::Authors, vec![SubField::Author(AuthorField::Name)])
+/// );
+/// assert_eq!(specs[3].to_string(), "citations.title");
+/// ```
+#[macro_export]
+macro_rules! fields {
+    () => {
+        ::std::vec::Vec::<$crate::FieldSpec>::new()
+    };
+    ($($selection:tt)+) => {
+        $crate::__select_fields!([] $($selection)+)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __select_fields {
+    ([$($specs:expr),*]) => {
+        ::std::vec![$($specs),*]
+    };
+    ([$($specs:expr),*] authors { $($subfield:ident),* $(,)? } $(, $($rest:tt)*)?) => {
+        $crate::__select_fields!(
+            [$($specs,)* $crate::FieldSpec::nested(
+                $crate::PaperField::Authors,
+                ::std::vec![$($crate::SubField::Author($crate::__author_field!($subfield))),*],
+            )]
+            $($($rest)*)?
+        )
+    };
+    ([$($specs:expr),*] citations { $($subfield:ident),* $(,)? } $(, $($rest:tt)*)?) => {
+        $crate::__select_fields!(
+            [$($specs,)* $crate::FieldSpec::nested(
+                $crate::PaperField::Citations,
+                ::std::vec![$($crate::SubField::Paper($crate::__paper_field!($subfield))),*],
+            )]
+            $($($rest)*)?
+        )
+    };
+    ([$($specs:expr),*] references { $($subfield:ident),* $(,)? } $(, $($rest:tt)*)?) => {
+        $crate::__select_fields!(
+            [$($specs,)* $crate::FieldSpec::nested(
+                $crate::PaperField::References,
+                ::std::vec![$($crate::SubField::Paper($crate::__paper_field!($subfield))),*],
+            )]
+            $($($rest)*)?
+        )
+    };
+    ([$($specs:expr),*] $field:ident $(, $($rest:tt)*)?) => {
+        $crate::__select_fields!(
+            [$($specs,)* $crate::FieldSpec::from($crate::__paper_field!($field))]
+            $($($rest)*)?
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __paper_field {
+    (corpus_id) => {
+        $crate::PaperField::CorpusId
+    };
+    (external_ids) => {
+        $crate::PaperField::ExternalIds
+    };
+    (url) => {
+        $crate::PaperField::URL
+    };
+    (title) => {
+        $crate::PaperField::Title
+    };
+    (abstract) => {
+        $crate::PaperField::Abstract
+    };
+    (venue) => {
+        $crate::PaperField::Venue
+    };
+    (publication_venue) => {
+        $crate::PaperField::PublicationVenue
+    };
+    (year) => {
+        $crate::PaperField::Year
+    };
+    (reference_count) => {
+        $crate::PaperField::ReferenceCount
+    };
+    (citation_count) => {
+        $crate::PaperField::CitationCount
+    };
+    (influential_citation_count) => {
+        $crate::PaperField::InfluentialCitationCount
+    };
+    (is_open_access) => {
+        $crate::PaperField::IsOpenAccess
+    };
+    (open_access_pdf) => {
+        $crate::PaperField::OpenAccessPDF
+    };
+    (fields_of_study) => {
+        $crate::PaperField::FieldsOfStudy
+    };
+    (s2_fields_of_study) => {
+        $crate::PaperField::S2FieldsOfStudy
+    };
+    (publication_types) => {
+        $crate::PaperField::PublicationTypes
+    };
+    (publication_date) => {
+        $crate::PaperField::PublicationDate
+    };
+    (journal) => {
+        $crate::PaperField::Journal
+    };
+    (citation_styles) => {
+        $crate::PaperField::CitationStyles
+    };
+    (authors) => {
+        $crate::PaperField::Authors
+    };
+    (citations) => {
+        $crate::PaperField::Citations
+    };
+    (references) => {
+        $crate::PaperField::References
+    };
+    (embedding) => {
+        $crate::PaperField::Embedding
+    };
+    (tldr) => {
+        $crate::PaperField::Tldr
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __author_field {
+    (external_ids) => {
+        $crate::AuthorField::ExternalIds
+    };
+    (url) => {
+        $crate::AuthorField::URL
+    };
+    (name) => {
+        $crate::AuthorField::Name
+    };
+    (affiliations) => {
+        $crate::AuthorField::Affiliations
+    };
+    (homepage) => {
+        $crate::AuthorField::Homepage
+    };
+    (paper_count) => {
+        $crate::AuthorField::PaperCount
+    };
+    (citation_count) => {
+        $crate::AuthorField::CitationCount
+    };
+    (h_index) => {
+        $crate::AuthorField::HIndex
+    };
+    (papers) => {
+        $crate::AuthorField::Papers
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_macro() {
+        let specs = crate::fields! {
+            title,
+            abstract,
+            authors { name, h_index },
+            references { title, year },
+        };
+        assert_eq!(
+            specs,
+            vec![
+                FieldSpec::from(PaperField::Title),
+                FieldSpec::from(PaperField::Abstract),
+                FieldSpec::nested(
+                    PaperField::Authors,
+                    vec![
+                        SubField::Author(AuthorField::Name),
+                        SubField::Author(AuthorField::HIndex)
+                    ]
+                ),
+                FieldSpec::nested(
+                    PaperField::References,
+                    vec![
+                        SubField::Paper(PaperField::Title),
+                        SubField::Paper(PaperField::Year)
+                    ]
+                ),
+            ]
+        );
+        assert_eq!(specs[2].to_string(), "authors.name,authors.hIndex");
+        assert_eq!(
+            merge_selected_fields(&[PaperField::Authors], &specs[2..3]),
+            "authors.name,authors.hIndex"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Extractive summaries of abstracts
+//!
+//! Semantic Scholar only generates a TL;DR for part of its corpus, and Connected Papers for
+//! even fewer papers. [`summarize`] picks the most central sentences of an abstract with
+//! TextRank instead, so that every paper with an abstract gets a one-line summary.
+
+use crate::utils::STOP_WORDS;
+use std::collections::HashSet;
+
+const DAMPING: f64 = 0.85;
+const ITERATIONS: usize = 50;
+
+/// Words ending a sentence only in appearance
+const ABBREVIATIONS: &[&str] = &[
+    "e.g", "i.e", "al", "etc", "fig", "figs", "eq", "eqs", "vs", "cf", "approx", "ref", "refs",
+    "sec", "no", "dr", "prof",
+];
+
+/// Pick the `sentences` most central sentences of a text with TextRank
+///
+/// Sentences are linked by the content words they share, normalized by their lengths, and
+/// ranked with PageRank over these links. The picked sentences are joined in their original
+/// order. Returns `None` for a text without any sentence.
+pub fn summarize(text: &str, sentences: usize) -> Option<String> {
+    let split = split_sentences(text);
+    if split.is_empty() || sentences == 0 {
+        return None;
+    }
+    let words = split
+        .iter()
+        .map(|sentence| content_words(sentence))
+        .collect::<Vec<_>>();
+    let scores = text_rank(&words);
+
+    let mut ranked = (0..split.len()).collect::<Vec<_>>();
+    // Ties favour earlier sentences, which tend to state the topic
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+    ranked.truncate(sentences);
+    ranked.sort_unstable();
+    Some(
+        ranked
+            .into_iter()
+            .map(|i| split[i])
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn text_rank(words: &[HashSet<String>]) -> Vec<f64> {
+    let n = words.len();
+    let mut weights = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            let common = words[i].intersection(&words[j]).count();
+            let norm = (words[i].len() as f64).ln() + (words[j].len() as f64).ln();
+            if common > 0 && norm > 0.0 {
+                weights[i][j] = common as f64 / norm;
+                weights[j][i] = weights[i][j];
+            }
+        }
+    }
+    let totals = weights
+        .iter()
+        .map(|row| row.iter().sum::<f64>())
+        .collect::<Vec<_>>();
+
+    let mut scores = vec![1.0; n];
+    for _ in 0..ITERATIONS {
+        scores = (0..n)
+            .map(|i| {
+                let inbound = (0..n)
+                    .filter(|&j| totals[j] > 0.0)
+                    .map(|j| weights[j][i] / totals[j] * scores[j])
+                    .sum::<f64>();
+                1.0 - DAMPING + DAMPING * inbound
+            })
+            .collect();
+    }
+    scores
+}
+
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let end = i + c.len_utf8();
+        let next = text[end..].chars().next();
+        let after = text[end..].trim_start().chars().next();
+        let ends = match (next, after) {
+            (None, _) => true,
+            (Some(next), Some(after)) => {
+                next.is_whitespace()
+                    && (after.is_uppercase() || after.is_ascii_digit())
+                    && (c != '.' || !is_abbreviation(&text[start..i]))
+            }
+            _ => false,
+        };
+        if ends {
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence);
+            }
+            start = end;
+            while chars.peek().is_some_and(|(_, c)| c.is_whitespace()) {
+                chars.next();
+            }
+        }
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+/// Whether text ending right before a period ends with an abbreviation or initial
+fn is_abbreviation(text: &str) -> bool {
+    let last = text
+        .split_whitespace()
+        .last()
+        .unwrap_or_default()
+        .trim_start_matches(['(', '['])
+        .to_lowercase();
+    last.chars().count() == 1 && last.chars().all(char::is_alphabetic)
+        || ABBREVIATIONS.contains(&last.as_str())
+}
+
+fn content_words(sentence: &str) -> HashSet<String> {
+    sentence
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 2)
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let text = concat!(
+            "Citation graphs link papers to the papers they cite. ",
+            "We build citation graphs of papers for literature review, e.g. Surveys. ",
+            "The weather was nice. ",
+            "Graphs of papers help readers find related papers in the citation graphs."
+        );
+        assert_eq!(
+            split_sentences(text)[1],
+            "We build citation graphs of papers for literature review, e.g. Surveys."
+        );
+        assert_eq!(
+            summarize(text, 1).as_deref(),
+            Some("Citation graphs link papers to the papers they cite.")
+        );
+        assert!(!summarize(text, 3).unwrap().contains("weather"));
+        assert_eq!(summarize(text, 4).as_deref(), Some(text));
+        assert_eq!(summarize("", 1), None);
+    }
+}
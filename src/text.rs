@@ -0,0 +1,41 @@
+//! Title normalization and language detection
+//!
+//! Titles of the same paper differ across sources in case, punctuation, diacritics and
+//! Unicode compatibility forms. [`normalize_title`] folds these differences away so titles
+//! can be compared for deduplication and title matching.
+
+pub use crate::utils::normalize_title;
+
+/// Whether two titles are equal once normalized
+pub fn titles_match(a: &str, b: &str) -> bool {
+    normalize_title(a) == normalize_title(b)
+}
+
+/// Detect the language of a text, returning its ISO 639-3 code, e.g. `eng` or `deu`
+///
+/// Returns `None` when the text is too short or ambiguous for a reliable guess.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    whatlang::detect(text)
+        .filter(whatlang::Info::is_reliable)
+        .map(|info| info.lang().code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(
+            normalize_title("Ｓｃｈrödinger’s  Cat: A Re-Analysis"),
+            "schrodinger s cat a re analysis"
+        );
+        assert!(titles_match("Café au lait", "CAFE AU LAIT."));
+        assert_eq!(
+            detect_language(
+                "Die Quantenmechanik ist eine physikalische Theorie, mit der die Eigenschaften und Gesetzmäßigkeiten von Zuständen und Vorgängen der Materie beschrieben werden."
+            ),
+            Some("deu")
+        );
+    }
+}
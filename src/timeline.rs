@@ -0,0 +1,155 @@
+//! Temporal layering
+//!
+//! Slices a graph by publication year to follow how a research area grew: each
+//! [`GraphSlice`] is a snapshot of the papers published up to the end of its period, with
+//! the papers new in that period and the clusters they formed, ready to be animated frame by
+//! frame or tabulated.
+
+use crate::client::Graph;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::RangeInclusive,
+};
+
+/// Snapshot of a graph at the end of a period
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphSlice {
+    /// Publication years of the period
+    pub years: RangeInclusive<u32>,
+    /// Subgraph of the papers published up to the end of the period
+    pub graph: Graph,
+    /// Ids of the papers published during the period, sorted
+    pub new_nodes: Vec<String>,
+    /// Groups of at least two new papers linked to each other by edges, largest first
+    pub emerging: Vec<Vec<String>>,
+}
+
+impl Graph {
+    /// Split the graph into one snapshot per period of publication years
+    ///
+    /// Each snapshot keeps the papers published up to the end of its period and the edges
+    /// between them; papers without a year are left out of every snapshot. The common
+    /// citations, references and authors are those of the whole graph.
+    pub fn slice_by_year(&self, ranges: &[RangeInclusive<u32>]) -> Vec<GraphSlice> {
+        ranges
+            .iter()
+            .map(|years| {
+                let graph = self.until(*years.end());
+                let new_nodes = graph
+                    .nodes
+                    .iter()
+                    .filter(|(_, paper)| paper.year.is_some_and(|year| years.contains(&year)))
+                    .map(|(id, _)| id.clone())
+                    .collect::<BTreeSet<_>>();
+                let emerging = clusters(&graph, &new_nodes);
+                GraphSlice {
+                    years: years.clone(),
+                    graph,
+                    new_nodes: new_nodes.into_iter().collect(),
+                    emerging,
+                }
+            })
+            .collect()
+    }
+
+    /// Subgraph of the papers published up to `year`
+    fn until(&self, year: u32) -> Graph {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|(_, paper)| paper.year.is_some_and(|published| published <= year))
+            .map(|(id, paper)| (id.clone(), paper.clone()))
+            .collect::<HashMap<_, _>>();
+        let edges = self
+            .edges
+            .iter()
+            .filter(|edge| nodes.contains_key(&edge.0) && nodes.contains_key(&edge.1))
+            .cloned()
+            .collect();
+        let path_lengths = self
+            .path_lengths
+            .iter()
+            .filter(|(id, _)| nodes.contains_key(*id))
+            .map(|(id, length)| (id.clone(), *length))
+            .collect();
+        let mut parameters = self.parameters.clone();
+        parameters.total_nodes = nodes.len() as u32;
+        Graph {
+            nodes,
+            edges,
+            citations: self.citations.clone(),
+            references: self.references.clone(),
+            authors: self.authors.clone(),
+            parameters,
+            path_lengths,
+            start_id: self.start_id.clone(),
+            current_corpus_date: self.current_corpus_date,
+            creation_time: self.creation_time,
+        }
+    }
+}
+
+/// Connected groups of at least two of the given nodes, using only edges between them
+fn clusters(graph: &Graph, ids: &BTreeSet<String>) -> Vec<Vec<String>> {
+    let mut neighbors = BTreeMap::<&str, Vec<&str>>::new();
+    for edge in &graph.edges {
+        if edge.0 != edge.1 && ids.contains(&edge.0) && ids.contains(&edge.1) {
+            neighbors.entry(edge.0.as_str()).or_default().push(&edge.1);
+            neighbors.entry(edge.1.as_str()).or_default().push(&edge.0);
+        }
+    }
+
+    let mut seen = BTreeSet::new();
+    let mut clusters = Vec::new();
+    for &start in neighbors.keys() {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut cluster = vec![start.to_owned()];
+        let mut stack = vec![start];
+        while let Some(id) = stack.pop() {
+            for &next in &neighbors[id] {
+                if seen.insert(next) {
+                    cluster.push(next.to_owned());
+                    stack.push(next);
+                }
+            }
+        }
+        cluster.sort_unstable();
+        clusters.push(cluster);
+    }
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Edge, Paper};
+
+    #[test]
+    fn test_slice_by_year() {
+        let mut graph = Graph::example();
+        let mut newest = Paper::new("newest");
+        newest.year = Some(2023);
+        graph.nodes.insert("newest".to_owned(), newest);
+        graph
+            .nodes
+            .insert("undated".to_owned(), Paper::new("undated"));
+        graph
+            .edges
+            .push(Edge("newer".to_owned(), "newest".to_owned(), 0.5));
+
+        let slices = graph.slice_by_year(&[2010..=2015, 2016..=2020, 2021..=2025]);
+        let summary = slices
+            .iter()
+            .map(|slice| (slice.graph.nodes.len(), slice.graph.edges.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![(1, 0), (2, 1), (4, 3)]);
+        assert_eq!(slices[1].new_nodes, vec!["seed"]);
+        assert_eq!(slices[1].graph.parameters.total_nodes, 2);
+        assert!(slices[1].emerging.is_empty());
+        assert_eq!(slices[2].new_nodes, vec!["newer", "newest"]);
+        assert_eq!(slices[2].emerging, vec![vec!["newer", "newest"]]);
+    }
+}
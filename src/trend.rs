@@ -0,0 +1,200 @@
+//! Emerging topics
+//!
+//! Scores topics, keyphrases of a corpus or communities of a graph, by how fast they grow:
+//! the slope of their papers per year over the last years, relative to their size, weighted
+//! by how much their papers are cited per year since publication. Topics that only grew in
+//! absolute terms because the whole corpus did still stand out less than newer ones.
+
+use crate::{
+    client::Graph,
+    keywords::{keywords, phrases},
+    paper::PaperLike,
+};
+
+/// Number of publication years the growth is measured over, up to the current year
+pub const TREND_WINDOW: u32 = 5;
+
+/// Fewest papers a keyphrase must occur in to count as a topic
+const MIN_PAPERS: usize = 3;
+
+/// Keyphrases of a corpus considered as topics
+const CANDIDATES: usize = 100;
+
+/// Topic ranked by its recent growth
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmergingTopic {
+    /// The keyphrase or community label
+    pub topic: String,
+    /// Ids of the papers of the topic
+    pub papers: Vec<String>,
+    /// Slope of the papers per year over the window, divided by their mean per year
+    pub growth: f64,
+    /// Mean citations per year since publication of the papers
+    pub citation_rate: f64,
+    /// `growth` times `1 + ln(1 + citation_rate)`
+    pub score: f64,
+}
+
+/// Rank the keyphrases of a corpus by their growth up to `current_year`
+///
+/// Keyphrases occurring in at least three papers are scored over the last
+/// [`TREND_WINDOW`] years, and only growing ones are returned, best first.
+pub fn emerging_topics<P: PaperLike>(
+    papers: &[P],
+    current_year: u32,
+    limit: usize,
+) -> Vec<EmergingTopic> {
+    let phrases = papers.iter().map(phrases).collect::<Vec<_>>();
+    let topics = keywords(papers, usize::MAX)
+        .into_iter()
+        .filter(|keyword| keyword.papers >= MIN_PAPERS)
+        .take(CANDIDATES)
+        .filter_map(|keyword| {
+            let members = papers
+                .iter()
+                .zip(&phrases)
+                .filter(|(_, phrases)| phrases.contains(&keyword.phrase))
+                .map(|(paper, _)| paper)
+                .collect::<Vec<_>>();
+            score(keyword.phrase, &members, current_year)
+        })
+        .collect();
+    rank(topics, limit)
+}
+
+impl Graph {
+    /// Rank the [labeled communities](Graph::labeled_communities) of the graph by their
+    /// growth up to `current_year`, see [`emerging_topics`]
+    ///
+    /// Communities of a single paper are left out.
+    pub fn emerging_topics(&self, current_year: u32, limit: usize) -> Vec<EmergingTopic> {
+        let topics = self
+            .labeled_communities()
+            .into_iter()
+            .filter(|community| community.nodes.len() > 1)
+            .filter_map(|community| {
+                let members = community
+                    .nodes
+                    .iter()
+                    .map(|id| &self.nodes[id])
+                    .collect::<Vec<_>>();
+                score(community.label, &members, current_year)
+            })
+            .collect();
+        rank(topics, limit)
+    }
+}
+
+/// Score the papers of a topic, `None` unless it grows
+fn score<P: PaperLike>(topic: String, papers: &[&P], current_year: u32) -> Option<EmergingTopic> {
+    let first_year = current_year.saturating_sub(TREND_WINDOW - 1);
+    let mut counts = vec![0.0; TREND_WINDOW as usize];
+    for year in papers.iter().filter_map(|paper| paper.year()) {
+        if (first_year..=current_year).contains(&year) {
+            counts[(year - first_year) as usize] += 1.0;
+        }
+    }
+    let n = counts.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = counts.iter().sum::<f64>() / n;
+    // Exact for whole counts, as the deviations of `x` sum to zero
+    let covariance = counts
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (x as f64 - mean_x) * y)
+        .sum::<f64>();
+    let variance = (0..counts.len())
+        .map(|x| (x as f64 - mean_x).powi(2))
+        .sum::<f64>();
+    if mean_y == 0.0 || covariance <= 0.0 {
+        return None;
+    }
+    let growth = covariance / variance / mean_y;
+
+    let rates = papers
+        .iter()
+        .filter_map(|paper| {
+            let year = paper.year().filter(|year| *year <= current_year)?;
+            let age = (current_year - year + 1) as f64;
+            Some(f64::from(paper.citation_count().unwrap_or(0)) / age)
+        })
+        .collect::<Vec<_>>();
+    let citation_rate = if rates.is_empty() {
+        0.0
+    } else {
+        rates.iter().sum::<f64>() / rates.len() as f64
+    };
+
+    Some(EmergingTopic {
+        topic,
+        papers: papers.iter().map(|paper| paper.id().to_owned()).collect(),
+        growth,
+        citation_rate,
+        score: growth * (1.0 + citation_rate.ln_1p()),
+    })
+}
+
+fn rank(mut topics: Vec<EmergingTopic>, limit: usize) -> Vec<EmergingTopic> {
+    topics.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.topic.cmp(&b.topic))
+    });
+    topics.truncate(limit);
+    topics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2NestedPaper;
+
+    fn paper(id: &str, title: &str, year: u32, citations: u32) -> S2NestedPaper {
+        let mut paper = S2NestedPaper::new(id);
+        paper.title = Some(title.to_owned());
+        paper.year = Some(year);
+        paper.citation_count = Some(citations);
+        paper
+    }
+
+    #[test]
+    fn test_emerging_topics() {
+        let mut papers = Vec::new();
+        for (i, year) in [2021, 2022, 2023, 2023, 2024, 2024, 2024]
+            .into_iter()
+            .enumerate()
+        {
+            papers.push(paper(
+                &format!("llm{}", i),
+                "Large language models",
+                year,
+                40,
+            ));
+        }
+        for (i, year) in [2020, 2020, 2021, 2021, 2024].into_iter().enumerate() {
+            papers.push(paper(
+                &format!("svm{}", i),
+                "Support vector machines",
+                year,
+                5,
+            ));
+        }
+        for (i, year) in [2020, 2022, 2024].into_iter().enumerate() {
+            papers.push(paper(
+                &format!("gan{}", i),
+                "Generative adversarial networks",
+                year,
+                2,
+            ));
+        }
+
+        let topics = emerging_topics(&papers, 2024, 10);
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].topic, "large language models");
+        assert_eq!(topics[0].papers.len(), 7);
+        assert!(topics[0].growth > 0.0);
+        assert!(topics[0].citation_rate > 10.0);
+
+        assert!(Graph::example().emerging_topics(2024, 10).is_empty());
+    }
+}
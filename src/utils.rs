@@ -1,28 +1,139 @@
-use reqwest::{Client, RequestBuilder};
+#[cfg(any(feature = "connected-papers", feature = "semantic-scholar"))]
+use serde::{Deserialize, Deserializer};
 
-pub(crate) struct APIKey {
-    pub(crate) header: String,
-    pub(crate) value: String,
+#[cfg(any(
+    feature = "connected-papers",
+    feature = "semantic-scholar",
+    feature = "crossref",
+    feature = "pdf"
+))]
+mod config;
+#[cfg(any(
+    feature = "connected-papers",
+    feature = "semantic-scholar",
+    feature = "crossref",
+    feature = "pdf"
+))]
+pub(crate) use config::HttpConfig;
+#[cfg(any(
+    feature = "connected-papers",
+    feature = "semantic-scholar",
+    feature = "crossref"
+))]
+pub(crate) mod http;
+#[cfg(any(
+    feature = "connected-papers",
+    feature = "semantic-scholar",
+    feature = "crossref"
+))]
+pub(crate) use http::*;
+
+/// Deserialize a `null` value as the default, e.g. an empty list
+#[cfg(any(feature = "connected-papers", feature = "semantic-scholar"))]
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
 }
 
-pub(crate) fn build_request(
-    client: &Client,
-    method: Method,
-    url: &str,
-    api_key: Option<APIKey>,
-) -> RequestBuilder {
-    let mut req_builder = match method {
-        Method::Get => client.get(url),
-        Method::Post => client.post(url),
+/// Implement accessors returning optional list fields as slices, empty when missing or `null`
+macro_rules! list_accessors {
+    ($ty:ty { $($field:ident: $item:ty),* $(,)? }) => {
+        impl $ty {
+            $(
+                #[doc = concat!("The `", stringify!($field), "` field, empty when missing or `null`")]
+                pub fn $field(&self) -> &[$item] {
+                    self.$field.as_deref().unwrap_or_default()
+                }
+            )*
+        }
     };
-    if let Some(APIKey { header, value }) = api_key {
-        req_builder = req_builder.header(header, value);
+}
+pub(crate) use list_accessors;
+
+/// 64-bit FNV-1a hash of the concatenated parts, as 16 hex digits
+///
+/// Unlike the standard library hashers, it is stable across Rust versions, so it can key
+/// files written to disk.
+pub(crate) fn fnv1a(parts: &[&[u8]]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in parts.iter().copied().flatten() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
     }
-    req_builder
+    format!("{:016x}", hash)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub(crate) enum Method {
-    Get,
-    Post,
+/// Percent-encode a query parameter value, keeping only the unreserved characters of
+/// RFC 3986 as they are
+///
+/// Spaces become `%20` rather than `+`, which the API would read as a space either way but
+/// which bulk search queries use as the AND operator.
+#[cfg(feature = "semantic-scholar")]
+pub(crate) fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(byte))
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
 }
+
+/// Escape text for use in XML and HTML
+#[cfg(any(feature = "export", feature = "render"))]
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Normalize a title for comparison
+///
+/// Applies NFKC compatibility folding, strips diacritics, lowercases, and collapses every run
+/// of non-alphanumeric characters into a single space.
+#[cfg(any(feature = "connected-papers", feature = "text"))]
+pub fn normalize_title(title: &str) -> String {
+    use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+    let folded = title
+        .nfkd()
+        .filter(|c| !is_combining_mark(*c))
+        .nfkc()
+        .flat_map(char::to_lowercase)
+        .collect::<String>();
+    folded
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Common English words and boilerplate of abstracts carrying no topic
+#[cfg(any(feature = "summary", feature = "analysis"))]
+pub(crate) const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "have", "his", "how", "its", "may", "new", "now", "see", "two",
+    "who", "did", "this", "that", "with", "from", "they", "been", "were", "which", "their",
+    "there", "these", "those", "then", "than", "into", "also", "such", "when", "where", "while",
+    "each", "other", "some", "more", "most", "over", "only", "both", "between", "about", "using",
+    "used", "use", "based", "paper", "show", "shows", "propose", "proposed", "present", "study",
+    "results", "approach", "method", "methods", "we", "is", "in", "of", "to", "a", "an", "on",
+    "by", "be", "as", "at", "or", "it", "via", "however", "thus", "here", "within", "without",
+    "across", "through", "what", "well", "many", "much", "very", "no", "so", "if", "do", "does",
+    "could", "would", "will", "should", "must", "being", "after", "before", "under", "further",
+];
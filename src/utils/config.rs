@@ -0,0 +1,36 @@
+//! HTTP client settings shared by the client builders
+
+use crate::error::{Error, Result};
+use reqwest::{Client, Proxy};
+use std::time::Duration;
+
+/// HTTP settings shared by the client builders
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HttpConfig {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) proxy: Option<String>,
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+}
+
+impl HttpConfig {
+    /// Build the underlying HTTP client
+    pub(crate) fn build(&self, user_agent: &str, default_timeout: Duration) -> Result<Client> {
+        let mut builder = Client::builder()
+            .timeout(self.timeout.unwrap_or(default_timeout))
+            .user_agent(user_agent);
+        if let Some(ref url) = self.proxy {
+            let proxy = Proxy::all(url).map_err(|e| {
+                Error::InvalidParameter(format!("invalid proxy URL {}: {}", url, e))
+            })?;
+            builder = builder.proxy(proxy);
+        }
+        #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+        for pem in &self.root_certificates {
+            let certificate = reqwest::Certificate::from_pem(pem)
+                .map_err(|e| Error::InvalidParameter(format!("invalid root certificate: {}", e)))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        Ok(builder.build()?)
+    }
+}
@@ -0,0 +1,629 @@
+//! HTTP transport shared by the API clients
+//!
+//! Builds and sends requests, records or replays them through a cassette, coalesces identical
+//! requests in flight and turns unsuccessful responses into errors with their context.
+
+use crate::{
+    cassette::{Cassette, CassetteMode, Recording},
+    error::{ApiError, Error, ErrorContext, Result},
+};
+use encoding_rs::{Encoding, UTF_8};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use mime::Mime;
+use reqwest::{Client, RequestBuilder, StatusCode, header::CONTENT_TYPE};
+use serde::{Deserialize, de::DeserializeOwned};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+pub(crate) struct APIKey {
+    pub(crate) header: String,
+    pub(crate) value: String,
+}
+
+pub(crate) fn build_request(
+    client: &Client,
+    method: Method,
+    url: &str,
+    api_key: Option<APIKey>,
+) -> RequestBuilder {
+    let mut req_builder = match method {
+        Method::Get => client.get(url),
+        #[cfg(feature = "semantic-scholar")]
+        Method::Post => client.post(url),
+    };
+    if let Some(APIKey { header, value }) = api_key {
+        req_builder = req_builder.header(header, value);
+    }
+    req_builder
+}
+
+/// Per-client settings applied when sending requests, cheap to clone
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Transport {
+    pub(crate) cassette: Option<Arc<Cassette>>,
+    pub(crate) max_response_size: Option<u64>,
+    pub(crate) coalescer: Option<Coalescer>,
+}
+
+/// Request shared by the identical requests sent while it runs
+type InFlight = Shared<BoxFuture<'static, Result<Response>>>;
+
+/// Requests in flight, keyed like cassette recordings
+///
+/// Identical requests sent concurrently wait for the first one instead of reaching the API,
+/// and all get its response. Requests are forgotten once they complete, so nothing is cached.
+#[derive(Clone, Default)]
+pub(crate) struct Coalescer {
+    in_flight: Arc<Mutex<HashMap<String, InFlight>>>,
+}
+
+impl std::fmt::Debug for Coalescer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        f.debug_struct("Coalescer")
+            .field("in_flight", &in_flight.len())
+            .finish()
+    }
+}
+
+impl Coalescer {
+    /// Send a request, or join the identical request already in flight
+    async fn send(
+        &self,
+        client: Client,
+        request: reqwest::Request,
+        transport: &Transport,
+        context: ErrorContext,
+    ) -> Result<Response> {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .unwrap_or_default();
+        let key = Cassette::key(request.method().as_str(), request.url().as_str(), body);
+        let flight = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight
+                .entry(key.clone())
+                .or_insert_with(|| {
+                    // The in-flight future must not hold the coalescer itself
+                    let transport = Transport {
+                        coalescer: None,
+                        ..transport.clone()
+                    };
+                    async move {
+                        execute(client, request, &transport, &context)
+                            .await
+                            .map_err(|e| e.with_context(context))
+                    }
+                    .boxed()
+                    .shared()
+                })
+                .clone()
+        };
+
+        let response = flight.clone().await;
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| current.ptr_eq(&flight))
+        {
+            in_flight.remove(&key);
+        }
+        response
+    }
+}
+
+/// Response headers that may carry the server-side request identifier
+const REQUEST_ID_HEADERS: [&str; 3] = ["x-request-id", "x-amzn-requestid", "x-amz-cf-id"];
+
+/// Longest query parameter value kept in error contexts
+const MAX_PARAM_LENGTH: usize = 100;
+
+/// Status, body and request identifier of an API response
+#[derive(Clone)]
+pub(crate) struct Response {
+    pub(crate) status: StatusCode,
+    /// Body as received, decoded on demand with `encoding`
+    pub(crate) body: Vec<u8>,
+    pub(crate) encoding: &'static Encoding,
+    pub(crate) request_id: Option<String>,
+    pub(crate) context: ErrorContext,
+}
+
+impl Response {
+    /// Body decoded with the charset of its `Content-Type`
+    pub(crate) fn text(&self) -> Cow<'_, str> {
+        decode(&self.body, self.encoding)
+    }
+
+    /// Deserialize the JSON body
+    pub(crate) fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        parse_json(&self.text()).map_err(|e| e.with_context(self.context.clone()))
+    }
+
+    /// Turn an unsuccessful response into an error
+    ///
+    /// Rejections of unsupported fields become [`Error::UnsupportedFields`].
+    pub(crate) fn into_error(self) -> Error {
+        let body = self.text().into_owned();
+        let message = error_message(&body);
+        let error = match message.as_deref().and_then(unsupported_fields) {
+            Some(fields) => Error::UnsupportedFields(fields),
+            None => Error::RequestFailed(ApiError {
+                status: self.status.as_u16(),
+                message,
+                body,
+                request_id: self.request_id,
+            }),
+        };
+        error.with_context(self.context)
+    }
+}
+
+/// Message of a JSON error body such as `{"error": "..."}` or `{"message": "..."}`
+fn error_message(body: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        error: Option<String>,
+        message: Option<String>,
+    }
+    let body = serde_json::from_str::<ErrorBody>(body).ok()?;
+    body.error.or(body.message)
+}
+
+/// Field names of a message such as `Unrecognized or unsupported fields: [citations, embedding]`
+fn unsupported_fields(message: &str) -> Option<Vec<String>> {
+    let lowercase = message.to_lowercase();
+    if !lowercase.contains("unsupported field") && !lowercase.contains("unrecognized field") {
+        return None;
+    }
+    let (_, fields) = message.split_once(':')?;
+    let fields = fields
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|field| field.trim_matches(|c: char| matches!(c, '[' | ']' | '"' | '\'' | '.')))
+        .filter(|field| !field.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    (!fields.is_empty()).then_some(fields)
+}
+
+/// Context of a request for its errors, with long parameter values truncated
+fn request_context(request: &reqwest::Request, attempt: u32) -> ErrorContext {
+    let params = request
+        .url()
+        .query_pairs()
+        .map(|(key, value)| {
+            let value = match value.char_indices().nth(MAX_PARAM_LENGTH) {
+                Some((end, _)) => format!("{}...", &value[..end]),
+                None => value.into_owned(),
+            };
+            (key.into_owned(), value)
+        })
+        .collect();
+    ErrorContext {
+        endpoint: format!("{} {}", request.method(), request.url().path()),
+        params,
+        attempt,
+    }
+}
+
+fn request_id(resp: &reqwest::Response) -> Option<String> {
+    REQUEST_ID_HEADERS.iter().find_map(|name| {
+        resp.headers()
+            .get(*name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    })
+}
+
+/// Send a request, recording or replaying it through the cassette if one is set
+///
+/// Identical requests in flight are coalesced when the transport has a coalescer. Errors,
+/// including those of unsuccessful responses, carry the endpoint and parameters.
+pub(crate) async fn send(request: RequestBuilder, transport: &Transport) -> Result<Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let context = request_context(&request, 1);
+    if let Some(ref coalescer) = transport.coalescer {
+        return coalescer.send(client, request, transport, context).await;
+    }
+    execute(client, request, transport, &context)
+        .await
+        .map_err(|e| e.with_context(context))
+}
+
+async fn execute(
+    client: Client,
+    request: reqwest::Request,
+    transport: &Transport,
+    context: &ErrorContext,
+) -> Result<Response> {
+    let Some(ref cassette) = transport.cassette else {
+        let resp = client.execute(request).await?;
+        return Ok(Response {
+            status: resp.status(),
+            encoding: charset(&resp),
+            request_id: request_id(&resp),
+            body: read_body(resp, transport.max_response_size).await?,
+            context: context.clone(),
+        });
+    };
+
+    let method = request.method().to_string();
+    let url = request.url().to_string();
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default();
+    let key = Cassette::key(&method, &url, body);
+
+    match cassette.mode() {
+        CassetteMode::Replay => {
+            let recording = cassette
+                .load(&key)?
+                .ok_or_else(|| Error::NotRecorded(format!("{} {}", method, url)))?;
+            Ok(Response {
+                status: StatusCode::from_u16(recording.status)
+                    .map_err(|e| Error::JsonError(e.to_string()))?,
+                body: recording.body.into_bytes(),
+                encoding: UTF_8,
+                request_id: recording.request_id,
+                context: context.clone(),
+            })
+        }
+        CassetteMode::Record => {
+            let resp = client.execute(request).await?;
+            let status = resp.status();
+            let encoding = charset(&resp);
+            let request_id = request_id(&resp);
+            let body = read_body(resp, transport.max_response_size).await?;
+            cassette.save(
+                &key,
+                &Recording {
+                    method,
+                    url,
+                    status: status.as_u16(),
+                    body: decode(&body, encoding).into_owned(),
+                    request_id: request_id.clone(),
+                },
+            )?;
+            Ok(Response {
+                status,
+                body,
+                encoding,
+                request_id,
+                context: context.clone(),
+            })
+        }
+    }
+}
+
+/// Read a response body, failing as soon as it exceeds the size limit
+async fn read_body(mut resp: reqwest::Response, limit: Option<u64>) -> Result<Vec<u8>> {
+    if let Some((size, limit)) = resp
+        .content_length()
+        .zip(limit)
+        .filter(|&(size, limit)| size > limit)
+    {
+        return Err(Error::ResponseTooLarge { size, limit });
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if let Some(limit) = limit.filter(|&limit| body.len() as u64 > limit) {
+            return Err(Error::ResponseTooLarge {
+                size: body.len() as u64,
+                limit,
+            });
+        }
+    }
+    Ok(body)
+}
+
+/// Encoding named by the `charset` of the `Content-Type` header, UTF-8 when missing or unknown
+///
+/// Bodies are decoded with it whether or not a size limit applied while reading them.
+fn charset(resp: &reqwest::Response) -> &'static Encoding {
+    resp.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Mime>().ok())
+        .and_then(|mime| {
+            mime.get_param(mime::CHARSET)
+                .and_then(|charset| Encoding::for_label(charset.as_str().as_bytes()))
+        })
+        .unwrap_or(UTF_8)
+}
+
+/// Decode a body, replacing malformed sequences
+pub(crate) fn decode<'a>(body: &'a [u8], encoding: &'static Encoding) -> Cow<'a, str> {
+    encoding.decode(body).0
+}
+
+/// HTTP method of a request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// `GET`
+    Get,
+    /// `POST`
+    #[cfg(feature = "semantic-scholar")]
+    Post,
+}
+
+/// Deserialize a JSON response body
+///
+/// With the `strict` feature enabled, every JSON field that is not mapped by the target
+/// model is reported through `tracing` with its path and value kind. Parsing itself stays
+/// lenient, so unknown fields never turn into errors.
+pub(crate) fn parse_json<T: DeserializeOwned>(body: &str) -> Result<T> {
+    #[cfg(feature = "strict")]
+    {
+        parse_reporting(&serde_json::from_str(body)?, report_unmapped)
+    }
+    #[cfg(not(feature = "strict"))]
+    {
+        Ok(serde_json::from_str(body)?)
+    }
+}
+
+/// Deserialize a JSON value read from a response body, e.g. after rewriting part of it
+///
+/// Unmapped fields are reported with the `strict` feature like in [`parse_json`].
+#[cfg(feature = "connected-papers")]
+pub(crate) fn parse_value<T: DeserializeOwned>(value: serde_json::Value) -> Result<T> {
+    #[cfg(feature = "strict")]
+    {
+        parse_reporting(&value, report_unmapped)
+    }
+    #[cfg(not(feature = "strict"))]
+    {
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+#[cfg(feature = "strict")]
+fn report_unmapped(field: &str, kind: &'static str) {
+    tracing::warn!(field, kind, "unmapped field in API response");
+}
+
+/// Deserialize a JSON value, passing the path and value kind of every unmapped field to `report`
+#[cfg(feature = "strict")]
+fn parse_reporting<T: DeserializeOwned>(
+    value: &serde_json::Value,
+    mut report: impl FnMut(&str, &'static str),
+) -> Result<T> {
+    let parsed = serde_ignored::deserialize(value, |path| {
+        let kind = lookup(value, &path).map_or("unknown", value_kind);
+        report(&path.to_string(), kind);
+    })?;
+    Ok(parsed)
+}
+
+#[cfg(feature = "strict")]
+fn lookup<'v>(
+    value: &'v serde_json::Value,
+    path: &serde_ignored::Path<'_>,
+) -> Option<&'v serde_json::Value> {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => Some(value),
+        Path::Seq { parent, index } => lookup(value, parent)?.get(*index),
+        Path::Map { parent, key } => lookup(value, parent)?.get(key.as_str()),
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => lookup(value, parent),
+    }
+}
+
+#[cfg(feature = "strict")]
+fn value_kind(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "bool",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_body() {
+        assert_eq!(
+            error_message(r#"{"error": "Paper not found"}"#).as_deref(),
+            Some("Paper not found")
+        );
+        assert_eq!(
+            error_message(r#"{"message": "Too Many Requests"}"#).as_deref(),
+            Some("Too Many Requests")
+        );
+        assert_eq!(error_message("Bad Gateway"), None);
+        assert_eq!(
+            unsupported_fields("Unrecognized or unsupported fields: [citations, embedding]"),
+            Some(vec!["citations".to_owned(), "embedding".to_owned()])
+        );
+        assert_eq!(unsupported_fields("Paper not found"), None);
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_requests() {
+        let dir = std::env::temp_dir().join(format!("coalesce-{}", std::process::id()));
+        let url = "https://example.org/paper";
+        Cassette::record(&dir)
+            .save(
+                &Cassette::key("GET", url, b""),
+                &Recording {
+                    method: "GET".to_owned(),
+                    url: url.to_owned(),
+                    status: 200,
+                    body: "{}".to_owned(),
+                    request_id: None,
+                },
+            )
+            .unwrap();
+        let coalescer = Coalescer::default();
+        let transport = Transport {
+            cassette: Some(Arc::new(Cassette::replay(&dir))),
+            coalescer: Some(coalescer.clone()),
+            ..Default::default()
+        };
+
+        let client = Client::new();
+        let (a, b) = tokio::join!(
+            send(client.get(url), &transport),
+            send(client.get(url), &transport)
+        );
+        assert_eq!(a.unwrap().text(), "{}");
+        assert_eq!(b.unwrap().text(), "{}");
+        assert!(coalescer.in_flight.lock().unwrap().is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(feature = "strict", feature = "connected-papers"))]
+    fn test_unmapped_fields() {
+        let body = serde_json::json!({
+            "id": "a",
+            "paperId": "a",
+            "title": "Graph",
+            "influentialScore": 0.5,
+            "authors": [{"name": "Ada", "hIndex": 3}],
+            "pos": [0.1, 0.2]
+        });
+        let mut reported = Vec::new();
+        let paper: crate::Paper = parse_reporting(&body, |field, kind| {
+            reported.push((field.to_owned(), kind));
+        })
+        .unwrap();
+        assert_eq!(paper.title.as_deref(), Some("Graph"));
+        assert_eq!(
+            reported,
+            [
+                ("authors.?.0.hIndex".to_owned(), "number"),
+                ("influentialScore".to_owned(), "number"),
+            ]
+        );
+    }
+
+    /// Serve each raw HTTP response to one connection on a local port, returning its URL
+    fn serve<R: AsRef<[u8]> + Send + 'static>(responses: Vec<R>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/paper", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream.write_all(response.as_ref()).unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_response_size_limit() {
+        let ok = |body: &str| {
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        };
+        let chunked = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+                       a\r\n0123456789\r\na\r\n0123456789\r\n0\r\n\r\n";
+        let url = serve(vec![ok("[]"), ok(&"x".repeat(64)), chunked.to_owned()]);
+        let client = Client::builder().no_proxy().build().unwrap();
+        let transport = Transport {
+            max_response_size: Some(16),
+            ..Default::default()
+        };
+
+        let response = send(client.get(&url), &transport).await.unwrap();
+        assert_eq!(response.text(), "[]");
+        // Rejected from the announced length
+        let Err(error) = send(client.get(&url), &transport).await else {
+            panic!("body over the limit accepted");
+        };
+        assert_eq!(
+            *error.inner(),
+            Error::ResponseTooLarge {
+                size: 64,
+                limit: 16
+            }
+        );
+        assert_eq!(error.context().unwrap().endpoint, "GET /paper");
+        // Rejected while reading a body of unknown length
+        let Err(error) = send(client.get(&url), &transport).await else {
+            panic!("body over the limit accepted");
+        };
+        assert_eq!(
+            *error.inner(),
+            Error::ResponseTooLarge {
+                size: 20,
+                limit: 16
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_body_charset() {
+        let latin1 = || {
+            let mut response =
+                b"HTTP/1.1 200 OK\r\nContent-Type: application/json; charset=ISO-8859-1\r\n\
+                                 Content-Length: 8\r\nConnection: close\r\n\r\n"
+                    .to_vec();
+            response.extend_from_slice(b"[\"caf\xe9\"]");
+            response
+        };
+        let url = serve(vec![latin1(), latin1()]);
+        let client = Client::builder().no_proxy().build().unwrap();
+        // Decoded the same with and without a size limit
+        for max_response_size in [None, Some(64)] {
+            let transport = Transport {
+                max_response_size,
+                ..Default::default()
+            };
+            let response = send(client.get(&url), &transport).await.unwrap();
+            assert_eq!(response.json::<Vec<String>>().unwrap(), ["café"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_id() {
+        let failure = |header: &str| {
+            let body = r#"{"message": "Too Many Requests"}"#;
+            format!(
+                "HTTP/1.1 429 Too Many Requests\r\n{}: req-42\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                header,
+                body.len(),
+                body
+            )
+        };
+        let url = serve(vec![failure("x-request-id"), failure("x-amzn-requestid")]);
+        let client = Client::builder().no_proxy().build().unwrap();
+        for _ in 0..2 {
+            let Ok(response) = send(client.get(&url), &Transport::default()).await else {
+                panic!("unsuccessful response turned into an error");
+            };
+            match response.into_error().inner() {
+                Error::RequestFailed(failure) => {
+                    assert_eq!(failure.status, 429);
+                    assert_eq!(failure.request_id.as_deref(), Some("req-42"));
+                }
+                error => panic!("unexpected error {:?}", error),
+            }
+        }
+    }
+}
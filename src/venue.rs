@@ -0,0 +1,168 @@
+//! Venue statistics
+//!
+//! [`venue_report`] aggregates a corpus per journal or conference: how many of its papers
+//! each venue published, how much they are cited and how many are open access, to compare
+//! venues before submitting or to report on the output of a group.
+
+use crate::{export::escape_csv, paper::PaperLike, utils::normalize_title};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+/// Statistics of the papers of a venue
+#[derive(Debug, Clone, PartialEq)]
+pub struct VenueStats {
+    /// Most common spelling of the venue name among the papers
+    pub venue: String,
+    /// Number of papers
+    pub papers: usize,
+    /// Median citation count of the papers with a known count
+    pub median_citations: Option<f64>,
+    /// Share of open access papers among those with a known status, in `[0, 1]`
+    pub open_access_share: Option<f64>,
+}
+
+/// Statistics of a corpus per venue
+#[derive(Debug, Clone, PartialEq)]
+pub struct VenueReport {
+    /// Venues by decreasing number of papers, then by name
+    pub venues: Vec<VenueStats>,
+    /// Number of papers without a venue
+    pub unknown: usize,
+}
+
+/// Aggregate papers per venue
+///
+/// Venue names are compared ignoring case and punctuation, so `Nature` and `NATURE.` count
+/// as the same venue.
+pub fn venue_report<P: PaperLike>(papers: &[P]) -> VenueReport {
+    let mut groups = BTreeMap::<String, Vec<&P>>::new();
+    let mut unknown = 0;
+    for paper in papers {
+        let key = paper.venue().map(normalize_title).unwrap_or_default();
+        if key.is_empty() {
+            unknown += 1;
+        } else {
+            groups.entry(key).or_default().push(paper);
+        }
+    }
+
+    let mut venues = groups
+        .into_values()
+        .map(|papers| {
+            let mut names = HashMap::<&str, usize>::new();
+            for paper in &papers {
+                *names
+                    .entry(paper.venue().unwrap_or_default().trim())
+                    .or_default() += 1;
+            }
+            let venue = names
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(name, _)| name.to_owned())
+                .unwrap_or_default();
+
+            let mut citations = papers
+                .iter()
+                .filter_map(|paper| paper.citation_count())
+                .collect::<Vec<_>>();
+            citations.sort_unstable();
+            let median_citations = match citations.len() {
+                0 => None,
+                n if n % 2 == 1 => Some(f64::from(citations[n / 2])),
+                n => Some((f64::from(citations[n / 2 - 1]) + f64::from(citations[n / 2])) / 2.0),
+            };
+
+            let statuses = papers
+                .iter()
+                .filter_map(|paper| paper.is_open_access())
+                .collect::<Vec<_>>();
+            let open_access_share = (!statuses.is_empty()).then(|| {
+                statuses.iter().filter(|open| **open).count() as f64 / statuses.len() as f64
+            });
+
+            VenueStats {
+                venue,
+                papers: papers.len(),
+                median_citations,
+                open_access_share,
+            }
+        })
+        .collect::<Vec<_>>();
+    venues.sort_by(|a, b| b.papers.cmp(&a.papers).then_with(|| a.venue.cmp(&b.venue)));
+    VenueReport { venues, unknown }
+}
+
+impl VenueReport {
+    /// Write the report as CSV, one row per venue
+    ///
+    /// The columns are `Venue`, `Papers`, `Median citations` and `Open access share`, the
+    /// share with two decimals; unknown values are left empty. Rows end with CRLF.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("Venue,Papers,Median citations,Open access share\r\n");
+        for stats in &self.venues {
+            let _ = write!(
+                csv,
+                "{},{},{},{}\r\n",
+                escape_csv(&stats.venue),
+                stats.papers,
+                stats
+                    .median_citations
+                    .map(|median| median.to_string())
+                    .unwrap_or_default(),
+                stats
+                    .open_access_share
+                    .map(|share| format!("{:.2}", share))
+                    .unwrap_or_default()
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::S2NestedPaper;
+
+    fn paper(venue: Option<&str>, citations: u32, open: Option<bool>) -> S2NestedPaper {
+        let mut paper = S2NestedPaper::new("a");
+        paper.venue = venue.map(ToOwned::to_owned);
+        paper.citation_count = Some(citations);
+        paper.is_open_access = open;
+        paper
+    }
+
+    #[test]
+    fn test_venue_report() {
+        let papers = [
+            paper(Some("Nature"), 10, Some(true)),
+            paper(Some("NATURE."), 30, Some(false)),
+            paper(Some("Nature"), 20, None),
+            paper(Some("ACL, Findings"), 4, Some(true)),
+            paper(Some("ACL, Findings"), 7, None),
+            paper(None, 1, None),
+        ];
+        let report = venue_report(&papers);
+        assert_eq!(report.unknown, 1);
+        assert_eq!(
+            report.venues[0],
+            VenueStats {
+                venue: "Nature".to_owned(),
+                papers: 3,
+                median_citations: Some(20.0),
+                open_access_share: Some(0.5),
+            }
+        );
+        assert_eq!(report.venues[1].median_citations, Some(5.5));
+        assert_eq!(
+            report.to_csv(),
+            concat!(
+                "Venue,Papers,Median citations,Open access share\r\n",
+                "Nature,3,20,0.50\r\n",
+                "\"ACL, Findings\",2,5.5,1.00\r\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Cited-by alerts
+//!
+//! Keeps the ids of the papers citing watched papers in a JSON file, so that repeated pulls of
+//! the citations endpoint only report the papers that started citing them since the last run.
+
+use crate::{
+    error::Result,
+    ss::{FetchBudget, PaperCitationsParamBuilder, PaperField, PaperId, S2Paper, SemanticScholar},
+};
+#[cfg(feature = "stream")]
+use async_stream::stream;
+#[cfg(feature = "stream")]
+use futures::Stream;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+use std::{
+    collections::{BTreeMap, BTreeSet, btree_map::Entry},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Fields requested for the citing papers
+const WATCH_FIELDS: [PaperField; 5] = [
+    PaperField::Title,
+    PaperField::Year,
+    PaperField::Authors,
+    PaperField::ExternalIds,
+    PaperField::URL,
+];
+
+/// Page size of the citation pulls, the maximum the endpoint accepts
+const PAGE_SIZE: u16 = 1000;
+
+/// Ids of the papers citing each watched paper, persisted as a JSON file
+#[derive(Debug, Clone, Default)]
+pub struct CitationStore {
+    path: Option<PathBuf>,
+    citing: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl CitationStore {
+    /// Create an empty in-memory store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the store at `path`, starting empty if the file does not exist
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let citing = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path),
+            citing,
+        })
+    }
+
+    /// The file backing this store, if any
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Write the store to its file; in-memory stores are left untouched
+    pub fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.path {
+            std::fs::write(path, serde_json::to_string_pretty(&self.citing)?)?;
+        }
+        Ok(())
+    }
+
+    /// Ids of the papers known to cite a paper, `None` if the paper was never recorded
+    pub fn citing(&self, paper_id: &str) -> Option<&BTreeSet<String>> {
+        self.citing.get(paper_id)
+    }
+
+    /// Record the papers citing a paper and return those not seen before
+    ///
+    /// The first record of a paper sets the baseline: its citing papers are stored and none
+    /// is returned, so that starting to watch a well-cited paper does not report its whole
+    /// history.
+    pub fn record(&mut self, paper_id: &str, citing: Vec<S2Paper>) -> Vec<S2Paper> {
+        match self.citing.entry(paper_id.to_owned()) {
+            Entry::Vacant(entry) => {
+                entry.insert(citing.into_iter().map(|paper| paper.paper_id).collect());
+                Vec::new()
+            }
+            Entry::Occupied(mut entry) => {
+                let seen = entry.get_mut();
+                citing
+                    .into_iter()
+                    .filter(|paper| seen.insert(paper.paper_id.clone()))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl SemanticScholar {
+    /// Fetch every citation of a paper and return the citing papers not in the store yet
+    ///
+    /// Citing papers without a Semantic Scholar id cannot be told apart and are left out.
+    /// The store is saved once the citations are recorded; see [`CitationStore::record`]
+    /// for the first pull of a paper.
+    pub async fn new_citations(
+        &self,
+        paper_id: &PaperId,
+        store: &mut CitationStore,
+    ) -> Result<Vec<S2Paper>> {
+        let mut builder = PaperCitationsParamBuilder::new(paper_id);
+        for field in WATCH_FIELDS {
+            builder.field(field);
+        }
+        let param = builder.limit(PAGE_SIZE).build()?;
+        let page = self.fetch_all(&param, FetchBudget::default()).await?;
+        let citing = page
+            .items
+            .into_iter()
+            .filter_map(|citation| citation.citing_paper)
+            .collect();
+        let new = store.record(&paper_id.to_string(), citing);
+        store.save()?;
+        Ok(new)
+    }
+
+    /// Pull the citations of a paper every `interval`, passing each pull to `on_pull`
+    ///
+    /// `on_pull` receives the newly citing papers, or the error of a failed pull, and returns
+    /// [`ControlFlow::Break`] to stop watching; it is the callback form of
+    /// [`SemanticScholar::watch_citations`], available without the `stream` feature.
+    pub async fn watch_citations_with(
+        &self,
+        paper_id: &PaperId,
+        interval: Duration,
+        store: &mut CitationStore,
+        mut on_pull: impl FnMut(Result<Vec<S2Paper>>) -> ControlFlow<()>,
+    ) {
+        loop {
+            if on_pull(self.new_citations(paper_id, store).await).is_break() {
+                return;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    /// Pull the citations of a paper every `interval` and stream the newly citing papers
+    ///
+    /// The stream does not end on its own. A failed pull yields its error and the watch
+    /// carries on at the next interval, so drop the stream to stop watching.
+    pub fn watch_citations(
+        &self,
+        paper_id: &PaperId,
+        interval: Duration,
+        mut store: CitationStore,
+    ) -> Pin<Box<dyn Stream<Item = Result<S2Paper>> + Send + '_>> {
+        let paper_id = paper_id.clone();
+        Box::pin(stream! {
+            loop {
+                match self.new_citations(&paper_id, &mut store).await {
+                    Ok(papers) => {
+                        for paper in papers {
+                            yield Ok(paper);
+                        }
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn papers(ids: &[&str]) -> Vec<S2Paper> {
+        ids.iter()
+            .map(|id| serde_json::from_value(serde_json::json!({ "paperId": id })).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_citation_store() {
+        let mut store = CitationStore::new();
+        assert!(store.citing("a").is_none());
+        assert!(store.record("a", papers(&["x", "y"])).is_empty());
+        assert_eq!(store.citing("a").map(BTreeSet::len), Some(2));
+
+        let new = store.record("a", papers(&["y", "z"]));
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].paper_id, "z");
+        assert!(store.record("a", papers(&["x", "z"])).is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! Connected Papers graph payloads
+
+use crate::{CP_URL, Served, fixture, load, replay};
+use connected_papers::{
+    ConnectedPapers, GRAPH_VERSION, Graph, GraphResponse, GraphResponseType, PaperId,
+    PublicationType,
+};
+
+#[test]
+fn test_small_graph() {
+    let response = load::<GraphResponse>("cp/graph_small.json");
+    assert_eq!(response.status, GraphResponseType::FreshGraph);
+    assert_eq!(response.remaining_requests, Some(42));
+    let graph = response.graph_json.unwrap();
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.edges.len(), 2);
+    assert_eq!(graph.citations.len(), 3);
+    assert_eq!(graph.references.len(), 3);
+    assert_eq!(graph.authors[0].mention_indexes, Some(vec![0, 1]));
+    assert_eq!(graph.parameters.params_version, GRAPH_VERSION);
+
+    let seed = &graph.nodes[&graph.start_id];
+    assert_eq!(
+        seed.title.as_deref(),
+        Some("Construction of the Literature Graph in Semantic Scholar")
+    );
+    assert_eq!(seed.authors()[1].ids, Some(vec![None]));
+    assert_eq!(
+        seed.publication_types(),
+        [PublicationType::JournalArticle, PublicationType::Conference]
+    );
+    assert_eq!(seed.pos.map(|pos| pos.len()), Some(2));
+    assert_eq!(
+        graph.citations[0].local_references.as_ref().map(Vec::len),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_large_graph() {
+    let response = load::<GraphResponse>("cp/graph_large.json");
+    assert_eq!(response.status, GraphResponseType::OldGraph);
+    let graph = response.graph_json.unwrap();
+    assert_eq!(graph.nodes.len(), 41);
+    assert_eq!(graph.parameters.total_nodes, 41);
+    assert_eq!(graph.path_lengths.len(), 41);
+    // `null` lists of common papers and authors
+    assert!(graph.citations.is_empty());
+    assert!(graph.references.is_empty());
+    assert!(graph.authors.is_empty());
+
+    for (id, paper) in &graph.nodes {
+        assert_eq!(*id, paper.paper_id);
+        assert!(paper.pos.is_some(), "node {} has no position", id);
+    }
+    for edge in &graph.edges {
+        assert!(graph.nodes.contains_key(&edge.0) && graph.nodes.contains_key(&edge.1));
+        assert!((0.0..=1.0).contains(&edge.2));
+    }
+    let doi = graph
+        .nodes
+        .values()
+        .find_map(|paper| paper.doi.clone())
+        .unwrap();
+    assert!(graph.find_node(&PaperId::doi(&doi)).is_some());
+}
+
+#[test]
+fn test_unversioned_graph() {
+    let value = serde_json::from_str(&fixture("cp/graph_v0.json")).unwrap();
+    let graph = Graph::from_json(value).unwrap();
+    assert_eq!(graph.parameters.params_version, GRAPH_VERSION);
+    assert_eq!(graph.nodes.len(), 5);
+    assert!(serde_json::from_str::<Graph>(&fixture("cp/graph_v0.json")).is_err());
+}
+
+#[test]
+fn test_status_only() {
+    let response = load::<GraphResponse>("cp/in_progress.json");
+    assert_eq!(response.status, GraphResponseType::InProgress);
+    assert_eq!(response.progress, Some(37.5));
+    assert!(response.graph_json.is_none());
+
+    let response = load::<GraphResponse>("cp/out_of_requests.json");
+    assert_eq!(response.status, GraphResponseType::OutOfRequests);
+    assert_eq!(response.remaining_requests, Some(0));
+}
+
+#[tokio::test]
+async fn test_replayed_graph() {
+    let expected = load::<GraphResponse>("cp/graph_small.json");
+    let id = &expected.graph_json.as_ref().unwrap().start_id;
+    let cassette = replay(
+        "cp-graph",
+        &[Served::get(
+            format!("{}/graph/0/{}", CP_URL, id),
+            200,
+            "cp/graph_small.json",
+        )],
+    );
+    let client = ConnectedPapers::builder()
+        .cassette(cassette)
+        .build()
+        .unwrap();
+    assert_eq!(client.get_graph(id, false).await.unwrap(), expected);
+}
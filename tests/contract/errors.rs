@@ -0,0 +1,97 @@
+//! Error bodies turned into typed errors
+
+use crate::{CP_URL, S2_URL, Served, replay};
+use connected_papers::{
+    ConnectedPapers, Error, PaperField, PaperSearchParamBuilder, SemanticScholar,
+};
+
+/// Search for `query`, requesting titles, answered with the given error fixture
+async fn search_error(test: &str, query: &str, status: u16, fixture: &str) -> Error {
+    let cassette = replay(
+        test,
+        &[Served::get(
+            format!("{}/paper/search?query={}&fields=title", S2_URL, query),
+            status,
+            fixture,
+        )],
+    );
+    let client = SemanticScholar::builder()
+        .cassette(cassette)
+        .build()
+        .unwrap();
+    let param = PaperSearchParamBuilder::new(query)
+        .field(PaperField::Title)
+        .build()
+        .unwrap();
+    client.query(&param).await.unwrap_err()
+}
+
+#[tokio::test]
+async fn test_semantic_scholar_errors() {
+    let error = search_error("s2-429", "graph", 429, "errors/s2_rate_limited.json").await;
+    match error.inner() {
+        Error::RequestFailed(failure) => {
+            assert_eq!(failure.status, 429);
+            assert!(
+                failure
+                    .message
+                    .as_deref()
+                    .unwrap()
+                    .starts_with("Too Many Requests")
+            );
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+    assert_eq!(
+        error.context().unwrap().endpoint,
+        "GET /graph/v1/paper/search"
+    );
+
+    let error = search_error(
+        "s2-fields",
+        "fields",
+        400,
+        "errors/s2_unsupported_fields.json",
+    )
+    .await;
+    assert_eq!(
+        *error.inner(),
+        Error::UnsupportedFields(vec!["citationz".to_owned(), "refs".to_owned()])
+    );
+
+    let error = search_error("s2-404", "missing", 404, "errors/s2_not_found.json").await;
+    assert!(matches!(error.inner(), Error::RequestFailed(failure) if failure.status == 404));
+
+    let error = search_error("s2-400", "limit", 400, "errors/s2_bad_request.json").await;
+    match error.inner() {
+        Error::RequestFailed(failure) => assert_eq!(
+            failure.message.as_deref(),
+            Some("Unacceptable query params: [limit=1000]")
+        ),
+        error => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[tokio::test]
+async fn test_connected_papers_errors() {
+    let cassette = replay(
+        "cp-500",
+        &[Served::get(
+            format!("{}/graph/0/abc", CP_URL),
+            500,
+            "errors/cp_internal_error.txt",
+        )],
+    );
+    let client = ConnectedPapers::builder()
+        .cassette(cassette)
+        .build()
+        .unwrap();
+    match client.get_graph("abc", false).await.unwrap_err().inner() {
+        Error::RequestFailed(failure) => {
+            assert_eq!(failure.status, 500);
+            assert_eq!(failure.message, None);
+            assert_eq!(failure.body, "Internal Server Error");
+        }
+        error => panic!("unexpected error {:?}", error),
+    }
+}
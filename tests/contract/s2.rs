@@ -0,0 +1,194 @@
+//! Semantic Scholar search and batch payloads
+
+use crate::{S2_URL, Served, load, replay};
+use connected_papers::{
+    CitationStore, PaperBatchParam, PaperBulkSearchResponse, PaperCitationsResponse, PaperField,
+    PaperId, PaperSearchParamBuilder, PaperSearchResponse, S2NestedPaper, SemanticScholar,
+};
+use serde_json::json;
+
+#[test]
+fn test_search_pages() {
+    let page = load::<PaperSearchResponse>("s2/search_page.json");
+    assert_eq!(
+        (page.total, page.offset, page.next),
+        (Some(1423), Some(0), Some(3))
+    );
+    let papers = page.data();
+    assert_eq!(papers.len(), 3);
+
+    let paper = &papers[0];
+    let external_ids = paper.external_ids.as_ref().unwrap();
+    assert_eq!(external_ids.corpus_id, Some(19170988));
+    assert_eq!(external_ids.arxiv.as_deref(), Some("1805.02262"));
+    assert_eq!(
+        paper.publication_venue.as_ref().unwrap().type_.as_deref(),
+        Some("conference")
+    );
+    assert_eq!(paper.authors.as_ref().unwrap()[2].author_id, None);
+    let pdf = paper.open_access_pdf.as_ref().unwrap();
+    assert_eq!(pdf.license.as_deref(), Some("CCBY"));
+
+    // Fields sent as `null` or empty
+    let sparse = &papers[1];
+    assert_eq!((sparse.year, sparse.journal.as_ref()), (None, None));
+    assert!(sparse.open_access_pdf.is_none() && sparse.fields_of_study.is_none());
+    assert_eq!(sparse.authors.as_deref(), Some(&[][..]));
+
+    assert!(papers[2].tldr.as_ref().unwrap().text.is_some());
+
+    let last = load::<PaperSearchResponse>("s2/search_last_page.json");
+    assert_eq!(last.next, None);
+    assert_eq!(last.data().len(), 2);
+}
+
+#[test]
+fn test_bulk_pages() {
+    let page = load::<PaperBulkSearchResponse>("s2/bulk_page.json");
+    assert_eq!(page.total, Some(8741));
+    assert_eq!(
+        page.token.as_deref(),
+        Some("PCOA3RZZB2ADADAEYCX2BLJJRSKMTHPR")
+    );
+    assert_eq!(page.data().len(), 2);
+
+    let last = load::<PaperBulkSearchResponse>("s2/bulk_last_page.json");
+    assert_eq!(last.token, None);
+    assert_eq!(last.data()[0].citation_count, Some(0));
+}
+
+#[tokio::test]
+async fn test_batch_with_nulls() {
+    let ids = vec![
+        PaperId::id("649def34f8be52c8b66281af98ae884c09aef38b"),
+        PaperId::doi("10.0000/unknown"),
+        PaperId::id("cb92a7f9d9dbcf9145e32fdfa0e70e2a6b828eb1"),
+        PaperId::arxiv("0000.00000"),
+    ];
+    let cassette = replay(
+        "s2-batch",
+        &[Served::post(
+            format!("{}/paper/batch?fields=title", S2_URL),
+            &json!({ "ids": ids }),
+            200,
+            "s2/batch_with_nulls.json",
+        )],
+    );
+    let client = SemanticScholar::builder()
+        .cassette(cassette)
+        .build()
+        .unwrap();
+    let param = PaperBatchParam {
+        ids,
+        fields: Some(vec![PaperField::Title]),
+        nested: Vec::new(),
+    };
+    let papers = client.query(&param).await.unwrap();
+    assert_eq!(
+        papers.iter().map(Option::is_some).collect::<Vec<_>>(),
+        [true, false, true, false]
+    );
+    assert_eq!(
+        papers,
+        load::<Vec<Option<S2NestedPaper>>>("s2/batch_with_nulls.json")
+    );
+}
+
+#[tokio::test]
+async fn test_citations_with_null_ids() {
+    let id = "649def34f8be52c8b66281af98ae884c09aef38b";
+    let cassette = replay(
+        "s2-citations",
+        &[Served::get(
+            format!(
+                "{}/paper/{}/citations?fields=title,year,authors,externalIds,url&limit=1000",
+                S2_URL, id
+            ),
+            200,
+            "s2/citations_with_null_ids.json",
+        )],
+    );
+    let client = SemanticScholar::builder()
+        .cassette(cassette)
+        .build()
+        .unwrap();
+
+    let page = load::<PaperCitationsResponse>("s2/citations_with_null_ids.json");
+    assert_eq!(
+        page.data()
+            .iter()
+            .map(|citation| citation.citing_paper.is_some())
+            .collect::<Vec<_>>(),
+        [true, false, true, false]
+    );
+
+    // Papers without an id are left out of the new citations
+    let mut store = CitationStore::new();
+    store.record(id, Vec::new());
+    let new = client
+        .new_citations(&PaperId::id(id), &mut store)
+        .await
+        .unwrap();
+    assert_eq!(
+        new.iter()
+            .map(|paper| paper.paper_id.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "3c8a4b1f2e6d9a0b7c5e4f3a2b1c0d9e8f7a6b5c",
+            "b7e1d2c3a4f5e6d7c8b9a0f1e2d3c4b5a6f7e8d9"
+        ]
+    );
+    assert_eq!(store.citing(id).map(|citing| citing.len()), Some(2));
+}
+
+#[tokio::test]
+async fn test_replayed_search() {
+    let cassette = replay(
+        "s2-search",
+        &[Served::get(
+            format!("{}/paper/search?query=literature&fields=title", S2_URL),
+            200,
+            "s2/search_last_page.json",
+        )],
+    );
+    let client = SemanticScholar::builder()
+        .cassette(cassette)
+        .build()
+        .unwrap();
+    let param = PaperSearchParamBuilder::new("literature")
+        .field(PaperField::Title)
+        .build()
+        .unwrap();
+    let page = client.query(&param).await.unwrap();
+    assert_eq!(
+        page,
+        load::<PaperSearchResponse>("s2/search_last_page.json")
+    );
+}
+
+#[tokio::test]
+async fn test_replayed_resolution() {
+    let ids = [
+        PaperId::doi("10.18653/v1/N18-3011"),
+        PaperId::pubmed(1),
+        PaperId::arxiv("2004.07180"),
+    ];
+    let cassette = replay(
+        "s2-resolve",
+        &[Served::post(
+            format!("{}/paper/batch?fields=externalIds", S2_URL),
+            &json!({ "ids": ids }),
+            200,
+            "s2/resolve_batch.json",
+        )],
+    );
+    let client = SemanticScholar::builder()
+        .cassette(cassette)
+        .build()
+        .unwrap();
+    let mapping = client.resolve_ids(&ids).await.unwrap();
+    assert_eq!(mapping.unresolved, vec![PaperId::pubmed(1)]);
+    assert_eq!(mapping.get(&ids[0]).unwrap().corpus_id(), Some(19170988));
+    // `null` external ids
+    assert_eq!(mapping.get(&ids[2]).unwrap().corpus_id(), None);
+}
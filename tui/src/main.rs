@@ -0,0 +1,221 @@
+use connected_papers::{ConnectedPapers, Graph, Paper, PaperLike, ProgressEvent, ProgressSink};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::{Line, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Similarity,
+    Citations,
+    Year,
+}
+
+impl SortKey {
+    fn label(&self) -> &'static str {
+        match self {
+            SortKey::Similarity => "similarity",
+            SortKey::Citations => "citations",
+            SortKey::Year => "year",
+        }
+    }
+}
+
+struct App {
+    graph: Graph,
+    papers: Vec<Paper>,
+    state: ListState,
+    sort: SortKey,
+    message: Option<String>,
+}
+
+impl App {
+    fn new(graph: Graph) -> Self {
+        let papers = graph.nodes.values().cloned().collect();
+        let mut app = Self {
+            graph,
+            papers,
+            state: ListState::default(),
+            sort: SortKey::Similarity,
+            message: None,
+        };
+        app.sort_by(SortKey::Similarity);
+        app
+    }
+
+    fn sort_by(&mut self, key: SortKey) {
+        self.sort = key;
+        match key {
+            // The origin paper has the shortest path, closer papers are more similar
+            SortKey::Similarity => self.papers.sort_by(|a, b| {
+                let a = a.path_length.unwrap_or(f64::MAX);
+                let b = b.path_length.unwrap_or(f64::MAX);
+                a.total_cmp(&b)
+            }),
+            SortKey::Citations => self
+                .papers
+                .sort_by_key(|paper| std::cmp::Reverse(paper.citations_length.unwrap_or(0))),
+            SortKey::Year => self
+                .papers
+                .sort_by_key(|paper| std::cmp::Reverse(paper.year.unwrap_or(0))),
+        }
+        self.state.select(Some(0));
+    }
+
+    fn selected(&self) -> Option<&Paper> {
+        self.state.selected().and_then(|i| self.papers.get(i))
+    }
+
+    fn open(&mut self, pdf: bool) {
+        let Some(paper) = self.selected() else {
+            return;
+        };
+        let url = if pdf {
+            paper.pdf_urls().first().cloned()
+        } else {
+            paper.primary_link()
+        };
+        self.message = Some(match url {
+            Some(url) => match open::that(&url) {
+                Ok(()) => format!("Opened {}", url),
+                Err(e) => format!("Failed to open {}: {}", url, e),
+            },
+            None if pdf => "No PDF available".to_owned(),
+            None => "No URL available".to_owned(),
+        });
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, mut app: App) -> anyhow::Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, &mut app))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => app.state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => app.state.select_previous(),
+                KeyCode::Char('s') => app.sort_by(SortKey::Similarity),
+                KeyCode::Char('c') => app.sort_by(SortKey::Citations),
+                KeyCode::Char('y') => app.sort_by(SortKey::Year),
+                KeyCode::Char('o') | KeyCode::Enter => app.open(false),
+                KeyCode::Char('p') => app.open(true),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, footer] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let [list_area, detail_area] =
+        Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(main);
+
+    let items = app
+        .papers
+        .iter()
+        .map(|paper| {
+            let title = paper.title.as_deref().unwrap_or(&paper.id);
+            let year = paper.year.map(|y| y.to_string()).unwrap_or_default();
+            let line = Line::from(format!("{} {}", year, title));
+            if paper.id == app.graph.start_id {
+                ListItem::new(line.bold())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect::<Vec<_>>();
+    let list = List::new(items)
+        .block(Block::bordered().title(format!(
+            " Papers ({}) sorted by {} ",
+            app.papers.len(),
+            app.sort.label()
+        )))
+        .highlight_style(Style::new().reversed())
+        .highlight_symbol("> ");
+    frame.render_stateful_widget(list, list_area, &mut app.state);
+
+    let details = app.selected().map(details).unwrap_or_default();
+    let paragraph = Paragraph::new(details)
+        .block(Block::bordered().title(" Details "))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, detail_area);
+
+    let help = app.message.clone().unwrap_or_else(|| {
+        "↑/↓ move  s/c/y sort by similarity/citations/year  o open  p PDF  q quit".to_owned()
+    });
+    frame.render_widget(Line::from(help).dim(), footer);
+}
+
+fn details(paper: &Paper) -> Text<'static> {
+    let mut lines = vec![
+        Line::from(paper.title.clone().unwrap_or_else(|| paper.id.clone())).bold(),
+        Line::from(
+            paper
+                .authors()
+                .iter()
+                .filter_map(|author| author.name.clone())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    ];
+    let venue = paper
+        .venue
+        .clone()
+        .or_else(|| paper.journal_name.clone())
+        .unwrap_or_default();
+    let year = paper.year.map(|y| y.to_string()).unwrap_or_default();
+    lines.push(Line::from(format!("{} {}", year, venue).trim().to_owned()).italic());
+    lines.push(Line::from(format!(
+        "Citations: {}  References: {}",
+        paper.citations_length.unwrap_or(0),
+        paper.references_length.unwrap_or(0)
+    )));
+    if let Some(ref doi) = paper.doi {
+        lines.push(Line::from(format!("DOI: {}", doi)));
+    }
+    if let Some(ref tldr) = paper.tldr {
+        lines.push(Line::default());
+        lines.push(Line::from("TL;DR").bold());
+        lines.push(Line::from(tldr.clone()));
+    }
+    if let Some(ref abstract_) = paper.abstract_ {
+        lines.push(Line::default());
+        lines.push(Line::from("Abstract").bold());
+        lines.push(Line::from(abstract_.clone()));
+    }
+    Text::from(lines)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let Some(id) = std::env::args().nth(1) else {
+        eprintln!("Usage: connected-papers-tui <paper-id>");
+        std::process::exit(2);
+    };
+    let client =
+        ConnectedPapers::from_env().unwrap_or_else(|_| ConnectedPapers::with_api_key("TEST_TOKEN"));
+
+    let progress = ProgressSink::callback(|event| match event {
+        ProgressEvent::GraphQueued => eprintln!("Queued"),
+        ProgressEvent::GraphBuilding(percent) => eprintln!("Building ({:.0}%)", percent),
+        ProgressEvent::Completed => eprintln!("Done"),
+        _ => {}
+    });
+    let response = client.wait_for_graph(&id, false, &progress).await?;
+    let Some(graph) = response.and_then(|response| response.graph_json) else {
+        anyhow::bail!("no graph available for {}", id);
+    };
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, App::new(graph));
+    ratatui::restore();
+    result
+}